@@ -0,0 +1,330 @@
+//! One-file archive of the whole app, for moving to a new PC
+//!
+//! A zip with the data file, attachment contents, the settings and the newest
+//! backups, plus a `manifest.json`. Downloaded and cached files (Data Dragon,
+//! raw game payloads, logs) are left out; they come back on their own. So are
+//! secrets like the Riot API key and the OBS page token, which stay on the PC
+//! they were set up on.
+
+use crate::settings::Settings;
+use crate::storage::{AppData, Storage, StorageError, DEFAULT_BACKUPS_KEPT};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use uuid::Uuid;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Archive layout version, bumped on incompatible changes
+const ARCHIVE_FORMAT: u32 = 1;
+
+/// Newest backups included
+const ARCHIVED_BACKUPS: usize = 5;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DATA_FILE: &str = "data.json";
+const SETTINGS_FILE: &str = "settings.json";
+const ATTACHMENTS_FOLDER: &str = "attachments/";
+const BACKUPS_FOLDER: &str = "backups/";
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error("Not a valid archive: {0}")]
+    Zip(#[from] ZipError),
+    #[error("Archive contents are damaged: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Not an archive from this app: {0} is missing")]
+    Missing(&'static str),
+    #[error("Archive format {0} is not supported by this version")]
+    UnsupportedFormat(u32),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    created: DateTime<Utc>,
+    app_version: String,
+}
+
+/// What an archive holds
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct ArchiveSummary {
+    pub matchups: usize,
+    pub matches: usize,
+    pub attachments: usize,
+    pub backups: usize,
+    pub settings: bool,
+}
+
+/// What `restore` brought back, with the settings left for the caller to apply
+#[derive(Debug)]
+pub struct RestoredArchive {
+    pub summary: ArchiveSummary,
+    pub settings: Option<Settings>,
+}
+
+/// Write everything in `storage`'s data directory worth moving, plus
+/// `settings`, to a zip at `path`
+pub fn export(
+    path: &Path,
+    storage: &Storage,
+    settings: &Settings,
+    now: DateTime<Utc>,
+) -> Result<ArchiveSummary, ArchiveError> {
+    let data = storage.load()?;
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let mut summary = ArchiveSummary {
+        matchups: data.matchups.len(),
+        matches: data.matches.len(),
+        settings: true,
+        ..Default::default()
+    };
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT,
+        created: now,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.start_file(DATA_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&data)?)?;
+    zip.start_file(SETTINGS_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&settings.without_secrets())?)?;
+
+    let attachments_dir = storage.attachments_dir();
+    if attachments_dir.exists() {
+        for entry in fs::read_dir(&attachments_dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_file() {
+                zip.start_file(format!("{}{}", ATTACHMENTS_FOLDER, name), options)?;
+                zip.write_all(&fs::read(&path)?)?;
+                summary.attachments += 1;
+            }
+        }
+    }
+
+    let backups = Storage::backups(&storage.backups_dir())?;
+    for backup in backups.iter().rev().take(ARCHIVED_BACKUPS) {
+        let Some(name) = backup.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        zip.start_file(format!("{}{}", BACKUPS_FOLDER, name), options)?;
+        zip.write_all(&fs::read(backup)?)?;
+        summary.backups += 1;
+    }
+
+    zip.finish()?;
+    Ok(summary)
+}
+
+/// Replace the data in `storage` with the archive at `path`
+///
+/// Everything is unpacked to a folder next to the data first, so a damaged
+/// archive leaves the current data as it was; only then is the current data
+/// backed up and the unpacked files moved into place. Attachments are added
+/// next to the ones already here, and backups with a name already taken are
+/// skipped.
+pub fn restore(path: &Path, storage: &Storage) -> Result<RestoredArchive, ArchiveError> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    let manifest: Manifest =
+        read_json(&mut zip, MANIFEST_FILE)?.ok_or(ArchiveError::Missing(MANIFEST_FILE))?;
+    if manifest.format > ARCHIVE_FORMAT {
+        return Err(ArchiveError::UnsupportedFormat(manifest.format));
+    }
+    let mut data: AppData =
+        read_json(&mut zip, DATA_FILE)?.ok_or(ArchiveError::Missing(DATA_FILE))?;
+    let settings: Option<Settings> = read_json(&mut zip, SETTINGS_FILE)?;
+
+    let staging = storage
+        .attachments_dir()
+        .with_file_name(format!(".restore-{}", Uuid::new_v4()));
+    let unpacked = unpack(&mut zip, &staging);
+    let moved = unpacked.and_then(|files| {
+        storage.backup(&storage.backups_dir(), DEFAULT_BACKUPS_KEPT)?;
+        move_into_place(files, storage)
+    });
+    let _ = fs::remove_dir_all(&staging);
+    let (attachments, backups) = moved?;
+
+    storage.save(&mut data)?;
+    let summary = ArchiveSummary {
+        matchups: data.matchups.len(),
+        matches: data.matches.len(),
+        attachments,
+        backups,
+        settings: settings.is_some(),
+    };
+    Ok(RestoredArchive { summary, settings })
+}
+
+/// An attachment or backup unpacked to the staging folder
+struct Unpacked {
+    staged: PathBuf,
+    name: String,
+    is_backup: bool,
+}
+
+/// Unpack the attachments and backups in `zip` to `staging`
+///
+/// Only plain file names are taken, and for attachments only the names the
+/// app gives them, so nothing can land outside the folders or stand in for
+/// another file.
+fn unpack(zip: &mut ZipArchive<File>, staging: &Path) -> Result<Vec<Unpacked>, ArchiveError> {
+    fs::create_dir_all(staging)?;
+    let mut files = Vec::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        let entry = file.name().to_string();
+        let (name, is_backup) = if let Some(name) = entry.strip_prefix(ATTACHMENTS_FOLDER) {
+            (name, false)
+        } else if let Some(name) = entry.strip_prefix(BACKUPS_FOLDER) {
+            (name, true)
+        } else {
+            continue;
+        };
+        if Path::new(name).file_name() != Some(OsStr::new(name)) {
+            continue;
+        }
+        let stem = Path::new(name).file_stem().and_then(OsStr::to_str);
+        if !is_backup && stem.is_none_or(|stem| Uuid::parse_str(stem).is_err()) {
+            continue;
+        }
+
+        let staged = staging.join(files.len().to_string());
+        io::copy(&mut file, &mut File::create(&staged)?)?;
+        files.push(Unpacked {
+            staged,
+            name: name.to_string(),
+            is_backup,
+        });
+    }
+    Ok(files)
+}
+
+/// Move unpacked files into `storage`'s folders, returning how many
+/// attachments and backups were moved
+fn move_into_place(
+    files: Vec<Unpacked>,
+    storage: &Storage,
+) -> Result<(usize, usize), ArchiveError> {
+    let attachments_dir = storage.attachments_dir();
+    let backups_dir = storage.backups_dir();
+    let (mut attachments, mut backups) = (0, 0);
+    for file in files {
+        let dir = if file.is_backup {
+            &backups_dir
+        } else {
+            &attachments_dir
+        };
+        let target = dir.join(&file.name);
+        if file.is_backup && target.exists() {
+            continue;
+        }
+        fs::create_dir_all(dir)?;
+        fs::rename(&file.staged, &target)?;
+        if file.is_backup {
+            backups += 1;
+        } else {
+            attachments += 1;
+        }
+    }
+    Ok((attachments, backups))
+}
+
+/// Parse the file `name` in the archive, `None` when it isn't there
+fn read_json<T: DeserializeOwned>(
+    zip: &mut ZipArchive<File>,
+    name: &str,
+) -> Result<Option<T>, ArchiveError> {
+    match zip.by_name(name) {
+        Ok(file) => Ok(Some(serde_json::from_reader(file)?)),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attachments::{AttachmentKind, AttachmentStore};
+    use crate::matchup::Matchup;
+
+    #[test]
+    fn test_export_and_restore() {
+        let old = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(old.path().to_path_buf())).unwrap();
+        let mut data = AppData::default();
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        data.matchups.insert(matchup.id.clone(), matchup.clone());
+        storage.save(&mut data).unwrap();
+        fs::create_dir_all(storage.backups_dir()).unwrap();
+        fs::write(
+            storage.backups_dir().join("data-20240101-000000.json"),
+            "{}",
+        )
+        .unwrap();
+        let attachment = AttachmentStore::new(storage.attachments_dir())
+            .add_drawing(&serde_json::json!({ "strokes": [] }))
+            .unwrap();
+        let mut settings = Settings {
+            default_import_count: 42,
+            ..Default::default()
+        };
+        settings.obs.token = Some("secret".to_string());
+
+        let path = old.path().join("archive.zip");
+        let exported = export(&path, &storage, &settings, Utc::now()).unwrap();
+        assert_eq!(
+            exported,
+            ArchiveSummary {
+                matchups: 1,
+                matches: 0,
+                attachments: 1,
+                backups: 1,
+                settings: true
+            }
+        );
+
+        let new = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(new.path().to_path_buf())).unwrap();
+        let restored = restore(&path, &storage).unwrap();
+        assert_eq!(restored.summary, exported);
+        assert_eq!(restored.settings, Some(settings.without_secrets()));
+        assert!(storage.load().unwrap().matchups.contains_key(&matchup.id));
+        let store = AttachmentStore::new(storage.attachments_dir());
+        assert_eq!(
+            store.read(&attachment).unwrap().attachment.kind,
+            AttachmentKind::Drawing
+        );
+        // The empty data that was here is backed up next to the restored backup
+        assert_eq!(Storage::backups(&storage.backups_dir()).unwrap().len(), 2);
+
+        // Nothing is left behind from unpacking
+        assert!(fs::read_dir(new.path()).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(".restore")));
+
+        fs::write(&path, "not a zip").unwrap();
+        assert!(matches!(
+            restore(&path, &storage),
+            Err(ArchiveError::Zip(_))
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Files attached to matchup versions: voice memos recorded after a game and
+//! drawings, such as ward or freeze positions sketched on the minimap
+//!
+//! Contents live in the `attachments` folder next to the data file, one file
+//! per attachment named after its ID; versions only keep the metadata. Since
+//! that metadata can arrive from imported files, the file name is always
+//! derived from the ID and type, never read from it.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Folder name in the data directory
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Largest voice memo, several minutes of compressed audio
+const MAX_AUDIO_BYTES: usize = 5 * 1024 * 1024;
+
+/// Largest drawing, as JSON
+const MAX_DRAWING_BYTES: usize = 256 * 1024;
+
+/// Drawings are stored as the JSON the frontend sends
+const DRAWING_TYPE: &str = "application/json";
+
+/// Audio formats browsers record in, with the file extension used for each
+const AUDIO_TYPES: [(&str, &str); 5] = [
+    ("audio/webm", "webm"),
+    ("audio/ogg", "ogg"),
+    ("audio/mp4", "m4a"),
+    ("audio/mpeg", "mp3"),
+    ("audio/wav", "wav"),
+];
+
+#[derive(Error, Debug)]
+pub enum AttachmentError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Attachment data is not valid base64")]
+    InvalidData,
+    #[error("Attachment is larger than {limit} bytes")]
+    TooLarge { limit: usize },
+    #[error("Unsupported attachment type: {0}")]
+    UnsupportedType(String),
+    #[error("Drawing must be a JSON object")]
+    InvalidDrawing,
+    #[error("Attachment {0} was not stored by this app")]
+    InvalidId(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Audio,
+    /// Strokes and shapes as JSON, drawn over an image chosen by the frontend
+    Drawing,
+}
+
+impl AttachmentKind {
+    fn max_bytes(self) -> usize {
+        match self {
+            AttachmentKind::Audio => MAX_AUDIO_BYTES,
+            AttachmentKind::Drawing => MAX_DRAWING_BYTES,
+        }
+    }
+
+    /// File extension for a MIME type this kind accepts
+    fn extension(self, mime_type: &str) -> Option<&'static str> {
+        let types: &[(&str, &str)] = match self {
+            AttachmentKind::Audio => &AUDIO_TYPES,
+            AttachmentKind::Drawing => &[(DRAWING_TYPE, "json")],
+        };
+        types
+            .iter()
+            .find(|(mime, _)| *mime == mime_type)
+            .map(|(_, extension)| *extension)
+    }
+}
+
+/// An attached file, as referenced from a version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attachment {
+    pub id: String,
+    pub kind: AttachmentKind,
+    /// Without parameters, e.g. "audio/webm"
+    pub mime_type: String,
+    pub size: u64,
+    pub created: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Name of the file holding the contents, `<id>.<extension>`
+    fn file_name(&self) -> Result<String, AttachmentError> {
+        if Uuid::parse_str(&self.id).is_err() {
+            return Err(AttachmentError::InvalidId(self.id.clone()));
+        }
+        let extension = self
+            .kind
+            .extension(&self.mime_type)
+            .ok_or_else(|| AttachmentError::UnsupportedType(self.mime_type.clone()))?;
+        Ok(format!("{}.{}", self.id, extension))
+    }
+}
+
+/// An attachment with its contents, for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentData {
+    pub attachment: Attachment,
+    /// Base64 encoded contents
+    pub data: String,
+}
+
+pub struct AttachmentStore {
+    dir: PathBuf,
+}
+
+impl AttachmentStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Save base64 encoded contents as a new attachment
+    pub fn add(
+        &self,
+        kind: AttachmentKind,
+        mime_type: &str,
+        contents: &str,
+    ) -> Result<Attachment, AttachmentError> {
+        // Recorders report codecs too, e.g. "audio/webm;codecs=opus"
+        let mime_type = mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        let extension = kind
+            .extension(&mime_type)
+            .ok_or_else(|| AttachmentError::UnsupportedType(mime_type.clone()))?;
+
+        let bytes = STANDARD
+            .decode(contents.trim())
+            .map_err(|_| AttachmentError::InvalidData)?;
+        self.write(kind, mime_type, extension, bytes)
+    }
+
+    /// Save a drawing; its layout is up to the frontend as long as it's an object
+    pub fn add_drawing(&self, drawing: &serde_json::Value) -> Result<Attachment, AttachmentError> {
+        if !drawing.is_object() {
+            return Err(AttachmentError::InvalidDrawing);
+        }
+        let bytes = serde_json::to_vec(drawing).map_err(std::io::Error::from)?;
+        self.write(
+            AttachmentKind::Drawing,
+            DRAWING_TYPE.to_string(),
+            "json",
+            bytes,
+        )
+    }
+
+    fn write(
+        &self,
+        kind: AttachmentKind,
+        mime_type: String,
+        extension: &str,
+        bytes: Vec<u8>,
+    ) -> Result<Attachment, AttachmentError> {
+        if bytes.len() > kind.max_bytes() {
+            return Err(AttachmentError::TooLarge {
+                limit: kind.max_bytes(),
+            });
+        }
+
+        let attachment = Attachment {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            mime_type,
+            size: bytes.len() as u64,
+            created: Utc::now(),
+        };
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(format!("{}.{}", attachment.id, extension)),
+            bytes,
+        )?;
+        Ok(attachment)
+    }
+
+    pub fn read(&self, attachment: &Attachment) -> Result<AttachmentData, AttachmentError> {
+        let bytes = fs::read(self.dir.join(attachment.file_name()?))?;
+        Ok(AttachmentData {
+            attachment: attachment.clone(),
+            data: STANDARD.encode(bytes),
+        })
+    }
+
+    /// Delete the contents; an already missing file is fine
+    pub fn remove(&self, attachment: &Attachment) -> Result<(), AttachmentError> {
+        match fs::remove_file(self.dir.join(attachment.file_name()?)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().join(ATTACHMENTS_DIR));
+        let data = STANDARD.encode(b"voice memo");
+
+        let attachment = store
+            .add(AttachmentKind::Audio, "audio/webm;codecs=opus", &data)
+            .unwrap();
+        assert_eq!(attachment.mime_type, "audio/webm");
+        assert_eq!(attachment.size, 10);
+        assert_eq!(store.read(&attachment).unwrap().data, data);
+
+        assert!(matches!(
+            store.add(AttachmentKind::Audio, "video/mp4", &data),
+            Err(AttachmentError::UnsupportedType(_))
+        ));
+        assert!(matches!(
+            store.add(AttachmentKind::Audio, "audio/ogg", "not base64!"),
+            Err(AttachmentError::InvalidData)
+        ));
+
+        store.remove(&attachment).unwrap();
+        assert!(store.read(&attachment).is_err());
+        store.remove(&attachment).unwrap();
+
+        // IDs from imported data can't point outside the folder
+        let outside = Attachment {
+            id: "../../data".to_string(),
+            ..attachment.clone()
+        };
+        assert!(matches!(
+            store.read(&outside),
+            Err(AttachmentError::InvalidId(_))
+        ));
+        assert!(store.remove(&outside).is_err());
+
+        let drawing = serde_json::json!({ "strokes": [[[10, 20], [30, 40]]] });
+        let sketch = store.add_drawing(&drawing).unwrap();
+        assert_eq!(sketch.kind, AttachmentKind::Drawing);
+        let huge = serde_json::json!({ "strokes": vec![0; MAX_DRAWING_BYTES] });
+        assert!(matches!(
+            store.add_drawing(&huge),
+            Err(AttachmentError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            store.add_drawing(&serde_json::json!([1, 2])),
+            Err(AttachmentError::InvalidDrawing)
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+//! Headless entry point for scripted exports, backups and reports
+
+fn main() {
+    std::process::exit(matchuphelper_lib::cli::run(
+        std::env::args().skip(1).collect(),
+    ))
+}
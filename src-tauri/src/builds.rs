@@ -0,0 +1,141 @@
+//! Popular builds for a pairing, from a public builds site
+//!
+//! The source is a URL template from the settings with `{champion}`, `{enemy}`
+//! and `{role}` placeholders, answering with the most played runes, summoner
+//! spells and items:
+//! `{ "runes": ["Conqueror"], "summoner_spells": ["Flash"], "items": ["3078"] }`.
+//! A rune page with perk IDs may be given as `rune_page` too. Builds are only
+//! staged as an update for me to review; nothing is saved until I accept it.
+
+use crate::community;
+use crate::matchup::{MatchupUpdate, MatchupVersion, RunePage};
+use crate::settings::CommunityBuildSettings;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum BuildsError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Builds source returned HTTP {0}")]
+    Status(u16),
+    #[error("Unexpected builds source response")]
+    InvalidResponse,
+}
+
+/// What the source answers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct CommunityBuild {
+    pub runes: Vec<String>,
+    pub summoner_spells: Vec<String>,
+    /// Item IDs
+    pub items: Vec<String>,
+    pub rune_page: Option<RunePage>,
+}
+
+/// A build staged on top of the current notes, for review before saving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedBuild {
+    pub update: MatchupUpdate,
+    /// Host the build came from
+    pub source: String,
+}
+
+/// Check that a template can be filled in and sent
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if !template.starts_with("https://") {
+        return Err("Community builds URL must start with https://".to_string());
+    }
+    if !template.contains("{champion}") || !template.contains("{enemy}") {
+        return Err("Community builds URL needs {champion} and {enemy} placeholders".to_string());
+    }
+    Ok(())
+}
+
+/// The most popular build for the pairing, `None` when the source has none
+pub fn fetch(
+    settings: &CommunityBuildSettings,
+    my_champion: &str,
+    enemy_champion: &str,
+    role: &str,
+) -> Result<Option<(CommunityBuild, String)>, BuildsError> {
+    let template = settings.url_template.as_deref().unwrap_or_default();
+    let url = community::fill_template(template, my_champion, enemy_champion, role);
+    let response = reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()?
+        .get(&url)
+        .send()?;
+
+    match response.status() {
+        StatusCode::NOT_FOUND => return Ok(None),
+        status if !status.is_success() => return Err(BuildsError::Status(status.as_u16())),
+        _ => {}
+    }
+
+    let source = response.url().host_str().unwrap_or_default().to_string();
+    let build: CommunityBuild = response.json().map_err(|_| BuildsError::InvalidResponse)?;
+    if build
+        .rune_page
+        .as_ref()
+        .is_some_and(|page| page.validate().is_err())
+    {
+        return Err(BuildsError::InvalidResponse);
+    }
+    Ok(Some((build, source)))
+}
+
+/// The current notes with the build's runes, spells and items swapped in
+///
+/// Parts the source left empty keep what the notes already say.
+pub fn stage(current: Option<&MatchupVersion>, build: CommunityBuild) -> MatchupUpdate {
+    let mut update = current.map(MatchupUpdate::from_version).unwrap_or_default();
+    if !build.runes.is_empty() || build.rune_page.is_some() {
+        update.runes = match build.rune_page {
+            Some(ref page) if build.runes.is_empty() => page.rune_names(),
+            _ => build.runes,
+        };
+        update.rune_page = build.rune_page;
+    }
+    if !build.summoner_spells.is_empty() {
+        update.summoner_spells = build.summoner_spells;
+    }
+    if !build.items.is_empty() {
+        update.items = build.items;
+    }
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::Matchup;
+
+    #[test]
+    fn test_stage() {
+        let mut matchup =
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        matchup.add_version(MatchupUpdate {
+            notes: "Short trades".to_string(),
+            summoner_spells: vec!["Flash".to_string(), "Ghost".to_string()],
+            ..Default::default()
+        });
+        let build: CommunityBuild = serde_json::from_str(
+            r#"{ "runes": ["Conqueror", "Precision"], "items": ["6631", "3047"] }"#,
+        )
+        .unwrap();
+
+        let update = stage(matchup.current(), build);
+        assert_eq!(update.notes, "Short trades");
+        assert_eq!(update.runes, vec!["Conqueror", "Precision"]);
+        assert_eq!(update.summoner_spells, vec!["Flash", "Ghost"]);
+        assert_eq!(update.items, vec!["6631", "3047"]);
+
+        assert!(validate_template("https://builds.example.com/{champion}/{enemy}").is_ok());
+        assert!(validate_template("https://builds.example.com/{champion}").is_err());
+    }
+}
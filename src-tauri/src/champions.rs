@@ -0,0 +1,487 @@
+//! Champion names: the known roster, aliases and canonicalization
+//!
+//! Names are stored using Data Dragon IDs ("KogMaw", "MonkeyKing") so they
+//! line up with the champion icons used by the frontend.
+
+use crate::search::{edit_distance, normalize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Known champions as (champion ID, Data Dragon name)
+const CHAMPIONS: &[(i32, &str)] = &[
+    (1, "Annie"),
+    (2, "Olaf"),
+    (3, "Galio"),
+    (4, "TwistedFate"),
+    (5, "XinZhao"),
+    (6, "Urgot"),
+    (7, "Leblanc"),
+    (8, "Vladimir"),
+    (9, "Fiddlesticks"),
+    (10, "Kayle"),
+    (11, "MasterYi"),
+    (12, "Alistar"),
+    (13, "Ryze"),
+    (14, "Sion"),
+    (15, "Sivir"),
+    (16, "Soraka"),
+    (17, "Teemo"),
+    (18, "Tristana"),
+    (19, "Warwick"),
+    (20, "Nunu"),
+    (21, "MissFortune"),
+    (22, "Ashe"),
+    (23, "Tryndamere"),
+    (24, "Jax"),
+    (25, "Morgana"),
+    (26, "Zilean"),
+    (27, "Singed"),
+    (28, "Evelynn"),
+    (29, "Twitch"),
+    (30, "Karthus"),
+    (31, "Chogath"),
+    (32, "Amumu"),
+    (33, "Rammus"),
+    (34, "Anivia"),
+    (35, "Shaco"),
+    (36, "DrMundo"),
+    (37, "Sona"),
+    (38, "Kassadin"),
+    (39, "Irelia"),
+    (40, "Janna"),
+    (41, "Gangplank"),
+    (42, "Corki"),
+    (43, "Karma"),
+    (44, "Taric"),
+    (45, "Veigar"),
+    (48, "Trundle"),
+    (50, "Swain"),
+    (51, "Caitlyn"),
+    (53, "Blitzcrank"),
+    (54, "Malphite"),
+    (55, "Katarina"),
+    (56, "Nocturne"),
+    (57, "Maokai"),
+    (58, "Renekton"),
+    (59, "JarvanIV"),
+    (60, "Elise"),
+    (61, "Orianna"),
+    (62, "MonkeyKing"),
+    (63, "Brand"),
+    (64, "LeeSin"),
+    (67, "Vayne"),
+    (68, "Rumble"),
+    (69, "Cassiopeia"),
+    (72, "Skarner"),
+    (74, "Heimerdinger"),
+    (75, "Nasus"),
+    (76, "Nidalee"),
+    (77, "Udyr"),
+    (78, "Poppy"),
+    (79, "Gragas"),
+    (80, "Pantheon"),
+    (81, "Ezreal"),
+    (82, "Mordekaiser"),
+    (83, "Yorick"),
+    (84, "Akali"),
+    (85, "Kennen"),
+    (86, "Garen"),
+    (89, "Leona"),
+    (90, "Malzahar"),
+    (91, "Talon"),
+    (92, "Riven"),
+    (96, "KogMaw"),
+    (98, "Shen"),
+    (99, "Lux"),
+    (101, "Xerath"),
+    (102, "Shyvana"),
+    (103, "Ahri"),
+    (104, "Graves"),
+    (105, "Fizz"),
+    (106, "Volibear"),
+    (107, "Rengar"),
+    (110, "Varus"),
+    (111, "Nautilus"),
+    (112, "Viktor"),
+    (113, "Sejuani"),
+    (114, "Fiora"),
+    (115, "Ziggs"),
+    (117, "Lulu"),
+    (119, "Draven"),
+    (120, "Hecarim"),
+    (121, "Khazix"),
+    (122, "Darius"),
+    (126, "Jayce"),
+    (127, "Lissandra"),
+    (131, "Diana"),
+    (133, "Quinn"),
+    (134, "Syndra"),
+    (136, "AurelionSol"),
+    (141, "Kayn"),
+    (142, "Zoe"),
+    (143, "Zyra"),
+    (145, "Kaisa"),
+    (147, "Seraphine"),
+    (150, "Gnar"),
+    (154, "Zac"),
+    (157, "Yasuo"),
+    (161, "Velkoz"),
+    (163, "Taliyah"),
+    (164, "Camille"),
+    (166, "Akshan"),
+    (200, "Belveth"),
+    (201, "Braum"),
+    (202, "Jhin"),
+    (203, "Kindred"),
+    (221, "Zeri"),
+    (222, "Jinx"),
+    (223, "TahmKench"),
+    (233, "Briar"),
+    (234, "Viego"),
+    (235, "Senna"),
+    (236, "Lucian"),
+    (238, "Zed"),
+    (240, "Kled"),
+    (245, "Ekko"),
+    (246, "Qiyana"),
+    (254, "Vi"),
+    (266, "Aatrox"),
+    (267, "Nami"),
+    (268, "Azir"),
+    (350, "Yuumi"),
+    (360, "Samira"),
+    (412, "Thresh"),
+    (420, "Illaoi"),
+    (421, "RekSai"),
+    (427, "Ivern"),
+    (429, "Kalista"),
+    (432, "Bard"),
+    (497, "Rakan"),
+    (498, "Xayah"),
+    (516, "Ornn"),
+    (517, "Sylas"),
+    (518, "Neeko"),
+    (523, "Aphelios"),
+    (526, "Rell"),
+    (555, "Pyke"),
+    (711, "Vex"),
+    (777, "Yone"),
+    (799, "Ambessa"),
+    (800, "Mel"),
+    (804, "Yunara"),
+    (875, "Sett"),
+    (876, "Lillia"),
+    (887, "Gwen"),
+    (888, "Renata"),
+    (893, "Aurora"),
+    (895, "Nilah"),
+    (897, "KSante"),
+    (901, "Smolder"),
+    (902, "Milio"),
+    (910, "Hwei"),
+    (950, "Naafiri"),
+];
+
+/// Common nicknames and alternative spellings, keyed by normalized alias
+const ALIASES: &[(&str, &str)] = &[
+    ("asol", "AurelionSol"),
+    ("blitz", "Blitzcrank"),
+    ("cait", "Caitlyn"),
+    ("cass", "Cassiopeia"),
+    ("cho", "Chogath"),
+    ("ez", "Ezreal"),
+    ("fiddle", "Fiddlesticks"),
+    ("gp", "Gangplank"),
+    ("heimer", "Heimerdinger"),
+    ("j4", "JarvanIV"),
+    ("jarvan", "JarvanIV"),
+    ("kass", "Kassadin"),
+    ("kat", "Katarina"),
+    ("kha", "Khazix"),
+    ("kog", "KogMaw"),
+    ("lb", "Leblanc"),
+    ("lee", "LeeSin"),
+    ("malph", "Malphite"),
+    ("mf", "MissFortune"),
+    ("morde", "Mordekaiser"),
+    ("mundo", "DrMundo"),
+    ("noc", "Nocturne"),
+    ("nunuwillump", "Nunu"),
+    ("renataglasc", "Renata"),
+    ("sej", "Sejuani"),
+    ("tahm", "TahmKench"),
+    ("tf", "TwistedFate"),
+    ("trynd", "Tryndamere"),
+    ("vel", "Velkoz"),
+    ("voli", "Volibear"),
+    ("wukong", "MonkeyKing"),
+    ("ww", "Warwick"),
+    ("xin", "XinZhao"),
+    ("yi", "MasterYi"),
+];
+
+/// Maximum edit distance for "did you mean" suggestions
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// A roster of (champion ID, Data Dragon name), shared rather than copied by lookups
+type Roster = Arc<Vec<(i32, String)>>;
+
+/// Roster downloaded from Data Dragon; the embedded table is used while unset
+static ROSTER: RwLock<Option<Roster>> = RwLock::new(None);
+
+/// The embedded table as a roster, built on first use
+static EMBEDDED_ROSTER: LazyLock<Roster> = LazyLock::new(|| {
+    Arc::new(
+        CHAMPIONS
+            .iter()
+            .map(|(id, name)| (*id, name.to_string()))
+            .collect(),
+    )
+});
+
+/// Replace the embedded roster with one from Data Dragon
+pub fn set_roster(roster: Vec<(i32, String)>) {
+    if let Ok(mut current) = ROSTER.write() {
+        *current = (!roster.is_empty()).then(|| Arc::new(roster));
+    }
+}
+
+/// Display names in the chosen locale, keyed by Data Dragon name
+static DISPLAY_NAMES: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+/// Set the display names for the chosen locale
+pub fn set_display_names(names: HashMap<String, String>) {
+    if let Ok(mut current) = DISPLAY_NAMES.write() {
+        *current = Some(names);
+    }
+}
+
+/// Name to show for a canonical champion name, e.g. "Kog'Maw" for "KogMaw"
+pub fn display_name(name: &str) -> String {
+    let names = DISPLAY_NAMES.read().ok();
+    display_name_in(name, names.as_ref().and_then(|n| n.as_ref()))
+}
+
+fn display_name_in(name: &str, names: Option<&HashMap<String, String>>) -> String {
+    names
+        .and_then(|names| names.get(name).cloned())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Canonical name for a localized display name
+fn name_for_display_name(key: &str, names: Option<&HashMap<String, String>>) -> Option<String> {
+    names?
+        .iter()
+        .find(|(_, display)| normalize(display) == key)
+        .map(|(name, _)| name.clone())
+}
+
+/// Nicknames from the settings as (normalized alias, canonical name)
+static CUSTOM_ALIASES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// Set my own nicknames, alias to champion; unknown champions are skipped
+///
+/// Real champion names always win over an alias spelled the same way.
+pub fn set_custom_aliases(aliases: &HashMap<String, String>) {
+    let resolved = resolve_custom_aliases(aliases);
+    if let Ok(mut current) = CUSTOM_ALIASES.write() {
+        *current = resolved;
+    }
+}
+
+/// My nicknames as (normalized alias, canonical name), without unknown champions
+fn resolve_custom_aliases(aliases: &HashMap<String, String>) -> Vec<(String, String)> {
+    aliases
+        .iter()
+        .filter_map(|(alias, champion)| {
+            let key = normalize(alias);
+            let name = canonicalize(champion).ok()?;
+            (!key.is_empty()).then_some((key, name))
+        })
+        .collect()
+}
+
+/// Canonical name for one of my own nicknames
+fn name_for_custom_alias(key: &str, custom: &[(String, String)]) -> Option<String> {
+    custom
+        .iter()
+        .find(|(alias, _)| alias == key)
+        .map(|(_, name)| name.clone())
+}
+
+/// Built-in and custom nicknames of a canonical champion name, normalized
+pub fn aliases(name: &str) -> Vec<String> {
+    let custom = CUSTOM_ALIASES.read().ok();
+    aliases_with(name, custom.as_deref().map_or(&[], Vec::as_slice))
+}
+
+fn aliases_with(name: &str, custom: &[(String, String)]) -> Vec<String> {
+    ALIASES
+        .iter()
+        .filter(|(_, champion)| champion.eq_ignore_ascii_case(name))
+        .map(|(alias, _)| alias.to_string())
+        .chain(
+            custom
+                .iter()
+                .filter(|(_, champion)| champion.eq_ignore_ascii_case(name))
+                .map(|(alias, _)| alias.clone()),
+        )
+        .collect()
+}
+
+/// The embedded roster, for use when Data Dragon can't be reached
+pub fn embedded_roster() -> impl Iterator<Item = (i32, &'static str)> {
+    CHAMPIONS.iter().copied()
+}
+
+/// Every known champion as (champion ID, Data Dragon name)
+fn roster() -> Roster {
+    ROSTER
+        .read()
+        .ok()
+        .and_then(|roster| roster.clone())
+        .unwrap_or_else(|| EMBEDDED_ROSTER.clone())
+}
+
+/// Data Dragon name for a champion ID
+pub fn name_for_id(id: i32) -> Option<String> {
+    roster()
+        .iter()
+        .find(|(champion_id, _)| *champion_id == id)
+        .map(|(_, name)| name.clone())
+}
+
+/// Champion ID for a canonical (Data Dragon) name
+pub fn id_for_name(name: &str) -> Option<i32> {
+    roster()
+        .iter()
+        .find(|(_, champion)| champion.eq_ignore_ascii_case(name))
+        .map(|(id, _)| *id)
+}
+
+/// Resolve a user-typed champion name or alias to its canonical name
+pub fn canonicalize(input: &str) -> Result<String, String> {
+    let display_names = DISPLAY_NAMES.read().ok();
+    let custom = CUSTOM_ALIASES.read().ok();
+    canonicalize_with(
+        input,
+        display_names.as_ref().and_then(|n| n.as_ref()),
+        custom.as_deref().map_or(&[], Vec::as_slice),
+    )
+}
+
+/// `canonicalize` with `display_names` for the chosen locale and `custom` as
+/// my own nicknames
+fn canonicalize_with(
+    input: &str,
+    display_names: Option<&HashMap<String, String>>,
+    custom: &[(String, String)],
+) -> Result<String, String> {
+    let key = normalize(input);
+    if key.is_empty() {
+        return Err("Champion name cannot be empty".to_string());
+    }
+
+    let roster = roster();
+    if let Some((_, name)) = roster.iter().find(|(_, name)| normalize(name) == key) {
+        return Ok(name.clone());
+    }
+    if let Some(name) = name_for_display_name(&key, display_names) {
+        return Ok(name);
+    }
+    if let Some(name) = name_for_custom_alias(&key, custom) {
+        return Ok(name);
+    }
+    if let Some((_, name)) = ALIASES.iter().find(|(alias, _)| *alias == key) {
+        return Ok(name.to_string());
+    }
+
+    let closest = roster
+        .iter()
+        .map(|(_, name)| (edit_distance(&key, &normalize(name)), name))
+        .min_by_key(|(distance, _)| *distance);
+
+    match closest {
+        Some((distance, name)) if distance <= MAX_SUGGESTION_DISTANCE => Err(format!(
+            "Unknown champion '{}'. Did you mean {}?",
+            input.trim(),
+            name
+        )),
+        _ => Err(format!("Unknown champion '{}'", input.trim())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_spellings() {
+        assert_eq!(canonicalize("Kog'Maw").unwrap(), "KogMaw");
+        assert_eq!(canonicalize("kogmaw").unwrap(), "KogMaw");
+        assert_eq!(canonicalize("  Dr. Mundo ").unwrap(), "DrMundo");
+        assert_eq!(canonicalize("LeBlanc").unwrap(), "Leblanc");
+    }
+
+    #[test]
+    fn test_aliases() {
+        assert_eq!(canonicalize("MF").unwrap(), "MissFortune");
+        assert_eq!(canonicalize("J4").unwrap(), "JarvanIV");
+        assert_eq!(canonicalize("Yi").unwrap(), "MasterYi");
+        assert_eq!(canonicalize("Wukong").unwrap(), "MonkeyKing");
+    }
+
+    #[test]
+    fn test_custom_aliases() {
+        // Kept out of the shared aliases so other tests don't see them
+        let custom = resolve_custom_aliases(&HashMap::from([
+            ("Cardmaster".to_string(), "tf".to_string()),
+            ("Garen".to_string(), "Darius".to_string()),
+            ("nobody".to_string(), "Nobody".to_string()),
+        ]));
+
+        assert_eq!(
+            canonicalize_with("card master", None, &custom).unwrap(),
+            "TwistedFate"
+        );
+        // Real names can't be taken over
+        assert_eq!(canonicalize_with("garen", None, &custom).unwrap(), "Garen");
+        assert!(canonicalize_with("nobody", None, &custom).is_err());
+        assert_eq!(
+            aliases_with("TwistedFate", &custom),
+            vec!["tf", "cardmaster"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        let err = canonicalize("Dariuss").unwrap_err();
+        assert!(err.contains("Did you mean Darius?"));
+
+        let err = canonicalize("Teemoooooo").unwrap_err();
+        assert!(!err.contains("Did you mean"));
+
+        assert!(canonicalize("").is_err());
+    }
+
+    #[test]
+    fn test_name_for_id() {
+        assert_eq!(name_for_id(122), Some("Darius".to_string()));
+        assert_eq!(id_for_name("darius"), Some(122));
+        assert_eq!(name_for_id(62), Some("MonkeyKing".to_string()));
+        assert_eq!(name_for_id(-1), None);
+    }
+
+    #[test]
+    fn test_localized_names() {
+        // Kept out of the shared names so other tests don't see them
+        let names = HashMap::from([("Nunu".to_string(), "Nunu y Willump".to_string())]);
+
+        assert_eq!(display_name_in("Nunu", Some(&names)), "Nunu y Willump");
+        assert_eq!(display_name_in("Darius", Some(&names)), "Darius");
+        assert_eq!(
+            canonicalize_with("nunu y willump", Some(&names), &[]).unwrap(),
+            "Nunu"
+        );
+    }
+}
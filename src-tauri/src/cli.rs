@@ -0,0 +1,331 @@
+//! Command line tool for scripting backups and reports without the GUI
+//!
+//! Built as the `matchuphelper-cli` binary. Works on the same data directory as
+//! the app, so avoid importing while the app is open: its next save would
+//! overwrite the imported data.
+
+use crate::error::{AppError, ErrorCode};
+use crate::matchup::MatchResult;
+use crate::settings::{SettingsError, SettingsStore};
+use crate::storage::{AppData, Storage, StorageError, DEFAULT_BACKUPS_KEPT};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: matchuphelper-cli [--data-dir <dir>] <command>
+
+Commands:
+  export <file>                Write all matchups and matches to <file>
+  import <file> [--replace]    Add the matchups and matches in <file> that are
+                               missing, or replace everything with --replace
+  backup [<dir>] [--keep <n>]  Copy the data file into <dir> (the data
+                               directory's backups folder by default), keeping
+                               the newest <n> backups (14 by default)
+  report [--json]              Print win rates per matchup
+";
+
+/// Run the command line tool, returning the process exit code
+pub fn run(args: Vec<String>) -> i32 {
+    match execute(args) {
+        Ok(()) => 0,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            2
+        }
+        Err(CliError::App(e)) => {
+            match e.details {
+                Some(details) => eprintln!("Error: {} ({})", e.message, details),
+                None => eprintln!("Error: {}", e.message),
+            }
+            1
+        }
+    }
+}
+
+enum CliError {
+    Usage(String),
+    App(AppError),
+}
+
+impl From<AppError> for CliError {
+    fn from(e: AppError) -> Self {
+        CliError::App(e)
+    }
+}
+
+impl From<StorageError> for CliError {
+    fn from(e: StorageError) -> Self {
+        CliError::App(e.into())
+    }
+}
+
+impl From<SettingsError> for CliError {
+    fn from(e: SettingsError) -> Self {
+        CliError::App(e.into())
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::App(e.into())
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(e: serde_json::Error) -> Self {
+        CliError::App(AppError::new(ErrorCode::StorageFailed, e.to_string()))
+    }
+}
+
+fn usage(message: &str) -> CliError {
+    CliError::Usage(message.to_string())
+}
+
+fn execute(args: Vec<String>) -> Result<(), CliError> {
+    let mut args = args.into_iter().peekable();
+    let mut data_dir = None;
+    if args.peek().map(String::as_str) == Some("--data-dir") {
+        args.next();
+        data_dir = Some(PathBuf::from(
+            args.next()
+                .ok_or_else(|| usage("--data-dir needs a directory"))?,
+        ));
+    }
+
+    let command = args.next().ok_or_else(|| usage("No command given"))?;
+    let rest: Vec<String> = args.collect();
+    if matches!(command.as_str(), "help" | "--help" | "-h") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let storage = open_storage(data_dir)?;
+    match command.as_str() {
+        "export" => export(&storage, &rest),
+        "import" => import(&storage, &rest),
+        "backup" => backup(&storage, &rest),
+        "report" => report(&storage, &rest),
+        other => Err(usage(&format!("Unknown command: {}", other))),
+    }
+}
+
+/// The app's storage, honouring the data directory from its settings
+fn open_storage(data_dir: Option<PathBuf>) -> Result<Storage, CliError> {
+    let data_dir = match data_dir {
+        Some(dir) => Some(dir),
+        None => SettingsStore::new()?.load()?.data_directory,
+    };
+    Ok(Storage::new(data_dir)?)
+}
+
+fn export(storage: &Storage, args: &[String]) -> Result<(), CliError> {
+    let [file] = args else {
+        return Err(usage("export needs a file"));
+    };
+
+    let data = storage.load()?;
+    fs::write(file, serde_json::to_string_pretty(&data)?)?;
+    println!(
+        "Exported {} matchups and {} matches to {}",
+        data.matchups.len(),
+        data.matches.len(),
+        file
+    );
+    Ok(())
+}
+
+fn import(storage: &Storage, args: &[String]) -> Result<(), CliError> {
+    let (file, replace) = match args {
+        [file] => (file, false),
+        [file, flag] if flag == "--replace" => (file, true),
+        _ => return Err(usage("import needs a file and optionally --replace")),
+    };
+
+    let contents = fs::read_to_string(file)?;
+    let mut imported: AppData = serde_json::from_str(&contents)
+        .map_err(|e| AppError::validation(format!("{} is not an export: {}", file, e)))?;
+    imported.upgrade();
+
+    if replace {
+        // Replacing can't be undone from the app, so keep what was there
+        let backup = storage.backup(&storage.backups_dir(), DEFAULT_BACKUPS_KEPT)?;
+        println!("Backed up the current data to {}", backup.display());
+        storage.save(&mut imported)?;
+        println!(
+            "Replaced the data with {} matchups and {} matches",
+            imported.matchups.len(),
+            imported.matches.len()
+        );
+        return Ok(());
+    }
+
+    let mut data = storage.load()?;
+    let summary = data.merge(imported);
+    storage.save(&mut data)?;
+    println!(
+        "Added {} matchups, {} matches, {} team notes, {} drafts, {} reminders, {} collections, {} scratchpad notes and {} practice targets",
+        summary.matchups,
+        summary.matches,
+        summary.team_notes,
+        summary.drafts,
+        summary.reminders,
+        summary.collections,
+        summary.scratch_notes,
+        summary.plan_targets
+    );
+    Ok(())
+}
+
+fn backup(storage: &Storage, args: &[String]) -> Result<(), CliError> {
+    let mut dir = None;
+    let mut keep = DEFAULT_BACKUPS_KEPT;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--keep" {
+            keep = args
+                .next()
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .ok_or_else(|| usage("--keep needs a number above 0"))?;
+        } else if dir.is_none() {
+            dir = Some(PathBuf::from(arg));
+        } else {
+            return Err(usage(&format!("Unexpected argument: {}", arg)));
+        }
+    }
+
+    let dir = dir.unwrap_or_else(|| storage.backups_dir());
+    let path = storage.backup(&dir, keep)?;
+    println!(
+        "Backed up {} to {}",
+        storage.data_path().display(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Overall and per-matchup results
+#[derive(Debug, Serialize)]
+struct Report {
+    matchups: usize,
+    games: u32,
+    wins: u32,
+    /// Matchups with games, most played first
+    by_matchup: Vec<MatchupReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct MatchupReport {
+    matchup_id: String,
+    my_champion: String,
+    enemy_champion: String,
+    role: String,
+    games: u32,
+    wins: u32,
+}
+
+fn build_report(data: &AppData) -> Report {
+    let mut by_matchup: Vec<MatchupReport> = data
+        .matchups
+        .values()
+        .filter_map(|matchup| {
+            let record = matchup.record(data.matches.values());
+            (record.games > 0).then(|| MatchupReport {
+                matchup_id: matchup.id.clone(),
+                my_champion: matchup.my_champion.clone(),
+                enemy_champion: matchup.enemy_champion.clone(),
+                role: matchup.role.clone(),
+                games: record.games,
+                wins: record.wins,
+            })
+        })
+        .collect();
+    by_matchup.sort_by(|a, b| {
+        b.games
+            .cmp(&a.games)
+            .then_with(|| a.my_champion.cmp(&b.my_champion))
+            .then_with(|| a.enemy_champion.cmp(&b.enemy_champion))
+    });
+
+    Report {
+        matchups: data.matchups.len(),
+        games: data.matches.len() as u32,
+        wins: data
+            .matches
+            .values()
+            .filter(|m| m.result == MatchResult::Win)
+            .count() as u32,
+        by_matchup,
+    }
+}
+
+fn report(storage: &Storage, args: &[String]) -> Result<(), CliError> {
+    let json = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return Err(usage("report only takes --json")),
+    };
+
+    let report = build_report(&storage.load()?);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} matchups, {} games, {} win rate",
+        report.matchups,
+        report.games,
+        percent(report.wins, report.games)
+    );
+    for m in &report.by_matchup {
+        println!(
+            "{:<32} {:>4} games {:>5}",
+            format!("{} vs {} ({})", m.my_champion, m.enemy_champion, m.role),
+            m.games,
+            percent(m.wins, m.games)
+        );
+    }
+    Ok(())
+}
+
+fn percent(wins: u32, games: u32) -> String {
+    if games == 0 {
+        return "-".to_string();
+    }
+    format!("{:.0}%", wins as f64 / games as f64 * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::{Match, Matchup};
+
+    #[test]
+    fn test_build_report() {
+        let mut data = AppData::default();
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        for result in [MatchResult::Win, MatchResult::Win, MatchResult::Loss] {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.linked_matchup = Some(matchup.id.clone());
+            data.matches.insert(m.id.clone(), m);
+        }
+        data.matchups.insert(matchup.id.clone(), matchup);
+        let unplayed = Matchup::new("Riven".to_string(), "Fiora".to_string(), "top".to_string());
+        data.matchups.insert(unplayed.id.clone(), unplayed);
+
+        let report = build_report(&data);
+        assert_eq!(report.matchups, 2);
+        assert_eq!((report.games, report.wins), (3, 2));
+        assert_eq!(report.by_matchup.len(), 1);
+        assert_eq!(report.by_matchup[0].wins, 2);
+        assert_eq!(percent(2, 3), "67%");
+    }
+}
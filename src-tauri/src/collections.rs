@@ -0,0 +1,163 @@
+//! Collections: named, ordered groups of matchups
+//!
+//! For example "Clash prep vs Team X" or "Most common lanes". A matchup can be
+//! in any number of collections, and collections are listed in my own order.
+
+use crate::matchup::Matchup;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Longest collection name
+const MAX_NAME_LENGTH: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// In display order
+    pub matchup_ids: Vec<String>,
+    /// Place among the collections, lowest first
+    #[serde(default)]
+    pub position: u32,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// User-provided fields of a collection, for creating and editing
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CollectionInput {
+    pub name: String,
+    pub description: String,
+    pub matchup_ids: Vec<String>,
+}
+
+impl CollectionInput {
+    /// Check the name and drop repeated matchups, keeping the first place
+    pub fn normalize(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err("Collection name cannot be empty".to_string());
+        }
+        if self.name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!(
+                "Collection name is limited to {} characters",
+                MAX_NAME_LENGTH
+            ));
+        }
+        self.description = self.description.trim().to_string();
+        let mut seen = Vec::new();
+        self.matchup_ids.retain(|id| {
+            let first = !seen.contains(id);
+            seen.push(id.clone());
+            first
+        });
+        Ok(self)
+    }
+}
+
+impl Collection {
+    /// Create a collection from normalized input, placed after the others
+    pub fn new(input: CollectionInput, position: u32) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: input.name,
+            description: input.description,
+            matchup_ids: input.matchup_ids,
+            position,
+            created: now,
+            updated: now,
+        }
+    }
+
+    /// Replace the contents with normalized input
+    pub fn update(&mut self, input: CollectionInput) {
+        self.name = input.name;
+        self.description = input.description;
+        self.matchup_ids = input.matchup_ids;
+        self.updated = Utc::now();
+    }
+
+    /// The collection's matchups in its order; deleted ones are skipped
+    pub fn matchups(&self, matchups: &HashMap<String, Matchup>) -> Vec<Matchup> {
+        self.matchup_ids
+            .iter()
+            .filter_map(|id| matchups.get(id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Collections in my order
+pub fn sorted<'a>(collections: impl IntoIterator<Item = &'a Collection>) -> Vec<Collection> {
+    let mut collections: Vec<Collection> = collections.into_iter().cloned().collect();
+    collections.sort_by(|a, b| {
+        a.position
+            .cmp(&b.position)
+            .then_with(|| a.created.cmp(&b.created))
+    });
+    collections
+}
+
+/// Put the collections in the order of `ids`; collections not listed go last
+pub fn reorder(collections: &mut HashMap<String, Collection>, ids: &[String]) {
+    let rest: Vec<String> = sorted(collections.values())
+        .into_iter()
+        .map(|c| c.id)
+        .filter(|id| !ids.contains(id))
+        .collect();
+    for (position, id) in ids.iter().chain(&rest).enumerate() {
+        if let Some(collection) = collections.get_mut(id) {
+            collection.position = position as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collections() {
+        let garen = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let matchups = HashMap::from([(garen.id.clone(), garen.clone())]);
+
+        let input = CollectionInput {
+            name: " Clash prep ".to_string(),
+            matchup_ids: vec![garen.id.clone(), "deleted".to_string(), garen.id.clone()],
+            ..Default::default()
+        }
+        .normalize()
+        .unwrap();
+        assert_eq!(input.name, "Clash prep");
+        assert_eq!(input.matchup_ids.len(), 2);
+
+        let clash = Collection::new(input, 0);
+        assert_eq!(clash.matchups(&matchups).len(), 1);
+        let common = Collection::new(
+            CollectionInput {
+                name: "Common lanes".to_string(),
+                ..Default::default()
+            },
+            1,
+        );
+
+        let mut collections = HashMap::from([
+            (clash.id.clone(), clash.clone()),
+            (common.id.clone(), common.clone()),
+        ]);
+        reorder(&mut collections, std::slice::from_ref(&common.id));
+        let order: Vec<String> = sorted(collections.values())
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(order, vec!["Common lanes", "Clash prep"]);
+
+        assert!(CollectionInput::default().normalize().is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Community win rates for a champion pairing, from a public stats site
+//!
+//! The source is a URL template from the settings with `{champion}`, `{enemy}`
+//! and `{role}` placeholders, answering `{ "win_rate": 0.52, "games": 1234 }`
+//! with the win rate as a fraction. Answers are cached on disk and refreshed
+//! at most every `refresh_hours`; a `429 Too Many Requests` pauses requests
+//! for as long as the source asks, and cached numbers are used meanwhile.
+
+use crate::settings::CommunityStatsSettings;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Cache file name in the data directory
+pub const COMMUNITY_CACHE_FILE: &str = "community_stats.json";
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Shortest wait between two requests to the source
+const MIN_REQUEST_INTERVAL_SECONDS: i64 = 2;
+
+/// Pause after a `429` that doesn't say how long to wait
+const DEFAULT_RETRY_AFTER_SECONDS: i64 = 60;
+
+#[derive(Error, Debug)]
+pub enum CommunityError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Stats source returned HTTP {0}")]
+    Status(u16),
+    #[error("Stats source is rate limiting requests")]
+    RateLimited { retry_after: Duration },
+    #[error("Unexpected stats source response")]
+    InvalidResponse,
+}
+
+/// Everyone's results in a pairing, as reported by the source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommunityWinRate {
+    /// 0.0..=1.0
+    pub win_rate: f64,
+    pub games: Option<u64>,
+    /// Host the numbers came from
+    pub source: String,
+    pub fetched: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceResponse {
+    win_rate: f64,
+    #[serde(default)]
+    games: Option<u64>,
+}
+
+/// Answers by pairing, saved next to the data file
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct Cache {
+    entries: HashMap<String, CacheEntry>,
+    last_request: Option<DateTime<Utc>>,
+    /// Set by a `429`; no requests are sent before this
+    blocked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    fetched: DateTime<Utc>,
+    /// `None` when the source had no numbers for the pairing
+    stats: Option<CommunityWinRate>,
+}
+
+pub struct CommunityStats {
+    cache_path: PathBuf,
+    settings: CommunityStatsSettings,
+}
+
+impl CommunityStats {
+    /// `None` unless the integration is enabled and has a source
+    pub fn new(cache_path: PathBuf, settings: CommunityStatsSettings) -> Option<Self> {
+        (settings.enabled && settings.url_template.is_some()).then_some(Self {
+            cache_path,
+            settings,
+        })
+    }
+
+    /// Community win rate of the pairing, from the cache when it's fresh enough
+    ///
+    /// While requests are paused or when the source fails, cached numbers are
+    /// returned however old they are; the error only surfaces without any.
+    pub fn win_rate(
+        &self,
+        my_champion: &str,
+        enemy_champion: &str,
+        role: &str,
+    ) -> Result<Option<CommunityWinRate>, CommunityError> {
+        let mut cache = self.load_cache();
+        let key = cache_key(my_champion, enemy_champion, role);
+        let result = lookup(&mut cache, &key, Utc::now(), self.refresh(), || {
+            self.fetch(my_champion, enemy_champion, role)
+        });
+        self.save_cache(&cache);
+        result
+    }
+
+    fn refresh(&self) -> Duration {
+        Duration::hours(self.settings.refresh_hours.max(1) as i64)
+    }
+
+    fn fetch(
+        &self,
+        my_champion: &str,
+        enemy_champion: &str,
+        role: &str,
+    ) -> Result<Option<CommunityWinRate>, CommunityError> {
+        let template = self.settings.url_template.as_deref().unwrap_or_default();
+        let url = fill_template(template, my_champion, enemy_champion, role);
+        let response = reqwest::blocking::Client::builder()
+            .timeout(TIMEOUT)
+            .build()?
+            .get(&url)
+            .send()?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => return Ok(None),
+            StatusCode::TOO_MANY_REQUESTS => {
+                let seconds = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_RETRY_AFTER_SECONDS);
+                return Err(CommunityError::RateLimited {
+                    retry_after: Duration::seconds(seconds),
+                });
+            }
+            status if !status.is_success() => return Err(CommunityError::Status(status.as_u16())),
+            _ => {}
+        }
+
+        let source = response.url().host_str().unwrap_or_default().to_string();
+        let body: SourceResponse = response
+            .json()
+            .map_err(|_| CommunityError::InvalidResponse)?;
+        if !(0.0..=1.0).contains(&body.win_rate) {
+            return Err(CommunityError::InvalidResponse);
+        }
+
+        Ok(Some(CommunityWinRate {
+            win_rate: body.win_rate,
+            games: body.games,
+            source,
+            fetched: Utc::now(),
+        }))
+    }
+
+    fn load_cache(&self) -> Cache {
+        fs::read_to_string(&self.cache_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// The cache only saves requests, so failing to write it is just logged
+    fn save_cache(&self, cache: &Cache) {
+        let written = serde_json::to_string(cache)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| fs::write(&self.cache_path, contents));
+        if let Err(e) = written {
+            tracing::warn!("Could not save community stats cache: {}", e);
+        }
+    }
+}
+
+/// Check that a template can be filled in and sent
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if !template.starts_with("https://") {
+        return Err("Community stats URL must start with https://".to_string());
+    }
+    if !template.contains("{champion}") || !template.contains("{enemy}") {
+        return Err("Community stats URL needs {champion} and {enemy} placeholders".to_string());
+    }
+    Ok(())
+}
+
+/// Fill in the placeholders, lowercased as sources expect them
+pub fn fill_template(
+    template: &str,
+    my_champion: &str,
+    enemy_champion: &str,
+    role: &str,
+) -> String {
+    template
+        .replace("{champion}", &my_champion.to_lowercase())
+        .replace("{enemy}", &enemy_champion.to_lowercase())
+        .replace("{role}", &role.to_lowercase())
+}
+
+fn cache_key(my_champion: &str, enemy_champion: &str, role: &str) -> String {
+    format!("{}:{}:{}", my_champion, enemy_champion, role).to_lowercase()
+}
+
+/// Use the cached entry for `key` if it's fresh, otherwise `fetch` if allowed
+fn lookup(
+    cache: &mut Cache,
+    key: &str,
+    now: DateTime<Utc>,
+    refresh: Duration,
+    fetch: impl FnOnce() -> Result<Option<CommunityWinRate>, CommunityError>,
+) -> Result<Option<CommunityWinRate>, CommunityError> {
+    let cached = cache.entries.get(key).cloned();
+    if let Some(ref entry) = cached {
+        if now - entry.fetched < refresh {
+            return Ok(entry.stats.clone());
+        }
+    }
+
+    let blocked = cache.blocked_until.is_some_and(|until| now < until)
+        || cache
+            .last_request
+            .is_some_and(|last| now - last < Duration::seconds(MIN_REQUEST_INTERVAL_SECONDS));
+    if blocked {
+        return Ok(cached.and_then(|entry| entry.stats));
+    }
+
+    cache.last_request = Some(now);
+    match fetch() {
+        Ok(stats) => {
+            cache.blocked_until = None;
+            cache.entries.insert(
+                key.to_string(),
+                CacheEntry {
+                    fetched: now,
+                    stats: stats.clone(),
+                },
+            );
+            Ok(stats)
+        }
+        Err(e) => {
+            if let CommunityError::RateLimited { retry_after } = e {
+                cache.blocked_until = Some(now + retry_after);
+            }
+            match cached {
+                Some(entry) => Ok(entry.stats),
+                None => Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_respects_cache_and_rate_limits() {
+        let now = Utc::now();
+        let stats = CommunityWinRate {
+            win_rate: 0.52,
+            games: Some(1000),
+            source: "stats.example.com".to_string(),
+            fetched: now,
+        };
+        let mut cache = Cache::default();
+        let refresh = Duration::hours(24);
+
+        let fetched = lookup(&mut cache, "darius:garen:top", now, refresh, || {
+            Ok(Some(stats.clone()))
+        });
+        assert_eq!(fetched.unwrap(), Some(stats.clone()));
+
+        // Fresh entries are not fetched again
+        let later = now + Duration::hours(1);
+        let cached = lookup(&mut cache, "darius:garen:top", later, refresh, || {
+            panic!("fetched a fresh entry")
+        });
+        assert_eq!(cached.unwrap(), Some(stats.clone()));
+
+        // A 429 keeps the stale numbers and pauses requests
+        let stale = now + Duration::hours(25);
+        let limited = lookup(&mut cache, "darius:garen:top", stale, refresh, || {
+            Err(CommunityError::RateLimited {
+                retry_after: Duration::minutes(5),
+            })
+        });
+        assert_eq!(limited.unwrap(), Some(stats));
+        let paused = lookup(
+            &mut cache,
+            "ahri:zed:mid",
+            stale + Duration::minutes(1),
+            refresh,
+            || panic!("fetched while rate limited"),
+        );
+        assert_eq!(paused.unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(
+            validate_template("https://stats.example.com/{champion}/{enemy}?role={role}").is_ok()
+        );
+        assert!(validate_template("http://stats.example.com/{champion}/{enemy}").is_err());
+        assert!(validate_template("https://stats.example.com/{champion}").is_err());
+        assert_eq!(
+            fill_template(
+                "https://s.example.com/{champion}/{enemy}/{role}",
+                "LeeSin",
+                "Khazix",
+                "jungle"
+            ),
+            "https://s.example.com/leesin/khazix/jungle"
+        );
+    }
+}
@@ -0,0 +1,866 @@
+//! Data Dragon: Riot's static game data CDN
+//!
+//! Files are downloaded once per patch into the app data directory. When the
+//! CDN can't be reached the newest cached patch is used, and failing that the
+//! embedded champion table.
+
+use crate::champions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use thiserror::Error;
+
+const DDRAGON_URL: &str = "https://ddragon.leagueoflegends.com";
+
+/// Locale used until one is chosen
+pub const DEFAULT_LOCALE: &str = "en_US";
+
+#[derive(Error, Debug)]
+pub enum DdragonError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unexpected Data Dragon response")]
+    InvalidResponse,
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+    #[error("Invalid locale: {0}")]
+    InvalidLocale(String),
+}
+
+/// Kinds of icons kept in the offline cache
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetKind {
+    Champion,
+    Item,
+    Rune,
+}
+
+impl AssetKind {
+    fn dir(self) -> &'static str {
+        match self {
+            AssetKind::Champion => "champion",
+            AssetKind::Item => "item",
+            AssetKind::Rune => "rune",
+        }
+    }
+}
+
+/// Where to load an icon from
+///
+/// `path` is set once the icon is cached and is meant for `convertFileSrc`;
+/// `url` is the CDN fallback.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AssetLocation {
+    pub path: Option<String>,
+    pub url: String,
+}
+
+/// A champion as listed in `champion.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChampionInfo {
+    pub id: i32,
+    /// Data Dragon name, e.g. "KogMaw"
+    pub name: String,
+    /// Name as shown in game, e.g. "Kog'Maw"
+    pub display_name: String,
+    pub title: String,
+    /// Square icon URL; `None` for the embedded fallback
+    pub icon: Option<String>,
+}
+
+/// An item from `item.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemInfo {
+    pub id: i32,
+    pub name: String,
+    /// Total cost in gold
+    pub gold: i32,
+    pub tags: Vec<String>,
+    pub icon: Option<String>,
+}
+
+/// A rune tree from `runesReforged.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuneTreeInfo {
+    pub id: i32,
+    pub name: String,
+    pub icon: String,
+    /// Runes by row, keystones first
+    pub slots: Vec<Vec<RuneInfo>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuneInfo {
+    pub id: i32,
+    pub name: String,
+    pub icon: String,
+    pub short_desc: String,
+}
+
+/// A summoner spell from `summoner.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SummonerSpellInfo {
+    pub id: i32,
+    pub name: String,
+    /// Base cooldown in seconds
+    pub cooldown: f64,
+    /// Game modes the spell is available in, e.g. "CLASSIC"
+    pub modes: Vec<String>,
+    pub icon: Option<String>,
+}
+
+/// A champion's abilities from its `champion/<name>.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChampionAbilities {
+    pub champion: String,
+    pub passive: String,
+    /// Q, W, E and R in order
+    pub abilities: Vec<AbilityInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AbilityInfo {
+    /// "Q", "W", "E" or "R"
+    pub key: String,
+    pub name: String,
+    /// Cooldown in seconds per rank
+    pub cooldowns: Vec<f64>,
+    /// Range per rank
+    pub ranges: Vec<f64>,
+}
+
+/// Downloads and caches Data Dragon files
+///
+/// Shared between commands and the startup refresh. Files are downloaded
+/// without holding the lock on the loaded data, so a slow CDN only holds up
+/// the callers waiting for that file.
+pub struct DataDragon {
+    http_client: reqwest::blocking::Client,
+    cache_dir: PathBuf,
+    loaded: Mutex<Loaded>,
+}
+
+/// Data loaded so far, all for one patch and locale
+#[derive(Default)]
+struct Loaded {
+    /// Language of names and descriptions, e.g. "es_ES"
+    locale: String,
+    version: Option<String>,
+    champions: Vec<ChampionInfo>,
+    items: Option<Vec<ItemInfo>>,
+    runes: Option<Vec<RuneTreeInfo>>,
+    spells: Option<Vec<SummonerSpellInfo>>,
+    abilities: HashMap<String, ChampionAbilities>,
+}
+
+/// Patch and locale to download files for
+struct Source {
+    version: String,
+    locale: String,
+}
+
+impl DataDragon {
+    pub fn new(cache_dir: PathBuf, locale: Option<String>) -> Self {
+        Self {
+            http_client: reqwest::blocking::Client::new(),
+            cache_dir,
+            loaded: Mutex::new(Loaded {
+                locale: locale.unwrap_or_else(|| DEFAULT_LOCALE.to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn loaded(&self) -> MutexGuard<'_, Loaded> {
+        // The data is replaced whole, so a panic elsewhere can't leave it half written
+        self.loaded.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Data Dragon version of the loaded data, e.g. "14.3.1"
+    pub fn version(&self) -> Option<String> {
+        self.loaded().version.clone()
+    }
+
+    /// Switch the language of names and descriptions; takes effect on the next `refresh`
+    pub fn set_locale(&self, locale: &str) -> Result<(), DdragonError> {
+        if !is_valid_locale(locale) {
+            return Err(DdragonError::InvalidLocale(locale.to_string()));
+        }
+
+        let mut loaded = self.loaded();
+        loaded.locale = locale.to_string();
+        loaded.version = None;
+        Ok(())
+    }
+
+    /// Load the current patch's data, downloading it if needed
+    ///
+    /// Also makes the downloaded roster, with localized display names, the one
+    /// used for champion names.
+    pub fn refresh(&self) -> Result<(), DdragonError> {
+        let locale = self.loaded().locale.clone();
+        let version = match self.latest_version() {
+            Ok(version) => version,
+            Err(e) => self.newest_cached_version(&locale).ok_or(e)?,
+        };
+        let source = Source { version, locale };
+        let champions =
+            parse_champions(&self.data_file(&source, "champion.json")?, &source.version)?;
+
+        let mut loaded = self.loaded();
+        // The locale changed while downloading; that change refreshes again
+        if loaded.locale != source.locale {
+            return Ok(());
+        }
+        champions::set_roster(champions.iter().map(|c| (c.id, c.name.clone())).collect());
+        champions::set_display_names(
+            champions
+                .iter()
+                .map(|c| (c.name.clone(), c.display_name.clone()))
+                .collect(),
+        );
+        if loaded.version.as_ref() != Some(&source.version) {
+            loaded.items = None;
+            loaded.runes = None;
+            loaded.spells = None;
+            loaded.abilities.clear();
+        }
+        loaded.champions = champions;
+        loaded.version = Some(source.version);
+        Ok(())
+    }
+
+    /// Patch and locale of the loaded data, refreshing first if nothing is loaded
+    fn source(&self) -> Result<Source, DdragonError> {
+        if self.loaded().version.is_none() {
+            self.refresh()?;
+        }
+        let loaded = self.loaded();
+        Ok(Source {
+            version: loaded
+                .version
+                .clone()
+                .ok_or(DdragonError::InvalidResponse)?,
+            locale: loaded.locale.clone(),
+        })
+    }
+
+    /// A cached value, or one loaded from a file of the current patch
+    ///
+    /// `cached` reads and `store` keeps the value; the file is read or
+    /// downloaded with the lock released, and only kept when the patch and
+    /// locale are still the same by then.
+    fn cached<T: Clone>(
+        &self,
+        cached: impl Fn(&Loaded) -> Option<&T>,
+        load: impl FnOnce(&Source) -> Result<T, DdragonError>,
+        store: impl FnOnce(&mut Loaded, T),
+    ) -> Result<T, DdragonError> {
+        if let Some(value) = cached(&self.loaded()) {
+            return Ok(value.clone());
+        }
+
+        let source = self.source()?;
+        let value = load(&source)?;
+        let mut loaded = self.loaded();
+        if loaded.version.as_ref() == Some(&source.version) && loaded.locale == source.locale {
+            store(&mut loaded, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Items purchasable on Summoner's Rift
+    pub fn items(&self) -> Result<Vec<ItemInfo>, DdragonError> {
+        self.cached(
+            |loaded| loaded.items.as_ref(),
+            |source| parse_items(&self.data_file(source, "item.json")?, &source.version),
+            |loaded, items| loaded.items = Some(items),
+        )
+    }
+
+    /// Rune trees with their runes
+    pub fn runes(&self) -> Result<Vec<RuneTreeInfo>, DdragonError> {
+        self.cached(
+            |loaded| loaded.runes.as_ref(),
+            |source| parse_runes(&self.data_file(source, "runesReforged.json")?),
+            |loaded, runes| loaded.runes = Some(runes),
+        )
+    }
+
+    /// Every summoner spell
+    pub fn spells(&self) -> Result<Vec<SummonerSpellInfo>, DdragonError> {
+        self.cached(
+            |loaded| loaded.spells.as_ref(),
+            |source| parse_spells(&self.data_file(source, "summoner.json")?, &source.version),
+            |loaded, spells| loaded.spells = Some(spells),
+        )
+    }
+
+    /// Abilities of a champion, by Data Dragon name
+    pub fn abilities(&self, champion: &str) -> Result<ChampionAbilities, DdragonError> {
+        self.cached(
+            |loaded| loaded.abilities.get(champion),
+            |source| {
+                let file = format!("champion/{}.json", champion);
+                parse_abilities(&self.data_file(source, &file)?, champion)
+            },
+            |loaded, abilities| {
+                loaded.abilities.insert(champion.to_string(), abilities);
+            },
+        )
+    }
+
+    /// Every champion, from the embedded table if nothing was loaded yet
+    pub fn champions(&self) -> Vec<ChampionInfo> {
+        let loaded = self.loaded();
+        if !loaded.champions.is_empty() {
+            return loaded.champions.clone();
+        }
+
+        champions::embedded_roster()
+            .map(|(id, name)| ChampionInfo {
+                id,
+                name: name.to_string(),
+                display_name: name.to_string(),
+                title: String::new(),
+                icon: None,
+            })
+            .collect()
+    }
+
+    /// Folder the icons are cached in
+    pub fn assets_dir(&self) -> PathBuf {
+        self.cache_dir.join("img")
+    }
+
+    /// Cache file for an icon; `key` is the champion name, item ID or rune ID
+    fn asset_path(&self, kind: AssetKind, key: &str) -> PathBuf {
+        self.assets_dir()
+            .join(kind.dir())
+            .join(format!("{}.png", key))
+    }
+
+    /// Every icon of a kind as (key, CDN URL)
+    fn asset_urls(&self, kind: AssetKind) -> Result<Vec<(String, String)>, DdragonError> {
+        Ok(match kind {
+            AssetKind::Champion => {
+                self.source()?;
+                self.loaded()
+                    .champions
+                    .iter()
+                    .filter_map(|c| Some((c.name.clone(), c.icon.clone()?)))
+                    .collect()
+            }
+            AssetKind::Item => self
+                .items()?
+                .into_iter()
+                .filter_map(|i| Some((i.id.to_string(), i.icon?)))
+                .collect(),
+            AssetKind::Rune => self
+                .runes()?
+                .into_iter()
+                .flat_map(|tree| {
+                    let runes: Vec<(String, String)> = tree
+                        .slots
+                        .into_iter()
+                        .flatten()
+                        .map(|rune| (rune.id.to_string(), rune.icon))
+                        .collect();
+                    std::iter::once((tree.id.to_string(), tree.icon)).chain(runes)
+                })
+                .collect(),
+        })
+    }
+
+    /// Cached path and CDN URL for an icon
+    pub fn resolve_asset(&self, kind: AssetKind, key: &str) -> Result<AssetLocation, DdragonError> {
+        let path = self.asset_path(kind, key);
+        let url = self
+            .asset_urls(kind)?
+            .into_iter()
+            .find(|(asset_key, _)| asset_key.eq_ignore_ascii_case(key))
+            .map(|(_, url)| url)
+            .ok_or_else(|| DdragonError::UnknownAsset(key.to_string()))?;
+
+        Ok(AssetLocation {
+            path: path.exists().then(|| path.to_string_lossy().into_owned()),
+            url,
+        })
+    }
+
+    /// Icons not cached yet, as (CDN URL, cache file)
+    pub fn missing_assets(&self) -> Result<Vec<(String, PathBuf)>, DdragonError> {
+        let mut missing = Vec::new();
+        for kind in [AssetKind::Champion, AssetKind::Item, AssetKind::Rune] {
+            for (key, url) in self.asset_urls(kind)? {
+                let path = self.asset_path(kind, &key);
+                if !path.exists() {
+                    missing.push((url, path));
+                }
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Newest version listed by the CDN
+    fn latest_version(&self) -> Result<String, DdragonError> {
+        let versions: Vec<String> = self
+            .http_client
+            .get(format!("{}/api/versions.json", DDRAGON_URL))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        versions
+            .into_iter()
+            .next()
+            .ok_or(DdragonError::InvalidResponse)
+    }
+
+    /// Newest version with a cached `champion.json` in `locale`
+    fn newest_cached_version(&self, locale: &str) -> Option<String> {
+        fs::read_dir(&self.cache_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join(locale).join("champion.json").exists())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .max_by_key(|version| version_key(version))
+    }
+
+    /// A `data/<locale>` file of `source`, from the cache or the CDN
+    fn data_file(&self, source: &Source, file: &str) -> Result<Value, DdragonError> {
+        let path = self
+            .cache_dir
+            .join(&source.version)
+            .join(&source.locale)
+            .join(file);
+        if path.exists() {
+            return Ok(serde_json::from_str(&fs::read_to_string(path)?)?);
+        }
+
+        let contents = self
+            .http_client
+            .get(format!(
+                "{}/cdn/{}/data/{}/{}",
+                DDRAGON_URL, source.version, source.locale, file
+            ))
+            .send()?
+            .error_for_status()?
+            .text()?;
+        let json = serde_json::from_str(&contents)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+        Ok(json)
+    }
+}
+
+/// Download one icon into the cache
+pub fn download_asset(
+    http_client: &reqwest::blocking::Client,
+    url: &str,
+    path: &Path,
+) -> Result<(), DdragonError> {
+    let bytes = http_client.get(url).send()?.error_for_status()?.bytes()?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Whether `locale` looks like a Data Dragon locale ("en_US", "es_ES")
+fn is_valid_locale(locale: &str) -> bool {
+    match locale.split_once('_') {
+        Some((language, region)) => {
+            language.len() == 2
+                && region.len() == 2
+                && language.chars().all(|c| c.is_ascii_lowercase())
+                && region.chars().all(|c| c.is_ascii_uppercase())
+        }
+        None => false,
+    }
+}
+
+/// Numeric parts of a version, for ordering "14.10.1" after "14.9.1"
+fn version_key(version: &str) -> Vec<u32> {
+    version.split('.').filter_map(|p| p.parse().ok()).collect()
+}
+
+/// Parse `champion.json` into champions sorted by name
+pub fn parse_champions(json: &Value, version: &str) -> Result<Vec<ChampionInfo>, DdragonError> {
+    let data = json
+        .get("data")
+        .and_then(|d| d.as_object())
+        .ok_or(DdragonError::InvalidResponse)?;
+
+    let mut champions: Vec<ChampionInfo> = data
+        .values()
+        .filter_map(|champion| {
+            let id = champion.get("key")?.as_str()?.parse().ok()?;
+            let name = champion.get("id")?.as_str()?.to_string();
+            let icon = image_url(champion, version, "champion");
+
+            Some(ChampionInfo {
+                id,
+                display_name: champion
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or(&name)
+                    .to_string(),
+                title: str_field(champion, "title"),
+                name,
+                icon,
+            })
+        })
+        .collect();
+    champions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(champions)
+}
+
+/// Icon URL for an `image.full` file in a versioned image folder
+fn image_url(entry: &Value, version: &str, folder: &str) -> Option<String> {
+    entry
+        .get("image")
+        .and_then(|i| i.get("full"))
+        .and_then(|f| f.as_str())
+        .map(|file| format!("{}/cdn/{}/img/{}/{}", DDRAGON_URL, version, folder, file))
+}
+
+fn str_field(entry: &Value, key: &str) -> String {
+    entry
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Parse `item.json`, keeping purchasable Summoner's Rift items sorted by ID
+pub fn parse_items(json: &Value, version: &str) -> Result<Vec<ItemInfo>, DdragonError> {
+    let data = json
+        .get("data")
+        .and_then(|d| d.as_object())
+        .ok_or(DdragonError::InvalidResponse)?;
+
+    let mut items: Vec<ItemInfo> = data
+        .iter()
+        .filter(|(_, item)| {
+            let purchasable = item
+                .pointer("/gold/purchasable")
+                .and_then(|p| p.as_bool())
+                .unwrap_or(false);
+            let on_rift = item
+                .pointer("/maps/11")
+                .and_then(|m| m.as_bool())
+                .unwrap_or(false);
+            purchasable && on_rift
+        })
+        .filter_map(|(id, item)| {
+            Some(ItemInfo {
+                id: id.parse().ok()?,
+                name: str_field(item, "name"),
+                gold: item
+                    .pointer("/gold/total")
+                    .and_then(|g| g.as_i64())
+                    .unwrap_or(0) as i32,
+                tags: item
+                    .get("tags")
+                    .and_then(|t| t.as_array())
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(|t| t.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                icon: image_url(item, version, "item"),
+            })
+        })
+        .collect();
+    items.sort_by_key(|item| item.id);
+
+    Ok(items)
+}
+
+/// Parse `runesReforged.json`
+pub fn parse_runes(json: &Value) -> Result<Vec<RuneTreeInfo>, DdragonError> {
+    let trees = json.as_array().ok_or(DdragonError::InvalidResponse)?;
+    let icon_url = |entry: &Value| format!("{}/cdn/img/{}", DDRAGON_URL, str_field(entry, "icon"));
+
+    Ok(trees
+        .iter()
+        .filter_map(|tree| {
+            let slots = tree
+                .get("slots")?
+                .as_array()?
+                .iter()
+                .map(|slot| {
+                    slot.get("runes")
+                        .and_then(|r| r.as_array())
+                        .map(|runes| {
+                            runes
+                                .iter()
+                                .filter_map(|rune| {
+                                    Some(RuneInfo {
+                                        id: rune.get("id")?.as_i64()? as i32,
+                                        name: str_field(rune, "name"),
+                                        icon: icon_url(rune),
+                                        short_desc: str_field(rune, "shortDesc"),
+                                    })
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect();
+
+            Some(RuneTreeInfo {
+                id: tree.get("id")?.as_i64()? as i32,
+                name: str_field(tree, "name"),
+                icon: icon_url(tree),
+                slots,
+            })
+        })
+        .collect())
+}
+
+/// Parse `summoner.json` into spells sorted by ID
+pub fn parse_spells(json: &Value, version: &str) -> Result<Vec<SummonerSpellInfo>, DdragonError> {
+    let data = json
+        .get("data")
+        .and_then(|d| d.as_object())
+        .ok_or(DdragonError::InvalidResponse)?;
+
+    let mut spells: Vec<SummonerSpellInfo> = data
+        .values()
+        .filter_map(|spell| {
+            Some(SummonerSpellInfo {
+                id: spell.get("key")?.as_str()?.parse().ok()?,
+                name: str_field(spell, "name"),
+                cooldown: spell
+                    .pointer("/cooldown/0")
+                    .and_then(|c| c.as_f64())
+                    .unwrap_or(0.0),
+                modes: spell
+                    .get("modes")
+                    .and_then(|m| m.as_array())
+                    .map(|modes| {
+                        modes
+                            .iter()
+                            .filter_map(|m| m.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                icon: image_url(spell, version, "spell"),
+            })
+        })
+        .collect();
+    spells.sort_by_key(|spell| spell.id);
+
+    Ok(spells)
+}
+
+/// Numbers of a per-rank array such as `cooldown` or `range`
+fn f64_array(entry: &Value, key: &str) -> Vec<f64> {
+    entry
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|values| values.iter().filter_map(|v| v.as_f64()).collect())
+        .unwrap_or_default()
+}
+
+/// Parse a single champion file into its abilities
+pub fn parse_abilities(json: &Value, champion: &str) -> Result<ChampionAbilities, DdragonError> {
+    let data = json
+        .get("data")
+        .and_then(|d| d.get(champion))
+        .ok_or(DdragonError::InvalidResponse)?;
+    let spells = data
+        .get("spells")
+        .and_then(|s| s.as_array())
+        .ok_or(DdragonError::InvalidResponse)?;
+
+    Ok(ChampionAbilities {
+        champion: champion.to_string(),
+        passive: data
+            .get("passive")
+            .map(|p| str_field(p, "name"))
+            .unwrap_or_default(),
+        abilities: spells
+            .iter()
+            .zip(["Q", "W", "E", "R"])
+            .map(|(spell, key)| AbilityInfo {
+                key: key.to_string(),
+                name: str_field(spell, "name"),
+                cooldowns: f64_array(spell, "cooldown"),
+                ranges: f64_array(spell, "range"),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_champions() {
+        let json = serde_json::json!({
+            "type": "champion",
+            "data": {
+                "KogMaw": {
+                    "id": "KogMaw",
+                    "key": "96",
+                    "name": "Kog'Maw",
+                    "title": "the Mouth of the Abyss",
+                    "image": { "full": "KogMaw.png" }
+                },
+                "Aatrox": {
+                    "id": "Aatrox",
+                    "key": "266",
+                    "name": "Aatrox",
+                    "title": "the Darkin Blade",
+                    "image": { "full": "Aatrox.png" }
+                }
+            }
+        });
+
+        let champions = parse_champions(&json, "14.3.1").unwrap();
+        assert_eq!(champions.len(), 2);
+        assert_eq!(champions[0].name, "Aatrox");
+        assert_eq!(champions[1].id, 96);
+        assert_eq!(champions[1].display_name, "Kog'Maw");
+        assert_eq!(
+            champions[1].icon.as_deref(),
+            Some("https://ddragon.leagueoflegends.com/cdn/14.3.1/img/champion/KogMaw.png")
+        );
+
+        assert!(parse_champions(&serde_json::json!({}), "14.3.1").is_err());
+    }
+
+    #[test]
+    fn test_is_valid_locale() {
+        assert!(is_valid_locale("es_ES"));
+        assert!(!is_valid_locale("es"));
+        assert!(!is_valid_locale("../../etc"));
+    }
+
+    #[test]
+    fn test_version_key() {
+        assert!(version_key("14.10.1") > version_key("14.9.1"));
+    }
+
+    #[test]
+    fn test_parse_items() {
+        let json = serde_json::json!({
+            "data": {
+                "3071": {
+                    "name": "Black Cleaver",
+                    "gold": { "total": 3000, "purchasable": true },
+                    "tags": ["Damage", "ArmorPenetration"],
+                    "maps": { "11": true, "12": true },
+                    "image": { "full": "3071.png" }
+                },
+                "3400": {
+                    "name": "Your Cut",
+                    "gold": { "total": 0, "purchasable": false },
+                    "maps": { "11": true }
+                }
+            }
+        });
+
+        let items = parse_items(&json, "14.3.1").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, 3071);
+        assert_eq!(items[0].gold, 3000);
+        assert_eq!(items[0].tags, vec!["Damage", "ArmorPenetration"]);
+    }
+
+    #[test]
+    fn test_parse_runes() {
+        let json = serde_json::json!([{
+            "id": 8000,
+            "name": "Precision",
+            "icon": "perk-images/Styles/7201_Precision.png",
+            "slots": [
+                { "runes": [{ "id": 8010, "name": "Conqueror", "icon": "c.png", "shortDesc": "Stack up" }] },
+                { "runes": [{ "id": 9111, "name": "Triumph", "icon": "t.png", "shortDesc": "Heal" }] }
+            ]
+        }]);
+
+        let trees = parse_runes(&json).unwrap();
+        assert_eq!(trees[0].id, 8000);
+        assert_eq!(trees[0].slots.len(), 2);
+        assert_eq!(trees[0].slots[0][0].name, "Conqueror");
+        assert_eq!(
+            trees[0].icon,
+            "https://ddragon.leagueoflegends.com/cdn/img/perk-images/Styles/7201_Precision.png"
+        );
+    }
+
+    #[test]
+    fn test_parse_spells() {
+        let json = serde_json::json!({
+            "data": {
+                "SummonerFlash": {
+                    "key": "4",
+                    "name": "Flash",
+                    "cooldown": [300.0],
+                    "modes": ["CLASSIC", "ARAM"],
+                    "image": { "full": "SummonerFlash.png" }
+                }
+            }
+        });
+
+        let spells = parse_spells(&json, "14.3.1").unwrap();
+        assert_eq!(spells[0].id, 4);
+        assert_eq!(spells[0].cooldown, 300.0);
+        assert_eq!(spells[0].modes, vec!["CLASSIC", "ARAM"]);
+    }
+
+    #[test]
+    fn test_asset_path() {
+        let ddragon = DataDragon::new(PathBuf::from("cache"), None);
+        assert_eq!(
+            ddragon.asset_path(AssetKind::Item, "3071"),
+            PathBuf::from("cache/img/item/3071.png")
+        );
+    }
+
+    #[test]
+    fn test_parse_abilities() {
+        let json = serde_json::json!({
+            "data": {
+                "Irelia": {
+                    "passive": { "name": "Ionian Fervor" },
+                    "spells": [
+                        { "name": "Bladesurge", "cooldown": [11, 10, 9, 8, 7], "range": [600, 600, 600, 600, 600] },
+                        { "name": "Defiant Dance", "cooldown": [20, 18, 16, 14, 12], "range": [775] },
+                        { "name": "Flawless Duet", "cooldown": [16, 15, 14, 13, 12], "range": [775] },
+                        { "name": "Vanguard's Edge", "cooldown": [140, 120, 100], "range": [1000] }
+                    ]
+                }
+            }
+        });
+
+        let abilities = parse_abilities(&json, "Irelia").unwrap();
+        assert_eq!(abilities.passive, "Ionian Fervor");
+        assert_eq!(abilities.abilities.len(), 4);
+        assert_eq!(abilities.abilities[2].key, "E");
+        assert_eq!(abilities.abilities[2].cooldowns[0], 16.0);
+        assert_eq!(abilities.abilities[3].cooldowns, vec![140.0, 120.0, 100.0]);
+
+        assert!(parse_abilities(&json, "Darius").is_err());
+    }
+}
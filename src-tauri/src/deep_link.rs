@@ -0,0 +1,87 @@
+//! `matchuphelper://matchup/<id>` links, as posted to the webhook, open the matchup
+//!
+//! A link opened while the app runs is handed over by the second instance it
+//! starts. The matchup is kept until the main window takes it with
+//! `take_linked_matchup`, so a link that starts the app isn't lost before the
+//! window is listening.
+
+use crate::webhook::MATCHUP_LINK_PREFIX;
+use crate::AppState;
+use tauri::plugin::TauriPlugin;
+use tauri::{App, AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_deep_link::DeepLinkExt;
+use uuid::Uuid;
+
+const MAIN_WINDOW: &str = "main";
+
+/// Keeps a second start of the app, e.g. by a link, to the running one
+///
+/// Registered before any other plugin, as the single instance plugin requires.
+/// Its deep link support passes the link on to `on_open_url`.
+pub fn single_instance() -> TauriPlugin<Wry> {
+    tauri_plugin_single_instance::init(|app, _args, _cwd| show_main(app))
+}
+
+/// Register the link scheme and follow the links the app is opened with
+pub fn setup(app: &App) -> tauri::Result<()> {
+    app.handle().plugin(tauri_plugin_deep_link::init())?;
+    // Installers register the scheme; this covers portable and development
+    // builds. A failure only means links don't open the app.
+    #[cfg(any(windows, target_os = "linux"))]
+    let _ = app.deep_link().register_all();
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, url.as_str());
+        }
+    });
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app.handle(), url.as_str());
+        }
+    }
+    Ok(())
+}
+
+/// The matchup ID in a matchup link
+fn matchup_id(url: &str) -> Option<&str> {
+    let id = url.strip_prefix(MATCHUP_LINK_PREFIX)?.trim_end_matches('/');
+    Uuid::parse_str(id).is_ok().then_some(id)
+}
+
+/// Keep the matchup of a link for the main window and tell it to take it
+fn open(app: &AppHandle, url: &str) {
+    let Some(id) = matchup_id(url) else {
+        tracing::warn!("Ignoring link {}", url);
+        return;
+    };
+    if let Ok(mut linked) = app.state::<AppState>().linked_matchup.lock() {
+        *linked = Some(id.to_string());
+    }
+    show_main(app);
+    let _ = app.emit_to(MAIN_WINDOW, "matchup-link-opened", ());
+}
+
+fn show_main(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matchup_id() {
+        let id = Uuid::new_v4().to_string();
+        let link = crate::webhook::matchup_link(&id);
+        assert_eq!(matchup_id(&link), Some(id.as_str()));
+        assert_eq!(matchup_id(&format!("{}/", link)), Some(id.as_str()));
+        assert_eq!(matchup_id("matchuphelper://matchup/../settings"), None);
+        assert_eq!(matchup_id("https://example.com"), None);
+    }
+}
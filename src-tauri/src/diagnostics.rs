@@ -0,0 +1,206 @@
+//! Diagnostics bundle to attach to bug reports
+//!
+//! A zip with the newest log files and a `diagnostics.json` describing the
+//! system, the saved data and the client connection. The saved data is only
+//! described by counts and settings: no notes, champions or account names.
+
+use crate::lcu::LcuClient;
+use crate::logging;
+use crate::settings::Settings;
+use crate::storage::{AppData, Storage, StorageError};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use sysinfo::System;
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Log files included, newest first
+const MAX_LOG_FILES: usize = 5;
+
+/// Only the end of longer log files is included
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct Diagnostics {
+    app_version: &'static str,
+    created: DateTime<Utc>,
+    system: SystemInfo,
+    storage: StorageSummary,
+    client: ClientInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct SystemInfo {
+    os: &'static str,
+    arch: &'static str,
+    /// e.g. "Windows 11 Pro", when it can be read
+    os_version: Option<String>,
+}
+
+/// What the saved data holds, without any of its text
+#[derive(Debug, Serialize)]
+struct StorageSummary {
+    schema_version: String,
+    last_updated: String,
+    data_file_bytes: Option<u64>,
+    matchups: usize,
+    versions: usize,
+    goals: usize,
+    matches: usize,
+    /// Matches brought in from the client or the Riot API
+    imported_matches: usize,
+    accounts: usize,
+    current_patch: Option<String>,
+    locale: Option<String>,
+    match_retention: bool,
+    riot_api_configured: bool,
+}
+
+impl StorageSummary {
+    fn new(data: &AppData) -> Self {
+        let metadata = &data.metadata;
+        Self {
+            schema_version: metadata.version.clone(),
+            last_updated: metadata.last_updated.clone(),
+            data_file_bytes: None,
+            matchups: data.matchups.len(),
+            versions: data.matchups.values().map(|m| m.versions.len()).sum(),
+            goals: data.matchups.values().map(|m| m.goals.len()).sum(),
+            matches: data.matches.len(),
+            imported_matches: data
+                .matches
+                .values()
+                .filter(|m| m.game_id.is_some())
+                .count(),
+            accounts: metadata.accounts.len(),
+            current_patch: metadata.current_patch.clone(),
+            locale: metadata.locale.clone(),
+            match_retention: metadata.match_retention.is_some(),
+            riot_api_configured: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ClientInfo {
+    connected: bool,
+    port: Option<u16>,
+    /// A riotgames.pem in the data directory replaces the built-in one
+    root_ca_override: bool,
+    insecure_allowed: bool,
+    auto_reconnect: bool,
+}
+
+impl Diagnostics {
+    /// Describe the saved data in `storage`, the state of `client` and which
+    /// integrations `settings` turn on
+    pub fn collect(
+        storage: &Storage,
+        client: &LcuClient,
+        settings: &Settings,
+    ) -> Result<Self, StorageError> {
+        let data = storage.load()?;
+        let mut summary = StorageSummary::new(&data);
+        summary.data_file_bytes = fs::metadata(storage.data_path()).ok().map(|m| m.len());
+        summary.riot_api_configured = settings.riot_api.is_some();
+
+        let status = client.status();
+        Ok(Self {
+            app_version: env!("CARGO_PKG_VERSION"),
+            created: Utc::now(),
+            system: SystemInfo {
+                os: std::env::consts::OS,
+                arch: std::env::consts::ARCH,
+                os_version: System::long_os_version(),
+            },
+            storage: summary,
+            client: ClientInfo {
+                connected: status.connected,
+                port: status.port,
+                root_ca_override: storage.riot_root_ca_path().exists(),
+                insecure_allowed: settings.allow_insecure_lcu,
+                auto_reconnect: client.auto_reconnect(),
+            },
+        })
+    }
+}
+
+/// Write the bundle to `path`, with the newest log files from `logs_dir`
+pub fn write_bundle(path: &Path, diagnostics: &Diagnostics, logs_dir: &Path) -> ZipResult<()> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(File::create(path)?);
+
+    zip.start_file("diagnostics.json", options)?;
+    zip.write_all(
+        serde_json::to_string_pretty(diagnostics)
+            .unwrap_or_default()
+            .as_bytes(),
+    )?;
+
+    for log in logging::log_files(logs_dir).into_iter().take(MAX_LOG_FILES) {
+        let Some(name) = log.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        zip.start_file(format!("logs/{}", name), options)?;
+        zip.write_all(&read_tail(&log)?)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn read_tail(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(MAX_LOG_BYTES)))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::{Matchup, MatchupUpdate};
+
+    #[test]
+    fn test_bundle_leaves_out_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(dir.path().to_path_buf())).unwrap();
+        let mut data = AppData::default();
+        let mut matchup =
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        matchup.add_version(MatchupUpdate {
+            notes: "Secret plan".to_string(),
+            ..Default::default()
+        });
+        data.matchups.insert(matchup.id.clone(), matchup);
+        storage.save(&mut data).unwrap();
+
+        let logs_dir = storage.logs_dir();
+        fs::create_dir_all(&logs_dir).unwrap();
+        fs::write(logs_dir.join("matchuphelper.log"), "connected").unwrap();
+
+        let diagnostics =
+            Diagnostics::collect(&storage, &LcuClient::new(), &Settings::default()).unwrap();
+        assert_eq!(diagnostics.storage.matchups, 1);
+        assert_eq!(diagnostics.storage.versions, 2);
+
+        let path = dir.path().join("bundle.zip");
+        write_bundle(&path, &diagnostics, &logs_dir).unwrap();
+
+        let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut json = String::new();
+        zip.by_name("diagnostics.json")
+            .unwrap()
+            .read_to_string(&mut json)
+            .unwrap();
+        assert!(!json.contains("Secret plan"));
+        assert!(!json.contains("Darius"));
+        assert!(zip.by_name("logs/matchuphelper.log").is_ok());
+    }
+}
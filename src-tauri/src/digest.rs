@@ -0,0 +1,328 @@
+//! Weekly review digest, the agenda for a Sunday review session
+//!
+//! Sums up the seven days before a point in time: games per matchup, the
+//! matchups whose win rate moved the most, matchups played without notes and
+//! goals completed. Returned as data and optionally written as Markdown.
+
+use crate::matchup::{self, Match, MatchRecord, MatchResult, Matchup, DEFAULT_MIN_NOTES_LENGTH};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Days covered by a digest
+const DIGEST_DAYS: i64 = 7;
+
+/// Win rate movers listed
+const MOVERS: usize = 5;
+
+/// Games needed before the week for a win rate change to mean something
+const MIN_MOVER_GAMES: u32 = 3;
+
+/// Games played in one pairing during the week
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DigestMatchup {
+    /// `None` when the pairing has no matchup yet
+    pub matchup_id: Option<String>,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+}
+
+/// A matchup whose win rate changed with this week's games
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WinRateMover {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    /// Win rate of the linked games before the week
+    pub before: f64,
+    /// Win rate including the week's games
+    pub after: f64,
+    pub games_this_week: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompletedGoal {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub text: String,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeeklyDigest {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub record: MatchRecord,
+    /// Most played first
+    pub matchups: Vec<DigestMatchup>,
+    /// Biggest change first
+    pub movers: Vec<WinRateMover>,
+    /// Pairings played this week without a matchup or with next to no notes
+    pub without_notes: Vec<DigestMatchup>,
+    /// Oldest first
+    pub goals_completed: Vec<CompletedGoal>,
+}
+
+impl WeeklyDigest {
+    /// The digest of the week up to `to`
+    pub fn new(matchups: &[&Matchup], matches: &[&Match], to: DateTime<Utc>) -> Self {
+        let from = to - Duration::days(DIGEST_DAYS);
+        let in_week = |date: DateTime<Utc>| date > from && date <= to;
+
+        let mut record = MatchRecord::default();
+        let mut pairings: HashMap<(String, String, String), DigestMatchup> = HashMap::new();
+        for m in matches.iter().filter(|m| in_week(m.date)) {
+            let won = m.result == MatchResult::Win;
+            record.games += 1;
+            record.wins += won as u32;
+            if !m.has_lane_opponent() || m.enemy_champion == "Unknown" {
+                continue;
+            }
+
+            let entry = pairings
+                .entry((
+                    m.my_champion.to_lowercase(),
+                    m.enemy_champion.to_lowercase(),
+                    m.role.to_lowercase(),
+                ))
+                .or_insert_with(|| DigestMatchup {
+                    matchup_id: None,
+                    my_champion: m.my_champion.clone(),
+                    enemy_champion: m.enemy_champion.clone(),
+                    role: m.role.clone(),
+                    record: MatchRecord::default(),
+                });
+            entry.record.games += 1;
+            entry.record.wins += won as u32;
+            if entry.matchup_id.is_none() {
+                entry.matchup_id = m.linked_matchup.clone().or_else(|| {
+                    matchup::find_matchup_for(matchups.iter().copied(), m).map(|mu| mu.id.clone())
+                });
+            }
+        }
+
+        let mut played: Vec<DigestMatchup> = pairings.into_values().collect();
+        played.sort_by(|a, b| {
+            b.record
+                .games
+                .cmp(&a.record.games)
+                .then_with(|| a.my_champion.cmp(&b.my_champion))
+                .then_with(|| a.enemy_champion.cmp(&b.enemy_champion))
+        });
+
+        let by_id: HashMap<&str, &Matchup> = matchups.iter().map(|m| (m.id.as_str(), *m)).collect();
+        let without_notes = played
+            .iter()
+            .filter(|p| {
+                p.matchup_id
+                    .as_deref()
+                    .and_then(|id| by_id.get(id))
+                    .is_none_or(|m| m.needs_notes(DEFAULT_MIN_NOTES_LENGTH))
+            })
+            .cloned()
+            .collect();
+
+        let mut movers: Vec<WinRateMover> = played
+            .iter()
+            .filter_map(|p| {
+                let matchup = by_id.get(p.matchup_id.as_deref()?)?;
+                let linked = |until: DateTime<Utc>| {
+                    let mut record = MatchRecord::default();
+                    for m in matches.iter().filter(|m| {
+                        m.linked_matchup.as_deref() == Some(matchup.id.as_str()) && m.date <= until
+                    }) {
+                        record.games += 1;
+                        record.wins += (m.result == MatchResult::Win) as u32;
+                    }
+                    record
+                };
+                let before = linked(from);
+                let after = linked(to);
+                if before.games < MIN_MOVER_GAMES || after.games == before.games {
+                    return None;
+                }
+                Some(WinRateMover {
+                    matchup_id: matchup.id.clone(),
+                    my_champion: matchup.my_champion.clone(),
+                    enemy_champion: matchup.enemy_champion.clone(),
+                    role: matchup.role.clone(),
+                    before: before.win_rate()?,
+                    after: after.win_rate()?,
+                    games_this_week: after.games - before.games,
+                })
+            })
+            .collect();
+        movers.sort_by(|a, b| {
+            (b.after - b.before)
+                .abs()
+                .total_cmp(&(a.after - a.before).abs())
+        });
+        movers.truncate(MOVERS);
+
+        let mut goals_completed: Vec<CompletedGoal> = matchups
+            .iter()
+            .flat_map(|m| {
+                m.goals.iter().filter_map(|goal| {
+                    let completed_at = goal.completed_at.filter(|at| goal.done && in_week(*at))?;
+                    Some(CompletedGoal {
+                        matchup_id: m.id.clone(),
+                        my_champion: m.my_champion.clone(),
+                        enemy_champion: m.enemy_champion.clone(),
+                        text: goal.text.clone(),
+                        completed_at,
+                    })
+                })
+            })
+            .collect();
+        goals_completed.sort_by_key(|g| g.completed_at);
+
+        Self {
+            from,
+            to,
+            record,
+            matchups: played,
+            movers,
+            without_notes,
+            goals_completed,
+        }
+    }
+
+    /// The digest as a review agenda
+    pub fn to_markdown(&self) -> String {
+        let day = |date: DateTime<Utc>| date.format("%Y-%m-%d").to_string();
+        let pairing = |my: &str, enemy: &str, role: &str| format!("{} vs {} ({})", my, enemy, role);
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# Weekly review, {} to {}\n",
+            day(self.from),
+            day(self.to)
+        );
+        let _ = writeln!(
+            out,
+            "{} games, {} wins, {} losses\n",
+            self.record.games,
+            self.record.wins,
+            self.record.games - self.record.wins
+        );
+
+        let _ = writeln!(out, "## Matchups played\n");
+        if self.matchups.is_empty() {
+            let _ = writeln!(out, "No lane games this week.\n");
+        } else {
+            for p in &self.matchups {
+                let _ = writeln!(
+                    out,
+                    "- {}: {} games, {} wins",
+                    pairing(&p.my_champion, &p.enemy_champion, &p.role),
+                    p.record.games,
+                    p.record.wins
+                );
+            }
+            out.push('\n');
+        }
+
+        if !self.movers.is_empty() {
+            let _ = writeln!(out, "## Win rate movers\n");
+            for m in &self.movers {
+                let _ = writeln!(
+                    out,
+                    "- {}: {:.0}% → {:.0}% ({} games this week)",
+                    pairing(&m.my_champion, &m.enemy_champion, &m.role),
+                    m.before * 100.0,
+                    m.after * 100.0,
+                    m.games_this_week
+                );
+            }
+            out.push('\n');
+        }
+
+        if !self.without_notes.is_empty() {
+            let _ = writeln!(out, "## Write notes for\n");
+            for p in &self.without_notes {
+                let _ = writeln!(
+                    out,
+                    "- [ ] {}",
+                    pairing(&p.my_champion, &p.enemy_champion, &p.role)
+                );
+            }
+            out.push('\n');
+        }
+
+        if !self.goals_completed.is_empty() {
+            let _ = writeln!(out, "## Goals completed\n");
+            for g in &self.goals_completed {
+                let _ = writeln!(
+                    out,
+                    "- {} vs {}: {}",
+                    g.my_champion, g.enemy_champion, g.text
+                );
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekly_digest() {
+        let mut garen = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        garen.add_goal("Respect level 6".to_string());
+        let goal_id = garen.goals[0].id.clone();
+        garen.set_goal_done(&goal_id, true);
+        let matchups = vec![&garen];
+        let now = Utc::now();
+
+        let game = |enemy: &str, days_ago: i64, result: MatchResult| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                enemy.to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = now - Duration::days(days_ago);
+            if enemy == "Garen" {
+                m.linked_matchup = Some(garen.id.clone());
+            }
+            m
+        };
+        let games = [
+            game("Garen", 20, MatchResult::Loss),
+            game("Garen", 19, MatchResult::Loss),
+            game("Garen", 18, MatchResult::Loss),
+            game("Garen", 2, MatchResult::Win),
+            game("Garen", 1, MatchResult::Win),
+            game("Teemo", 1, MatchResult::Loss),
+        ];
+        let matches: Vec<&Match> = games.iter().collect();
+
+        let digest = WeeklyDigest::new(&matchups, &matches, now);
+        assert_eq!(digest.record, MatchRecord { games: 3, wins: 2 });
+        assert_eq!(digest.matchups[0].enemy_champion, "Garen");
+        assert_eq!(digest.matchups[0].matchup_id.as_ref(), Some(&garen.id));
+        assert_eq!(digest.movers.len(), 1);
+        assert_eq!(digest.movers[0].before, 0.0);
+        assert_eq!(digest.movers[0].after, 0.4);
+        // Garen has no notes written yet either
+        assert_eq!(digest.without_notes.len(), 2);
+        assert_eq!(digest.goals_completed.len(), 1);
+
+        let markdown = digest.to_markdown();
+        assert!(markdown.contains("- Darius vs Garen (top): 2 games, 2 wins"));
+        assert!(markdown.contains("- [ ] Darius vs Teemo (top)"));
+        assert!(markdown.contains("Respect level 6"));
+    }
+}
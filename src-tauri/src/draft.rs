@@ -0,0 +1,215 @@
+//! Drafts: the full pick and ban sequence of a game, with notes per pick
+//!
+//! Drafts can be entered by hand or rebuilt from the client's champ select
+//! session, and are reviewed next to the matchup notes of the game.
+
+use crate::champions;
+use crate::matchup::MatchResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Bans or picks per draft, for both teams together
+const MAX_ACTIONS_OF_KIND: usize = 10;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Blue,
+    Red,
+}
+
+impl Side {
+    /// Side of a match history team ID, 100 for blue and 200 for red
+    pub fn from_team_id(team_id: i64) -> Option<Self> {
+        match team_id {
+            100 => Some(Side::Blue),
+            200 => Some(Side::Red),
+            _ => None,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Blue => Side::Red,
+            Side::Red => Side::Blue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftActionKind {
+    Ban,
+    Pick,
+}
+
+/// One ban or pick, in draft order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DraftAction {
+    pub kind: DraftActionKind,
+    pub side: Side,
+    pub champion: String,
+    /// Normalized role of the picking player, when known
+    #[serde(default)]
+    pub position: Option<String>,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub id: String,
+    pub date: DateTime<Utc>,
+    /// My team's side
+    pub my_side: Side,
+    pub actions: Vec<DraftAction>,
+    #[serde(default)]
+    pub result: Option<MatchResult>,
+    #[serde(default)]
+    pub notes: String,
+    /// Match the draft was played in
+    #[serde(default)]
+    pub linked_match: Option<String>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// User-provided fields of a draft, for creating and editing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftInput {
+    /// Defaults to now
+    #[serde(default)]
+    pub date: Option<DateTime<Utc>>,
+    pub my_side: Side,
+    #[serde(default)]
+    pub actions: Vec<DraftAction>,
+    #[serde(default)]
+    pub result: Option<MatchResult>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub linked_match: Option<String>,
+}
+
+impl DraftInput {
+    /// Check the sequence and resolve champion names to their canonical form
+    pub fn normalize(mut self) -> Result<Self, String> {
+        for kind in [DraftActionKind::Ban, DraftActionKind::Pick] {
+            if self.actions.iter().filter(|a| a.kind == kind).count() > MAX_ACTIONS_OF_KIND {
+                return Err(format!(
+                    "A draft has at most {} bans and {} picks",
+                    MAX_ACTIONS_OF_KIND, MAX_ACTIONS_OF_KIND
+                ));
+            }
+        }
+
+        let mut picked = Vec::new();
+        for action in &mut self.actions {
+            action.champion = champions::canonicalize(&action.champion)?;
+            if action.kind == DraftActionKind::Pick {
+                if picked.contains(&action.champion) {
+                    return Err(format!("{} is picked twice", action.champion));
+                }
+                picked.push(action.champion.clone());
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl Draft {
+    /// Create a draft from normalized input
+    pub fn new(input: DraftInput) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            date: input.date.unwrap_or(now),
+            my_side: input.my_side,
+            actions: input.actions,
+            result: input.result,
+            notes: input.notes,
+            linked_match: input.linked_match,
+            created: now,
+            updated: now,
+        }
+    }
+
+    /// Replace the contents with normalized input, keeping the date if none is given
+    pub fn update(&mut self, input: DraftInput) {
+        self.date = input.date.unwrap_or(self.date);
+        self.my_side = input.my_side;
+        self.actions = input.actions;
+        self.result = input.result;
+        self.notes = input.notes;
+        self.linked_match = input.linked_match;
+        self.updated = Utc::now();
+    }
+}
+
+/// Drafts passing the champion filter, most recent first
+///
+/// A champion matches when either side picked or banned it.
+pub fn filter_drafts<'a>(
+    drafts: impl IntoIterator<Item = &'a Draft>,
+    champion: Option<&str>,
+) -> Vec<Draft> {
+    let mut drafts: Vec<Draft> = drafts
+        .into_iter()
+        .filter(|draft| {
+            champion.is_none_or(|champion| {
+                draft
+                    .actions
+                    .iter()
+                    .any(|a| a.champion.eq_ignore_ascii_case(champion))
+            })
+        })
+        .cloned()
+        .collect();
+    drafts.sort_by(|a, b| b.date.cmp(&a.date));
+    drafts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(kind: DraftActionKind, side: Side, champion: &str) -> DraftAction {
+        DraftAction {
+            kind,
+            side,
+            champion: champion.to_string(),
+            position: None,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_draft_input() {
+        let input = DraftInput {
+            date: None,
+            my_side: Side::Blue,
+            actions: vec![
+                action(DraftActionKind::Ban, Side::Blue, "zed"),
+                action(DraftActionKind::Ban, Side::Red, "Zed"),
+                action(DraftActionKind::Pick, Side::Blue, "darius"),
+                action(DraftActionKind::Pick, Side::Red, "Garen"),
+            ],
+            result: None,
+            notes: String::new(),
+            linked_match: None,
+        }
+        .normalize()
+        .unwrap();
+        assert_eq!(input.actions[2].champion, "Darius");
+        let draft = Draft::new(input.clone());
+        assert_eq!(filter_drafts([&draft], Some("zed")).len(), 1);
+        assert!(filter_drafts([&draft], Some("Ahri")).is_empty());
+
+        let mut twice = input;
+        twice
+            .actions
+            .push(action(DraftActionKind::Pick, Side::Red, "Darius"));
+        assert!(twice.normalize().is_err());
+    }
+}
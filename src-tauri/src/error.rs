@@ -0,0 +1,246 @@
+//! Errors returned by every command
+//!
+//! Serialized as `{ code, message, details }` so the frontend can branch on
+//! `code` instead of matching on message text.
+
+use crate::archive::ArchiveError;
+use crate::attachments::AttachmentError;
+use crate::builds::BuildsError;
+use crate::ddragon::DdragonError;
+use crate::guide::GuideError;
+use crate::lcu::LcuError;
+use crate::match_history::HistoryError;
+use crate::migration::MigrationError;
+use crate::package::PackageError;
+use crate::riot_api::RiotApiError;
+use crate::settings::SettingsError;
+use crate::storage::StorageError;
+use crate::webhook::WebhookError;
+use serde::{Deserialize, Serialize};
+use std::sync::PoisonError;
+use thiserror::Error;
+
+/// What kind of failure an `AppError` is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorCode {
+    NotFound,
+    ValidationFailed,
+    /// A previous command panicked while holding shared state
+    StorageLocked,
+    StorageFailed,
+    LcuNotRunning,
+    LcuNotConnected,
+    LcuRequestFailed,
+    RiotApiFailed,
+    DataDragonFailed,
+    /// The action needs to be enabled in the settings first
+    PermissionDenied,
+    /// A required setting or file is missing
+    NotConfigured,
+    /// Opening or changing an app window failed
+    WindowFailed,
+    WebhookFailed,
+    ClipboardFailed,
+    /// A site such as the builds source or a guide page failed or answered unexpectedly
+    SourceFailed,
+}
+
+#[derive(Error, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Underlying error, for logs and bug reports
+    pub details: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl ToString) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    /// `what` is the kind of thing missing, e.g. "Matchup"
+    pub fn not_found(what: &str) -> Self {
+        Self::new(ErrorCode::NotFound, format!("{} not found", what))
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ValidationFailed, message)
+    }
+
+    pub fn lcu_not_connected() -> Self {
+        Self::new(ErrorCode::LcuNotConnected, "Not connected to League client")
+    }
+}
+
+/// Errors from input checks in the other modules, which report them as text
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::validation(message)
+    }
+}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(e: PoisonError<T>) -> Self {
+        Self::new(ErrorCode::StorageLocked, "App state is unavailable").with_details(e)
+    }
+}
+
+impl From<StorageError> for AppError {
+    fn from(e: StorageError) -> Self {
+        Self::new(ErrorCode::StorageFailed, "Could not access saved data").with_details(e)
+    }
+}
+
+impl From<SettingsError> for AppError {
+    fn from(e: SettingsError) -> Self {
+        Self::new(ErrorCode::StorageFailed, "Could not access settings").with_details(e)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorCode::StorageFailed, "Could not access a file").with_details(e)
+    }
+}
+
+impl From<LcuError> for AppError {
+    fn from(e: LcuError) -> Self {
+        let code = match e {
+            LcuError::ClientNotRunning | LcuError::CredentialsUnavailable(_) => {
+                ErrorCode::LcuNotRunning
+            }
+            LcuError::NotFound(_) => ErrorCode::NotFound,
+            _ => ErrorCode::LcuRequestFailed,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<MigrationError> for AppError {
+    fn from(e: MigrationError) -> Self {
+        match e {
+            MigrationError::Io(e) => e.into(),
+            MigrationError::Format { .. } => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<AttachmentError> for AppError {
+    fn from(e: AttachmentError) -> Self {
+        match e {
+            AttachmentError::Io(e) => e.into(),
+            _ => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<PackageError> for AppError {
+    fn from(e: PackageError) -> Self {
+        match e {
+            PackageError::Io(e) => e.into(),
+            _ => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<HistoryError> for AppError {
+    fn from(e: HistoryError) -> Self {
+        match e {
+            HistoryError::Io(e) => e.into(),
+            _ => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<ArchiveError> for AppError {
+    fn from(e: ArchiveError) -> Self {
+        match e {
+            ArchiveError::Io(e) => e.into(),
+            ArchiveError::Storage(e) => e.into(),
+            _ => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<DdragonError> for AppError {
+    fn from(e: DdragonError) -> Self {
+        let code = match e {
+            DdragonError::InvalidLocale(_) | DdragonError::UnknownAsset(_) => {
+                ErrorCode::ValidationFailed
+            }
+            _ => ErrorCode::DataDragonFailed,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<RiotApiError> for AppError {
+    fn from(e: RiotApiError) -> Self {
+        Self::new(ErrorCode::RiotApiFailed, e.to_string())
+    }
+}
+
+impl From<WebhookError> for AppError {
+    fn from(e: WebhookError) -> Self {
+        Self::new(ErrorCode::WebhookFailed, e.to_string())
+    }
+}
+
+impl From<BuildsError> for AppError {
+    fn from(e: BuildsError) -> Self {
+        Self::new(ErrorCode::SourceFailed, e.to_string())
+    }
+}
+
+impl From<GuideError> for AppError {
+    fn from(e: GuideError) -> Self {
+        match e {
+            GuideError::RequestError(_) | GuideError::Status(_) => {
+                Self::new(ErrorCode::SourceFailed, e.to_string())
+            }
+            _ => Self::validation(e.to_string()),
+        }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::new(ErrorCode::WindowFailed, "Could not update the window").with_details(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let error = AppError::not_found("Matchup");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "NotFound", "message": "Matchup not found", "details": null })
+        );
+    }
+
+    #[test]
+    fn test_lcu_error_codes() {
+        assert_eq!(
+            AppError::from(LcuError::ClientNotRunning).code,
+            ErrorCode::LcuNotRunning
+        );
+        assert_eq!(
+            AppError::from(LcuError::ServerError(500)).code,
+            ErrorCode::LcuRequestFailed
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Data change events, so every open window stays in sync without polling
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+/// Matchups were created, edited or deleted
+pub const MATCHUPS_CHANGED: &str = "matchups:changed";
+
+/// Matches were logged, edited or deleted
+pub const MATCHES_CHANGED: &str = "matches:changed";
+
+/// Matches were imported from the client or the Riot API
+pub const MATCHES_IMPORTED: &str = "matches:imported";
+
+/// Team notes were created, edited or deleted
+pub const TEAM_NOTES_CHANGED: &str = "team_notes:changed";
+
+/// Drafts were created, edited or deleted
+pub const DRAFTS_CHANGED: &str = "drafts:changed";
+
+/// Reminders were created, completed or deleted
+pub const REMINDERS_CHANGED: &str = "reminders:changed";
+
+/// Collections were created, edited, reordered or deleted
+pub const COLLECTIONS_CHANGED: &str = "collections:changed";
+
+/// The scratchpad was saved
+pub const SCRATCHPAD_CHANGED: &str = "scratchpad:changed";
+
+/// Practice targets were created, deleted or had a review logged
+pub const PLAN_CHANGED: &str = "plan:changed";
+
+/// Payload of the change events
+///
+/// Empty `ids` means anything may have changed and lists should be reloaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataChanged {
+    pub ids: Vec<String>,
+    pub deleted: bool,
+}
+
+fn emit(app: &AppHandle, event: &str, ids: Vec<String>, deleted: bool) {
+    // Nobody listening is fine; the data is already saved
+    let _ = app.emit(event, DataChanged { ids, deleted });
+}
+
+pub fn matchups_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, MATCHUPS_CHANGED, ids, false);
+}
+
+pub fn matchups_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, MATCHUPS_CHANGED, ids, true);
+}
+
+pub fn matches_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, MATCHES_CHANGED, ids, false);
+}
+
+pub fn matches_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, MATCHES_CHANGED, ids, true);
+}
+
+pub fn team_notes_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, TEAM_NOTES_CHANGED, ids, false);
+}
+
+pub fn team_notes_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, TEAM_NOTES_CHANGED, ids, true);
+}
+
+pub fn drafts_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, DRAFTS_CHANGED, ids, false);
+}
+
+pub fn drafts_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, DRAFTS_CHANGED, ids, true);
+}
+
+pub fn reminders_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, REMINDERS_CHANGED, ids, false);
+}
+
+pub fn reminders_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, REMINDERS_CHANGED, ids, true);
+}
+
+pub fn collections_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, COLLECTIONS_CHANGED, ids, false);
+}
+
+pub fn collections_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, COLLECTIONS_CHANGED, ids, true);
+}
+
+pub fn scratchpad_changed(app: &AppHandle) {
+    emit(app, SCRATCHPAD_CHANGED, Vec::new(), false);
+}
+
+pub fn plan_changed(app: &AppHandle, ids: Vec<String>) {
+    emit(app, PLAN_CHANGED, ids, false);
+}
+
+pub fn plan_deleted(app: &AppHandle, ids: Vec<String>) {
+    emit(app, PLAN_CHANGED, ids, true);
+}
+
+/// Emit every change event with no IDs, after all data was replaced at once
+pub fn data_replaced(app: &AppHandle) {
+    for event in [
+        MATCHUPS_CHANGED,
+        MATCHES_CHANGED,
+        TEAM_NOTES_CHANGED,
+        DRAFTS_CHANGED,
+        REMINDERS_CHANGED,
+        COLLECTIONS_CHANGED,
+        SCRATCHPAD_CHANGED,
+        PLAN_CHANGED,
+    ] {
+        emit(app, event, Vec::new(), false);
+    }
+}
+
+/// Emit `matches:imported` with the new match IDs, `matches:changed` for games
+/// merged into existing ones, plus `matchups:changed` if the import created matchups
+pub fn matches_imported(app: &AppHandle, summary: &crate::matchup::ImportSummary) {
+    if !summary.merged.is_empty() {
+        matches_changed(app, summary.merged.iter().map(|m| m.id.clone()).collect());
+    }
+    if summary.imported.is_empty() {
+        return;
+    }
+    emit(
+        app,
+        MATCHES_IMPORTED,
+        summary.imported.iter().map(|m| m.id.clone()).collect(),
+        false,
+    );
+    if summary.created > 0 {
+        matchups_changed(app, Vec::new());
+    }
+}
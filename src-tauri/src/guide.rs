@@ -0,0 +1,254 @@
+//! Guides and pastes fetched from a URL, turned into plain notes
+//!
+//! Plain text and Markdown are kept as they are. HTML pages are reduced to
+//! their readable text, with headings and list items kept as Markdown.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use thiserror::Error;
+
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Largest page downloaded
+const MAX_PAGE_BYTES: usize = 1024 * 1024;
+
+/// Elements whose contents are never text to read
+const SKIPPED_ELEMENTS: [&str; 6] = ["script", "style", "head", "noscript", "template", "svg"];
+
+/// Elements that start a new line
+const BLOCK_ELEMENTS: [&str; 14] = [
+    "p",
+    "div",
+    "br",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "tr",
+    "ul",
+    "ol",
+    "table",
+    "blockquote",
+    "pre",
+];
+
+#[derive(Error, Debug)]
+pub enum GuideError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Page returned HTTP {0}")]
+    Status(u16),
+    #[error("Not a web address: {0}")]
+    InvalidUrl(String),
+    #[error("Unsupported page type: {0}")]
+    UnsupportedContent(String),
+    #[error("Page is larger than {limit} bytes")]
+    TooLarge { limit: usize },
+    #[error("Page has no text to import")]
+    Empty,
+}
+
+/// Check the address and point paste sites at their raw text
+pub fn source_url(url: &str) -> Result<Url, GuideError> {
+    let mut parsed = Url::parse(url.trim()).map_err(|_| GuideError::InvalidUrl(url.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(GuideError::InvalidUrl(url.to_string()));
+    }
+    if parsed.host_str() == Some("pastebin.com") {
+        let path = parsed.path().trim_matches('/').to_string();
+        if !path.is_empty() && !path.contains('/') {
+            parsed.set_path(&format!("/raw/{}", path));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Download a page and return its readable text
+pub fn fetch(url: &Url) -> Result<String, GuideError> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()?
+        .get(url.clone())
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(GuideError::Status(response.status().as_u16()));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("text/plain")
+        .trim()
+        .to_lowercase();
+    if !content_type.starts_with("text/") {
+        return Err(GuideError::UnsupportedContent(content_type));
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > MAX_PAGE_BYTES)
+    {
+        return Err(GuideError::TooLarge {
+            limit: MAX_PAGE_BYTES,
+        });
+    }
+    let bytes = response.bytes()?;
+    if bytes.len() > MAX_PAGE_BYTES {
+        return Err(GuideError::TooLarge {
+            limit: MAX_PAGE_BYTES,
+        });
+    }
+
+    let body = String::from_utf8_lossy(&bytes);
+    let text = if content_type == "text/html" {
+        html_to_text(&body)
+    } else {
+        body.trim().to_string()
+    };
+    if text.is_empty() {
+        return Err(GuideError::Empty);
+    }
+    Ok(text)
+}
+
+/// Readable text of an HTML page, with headings and list items as Markdown
+pub fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        out.push_str(&decode_entities(&rest[..start]).replace(['\n', '\r', '\t'], " "));
+        rest = &rest[start..];
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        if !closing && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&close) {
+                Some(index) => &rest[index..],
+                None => "",
+            };
+            continue;
+        }
+        match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" if !closing => {
+                let level = name[1..].parse().unwrap_or(1);
+                out.push_str("\n\n");
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => out.push_str("\n\n"),
+            "li" if !closing => out.push_str("\n- "),
+            name if BLOCK_ELEMENTS.contains(&name) => out.push('\n'),
+            _ => {}
+        }
+    }
+    out.push_str(&decode_entities(rest));
+
+    // One space between words, and at most one blank line in a row
+    let mut text = String::new();
+    let mut blank = false;
+    for line in out.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() || line == "-" {
+            blank = !text.is_empty();
+            continue;
+        }
+        if blank {
+            text.push('\n');
+            blank = false;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html><head><title>Guide</title><style>p { color: red }</style></head>
+            <body><h2>Darius vs Garen</h2>
+            <p>Short trades &amp; <b>never</b>
+               fight in his E.</p>
+            <script>track();</script>
+            <ul><li>Ghost</li><li>Flash &#8211; always</li></ul></body></html>"#;
+
+        assert_eq!(
+            html_to_text(html),
+            "## Darius vs Garen\n\nShort trades & never fight in his E.\n\n- Ghost\n- Flash \u{2013} always"
+        );
+    }
+
+    #[test]
+    fn test_source_url() {
+        assert_eq!(
+            source_url("https://pastebin.com/AbC123").unwrap().as_str(),
+            "https://pastebin.com/raw/AbC123"
+        );
+        assert_eq!(
+            source_url("https://example.com/guides/darius")
+                .unwrap()
+                .as_str(),
+            "https://example.com/guides/darius"
+        );
+        assert!(source_url("file:///etc/passwd").is_err());
+        assert!(source_url("not a url").is_err());
+    }
+}
@@ -1,15 +1,29 @@
 //! League Client Update (LCU) API integration
 //! Connects to the local League of Legends client to fetch match history
 
+use crate::champions;
+use crate::draft::{DraftAction, DraftActionKind, Side};
+use crate::matchup::{
+    ArenaResult, EarlyGameStats, EndOfGameStats, GameLoadout, GameMode, Match, MatchResult,
+    MatchStats, QueueType, RankInfo, RunePage, TeamTotals, Tier,
+};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum LcuError {
     #[error("League client not running")]
     ClientNotRunning,
+    #[error("League client found but its port and token could not be read: {0}")]
+    CredentialsUnavailable(String),
     #[error("Failed to parse client info: {0}")]
     ParseError(String),
     #[error("HTTP request failed: {0}")]
@@ -18,6 +32,21 @@ pub enum LcuError {
     IoError(#[from] std::io::Error),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Client returned HTTP {0}")]
+    ServerError(u16),
+}
+
+/// Whether a failed request is worth retrying
+///
+/// Refused connections are not: the client is gone and the monitor handles that.
+fn is_transient(error: &LcuError) -> bool {
+    match error {
+        LcuError::ServerError(_) => true,
+        LcuError::RequestError(e) => e.is_timeout(),
+        _ => false,
+    }
 }
 
 /// LCU connection credentials
@@ -27,11 +56,132 @@ pub struct LcuCredentials {
     pub token: String,
 }
 
+/// Start of the client UI process name, which carries the port and token in its arguments
+///
+/// Linux truncates process names, so this is matched as a prefix.
+const CLIENT_PROCESS: &str = "LeagueClientUx";
+
+/// What to try when the client is running but its credentials can't be read
+#[cfg(target_os = "windows")]
+const CREDENTIALS_HINT: &str =
+    "the client may be running as administrator, try running this app as administrator too";
+#[cfg(target_os = "macos")]
+const CREDENTIALS_HINT: &str =
+    "no lockfile found next to the client, is League installed outside /Applications?";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const CREDENTIALS_HINT: &str =
+    "no lockfile found in ~/.config/riot-games/league-of-legends or the Wine prefix";
+
+/// Where the client writes its lockfile on a default install
+fn default_lockfile_paths() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        vec![PathBuf::from(r"C:\Riot Games\League of Legends\lockfile")]
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        vec![PathBuf::from(
+            "/Applications/League of Legends.app/Contents/LoL/lockfile",
+        )]
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        dirs::home_dir()
+            .map(|home| {
+                vec![
+                    home.join(".config/riot-games/league-of-legends/lockfile"),
+                    home.join(
+                        "Games/league-of-legends/drive_c/Riot Games/League of Legends/lockfile",
+                    ),
+                ]
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Value of a `--key=value` client argument
+fn arg_value<'a>(args: &'a [String], prefix: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|arg| arg.trim_matches('"').strip_prefix(prefix))
+        .filter(|value| !value.is_empty())
+}
+
+/// Port and token from the client's command line
+fn credentials_from_args(args: &[String]) -> Option<LcuCredentials> {
+    Some(LcuCredentials {
+        port: arg_value(args, "--app-port=")?.parse().ok()?,
+        token: arg_value(args, "--remoting-auth-token=")?.to_string(),
+    })
+}
+
+/// Port and token from a lockfile ("name:pid:port:token:protocol")
+fn parse_lockfile(contents: &str) -> Result<LcuCredentials, LcuError> {
+    let parts: Vec<&str> = contents.trim().split(':').collect();
+
+    if parts.len() < 4 {
+        return Err(LcuError::ParseError("Invalid lockfile format".to_string()));
+    }
+
+    let port = parts[2]
+        .parse::<u16>()
+        .map_err(|_| LcuError::ParseError("Invalid port".to_string()))?;
+    let token = parts[3].to_string();
+
+    Ok(LcuCredentials { port, token })
+}
+
 /// Connection status response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LcuConnectionStatus {
     pub connected: bool,
     pub summoner_name: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+/// What the client shows during champ select
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChampSelectInfo {
+    /// Normalized role ("top", "mid", ...), if positions are assigned
+    pub my_position: Option<String>,
+    /// My locked or hovered champion
+    pub my_champion: Option<String>,
+    /// Enemy pick in my position, when it can be told
+    pub enemy_champion: Option<String>,
+    /// Every enemy pick visible so far
+    pub enemy_picks: Vec<String>,
+    /// Enemy picks locked in on turns before mine, in pick order
+    #[serde(default)]
+    pub picked_before_me: Vec<String>,
+    /// Champions banned by either team
+    #[serde(default)]
+    pub bans: Vec<String>,
+    /// It's my turn to ban
+    #[serde(default)]
+    pub banning: bool,
+    /// My pick is locked in
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// The client's gameflow phase
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GameflowPhase {
+    None,
+    Lobby,
+    Matchmaking,
+    ReadyCheck,
+    ChampSelect,
+    GameStart,
+    InProgress,
+    Reconnect,
+    WaitingForStats,
+    PreEndOfGame,
+    EndOfGame,
+    #[serde(other)]
+    Other,
 }
 
 /// Processed match data from LCU
@@ -47,43 +197,244 @@ pub struct LcuMatchData {
     pub lane: String,
     pub win: bool,
     pub queue_id: i32,
+    /// Patch the game was played on, e.g. "14.3"
+    pub patch: Option<String>,
+    pub game_mode: GameMode,
+    pub arena: Option<ArenaResult>,
+    pub loadout: GameLoadout,
+    pub stats: MatchStats,
+    pub my_participant_id: i64,
+    pub enemy_participant_id: Option<i64>,
+    /// The lane opponent was a best guess rather than a clear lane match
+    pub opponent_uncertain: bool,
+    /// Riot ID of the lane opponent, e.g. "Name#EUW"
+    pub enemy_riot_id: Option<String>,
+    /// Map side, except in Arena
+    pub side: Option<Side>,
+    pub puuid: String,
+    /// The game object as returned by the client, kept for reprocessing
+    #[serde(skip)]
+    pub raw: serde_json::Value,
+}
+
+impl LcuMatchData {
+    /// Build a new match record from this game
+    pub fn to_match(&self) -> Match {
+        // ARAM and Arena have no lane opponent at all, unlike a failed detection
+        let enemy_champion = match (&self.enemy_champion_name, self.game_mode) {
+            (Some(name), _) => name.clone(),
+            (None, GameMode::Classic) => "Unknown".to_string(),
+            (None, _) => String::new(),
+        };
+        let result = if self.win {
+            MatchResult::Win
+        } else {
+            MatchResult::Loss
+        };
+        let mut m = Match::new(
+            self.my_champion_name.clone(),
+            enemy_champion,
+            self.role.clone(),
+            result,
+            Some(self.game_id.to_string()),
+        );
+        m.queue_id = Some(self.queue_id);
+        m.queue = Some(QueueType::from_queue_id(self.queue_id));
+        m.patch = self.patch.clone();
+        m.opponent_uncertain = self.opponent_uncertain;
+        m.enemy_riot_id = self.enemy_riot_id.clone();
+        m.side = self.side;
+        m.game_mode = Some(self.game_mode);
+        m.arena = self.arena.clone();
+        m.loadout = Some(self.loadout.clone());
+        m.stats = Some(self.stats.clone());
+        m.account_puuid = Some(self.puuid.clone());
+        if let Some(played_at) = chrono::DateTime::from_timestamp_millis(self.game_creation) {
+            m.date = played_at;
+        }
+        m
+    }
+}
+
+/// One player's line in a game scoreboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreboardPlayer {
+    pub participant_id: i64,
+    pub summoner_name: Option<String>,
+    pub champion_id: i32,
+    pub champion_name: String,
+    pub role: String,
+    pub stats: MatchStats,
+    pub champion_level: u32,
+    pub items: Vec<i32>,
+    pub summoner_spells: Vec<i32>,
+}
+
+/// One team in a game scoreboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreboardTeam {
+    pub team_id: i64,
+    pub win: bool,
+    pub players: Vec<ScoreboardPlayer>,
+}
+
+/// Full scoreboard of a game, for the match detail view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameDetails {
+    pub game_id: i64,
+    pub game_creation: i64,
+    pub game_duration: u32,
+    pub queue_id: i32,
+    pub teams: Vec<ScoreboardTeam>,
+}
+
+/// Games requested per match history page
+const MATCH_HISTORY_PAGE_SIZE: u32 = 20;
+
+/// Consecutive failed pages after which a paged fetch gives up
+const MAX_FAILED_PAGES: u32 = 3;
+
+/// Longest a single request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest to wait for the client to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Retries after a transient failure, with the delay doubling each time
+const MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Minimum time between requests to the same endpoint
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Riot's self-signed root CA, which signs the client's certificate
+const RIOT_ROOT_CA: &[u8] = include_bytes!("../certs/riotgames.pem");
+
+/// File in the data directory replacing the built-in root CA, should Riot change it
+pub const RIOT_ROOT_CA_FILE: &str = "riotgames.pem";
+
+/// HTTP client for the LCU API
+///
+/// With Riot's root CA, only certificates it signed are trusted. The client's
+/// certificate names 127.0.0.1 in its common name alone, which TLS libraries no
+/// longer match, so hostnames aren't checked; requests only go to localhost anyway.
+fn build_http_client(
+    root_ca: Option<reqwest::Certificate>,
+    allow_insecure: bool,
+) -> reqwest::blocking::Client {
+    let builder = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .connect_timeout(CONNECT_TIMEOUT);
+    let builder = match root_ca {
+        Some(cert) => builder
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(cert)
+            .danger_accept_invalid_hostnames(true),
+        None => builder.danger_accept_invalid_certs(allow_insecure),
+    };
+
+    builder.build().expect("Failed to create HTTP client")
+}
+
+/// Delay before retry number `attempt` (0-based)
+fn backoff_delay(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt)
+}
+
+/// Spaces out requests per endpoint so polling can't flood the client
+#[derive(Default)]
+struct RateLimiter {
+    last_request: HashMap<String, Instant>,
+}
+
+impl RateLimiter {
+    /// How long to wait before hitting `endpoint` at `now`; records the request
+    fn wait_time(&mut self, endpoint: &str, now: Instant) -> Duration {
+        // Query strings don't make a separate endpoint
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        let wait = self
+            .last_request
+            .get(path)
+            .map(|last| MIN_REQUEST_INTERVAL.saturating_sub(now.duration_since(*last)))
+            .unwrap_or_default();
+        self.last_request.insert(path.to_string(), now + wait);
+        wait
+    }
 }
 
 /// LCU API client
+///
+/// Clones share the connection and its rate limits. Requests are made on a
+/// clone taken out of the app state, so slow ones and their retries don't
+/// hold up other commands.
+#[derive(Clone)]
 pub struct LcuClient {
     credentials: Option<LcuCredentials>,
     http_client: reqwest::blocking::Client,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
     summoner_puuid: Option<String>,
+    summoner_name: Option<String>,
+    summoner_id: Option<i64>,
+    /// Ranks by queue type ("RANKED_SOLO_5x5", "RANKED_FLEX_SR"), fetched on connect
+    ranks: HashMap<String, RankInfo>,
+    /// Cleared when the user disconnects, so the monitor stops reconnecting
+    auto_reconnect: bool,
+    /// Bumped on every disconnect and TLS change, so a connection made on an
+    /// older clone isn't taken over
+    generation: u64,
 }
 
 impl LcuClient {
+    /// A client trusting the built-in Riot root CA
     pub fn new() -> Self {
-        let http_client = reqwest::blocking::Client::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-            .expect("Failed to create HTTP client");
-
+        let root_ca = reqwest::Certificate::from_pem(RIOT_ROOT_CA).ok();
         Self {
             credentials: None,
-            http_client,
+            http_client: build_http_client(root_ca, false),
+            rate_limiter: Arc::default(),
             summoner_puuid: None,
+            summoner_name: None,
+            summoner_id: None,
+            ranks: HashMap::new(),
+            auto_reconnect: true,
+            generation: 0,
         }
     }
 
+    /// Trust `root_ca_pem` instead of the built-in Riot root CA when given, or
+    /// skip certificate checks altogether if `allow_insecure`
+    pub fn configure_tls(
+        &mut self,
+        root_ca_pem: Option<&[u8]>,
+        allow_insecure: bool,
+    ) -> Result<(), LcuError> {
+        let root_ca = reqwest::Certificate::from_pem(root_ca_pem.unwrap_or(RIOT_ROOT_CA))
+            .map_err(|e| LcuError::ParseError(format!("Invalid root certificate: {}", e)))?;
+
+        self.http_client = build_http_client((!allow_insecure).then_some(root_ca), allow_insecure);
+        self.generation += 1;
+        Ok(())
+    }
+
     /// Try to connect to the League client
     pub fn connect(&mut self) -> Result<LcuConnectionStatus, LcuError> {
+        self.auto_reconnect = true;
         let credentials = self.get_credentials()?;
         self.credentials = Some(credentials);
 
         match self.get_current_summoner() {
             Ok(summoner) => {
                 self.summoner_puuid = Some(summoner.puuid.clone());
-                Ok(LcuConnectionStatus {
-                    connected: true,
-                    summoner_name: Some(summoner.display_name),
-                })
+                self.summoner_name = Some(summoner.display_name);
+                self.summoner_id = summoner.summoner_id;
+                // Rank is extra context, unranked or a failed lookup is fine
+                self.ranks = self.get_ranks().unwrap_or_default();
+                Ok(self.status())
             }
             Err(e) => {
+                tracing::warn!("Found the client but could not read the summoner: {}", e);
                 self.credentials = None;
                 Err(e)
             }
@@ -91,91 +442,147 @@ impl LcuClient {
     }
 
     /// Get LCU credentials from the running League process
+    ///
+    /// Reads the port and token from the client's arguments, falling back to its
+    /// lockfile when they can't be read.
     fn get_credentials(&self) -> Result<LcuCredentials, LcuError> {
-        #[cfg(target_os = "windows")]
-        {
-            let output = Command::new("wmic")
-                .args([
-                    "process",
-                    "where",
-                    "name='LeagueClientUx.exe'",
-                    "get",
-                    "commandline",
-                ])
-                .output()?;
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            if stdout.is_empty() || !stdout.contains("LeagueClientUx") {
-                return Err(LcuError::ClientNotRunning);
-            }
-
-            let port = Self::extract_value(&stdout, "--app-port=")
-                .and_then(|s| s.parse::<u16>().ok())
-                .ok_or_else(|| LcuError::ParseError("Could not find port".to_string()))?;
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cmd(UpdateKind::Always)
+                .with_exe(UpdateKind::Always),
+        );
+        let client = system
+            .processes()
+            .values()
+            .find(|p| p.name().to_string_lossy().starts_with(CLIENT_PROCESS));
 
-            let token = Self::extract_value(&stdout, "--remoting-auth-token=")
-                .ok_or_else(|| LcuError::ParseError("Could not find auth token".to_string()))?;
+        let mut lockfiles = Vec::new();
+        if let Some(process) = client {
+            let args: Vec<String> = process
+                .cmd()
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect();
+            if let Some(credentials) = credentials_from_args(&args) {
+                return Ok(credentials);
+            }
+            // Arguments are unreadable when the client runs elevated
+            if let Some(dir) = arg_value(&args, "--install-directory=") {
+                lockfiles.push(PathBuf::from(dir).join("lockfile"));
+            }
+            if let Some(dir) = process.exe().and_then(|exe| exe.parent()) {
+                lockfiles.push(dir.join("lockfile"));
+            }
+        }
+        lockfiles.extend(default_lockfile_paths());
 
-            Ok(LcuCredentials { port, token })
+        if let Some(path) = lockfiles.iter().find(|path| path.exists()) {
+            return parse_lockfile(&std::fs::read_to_string(path)?);
         }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            let lockfile_path = dirs::home_dir()
-                .map(|p| p.join(".config/riot-games/league-of-legends/lockfile"))
-                .ok_or(LcuError::ClientNotRunning)?;
+        match client {
+            Some(_) => Err(LcuError::CredentialsUnavailable(
+                CREDENTIALS_HINT.to_string(),
+            )),
+            None => Err(LcuError::ClientNotRunning),
+        }
+    }
 
-            if !lockfile_path.exists() {
-                return Err(LcuError::ClientNotRunning);
-            }
+    /// Make an authenticated request to the LCU API
+    fn request(&self, endpoint: &str) -> Result<String, LcuError> {
+        self.send(reqwest::Method::GET, endpoint, None)
+    }
 
-            let contents = std::fs::read_to_string(lockfile_path)?;
-            let parts: Vec<&str> = contents.split(':').collect();
+    /// GET an endpoint and deserialize its JSON response
+    fn get_json<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T, LcuError> {
+        self.send_json(reqwest::Method::GET, endpoint, None)
+    }
 
-            if parts.len() < 4 {
-                return Err(LcuError::ParseError("Invalid lockfile format".to_string()));
-            }
+    /// Send a request and deserialize its JSON response
+    pub fn send_json<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, LcuError> {
+        let response = self.send(method, endpoint, body)?;
+        serde_json::from_str(&response)
+            .map_err(|e| LcuError::ParseError(format!("JSON parse error: {}", e)))
+    }
 
-            let port = parts[2]
-                .parse::<u16>()
-                .map_err(|_| LcuError::ParseError("Invalid port".to_string()))?;
-            let token = parts[3].to_string();
+    /// Make an authenticated request with any method and an optional JSON body
+    ///
+    /// GET requests are retried with exponential backoff on server errors and
+    /// timeouts, which the client returns for a while right after login. Others
+    /// may have gone through before failing, so they aren't repeated.
+    pub fn send(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<String, LcuError> {
+        let retries = if method == reqwest::Method::GET {
+            MAX_RETRIES
+        } else {
+            0
+        };
 
-            Ok(LcuCredentials { port, token })
+        let mut attempt = 0;
+        loop {
+            match self.send_once(method.clone(), endpoint, body) {
+                Err(e) if attempt < retries && is_transient(&e) => {
+                    thread::sleep(backoff_delay(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
         }
     }
 
-    fn extract_value(text: &str, prefix: &str) -> Option<String> {
-        text.find(prefix).map(|start| {
-            let value_start = start + prefix.len();
-            let end = text[value_start..]
-                .find(|c: char| c.is_whitespace() || c == '"')
-                .map(|i| value_start + i)
-                .unwrap_or(text.len());
-            text[value_start..end].to_string()
-        })
-    }
-
-    /// Make an authenticated request to the LCU API
-    fn request(&self, endpoint: &str) -> Result<String, LcuError> {
+    fn send_once(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<String, LcuError> {
         let creds = self
             .credentials
             .as_ref()
             .ok_or(LcuError::ClientNotRunning)?;
 
+        let wait = self
+            .rate_limiter
+            .lock()
+            .map(|mut limiter| limiter.wait_time(endpoint, Instant::now()))
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+
         let url = format!("https://127.0.0.1:{}{}", creds.port, endpoint);
         let auth = STANDARD.encode(format!("riot:{}", creds.token));
 
-        let response = self
+        let mut request = self
             .http_client
-            .get(&url)
-            .header("Authorization", format!("Basic {}", auth))
-            .send()?;
+            .request(method, &url)
+            .header("Authorization", format!("Basic {}", auth));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send()?;
 
         let status = response.status();
         let text = response.text()?;
 
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(LcuError::NotFound(endpoint.to_string()));
+        }
+        if status.is_server_error() {
+            return Err(LcuError::ServerError(status.as_u16()));
+        }
         if !status.is_success() {
             return Err(LcuError::ApiError(format!(
                 "HTTP {}: {}",
@@ -196,9 +603,64 @@ impl LcuClient {
 
     /// Get match history with proper parsing
     pub fn get_match_history(&self, count: u32) -> Result<Vec<LcuMatchData>, LcuError> {
+        self.get_match_history_page(0, count)
+    }
+
+    /// Get match history page by page, up to `max` games
+    ///
+    /// The client caps how many games a single request returns, so deeper history
+    /// needs several requests. A failing page is skipped unless it is the first one or
+    /// too many fail in a row. `on_page` gets the number of games fetched so far.
+    pub fn get_match_history_paged(
+        &self,
+        max: u32,
+        mut on_page: impl FnMut(usize),
+    ) -> Result<Vec<LcuMatchData>, LcuError> {
+        let mut matches = Vec::new();
+        let mut failed_in_a_row = 0;
+        let mut beg_index = 0;
+
+        while beg_index < max {
+            let end_index = (beg_index + MATCH_HISTORY_PAGE_SIZE).min(max);
+            match self.get_match_history_page(beg_index, end_index) {
+                Ok(page) if page.is_empty() => break,
+                Ok(page) => {
+                    failed_in_a_row = 0;
+                    matches.extend(page);
+                }
+                Err(e) if beg_index == 0 || failed_in_a_row + 1 >= MAX_FAILED_PAGES => {
+                    if matches.is_empty() {
+                        return Err(e);
+                    }
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipped match history games {} to {}: {}",
+                        beg_index,
+                        end_index,
+                        e
+                    );
+                    failed_in_a_row += 1;
+                }
+            }
+
+            on_page(matches.len());
+            beg_index = end_index;
+        }
+
+        Ok(matches)
+    }
+
+    /// Get one page of match history, `end_index` exclusive
+    fn get_match_history_page(
+        &self,
+        beg_index: u32,
+        end_index: u32,
+    ) -> Result<Vec<LcuMatchData>, LcuError> {
         let endpoint = format!(
-            "/lol-match-history/v1/products/lol/current-summoner/matches?begIndex=0&endIndex={}",
-            count
+            "/lol-match-history/v1/products/lol/current-summoner/matches?begIndex={}&endIndex={}",
+            beg_index, end_index
         );
         let response = self.request(&endpoint)?;
 
@@ -221,8 +683,14 @@ impl LcuClient {
         let mut matches = Vec::new();
 
         for game in games {
-            if let Some(match_data) = self.parse_game(game, puuid) {
-                matches.push(match_data);
+            match self.parse_game(game, puuid) {
+                Some(match_data) => matches.push(match_data),
+                None => tracing::warn!(
+                    "Skipped game {} that could not be parsed",
+                    game.get("gameId")
+                        .and_then(|id| id.as_i64())
+                        .unwrap_or_default()
+                ),
             }
         }
 
@@ -230,10 +698,18 @@ impl LcuClient {
     }
 
     /// Parse a single game from match history
-    fn parse_game(&self, game: &serde_json::Value, puuid: &str) -> Option<LcuMatchData> {
+    pub fn parse_game(&self, game: &serde_json::Value, puuid: &str) -> Option<LcuMatchData> {
         let game_id = game.get("gameId")?.as_i64()?;
         let game_creation = game.get("gameCreation")?.as_i64()?;
         let queue_id = game.get("queueId")?.as_i64()? as i32;
+        let game_duration = game
+            .get("gameDuration")
+            .and_then(|d| d.as_u64())
+            .unwrap_or(0) as u32;
+        let patch = game
+            .get("gameVersion")
+            .and_then(|v| v.as_str())
+            .and_then(patch_from_game_version);
 
         // Find our participant
         let participants = game.get("participants")?.as_array()?;
@@ -257,8 +733,13 @@ impl LcuClient {
             p.get("participantId").and_then(|id| id.as_i64()) == Some(my_participant_id)
         })?;
 
+        let game_mode = game
+            .get("gameMode")
+            .and_then(|m| m.as_str())
+            .map(GameMode::from_lcu)
+            .unwrap_or(GameMode::Classic);
+
         let my_champion_id = my_participant.get("championId")?.as_i64()? as i32;
-        let my_team_id = my_participant.get("teamId")?.as_i64()?;
         let stats = my_participant.get("stats")?;
         let win = stats.get("win")?.as_bool()?;
 
@@ -274,22 +755,21 @@ impl LcuClient {
             .unwrap_or("NONE")
             .to_string();
 
-        // Find enemy laner (same lane, different team)
-        let mut enemy_champion_id = None;
-        for participant in participants {
-            let team_id = participant.get("teamId").and_then(|t| t.as_i64());
-            if team_id != Some(my_team_id) {
-                let enemy_timeline = participant.get("timeline");
-                let enemy_lane = enemy_timeline
-                    .and_then(|t| t.get("lane"))
-                    .and_then(|l| l.as_str());
-
-                if enemy_lane == Some(&lane) || lane == "NONE" {
-                    enemy_champion_id = participant.get("championId").and_then(|c| c.as_i64());
-                    break;
-                }
-            }
-        }
+        // Other modes have no lanes, so no lane opponent either
+        let opponent = if game_mode == GameMode::Classic {
+            find_lane_opponent(participants, my_participant)
+        } else {
+            None
+        };
+        let enemy_champion_id = opponent
+            .and_then(|(p, _)| p.get("championId"))
+            .and_then(|c| c.as_i64());
+        let enemy_participant_id = opponent
+            .and_then(|(p, _)| p.get("participantId"))
+            .and_then(|p| p.as_i64());
+        let opponent_uncertain = opponent.is_some_and(|(_, confident)| !confident);
+        let enemy_riot_id =
+            enemy_participant_id.and_then(|id| player_riot_id(participant_identities, id));
 
         Some(LcuMatchData {
             game_id,
@@ -298,21 +778,266 @@ impl LcuClient {
             my_champion_name: champion_id_to_name(my_champion_id),
             enemy_champion_id: enemy_champion_id.map(|id| id as i32),
             enemy_champion_name: enemy_champion_id.map(|id| champion_id_to_name(id as i32)),
-            role: normalize_role(&role, &lane),
+            role: match game_mode {
+                GameMode::Aram => "aram".to_string(),
+                GameMode::Arena => "arena".to_string(),
+                _ => normalize_role(&role, &lane),
+            },
             lane,
             win,
             queue_id,
+            patch,
+            game_mode,
+            loadout: parse_loadout(my_participant),
+            arena: (game_mode == GameMode::Arena)
+                .then(|| parse_arena(participants, my_participant_id)),
+            stats: parse_stats(stats, game_duration),
+            my_participant_id,
+            enemy_participant_id,
+            opponent_uncertain,
+            enemy_riot_id,
+            side: my_participant
+                .get("teamId")
+                .and_then(|t| t.as_i64())
+                .and_then(Side::from_team_id)
+                .filter(|_| game_mode != GameMode::Arena),
+            puuid: puuid.to_string(),
+            raw: game.clone(),
         })
     }
 
+    /// Write a rune page to the client and make it the active one
+    ///
+    /// Reuses the page named `name` when it exists, so repeated applies don't
+    /// use up rune page slots.
+    pub fn apply_rune_page(&self, name: &str, page: &RunePage) -> Result<(), LcuError> {
+        let pages: Vec<serde_json::Value> = self.get_json("/lol-perks/v1/pages")?;
+
+        let existing = pages.iter().find(|p| {
+            p.get("name").and_then(|n| n.as_str()) == Some(name)
+                && p.get("isEditable").and_then(|e| e.as_bool()) == Some(true)
+        });
+
+        let body = serde_json::json!({
+            "name": name,
+            "primaryStyleId": page.primary_style_id,
+            "subStyleId": page.sub_style_id,
+            "selectedPerkIds": page.selected_perk_ids,
+            "current": true,
+        });
+
+        match existing
+            .and_then(|p| p.get("id"))
+            .and_then(|id| id.as_i64())
+        {
+            Some(id) => {
+                self.send(
+                    reqwest::Method::PUT,
+                    &format!("/lol-perks/v1/pages/{}", id),
+                    Some(&body),
+                )?;
+                self.send(
+                    reqwest::Method::PUT,
+                    "/lol-perks/v1/currentpage",
+                    Some(&serde_json::json!(id)),
+                )?;
+            }
+            None => {
+                self.send(reqwest::Method::POST, "/lol-perks/v1/pages", Some(&body))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Full version of the installed game, e.g. "14.3.556.1234"
+    pub fn get_game_version(&self) -> Result<String, LcuError> {
+        self.get_json("/lol-patch/v1/game-version")
+    }
+
+    /// Read the rune page currently selected in the client
+    pub fn get_current_rune_page(&self) -> Result<RunePage, LcuError> {
+        let page: serde_json::Value = self.get_json("/lol-perks/v1/currentpage")?;
+
+        parse_rune_page(&page)
+            .ok_or_else(|| LcuError::ParseError("Incomplete rune page".to_string()))
+    }
+
+    /// Save an item set in the client, replacing any earlier set with the same title
+    pub fn save_item_set(&self, item_set: serde_json::Value) -> Result<(), LcuError> {
+        let summoner_id = self
+            .summoner_id
+            .ok_or_else(|| LcuError::ParseError("No summoner ID".to_string()))?;
+        let endpoint = format!("/lol-item-sets/v1/item-sets/{}/sets", summoner_id);
+
+        let mut sets: serde_json::Value = self.get_json(&endpoint)?;
+
+        let list = sets
+            .get_mut("itemSets")
+            .and_then(|s| s.as_array_mut())
+            .ok_or_else(|| LcuError::ParseError("Missing itemSets".to_string()))?;
+        list.retain(|s| s.get("title") != item_set.get("title"));
+        list.push(item_set);
+
+        self.send(reqwest::Method::PUT, &endpoint, Some(&sets))?;
+        Ok(())
+    }
+
+    /// Fetch my current ranks per queue type
+    fn get_ranks(&self) -> Result<HashMap<String, RankInfo>, LcuError> {
+        let stats: serde_json::Value = self.get_json("/lol-ranked/v1/current-ranked-stats")?;
+
+        Ok(parse_ranks(&stats))
+    }
+
+    /// My rank in the ranked queue matching `queue`, if ranked there
+    pub fn rank_for_queue(&self, queue: QueueType) -> Option<RankInfo> {
+        let queue_type = match queue {
+            QueueType::RankedFlex => "RANKED_FLEX_SR",
+            _ => "RANKED_SOLO_5x5",
+        };
+        self.ranks.get(queue_type).cloned()
+    }
+
+    /// Current gameflow phase (lobby, champ select, in game, ...)
+    pub fn get_gameflow_phase(&self) -> Result<GameflowPhase, LcuError> {
+        self.get_json("/lol-gameflow/v1/gameflow-phase")
+    }
+
+    /// End of game stats block, `None` when no game just ended
+    pub fn get_eog_stats_block(&self) -> Result<Option<serde_json::Value>, LcuError> {
+        match self.get_json("/lol-end-of-game/v1/eog-stats-block") {
+            Ok(block) => Ok(Some(block)),
+            Err(LcuError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Current champ select session, `None` outside of champ select
+    pub fn get_champ_select_session(&self) -> Result<Option<ChampSelectInfo>, LcuError> {
+        let session: serde_json::Value = match self.get_json("/lol-champ-select/v1/session") {
+            Ok(session) => session,
+            Err(LcuError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(parse_champ_select(&session))
+    }
+
+    /// Bans and picks completed so far in the current champ select, with my side
+    ///
+    /// `None` outside of champ select.
+    pub fn get_champ_select_draft(&self) -> Result<Option<(Side, Vec<DraftAction>)>, LcuError> {
+        match self.get_json::<serde_json::Value>("/lol-champ-select/v1/session") {
+            Ok(session) => Ok(parse_draft(&session)),
+            Err(LcuError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch the full raw game object from match history
+    pub fn get_game(&self, game_id: i64) -> Result<serde_json::Value, LcuError> {
+        let endpoint = format!("/lol-match-history/v1/games/{}", game_id);
+        self.get_json(&endpoint)
+    }
+
+    /// Fetch a game's timeline and compute my laning numbers at 10 minutes
+    pub fn get_early_game_stats(
+        &self,
+        game_id: i64,
+        my_participant_id: i64,
+        enemy_participant_id: Option<i64>,
+    ) -> Result<EarlyGameStats, LcuError> {
+        let endpoint = format!("/lol-match-history/v1/game-timelines/{}", game_id);
+        let timeline: serde_json::Value = self.get_json(&endpoint)?;
+
+        parse_early_game(&timeline, my_participant_id, enemy_participant_id)
+            .ok_or_else(|| LcuError::ParseError("Timeline too short or malformed".to_string()))
+    }
+
     /// Debug: get raw API response
     pub fn debug_endpoint(&self, endpoint: &str) -> Result<String, LcuError> {
         self.request(endpoint)
     }
 
+    /// Send an arbitrary request, returning the JSON response (`null` when empty)
+    pub fn raw_request(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value, LcuError> {
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| LcuError::ApiError(format!("Invalid HTTP method: {}", method)))?;
+        if !endpoint.starts_with('/') {
+            return Err(LcuError::ApiError(
+                "Endpoint must start with '/'".to_string(),
+            ));
+        }
+
+        let response = self.send(method, endpoint, body)?;
+        if response.trim().is_empty() {
+            return Ok(serde_json::Value::Null);
+        }
+        // Some endpoints answer with plain text
+        Ok(serde_json::from_str(&response).unwrap_or(serde_json::Value::String(response)))
+    }
+
     pub fn is_connected(&self) -> bool {
         self.credentials.is_some()
     }
+
+    /// PUUID of the logged in account, once connected
+    pub fn summoner_puuid(&self) -> Option<&str> {
+        self.summoner_puuid.as_deref()
+    }
+
+    /// Forget the current connection, e.g. after the client shut down
+    pub fn disconnect(&mut self) {
+        self.credentials = None;
+        self.summoner_puuid = None;
+        self.summoner_name = None;
+        self.summoner_id = None;
+        self.ranks.clear();
+        self.generation += 1;
+    }
+
+    /// Take over the connection `connected` made, unless this client was
+    /// disconnected or its TLS changed since `connected` was cloned from it
+    ///
+    /// Returns whether the connection was taken over.
+    pub fn adopt_connection(&mut self, connected: LcuClient) -> bool {
+        if connected.generation != self.generation {
+            return false;
+        }
+        self.credentials = connected.credentials;
+        self.summoner_puuid = connected.summoner_puuid;
+        self.summoner_name = connected.summoner_name;
+        self.summoner_id = connected.summoner_id;
+        self.ranks = connected.ranks;
+        self.auto_reconnect = connected.auto_reconnect;
+        true
+    }
+
+    /// Whether the background monitor should try to connect
+    pub fn auto_reconnect(&self) -> bool {
+        self.auto_reconnect
+    }
+
+    /// Disconnect and stay disconnected until `connect` is called again
+    pub fn disconnect_by_user(&mut self) {
+        self.disconnect();
+        self.auto_reconnect = false;
+    }
+
+    /// Current connection state
+    pub fn status(&self) -> LcuConnectionStatus {
+        LcuConnectionStatus {
+            connected: self.is_connected(),
+            summoner_name: self.summoner_name.clone(),
+            port: self.credentials.as_ref().map(|c| c.port),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -320,6 +1045,8 @@ struct CurrentSummoner {
     #[serde(rename = "displayName")]
     display_name: String,
     puuid: String,
+    #[serde(rename = "summonerId", default)]
+    summoner_id: Option<i64>,
 }
 
 impl Default for LcuClient {
@@ -328,181 +1055,629 @@ impl Default for LcuClient {
     }
 }
 
-/// Convert champion ID to name (basic mapping for common champions)
+/// Convert champion ID to name
 fn champion_id_to_name(id: i32) -> String {
-    match id {
-        1 => "Annie",
-        2 => "Olaf",
-        3 => "Galio",
-        4 => "TwistedFate",
-        5 => "XinZhao",
-        6 => "Urgot",
-        7 => "LeBlanc",
-        8 => "Vladimir",
-        9 => "Fiddlesticks",
-        10 => "Kayle",
-        11 => "MasterYi",
-        12 => "Alistar",
-        13 => "Ryze",
-        14 => "Sion",
-        15 => "Sivir",
-        16 => "Soraka",
-        17 => "Teemo",
-        18 => "Tristana",
-        19 => "Warwick",
-        20 => "Nunu",
-        21 => "MissFortune",
-        22 => "Ashe",
-        23 => "Tryndamere",
-        24 => "Jax",
-        25 => "Morgana",
-        26 => "Zilean",
-        27 => "Singed",
-        28 => "Evelynn",
-        29 => "Twitch",
-        30 => "Karthus",
-        31 => "Chogath",
-        32 => "Amumu",
-        33 => "Rammus",
-        34 => "Anivia",
-        35 => "Shaco",
-        36 => "DrMundo",
-        37 => "Sona",
-        38 => "Kassadin",
-        39 => "Irelia",
-        40 => "Janna",
-        41 => "Gangplank",
-        42 => "Corki",
-        43 => "Karma",
-        44 => "Taric",
-        45 => "Veigar",
-        48 => "Trundle",
-        50 => "Swain",
-        51 => "Caitlyn",
-        53 => "Blitzcrank",
-        54 => "Malphite",
-        55 => "Katarina",
-        56 => "Nocturne",
-        57 => "Maokai",
-        58 => "Renekton",
-        59 => "JarvanIV",
-        60 => "Elise",
-        61 => "Orianna",
-        62 => "Wukong",
-        63 => "Brand",
-        64 => "LeeSin",
-        67 => "Vayne",
-        68 => "Rumble",
-        69 => "Cassiopeia",
-        72 => "Skarner",
-        74 => "Heimerdinger",
-        75 => "Nasus",
-        76 => "Nidalee",
-        77 => "Udyr",
-        78 => "Poppy",
-        79 => "Gragas",
-        80 => "Pantheon",
-        81 => "Ezreal",
-        82 => "Mordekaiser",
-        83 => "Yorick",
-        84 => "Akali",
-        85 => "Kennen",
-        86 => "Garen",
-        89 => "Leona",
-        90 => "Malzahar",
-        91 => "Talon",
-        92 => "Riven",
-        96 => "KogMaw",
-        98 => "Shen",
-        99 => "Lux",
-        101 => "Xerath",
-        102 => "Shyvana",
-        103 => "Ahri",
-        104 => "Graves",
-        105 => "Fizz",
-        106 => "Volibear",
-        107 => "Rengar",
-        110 => "Varus",
-        111 => "Nautilus",
-        112 => "Viktor",
-        113 => "Sejuani",
-        114 => "Fiora",
-        115 => "Ziggs",
-        117 => "Lulu",
-        119 => "Draven",
-        120 => "Hecarim",
-        121 => "Khazix",
-        122 => "Darius",
-        126 => "Jayce",
-        127 => "Lissandra",
-        131 => "Diana",
-        133 => "Quinn",
-        134 => "Syndra",
-        136 => "AurelionSol",
-        141 => "Kayn",
-        142 => "Zoe",
-        143 => "Zyra",
-        145 => "Kaisa",
-        147 => "Seraphine",
-        150 => "Gnar",
-        154 => "Zac",
-        157 => "Yasuo",
-        161 => "Velkoz",
-        163 => "Taliyah",
-        164 => "Camille",
-        166 => "Akshan",
-        200 => "Belveth",
-        201 => "Braum",
-        202 => "Jhin",
-        203 => "Kindred",
-        221 => "Zeri",
-        222 => "Jinx",
-        223 => "TahmKench",
-        233 => "Briar",
-        234 => "Viego",
-        235 => "Senna",
-        236 => "Lucian",
-        238 => "Zed",
-        240 => "Kled",
-        245 => "Ekko",
-        246 => "Qiyana",
-        254 => "Vi",
-        266 => "Aatrox",
-        267 => "Nami",
-        268 => "Azir",
-        350 => "Yuumi",
-        360 => "Samira",
-        412 => "Thresh",
-        420 => "Illaoi",
-        421 => "RekSai",
-        427 => "Ivern",
-        429 => "Kalista",
-        432 => "Bard",
-        497 => "Rakan",
-        498 => "Xayah",
-        516 => "Ornn",
-        517 => "Sylas",
-        518 => "Neeko",
-        523 => "Aphelios",
-        526 => "Rell",
-        555 => "Pyke",
-        711 => "Vex",
-        777 => "Yone",
-        799 => "Ambessa",
-        875 => "Sett",
-        876 => "Lillia",
-        887 => "Gwen",
-        888 => "Renata",
-        893 => "Aurora",
-        895 => "Nilah",
-        897 => "KSante",
-        901 => "Smolder",
-        902 => "Milio",
-        910 => "Hwei",
-        950 => "Naafiri",
-        _ => return format!("Champion{}", id),
-    }
-    .to_string()
+    champions::name_for_id(id).unwrap_or_else(|| format!("Champion{}", id))
+}
+
+/// Reduce a full game version ("14.3.556.1234") to its patch ("14.3")
+pub fn patch_from_game_version(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let season: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = parts.next()?.parse().ok()?;
+    Some(format!("{}.{}", season, patch))
+}
+
+/// Riot ID of a participant as "Name#TAG", falling back to the old summoner name
+fn player_riot_id(identities: &[serde_json::Value], participant_id: i64) -> Option<String> {
+    let player = identities
+        .iter()
+        .find(|i| i.get("participantId").and_then(|p| p.as_i64()) == Some(participant_id))?
+        .get("player")?;
+    let game_name = player.get("gameName").and_then(|n| n.as_str());
+    let tag_line = player.get("tagLine").and_then(|t| t.as_str());
+    match (game_name, tag_line) {
+        (Some(name), Some(tag)) if !name.is_empty() => Some(format!("{}#{}", name, tag)),
+        _ => player
+            .get("summonerName")
+            .and_then(|n| n.as_str())
+            .filter(|n| !n.is_empty())
+            .map(str::to_string),
+    }
+}
+
+/// Map a raw game object into a typed scoreboard
+pub fn parse_game_details(game: &serde_json::Value) -> Option<GameDetails> {
+    let game_id = game.get("gameId")?.as_i64()?;
+    let game_creation = game.get("gameCreation")?.as_i64()?;
+    let queue_id = game.get("queueId")?.as_i64()? as i32;
+    let game_duration = game
+        .get("gameDuration")
+        .and_then(|d| d.as_u64())
+        .unwrap_or(0) as u32;
+
+    let participants = game.get("participants")?.as_array()?;
+    let identities = game
+        .get("participantIdentities")
+        .and_then(|i| i.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut teams: Vec<ScoreboardTeam> = Vec::new();
+    for participant in participants {
+        let participant_id = participant.get("participantId")?.as_i64()?;
+        let team_id = participant.get("teamId")?.as_i64()?;
+        let champion_id = participant.get("championId")?.as_i64()? as i32;
+        let stats = participant.get("stats")?;
+        let win = stats.get("win").and_then(|w| w.as_bool()).unwrap_or(false);
+
+        let timeline = participant.get("timeline");
+        let lane_value = |key: &str| {
+            timeline
+                .and_then(|t| t.get(key))
+                .and_then(|v| v.as_str())
+                .unwrap_or("NONE")
+                .to_string()
+        };
+        let (role, lane) = (lane_value("role"), lane_value("lane"));
+
+        let items = (0..=6)
+            .filter_map(|slot| stats.get(format!("item{}", slot)).and_then(|i| i.as_i64()))
+            .filter(|id| *id != 0)
+            .map(|id| id as i32)
+            .collect();
+        let summoner_spells = ["spell1Id", "spell2Id"]
+            .iter()
+            .filter_map(|key| participant.get(*key).and_then(|s| s.as_i64()))
+            .map(|id| id as i32)
+            .collect();
+
+        let player = ScoreboardPlayer {
+            participant_id,
+            summoner_name: player_riot_id(&identities, participant_id),
+            champion_id,
+            champion_name: champion_id_to_name(champion_id),
+            role: normalize_role(&role, &lane),
+            stats: parse_stats(stats, game_duration),
+            champion_level: stats
+                .get("champLevel")
+                .and_then(|l| l.as_u64())
+                .unwrap_or(0) as u32,
+            items,
+            summoner_spells,
+        };
+
+        match teams.iter_mut().find(|t| t.team_id == team_id) {
+            Some(team) => team.players.push(player),
+            None => teams.push(ScoreboardTeam {
+                team_id,
+                win,
+                players: vec![player],
+            }),
+        }
+    }
+    teams.sort_by_key(|t| t.team_id);
+
+    Some(GameDetails {
+        game_id,
+        game_creation,
+        game_duration,
+        queue_id,
+        teams,
+    })
+}
+
+/// Map champ select's `assignedPosition` to our role names
+pub fn normalize_position(position: &str) -> Option<String> {
+    let role = match position.to_lowercase().as_str() {
+        "top" => "top",
+        "jungle" => "jungle",
+        "middle" | "mid" => "mid",
+        "bottom" | "bot" => "adc",
+        "utility" | "support" => "support",
+        _ => return None,
+    };
+    Some(role.to_string())
+}
+
+/// Extract my position, my champion and the enemy laner from a champ select session
+fn parse_champ_select(session: &serde_json::Value) -> Option<ChampSelectInfo> {
+    let local_cell = session.get("localPlayerCellId")?.as_i64()?;
+    let my_team = session.get("myTeam")?.as_array()?;
+    let their_team = session
+        .get("theirTeam")
+        .and_then(|t| t.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    // Hovered picks show up as championPickIntent before locking in
+    let champion = |member: &serde_json::Value| {
+        ["championId", "championPickIntent"]
+            .iter()
+            .filter_map(|key| member.get(*key).and_then(|c| c.as_i64()))
+            .find(|id| *id > 0)
+            .map(|id| champion_id_to_name(id as i32))
+    };
+    let position = |member: &serde_json::Value| {
+        member
+            .get("assignedPosition")
+            .and_then(|p| p.as_str())
+            .and_then(normalize_position)
+    };
+
+    let me = my_team
+        .iter()
+        .find(|m| m.get("cellId").and_then(|c| c.as_i64()) == Some(local_cell))?;
+    let my_position = position(me);
+
+    let enemy_champion = my_position.as_ref().and_then(|mine| {
+        their_team
+            .iter()
+            .find(|m| position(m).as_ref() == Some(mine))
+            .and_then(champion)
+    });
+
+    let bans = ["myTeamBans", "theirTeamBans"]
+        .iter()
+        .filter_map(|key| session.get("bans")?.get(*key)?.as_array())
+        .flatten()
+        .filter_map(|id| id.as_i64())
+        .filter(|id| *id > 0)
+        .map(|id| champion_id_to_name(id as i32))
+        .collect();
+
+    let turns: Vec<&Vec<serde_json::Value>> = session
+        .get("actions")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|turn| turn.as_array())
+        .collect();
+    let is_action = |action: &serde_json::Value, kind: &str, flag: &str| {
+        action.get("type").and_then(|t| t.as_str()) == Some(kind)
+            && action.get(flag).and_then(|p| p.as_bool()) == Some(true)
+    };
+    let is_mine = |action: &serde_json::Value| {
+        action.get("actorCellId").and_then(|c| c.as_i64()) == Some(local_cell)
+    };
+    let has_action = |kind: &str, flag: &str| {
+        turns
+            .iter()
+            .copied()
+            .flatten()
+            .any(|action| is_mine(action) && is_action(action, kind, flag))
+    };
+
+    // Positions of enemies are hidden, so the turn order tells whether I picked first
+    let mut picked_before_me = Vec::new();
+    for turn in &turns {
+        if turn
+            .iter()
+            .any(|action| is_mine(action) && is_action(action, "pick", "completed"))
+        {
+            break;
+        }
+        picked_before_me.extend(
+            turn.iter()
+                .filter(|action| is_action(action, "pick", "completed"))
+                .filter(|action| {
+                    action.get("isAllyAction").and_then(|a| a.as_bool()) == Some(false)
+                })
+                .filter_map(|action| action.get("championId")?.as_i64().filter(|id| *id > 0))
+                .map(|id| champion_id_to_name(id as i32)),
+        );
+    }
+
+    Some(ChampSelectInfo {
+        my_position,
+        my_champion: champion(me),
+        enemy_champion,
+        enemy_picks: their_team.iter().filter_map(champion).collect(),
+        picked_before_me,
+        bans,
+        banning: has_action("ban", "isInProgress"),
+        locked: has_action("pick", "completed"),
+    })
+}
+
+/// Rebuild the completed bans and picks of a champ select session, in order
+fn parse_draft(session: &serde_json::Value) -> Option<(Side, Vec<DraftAction>)> {
+    let local_cell = session.get("localPlayerCellId")?.as_i64()?;
+    let members: Vec<&serde_json::Value> = ["myTeam", "theirTeam"]
+        .iter()
+        .filter_map(|key| session.get(*key)?.as_array())
+        .flatten()
+        .collect();
+    let member = |cell: i64| {
+        members
+            .iter()
+            .find(|m| m.get("cellId").and_then(|c| c.as_i64()) == Some(cell))
+    };
+    // Blue side holds cells 0-4 unless the client says otherwise
+    let side_of = |cell: i64| match member(cell).and_then(|m| m.get("team")?.as_i64()) {
+        Some(2) => Side::Red,
+        Some(_) => Side::Blue,
+        None if cell < 5 => Side::Blue,
+        None => Side::Red,
+    };
+    let my_side = side_of(local_cell);
+
+    let actions = session
+        .get("actions")?
+        .as_array()?
+        .iter()
+        .filter_map(|turn| turn.as_array())
+        .flatten()
+        .filter(|action| action.get("completed").and_then(|c| c.as_bool()) == Some(true))
+        .filter_map(|action| {
+            let kind = match action.get("type")?.as_str()? {
+                "ban" => DraftActionKind::Ban,
+                "pick" => DraftActionKind::Pick,
+                _ => return None,
+            };
+            let champion_id = action.get("championId")?.as_i64().filter(|id| *id > 0)?;
+            let cell = action.get("actorCellId")?.as_i64()?;
+            let side = match action.get("isAllyAction").and_then(|a| a.as_bool()) {
+                Some(true) => my_side,
+                Some(false) => my_side.opposite(),
+                None => side_of(cell),
+            };
+            let position = member(cell)
+                .and_then(|m| m.get("assignedPosition")?.as_str())
+                .and_then(normalize_position);
+            Some(DraftAction {
+                kind,
+                side,
+                champion: champion_id_to_name(champion_id as i32),
+                position: position.filter(|_| kind == DraftActionKind::Pick),
+                note: String::new(),
+            })
+        })
+        .collect();
+
+    Some((my_side, actions))
+}
+
+/// Read the game ID and my multikills and team totals from an end of game stats block
+pub fn parse_eog_stats(block: &serde_json::Value) -> Option<(i64, EndOfGameStats)> {
+    let game_id = block.get("gameId")?.as_i64()?;
+    let stat = |stats: Option<&serde_json::Value>, key: &str| {
+        stats
+            .and_then(|s| s.get(key))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32
+    };
+
+    let mine = block.get("localPlayer").and_then(|p| p.get("stats"));
+    let mut my_team = TeamTotals::default();
+    let mut enemy_team = TeamTotals::default();
+    for team in block.get("teams")?.as_array()? {
+        let totals = if team.get("isPlayerTeam").and_then(|p| p.as_bool()) == Some(true) {
+            &mut my_team
+        } else {
+            &mut enemy_team
+        };
+        for player in team
+            .get("players")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let stats = player.get("stats");
+            totals.kills += stat(stats, "CHAMPIONS_KILLED");
+            totals.gold_earned += stat(stats, "GOLD_EARNED");
+            totals.turrets_killed += stat(stats, "TURRETS_KILLED");
+        }
+    }
+
+    Some((
+        game_id,
+        EndOfGameStats {
+            largest_multi_kill: stat(mine, "LARGEST_MULTI_KILL"),
+            largest_killing_spree: stat(mine, "LARGEST_KILLING_SPREE"),
+            double_kills: stat(mine, "DOUBLE_KILLS"),
+            triple_kills: stat(mine, "TRIPLE_KILLS"),
+            quadra_kills: stat(mine, "QUADRA_KILLS"),
+            penta_kills: stat(mine, "PENTA_KILLS"),
+            my_team,
+            enemy_team,
+        },
+    ))
+}
+
+/// Summoner's Rift map ID
+const SUMMONERS_RIFT_MAP_ID: i32 = 11;
+
+/// Build a client item set for one champion with a single block of items
+pub fn build_item_set(title: &str, champion_id: i32, item_ids: &[i32]) -> serde_json::Value {
+    serde_json::json!({
+        "uid": uuid::Uuid::new_v4().to_string(),
+        "title": title,
+        "type": "custom",
+        "map": "any",
+        "mode": "any",
+        "sortrank": 0,
+        "startedFrom": "blank",
+        "associatedChampions": [champion_id],
+        "associatedMaps": [SUMMONERS_RIFT_MAP_ID],
+        "blocks": [{
+            "type": title,
+            "items": item_ids
+                .iter()
+                .map(|id| serde_json::json!({ "id": id.to_string(), "count": 1 }))
+                .collect::<Vec<_>>(),
+        }],
+    })
+}
+
+/// Read the per-queue ranks from `current-ranked-stats`, skipping unranked queues
+fn parse_ranks(stats: &serde_json::Value) -> HashMap<String, RankInfo> {
+    let Some(queues) = stats.get("queueMap").and_then(|q| q.as_object()) else {
+        return HashMap::new();
+    };
+
+    queues
+        .iter()
+        .filter_map(|(queue_type, entry)| {
+            let tier: Tier = serde_json::from_value(entry.get("tier")?.clone()).ok()?;
+            Some((
+                queue_type.clone(),
+                RankInfo {
+                    tier,
+                    division: entry
+                        .get("division")
+                        .and_then(|d| d.as_str())
+                        .filter(|d| *d != "NA")
+                        .unwrap_or("")
+                        .to_string(),
+                    league_points: entry
+                        .get("leaguePoints")
+                        .and_then(|lp| lp.as_i64())
+                        .unwrap_or(0) as i32,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Map a client rune page object
+fn parse_rune_page(page: &serde_json::Value) -> Option<RunePage> {
+    Some(RunePage {
+        primary_style_id: page.get("primaryStyleId")?.as_i64()? as i32,
+        sub_style_id: page.get("subStyleId")?.as_i64()? as i32,
+        selected_perk_ids: page
+            .get("selectedPerkIds")?
+            .as_array()?
+            .iter()
+            .filter_map(|id| id.as_i64())
+            .map(|id| id as i32)
+            .collect(),
+    })
+}
+
+/// Read runes and summoner spells from a participant
+fn parse_loadout(participant: &serde_json::Value) -> GameLoadout {
+    let stats = participant.get("stats");
+    let stat = |key: &str| {
+        stats
+            .and_then(|s| s.get(key))
+            .and_then(|v| v.as_i64())
+            .filter(|id| *id != 0)
+            .map(|id| id as i32)
+    };
+    let spell = |key: &str| {
+        participant
+            .get(key)
+            .and_then(|s| s.as_i64())
+            .map(|id| id as i32)
+    };
+
+    GameLoadout {
+        primary_tree: stat("perkPrimaryStyle"),
+        secondary_tree: stat("perkSubStyle"),
+        runes: (0..6)
+            .map(|i| format!("perk{}", i))
+            .chain((0..3).map(|i| format!("statPerk{}", i)))
+            .filter_map(|key| stat(&key))
+            .collect(),
+        summoner_spells: ["spell1Id", "spell2Id"]
+            .iter()
+            .filter_map(|key| spell(key))
+            .collect(),
+    }
+}
+
+/// Smite's summoner spell ID, the most reliable sign of a jungler
+const SMITE_SPELL_ID: i64 = 11;
+
+/// Pick my lane opponent among the enemy team
+///
+/// A single enemy with my lane and role is a confident match. Otherwise candidates
+/// are ranked (same lane and role, same lane, same normalized position, smite
+/// matching mine) and the best one is returned as a low-confidence guess.
+fn find_lane_opponent<'a>(
+    participants: &'a [serde_json::Value],
+    me: &serde_json::Value,
+) -> Option<(&'a serde_json::Value, bool)> {
+    let team = |p: &serde_json::Value| p.get("teamId").and_then(|t| t.as_i64());
+    let timeline_value = |p: &serde_json::Value, key: &str| {
+        p.get("timeline")
+            .and_then(|t| t.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or("NONE")
+            .to_string()
+    };
+    let has_smite = |p: &serde_json::Value| {
+        ["spell1Id", "spell2Id"]
+            .iter()
+            .any(|key| p.get(*key).and_then(|s| s.as_i64()) == Some(SMITE_SPELL_ID))
+    };
+
+    let my_lane = timeline_value(me, "lane");
+    let my_role = timeline_value(me, "role");
+    let my_position = normalize_role(&my_role, &my_lane);
+
+    let enemies: Vec<&serde_json::Value> = participants
+        .iter()
+        .filter(|p| team(p).is_some() && team(p) != team(me))
+        .collect();
+
+    let score = |p: &serde_json::Value| {
+        let lane = timeline_value(p, "lane");
+        let role = timeline_value(p, "role");
+        let same_lane = my_lane != "NONE" && lane == my_lane;
+        let mut score = 0;
+        if same_lane && role == my_role {
+            score += 4;
+        }
+        if same_lane {
+            score += 2;
+        }
+        if normalize_role(&role, &lane) == my_position {
+            score += 1;
+        }
+        if has_smite(p) == has_smite(me) {
+            score += 1;
+        }
+        score
+    };
+
+    let exact: Vec<&&serde_json::Value> = enemies
+        .iter()
+        .filter(|p| {
+            my_lane != "NONE"
+                && timeline_value(p, "lane") == my_lane
+                && timeline_value(p, "role") == my_role
+        })
+        .collect();
+    if let [only] = exact.as_slice() {
+        return Some((only, true));
+    }
+
+    // Keep the first of equally ranked candidates, like the old lane scan did
+    let best = enemies.into_iter().rev().max_by_key(|p| score(p))?;
+    Some((best, false))
+}
+
+/// Group Arena participants into their duos, relative to me
+fn parse_arena(participants: &[serde_json::Value], my_participant_id: i64) -> ArenaResult {
+    let subteam = |p: &serde_json::Value| {
+        p.get("stats")
+            .and_then(|s| s.get("playerSubteamId"))
+            .and_then(|t| t.as_i64())
+    };
+    let champion = |p: &serde_json::Value| {
+        p.get("championId")
+            .and_then(|c| c.as_i64())
+            .map(|id| champion_id_to_name(id as i32))
+            .unwrap_or_else(|| "Unknown".to_string())
+    };
+
+    let me = participants
+        .iter()
+        .find(|p| p.get("participantId").and_then(|id| id.as_i64()) == Some(my_participant_id));
+    let my_subteam = me.and_then(subteam);
+
+    let mut partner = None;
+    let mut duos: std::collections::BTreeMap<i64, Vec<String>> = Default::default();
+    for participant in participants {
+        let participant_id = participant.get("participantId").and_then(|id| id.as_i64());
+        if participant_id == Some(my_participant_id) {
+            continue;
+        }
+        match subteam(participant) {
+            Some(team) if Some(team) == my_subteam => partner = Some(champion(participant)),
+            Some(team) => duos.entry(team).or_default().push(champion(participant)),
+            None => {}
+        }
+    }
+
+    ArenaResult {
+        partner,
+        placement: me
+            .and_then(|p| p.get("stats"))
+            .and_then(|s| s.get("subteamPlacement"))
+            .and_then(|p| p.as_u64())
+            .map(|p| p as u32),
+        opponents: duos.into_values().collect(),
+    }
+}
+
+/// Game time used for the laning snapshot
+const EARLY_GAME_MS: i64 = 10 * 60 * 1000;
+
+/// Compute CS and gold at 10 minutes (and the diff against my lane opponent)
+fn parse_early_game(
+    timeline: &serde_json::Value,
+    my_participant_id: i64,
+    enemy_participant_id: Option<i64>,
+) -> Option<EarlyGameStats> {
+    let frames = timeline.get("frames")?.as_array()?;
+
+    let timestamp = |f: &serde_json::Value| f.get("timestamp").and_then(|t| t.as_i64());
+
+    // Last frame at or before the 10 minute mark, as long as the game got there
+    if !frames
+        .iter()
+        .any(|f| timestamp(f).unwrap_or(0) >= EARLY_GAME_MS)
+    {
+        return None;
+    }
+    let frame = frames
+        .iter()
+        .rev()
+        .find(|f| timestamp(f).is_some_and(|t| t <= EARLY_GAME_MS))?;
+
+    let participant_frames = frame.get("participantFrames")?;
+    let snapshot = |participant_id: i64| -> Option<(i32, i32)> {
+        let pf = participant_frames.get(participant_id.to_string())?;
+        let value = |key: &str| pf.get(key).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        Some((
+            value("minionsKilled") + value("jungleMinionsKilled"),
+            value("totalGold"),
+        ))
+    };
+
+    let (cs_at_10, gold_at_10) = snapshot(my_participant_id)?;
+    let enemy = enemy_participant_id.and_then(snapshot);
+
+    // Game times of my deaths
+    let deaths: Vec<i64> = frames
+        .iter()
+        .filter_map(|f| f.get("events").and_then(|e| e.as_array()))
+        .flatten()
+        .filter(|e| {
+            e.get("type").and_then(|t| t.as_str()) == Some("CHAMPION_KILL")
+                && e.get("victimId").and_then(|v| v.as_i64()) == Some(my_participant_id)
+        })
+        .filter_map(timestamp)
+        .collect();
+    let deaths_before_10 = deaths.iter().filter(|&&t| t < EARLY_GAME_MS).count() as u32;
+
+    Some(EarlyGameStats {
+        cs_at_10: cs_at_10.max(0) as u32,
+        gold_at_10: gold_at_10.max(0) as u32,
+        cs_diff_at_10: enemy.map(|(cs, _)| cs_at_10 - cs),
+        gold_diff_at_10: enemy.map(|(_, gold)| gold_at_10 - gold),
+        deaths_before_10: Some(deaths_before_10),
+        first_death_seconds: deaths.iter().min().map(|&t| (t / 1000) as u32),
+    })
+}
+
+/// Read my performance numbers from a participant `stats` block
+fn parse_stats(stats: &serde_json::Value, game_duration: u32) -> MatchStats {
+    let stat = |key: &str| stats.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+    MatchStats {
+        kills: stat("kills"),
+        deaths: stat("deaths"),
+        assists: stat("assists"),
+        cs: stat("totalMinionsKilled") + stat("neutralMinionsKilled"),
+        gold_earned: stat("goldEarned"),
+        damage_to_champions: stat("totalDamageDealtToChampions"),
+        vision_score: stat("visionScore"),
+        game_duration,
+    }
 }
 
 /// Normalize role from LCU format to our format
@@ -521,3 +1696,507 @@ fn normalize_role(role: &str, lane: &str) -> String {
         _ => lane.to_lowercase(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn frame(timestamp: i64, me: (i64, i64), enemy: (i64, i64)) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "participantFrames": {
+                "1": { "participantId": 1, "minionsKilled": me.0, "jungleMinionsKilled": 0, "totalGold": me.1 },
+                "6": { "participantId": 6, "minionsKilled": enemy.0, "jungleMinionsKilled": 4, "totalGold": enemy.1 }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_early_game() {
+        let timeline = json!({
+            "frames": [
+                frame(0, (0, 500), (0, 500)),
+                frame(540_000, (70, 3000), (60, 2800)),
+                frame(600_000, (80, 3500), (66, 3100)),
+                frame(660_000, (90, 4000), (70, 3300)),
+            ]
+        });
+
+        let early = parse_early_game(&timeline, 1, Some(6)).unwrap();
+        assert_eq!(early.cs_at_10, 80);
+        assert_eq!(early.gold_at_10, 3500);
+        assert_eq!(early.cs_diff_at_10, Some(10));
+        assert_eq!(early.gold_diff_at_10, Some(400));
+
+        let solo = parse_early_game(&timeline, 1, None).unwrap();
+        assert_eq!(solo.gold_diff_at_10, None);
+    }
+
+    #[test]
+    fn test_deaths_before_10() {
+        let mut early_frame = frame(540_000, (70, 3000), (60, 2800));
+        early_frame["events"] = json!([
+            { "type": "CHAMPION_KILL", "timestamp": 200_000, "victimId": 1, "killerId": 6 },
+            { "type": "CHAMPION_KILL", "timestamp": 300_000, "victimId": 6, "killerId": 1 },
+            { "type": "WARD_PLACED", "timestamp": 310_000, "creatorId": 1 }
+        ]);
+        let mut late_frame = frame(660_000, (90, 4000), (70, 3300));
+        late_frame["events"] = json!([
+            { "type": "CHAMPION_KILL", "timestamp": 650_000, "victimId": 1, "killerId": 6 }
+        ]);
+        let timeline =
+            json!({ "frames": [early_frame, frame(600_000, (80, 3500), (66, 3100)), late_frame] });
+
+        let early = parse_early_game(&timeline, 1, Some(6)).unwrap();
+        assert_eq!(early.deaths_before_10, Some(1));
+        assert_eq!(early.first_death_seconds, Some(200));
+    }
+
+    fn laner(id: i64, team: i64, lane: &str, role: &str, smite: bool) -> serde_json::Value {
+        json!({
+            "participantId": id,
+            "teamId": team,
+            "championId": id,
+            "spell1Id": 4,
+            "spell2Id": if smite { 11 } else { 12 },
+            "timeline": { "lane": lane, "role": role }
+        })
+    }
+
+    #[test]
+    fn test_find_lane_opponent_bottom_roles() {
+        let participants = vec![
+            laner(1, 100, "BOTTOM", "DUO_CARRY", false),
+            laner(6, 200, "BOTTOM", "DUO_SUPPORT", false),
+            laner(7, 200, "BOTTOM", "DUO_CARRY", false),
+        ];
+
+        let (opponent, confident) = find_lane_opponent(&participants, &participants[0]).unwrap();
+        assert_eq!(opponent["participantId"], 7);
+        assert!(confident);
+    }
+
+    #[test]
+    fn test_find_lane_opponent_fallback_is_uncertain() {
+        // I roamed so my lane came out as NONE; smite points at their jungler
+        let participants = vec![
+            laner(1, 100, "NONE", "NONE", true),
+            laner(6, 200, "TOP", "SOLO", false),
+            laner(7, 200, "JUNGLE", "NONE", true),
+        ];
+
+        let (opponent, confident) = find_lane_opponent(&participants, &participants[0]).unwrap();
+        assert_eq!(opponent["participantId"], 7);
+        assert!(!confident);
+    }
+
+    #[test]
+    fn test_parse_loadout() {
+        let participant = json!({
+            "spell1Id": 4,
+            "spell2Id": 12,
+            "stats": {
+                "perk0": 8010, "perk1": 9111, "perk2": 9104, "perk3": 8299, "perk4": 8444, "perk5": 8242,
+                "statPerk0": 5005, "statPerk1": 5008, "statPerk2": 5011,
+                "perkPrimaryStyle": 8000,
+                "perkSubStyle": 8400
+            }
+        });
+
+        let loadout = parse_loadout(&participant);
+        assert_eq!(loadout.runes.len(), 9);
+        assert_eq!(loadout.runes[0], 8010);
+        assert_eq!(loadout.primary_tree, Some(8000));
+        assert_eq!(loadout.summoner_spells, vec![4, 12]);
+    }
+
+    #[test]
+    fn test_parse_champ_select() {
+        let session = json!({
+            "localPlayerCellId": 2,
+            "myTeam": [
+                { "cellId": 1, "championId": 64, "assignedPosition": "jungle" },
+                { "cellId": 2, "championId": 0, "championPickIntent": 122, "assignedPosition": "top" }
+            ],
+            "theirTeam": [
+                { "cellId": 5, "championId": 86, "assignedPosition": "top" },
+                { "cellId": 6, "championId": 0, "assignedPosition": "middle" },
+                { "cellId": 7, "championId": 51, "assignedPosition": "bottom" }
+            ],
+            "bans": { "myTeamBans": [157, 0], "theirTeamBans": [238] },
+            "actions": [
+                [
+                    { "actorCellId": 1, "type": "ban", "isInProgress": false },
+                    { "actorCellId": 2, "type": "ban", "isInProgress": true }
+                ],
+                [{ "actorCellId": 5, "type": "pick", "championId": 86, "completed": true, "isAllyAction": false }],
+                [{ "actorCellId": 2, "type": "pick", "completed": false, "isAllyAction": true }],
+                [{ "actorCellId": 7, "type": "pick", "championId": 51, "completed": true, "isAllyAction": false }]
+            ]
+        });
+
+        let info = parse_champ_select(&session).unwrap();
+        assert_eq!(info.my_position.as_deref(), Some("top"));
+        assert_eq!(info.my_champion.as_deref(), Some("Darius"));
+        assert_eq!(info.enemy_champion.as_deref(), Some("Garen"));
+        assert_eq!(info.enemy_picks, vec!["Garen", "Caitlyn"]);
+        // Not locked yet, so every enemy pick so far came first
+        assert_eq!(info.picked_before_me, vec!["Garen", "Caitlyn"]);
+        assert_eq!(info.bans, vec!["Yasuo", "Zed"]);
+        assert!(info.banning);
+        assert!(!info.locked);
+    }
+
+    #[test]
+    fn test_parse_draft() {
+        let session = json!({
+            "localPlayerCellId": 7,
+            "myTeam": [{ "cellId": 7, "team": 2, "assignedPosition": "top" }],
+            "theirTeam": [{ "cellId": 0, "team": 1, "assignedPosition": "top" }],
+            "actions": [
+                [
+                    { "actorCellId": 0, "type": "ban", "championId": 238, "completed": true, "isAllyAction": false },
+                    { "actorCellId": 7, "type": "ban", "championId": 0, "completed": true, "isAllyAction": true }
+                ],
+                [{ "actorCellId": 0, "type": "pick", "championId": 86, "completed": true, "isAllyAction": false }],
+                [{ "actorCellId": 7, "type": "pick", "championId": 122, "completed": false, "isAllyAction": true }]
+            ]
+        });
+
+        let (side, actions) = parse_draft(&session).unwrap();
+        assert_eq!(side, Side::Red);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].champion, "Zed");
+        assert_eq!(actions[0].side, Side::Blue);
+        assert_eq!(actions[1].kind, DraftActionKind::Pick);
+        assert_eq!(actions[1].position.as_deref(), Some("top"));
+    }
+
+    #[test]
+    fn test_configure_tls() {
+        let mut client = LcuClient::new();
+        assert!(client.configure_tls(None, false).is_ok());
+        assert!(client.configure_tls(Some(RIOT_ROOT_CA), false).is_ok());
+        assert!(client
+            .configure_tls(Some(b"not a certificate"), false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_adopt_connection() {
+        let mut client = LcuClient::new();
+        let mut connecting = client.clone();
+        connecting.auto_reconnect = false;
+        assert!(client.adopt_connection(connecting));
+        assert!(!client.auto_reconnect());
+
+        let connecting = client.clone();
+        client.disconnect_by_user();
+        assert!(!client.adopt_connection(connecting.clone()));
+        assert!(client.configure_tls(None, false).is_ok());
+        assert!(!client.adopt_connection(connecting));
+    }
+
+    #[test]
+    fn test_gameflow_phase() {
+        let phase: GameflowPhase = serde_json::from_str("\"EndOfGame\"").unwrap();
+        assert_eq!(phase, GameflowPhase::EndOfGame);
+
+        let phase: GameflowPhase = serde_json::from_str("\"TerminatedInError\"").unwrap();
+        assert_eq!(phase, GameflowPhase::Other);
+    }
+
+    #[test]
+    fn test_build_item_set() {
+        let set = build_item_set("Darius vs Garen", 122, &[3078, 3047]);
+
+        assert_eq!(set["associatedChampions"], json!([122]));
+        assert_eq!(set["blocks"][0]["items"][1]["id"], "3047");
+    }
+
+    #[test]
+    fn test_parse_ranks() {
+        let stats = json!({
+            "queueMap": {
+                "RANKED_SOLO_5x5": { "tier": "PLATINUM", "division": "II", "leaguePoints": 41 },
+                "RANKED_FLEX_SR": { "tier": "", "division": "NA", "leaguePoints": 0 },
+                "RANKED_TFT": { "tier": "MASTER", "division": "NA", "leaguePoints": 120 }
+            }
+        });
+
+        let ranks = parse_ranks(&stats);
+        assert_eq!(ranks.len(), 2);
+        assert_eq!(ranks["RANKED_SOLO_5x5"].tier, Tier::Platinum);
+        assert_eq!(ranks["RANKED_SOLO_5x5"].division, "II");
+        assert_eq!(ranks["RANKED_TFT"].division, "");
+    }
+
+    #[test]
+    fn test_parse_arena() {
+        let player = |id: i64, champion: i64, subteam: i64| {
+            json!({
+                "participantId": id,
+                "championId": champion,
+                "stats": { "playerSubteamId": subteam, "subteamPlacement": 2 }
+            })
+        };
+        let participants = vec![
+            player(1, 122, 3),
+            player(2, 86, 3),
+            player(3, 1, 1),
+            player(4, 2, 1),
+            player(5, 3, 2),
+            player(6, 4, 2),
+        ];
+
+        let arena = parse_arena(&participants, 1);
+        assert_eq!(arena.partner.as_deref(), Some("Garen"));
+        assert_eq!(arena.placement, Some(2));
+        assert_eq!(arena.opponents.len(), 2);
+        assert_eq!(arena.opponents[0], vec!["Annie", "Olaf"]);
+    }
+
+    #[test]
+    fn test_patch_from_game_version() {
+        assert_eq!(
+            patch_from_game_version("14.3.556.1234").as_deref(),
+            Some("14.3")
+        );
+        assert_eq!(patch_from_game_version("25.S1.3").as_deref(), None);
+        assert_eq!(patch_from_game_version(""), None);
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let participant = |id: i64, team: i64, champion: i64, win: bool| {
+            json!({
+                "participantId": id,
+                "teamId": team,
+                "championId": champion,
+                "stats": { "win": win, "kills": 4 },
+                "timeline": { "role": "SOLO", "lane": "TOP" }
+            })
+        };
+        let game = json!({
+            "gameId": 7,
+            "gameCreation": 1_700_000_000_000i64,
+            "gameDuration": 1800,
+            "gameMode": "CLASSIC",
+            "gameVersion": "14.12.589.4223",
+            "queueId": 420,
+            "participants": [participant(1, 100, 122, true), participant(6, 200, 86, false)],
+            "participantIdentities": [
+                { "participantId": 1, "player": { "puuid": "me" } },
+                { "participantId": 6, "player": { "puuid": "them", "gameName": "Them", "tagLine": "EUW" } }
+            ]
+        });
+
+        let parsed = LcuClient::new().parse_game(&game, "me").unwrap();
+        assert_eq!(parsed.enemy_riot_id.as_deref(), Some("Them#EUW"));
+
+        let m = parsed.to_match();
+        assert_eq!(m.patch.as_deref(), Some("14.12"));
+        assert_eq!(m.enemy_champion, "Garen");
+        assert_eq!(m.result, MatchResult::Win);
+        assert_eq!(m.side, Some(Side::Blue));
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        // One game of a match history page, trimmed to the fields read
+        let page = json!({
+            "accountId": 1,
+            "games": {
+                "gameCount": 1,
+                "games": [{
+                    "gameId": 7,
+                    "gameCreation": 1_700_000_000_000i64,
+                    "gameDuration": 1694,
+                    "gameMode": "CLASSIC",
+                    "queueId": 420,
+                    "participants": [{
+                        "participantId": 1,
+                        "teamId": 100,
+                        "championId": 122,
+                        "stats": {
+                            "win": true,
+                            "kills": 8,
+                            "deaths": 3,
+                            "assists": 5,
+                            "totalMinionsKilled": 187,
+                            "neutralMinionsKilled": 12,
+                            "goldEarned": 12450,
+                            "totalDamageDealtToChampions": 24310,
+                            "visionScore": 21,
+                            "largestMultiKill": 2
+                        },
+                        "timeline": { "role": "SOLO", "lane": "TOP" }
+                    }],
+                    "participantIdentities": [
+                        { "participantId": 1, "player": { "puuid": "me" } }
+                    ]
+                }]
+            }
+        });
+        let game = &page["games"]["games"][0];
+
+        let parsed = LcuClient::new().parse_game(game, "me").unwrap();
+        assert_eq!(
+            parsed.stats,
+            MatchStats {
+                kills: 8,
+                deaths: 3,
+                assists: 5,
+                cs: 199,
+                gold_earned: 12450,
+                damage_to_champions: 24310,
+                vision_score: 21,
+                game_duration: 1694,
+            }
+        );
+
+        // Missing numbers, as in remakes, read as zero
+        let stats = parse_stats(&json!({ "win": false, "kills": 1 }), 180);
+        assert_eq!((stats.kills, stats.cs, stats.gold_earned), (1, 0, 0));
+        assert_eq!(stats.game_duration, 180);
+    }
+
+    #[test]
+    fn test_parse_game_details() {
+        let participant = |id: i64, team: i64, champion: i64, win: bool| {
+            json!({
+                "participantId": id,
+                "teamId": team,
+                "championId": champion,
+                "spell1Id": 4,
+                "spell2Id": 14,
+                "stats": { "win": win, "kills": 3, "deaths": 1, "assists": 2, "item0": 3078, "item1": 0, "champLevel": 14 },
+                "timeline": { "role": "SOLO", "lane": "TOP" }
+            })
+        };
+        let game = json!({
+            "gameId": 7,
+            "gameCreation": 1_700_000_000_000i64,
+            "gameDuration": 1800,
+            "queueId": 420,
+            "participants": [participant(1, 100, 122, true), participant(6, 200, 86, false)],
+            "participantIdentities": [
+                { "participantId": 1, "player": { "gameName": "Me", "tagLine": "EUW" } },
+                { "participantId": 6, "player": { "summonerName": "Them" } }
+            ]
+        });
+
+        let details = parse_game_details(&game).unwrap();
+        assert_eq!(details.teams.len(), 2);
+        assert!(details.teams[0].win);
+
+        let me = &details.teams[0].players[0];
+        assert_eq!(me.champion_name, "Darius");
+        assert_eq!(me.summoner_name.as_deref(), Some("Me#EUW"));
+        assert_eq!(me.items, vec![3078]);
+        assert_eq!(me.summoner_spells, vec![4, 14]);
+        assert_eq!(me.role, "top");
+
+        let them = &details.teams[1].players[0];
+        assert_eq!(them.summoner_name.as_deref(), Some("Them"));
+    }
+
+    #[test]
+    fn test_parse_early_game_short_game() {
+        let timeline = json!({ "frames": [frame(0, (0, 500), (0, 500)), frame(300_000, (30, 1500), (30, 1500))] });
+        assert!(parse_early_game(&timeline, 1, Some(6)).is_none());
+    }
+
+    #[test]
+    fn test_parse_eog_stats() {
+        let block = json!({
+            "gameId": 42,
+            "localPlayer": { "stats": { "LARGEST_MULTI_KILL": 3, "DOUBLE_KILLS": 2, "TRIPLE_KILLS": 1 } },
+            "teams": [
+                {
+                    "isPlayerTeam": true,
+                    "players": [
+                        { "stats": { "CHAMPIONS_KILLED": 7, "GOLD_EARNED": 11000, "TURRETS_KILLED": 2 } },
+                        { "stats": { "CHAMPIONS_KILLED": 3, "GOLD_EARNED": 9000 } }
+                    ]
+                },
+                {
+                    "isPlayerTeam": false,
+                    "players": [{ "stats": { "CHAMPIONS_KILLED": 4, "GOLD_EARNED": 8000 } }]
+                }
+            ]
+        });
+
+        let (game_id, stats) = parse_eog_stats(&block).unwrap();
+        assert_eq!(game_id, 42);
+        assert_eq!(stats.largest_multi_kill, 3);
+        assert_eq!(stats.double_kills, 2);
+        assert_eq!(stats.my_team.kills, 10);
+        assert_eq!(stats.my_team.gold_earned, 20000);
+        assert_eq!(stats.my_team.turrets_killed, 2);
+        assert_eq!(stats.enemy_team.kills, 4);
+    }
+
+    #[test]
+    fn test_credentials_from_args() {
+        let args: Vec<String> = [
+            "LeagueClientUx.exe",
+            "--remoting-auth-token=abc123",
+            "--app-port=54321",
+            "--install-directory=C:/Riot Games/League of Legends",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+
+        let credentials = credentials_from_args(&args).unwrap();
+        assert_eq!(credentials.port, 54321);
+        assert_eq!(credentials.token, "abc123");
+        assert_eq!(
+            arg_value(&args, "--install-directory="),
+            Some("C:/Riot Games/League of Legends")
+        );
+        assert!(credentials_from_args(&args[..2]).is_none());
+    }
+
+    #[test]
+    fn test_parse_lockfile() {
+        let credentials = parse_lockfile("LeagueClient:1234:54321:abc123:https\n").unwrap();
+        assert_eq!(credentials.port, 54321);
+        assert_eq!(credentials.token, "abc123");
+        assert!(parse_lockfile("LeagueClient:1234").is_err());
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(2), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.wait_time("/lol-gameflow/v1/gameflow-phase", now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.wait_time("/lol-gameflow/v1/gameflow-phase", now),
+            MIN_REQUEST_INTERVAL
+        );
+        assert_eq!(
+            limiter.wait_time("/lol-champ-select/v1/session", now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.wait_time(
+                "/lol-champ-select/v1/session?x=1",
+                now + MIN_REQUEST_INTERVAL
+            ),
+            Duration::ZERO
+        );
+    }
+}
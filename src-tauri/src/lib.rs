@@ -1,26 +1,138 @@
 //! MatchupHelper - Tauri commands and application logic
 
+mod archive;
+mod attachments;
+mod builds;
+mod champions;
+pub mod cli;
+mod collections;
+mod community;
+mod ddragon;
+#[cfg(desktop)]
+mod deep_link;
+mod diagnostics;
+mod digest;
+mod draft;
+mod error;
+mod events;
+mod guide;
 mod lcu;
+mod links;
+mod logging;
+mod markdown;
+mod match_history;
 mod matchup;
+mod migration;
+mod monitor;
+mod notifications;
+mod obs;
+mod overlay;
+mod package;
+mod plans;
+mod presence;
+mod quiz;
+mod reminders;
+mod report;
+mod review;
+mod riot_api;
+mod runes;
+mod sample;
+mod scratchpad;
+mod search;
+mod settings;
+#[cfg(desktop)]
+mod shortcuts;
 mod storage;
+mod team_notes;
+mod webhook;
+mod window_state;
 
-use lcu::{LcuClient, LcuConnectionStatus};
-use matchup::{Match, MatchResult, MatchUpdate, Matchup, MatchupFilter, MatchupUpdate, NewMatchup};
+use archive::ArchiveSummary;
+use attachments::{Attachment, AttachmentData, AttachmentError, AttachmentKind, AttachmentStore};
+use builds::StagedBuild;
+use collections::{Collection, CollectionInput};
+use community::CommunityStats;
+use ddragon::{
+    AssetKind, AssetLocation, ChampionAbilities, ChampionInfo, DataDragon, ItemInfo, RuneTreeInfo,
+    SummonerSpellInfo,
+};
+use diagnostics::Diagnostics;
+use digest::WeeklyDigest;
+use draft::{Draft, DraftInput, Side};
+use error::{AppError, ErrorCode};
+use lcu::{GameDetails, GameflowPhase, LcuClient, LcuConnectionStatus, LcuMatchData};
+use markdown::MatchupDetail;
+use match_history::HistoryImportReport;
+use matchup::{
+    BanSuggestions, BulkOperation, ChampionRoles, ChampionStats, ChecklistItem, Comment,
+    DifficultyPoint, EarlyGameImpact, EarlyGameStats, EndOfGameStats, GameMode, Goal, Granularity,
+    ImportOptions, ImportProgress, ImportSummary, LengthStats, Match, MatchFilter, MatchResult,
+    MatchUpdate, Matchup, MatchupBenchmark, MatchupFilter, MatchupLengthStats, MatchupListItem,
+    MatchupStats, MatchupSuggestion, MatchupUpdate, NewMatch, NewMatchup, NewPostGameReview,
+    NoteImpact, OpponentHistory, PatchStats, PickIntent, PickSuggestion, PoolDistribution,
+    PostGameReview, ProgressPoint, QueueType, QuickReference, SessionStats, TagSuggestion,
+    VodBookmark, VodRef, WinRatePoint,
+};
+use migration::{ImportReport, ImportSource, SourceKind};
+use monitor::ChampSelectUpdate;
+use obs::ObsServer;
+use package::{PackageImportReport, PackageOptions};
+use plans::{NewPlanTarget, PlanProgress, PlanTarget};
+use presence::RichPresence;
+use quiz::{QuizGrade, QuizQuestion, QuizTopic};
+use reminders::{NewReminder, Reminder};
+use report::{ReportFormat, ReportRange, StatsReport};
+use review::ReviewItem;
+use riot_api::{RiotApiClient, RiotApiSettings};
+use scratchpad::ScratchNote;
+use search::{ReplaceScope, ReplacedLine, SearchResult};
+use settings::{Settings, SettingsStore, WindowGeometry, WindowState};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use storage::Storage;
-use tauri::State;
+use storage::{Account, AppData, MergeSummary, Player, RetentionPolicy, Storage};
+use tauri::{AppHandle, Emitter, Manager, State};
+use team_notes::{TeamNote, TeamNoteFilter, TeamNoteInput};
 
 /// Application state
 pub struct AppState {
+    settings: Mutex<SettingsStore>,
     storage: Mutex<Storage>,
     lcu_client: Mutex<LcuClient>,
+    ddragon: DataDragon,
+    obs: ObsServer,
+    presence: RichPresence,
+    /// Matchup a `matchuphelper://` link asked to open, until the main window takes it
+    linked_matchup: Mutex<Option<String>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
+        let settings = SettingsStore::new().expect("Failed to initialize settings");
+        let loaded = settings.load().unwrap_or_default();
+        // An unplugged drive or a removed folder shouldn't keep the app from
+        // starting; setup logs the fallback once logging is up
+        let storage = match loaded.data_directory.clone() {
+            Some(dir) => Storage::new(Some(dir)).or_else(|_| Storage::new(None)),
+            None => Storage::new(None),
+        }
+        .expect("Failed to initialize storage");
+        let metadata = storage.load().map(|data| data.metadata).unwrap_or_default();
+        let ddragon = DataDragon::new(storage.ddragon_dir(), metadata.locale);
+
+        let mut lcu_client = LcuClient::new();
+        let root_ca = std::fs::read(storage.riot_root_ca_path()).ok();
+        // A bad certificate file leaves the client unconfigured, which connect reports
+        let _ = lcu_client.configure_tls(root_ca.as_deref(), loaded.allow_insecure_lcu);
+
         Self {
-            storage: Mutex::new(Storage::new().expect("Failed to initialize storage")),
-            lcu_client: Mutex::new(LcuClient::new()),
+            settings: Mutex::new(settings),
+            storage: Mutex::new(storage),
+            lcu_client: Mutex::new(lcu_client),
+            ddragon,
+            obs: ObsServer::default(),
+            presence: RichPresence::default(),
+            linked_matchup: Mutex::new(None),
         }
     }
 }
@@ -32,234 +144,4576 @@ impl Default for AppState {
 fn get_matchups(
     filter: Option<MatchupFilter>,
     state: State<AppState>,
-) -> Result<Vec<Matchup>, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let data = storage.load().map_err(|e| e.to_string())?;
+) -> Result<Vec<MatchupListItem>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
 
-    let matchups: Vec<Matchup> = if let Some(filter) = filter {
-        data.matchups
-            .values()
-            .filter(|m| m.matches_filter(&filter))
-            .cloned()
-            .collect()
-    } else {
-        data.matchups.values().cloned().collect()
-    };
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+    let mut matchups: Vec<Matchup> = data
+        .matchups
+        .values()
+        .filter(|m| m.matches_filter(&filter))
+        .cloned()
+        .collect();
 
-    Ok(matchups)
+    let matches: Vec<Match> = data.matches.into_values().collect();
+    matchup::sort_matchups(
+        &mut matchups,
+        filter.sort_by.unwrap_or_default(),
+        filter.descending.unwrap_or(false),
+        &matches,
+    );
+
+    let now = chrono::Utc::now();
+    let items = matchups
+        .into_iter()
+        .map(|matchup| {
+            let confidence = matchup.confidence(&matches, now);
+            let record = matchup.stats(&matches).record();
+            let games_since_review = matchup.games_since_touched(&matches);
+            MatchupListItem {
+                matchup,
+                confidence,
+                record,
+                games_since_review,
+            }
+        })
+        .filter(|item| filter.matches_confidence(item.confidence))
+        .collect();
+
+    Ok(items)
 }
 
-/// Get a single matchup by ID
+/// Get a single matchup by ID, with its notes rendered to sanitized HTML
 #[tauri::command]
-fn get_matchup(id: String, state: State<AppState>) -> Result<Matchup, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let data = storage.load().map_err(|e| e.to_string())?;
+fn get_matchup(id: String, state: State<AppState>) -> Result<MatchupDetail, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
 
     data.matchups
         .get(&id)
         .cloned()
-        .ok_or_else(|| "Matchup not found".to_string())
+        .map(|matchup| MatchupDetail::new(matchup, &data.matchups))
+        .ok_or_else(|| AppError::not_found("Matchup"))
+}
+
+/// Matchups whose current notes link to this one with `[[...]]`
+#[tauri::command]
+fn get_backlinks(id: String, state: State<AppState>) -> Result<Vec<Matchup>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    if !data.matchups.contains_key(&id) {
+        return Err(AppError::not_found("Matchup"));
+    }
+    Ok(links::backlinks(&id, &data.matchups))
 }
 
 /// Create a new matchup
 #[tauri::command]
-fn create_matchup(matchup: NewMatchup, state: State<AppState>) -> Result<Matchup, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let mut data = storage.load().map_err(|e| e.to_string())?;
+fn create_matchup(
+    matchup: NewMatchup,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let my_champion = champions::canonicalize(&matchup.my_champion)?;
+    let enemy_champion = champions::canonicalize(&matchup.enemy_champion)?;
 
-    let new_matchup = Matchup::new(matchup.my_champion, matchup.enemy_champion, matchup.role);
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let role = matchup_role(&data, &my_champion, matchup.role)?;
+    let mut new_matchup = Matchup::new(my_champion, enemy_champion, role);
+    new_matchup.versions[0].patch = data.metadata.current_patch.clone();
+    new_matchup.versions[0].author = data.metadata.local_author();
+    new_matchup.player = data.metadata.active_player.clone();
 
     data.matchups.insert(new_matchup.id.clone(), new_matchup.clone());
-    storage.save(&data).map_err(|e| e.to_string())?;
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![new_matchup.id.clone()]);
 
     Ok(new_matchup)
 }
 
+/// The role given for a new matchup, or the main role set for my champion
+fn matchup_role(
+    data: &AppData,
+    my_champion: &str,
+    role: Option<String>,
+) -> Result<String, AppError> {
+    match role.map(|r| r.trim().to_string()) {
+        Some(role) if !role.is_empty() => Ok(role),
+        _ => data
+            .metadata
+            .champion_roles
+            .get(my_champion)
+            .map(|roles| roles.primary.clone())
+            .ok_or_else(|| {
+                AppError::validation(format!("Choose a role to play {} in", my_champion))
+            }),
+    }
+}
+
 /// Update a matchup (creates a new version)
 #[tauri::command]
 fn update_matchup(
     id: String,
-    update: MatchupUpdate,
+    mut update: MatchupUpdate,
+    app: AppHandle,
     state: State<AppState>,
-) -> Result<Matchup, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let mut data = storage.load().map_err(|e| e.to_string())?;
+) -> Result<Matchup, AppError> {
+    update.validate()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if update.patch.is_none() {
+        update.patch = data.metadata.current_patch.clone();
+    }
+    let author = data.metadata.local_author();
 
     let matchup = data
         .matchups
         .get_mut(&id)
-        .ok_or_else(|| "Matchup not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+
+    matchup.edit_signed(author.as_deref(), |m| m.add_version(update));
+
+    let updated = matchup.clone();
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Import a guide or paste as a new version of a matchup's notes
+///
+/// HTML pages are reduced to their text. The rest of the current version is
+/// kept, and the version records the page it came from.
+#[tauri::command]
+fn import_notes_from_url(
+    matchup_id: String,
+    url: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let url = guide::source_url(&url)?;
+    // Fetched before locking the storage, so a slow page doesn't hold up other commands
+    let notes = guide::fetch(&url)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let author = data.metadata.local_author();
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
 
-    matchup.add_version(update);
+    let mut update = matchup
+        .current()
+        .map(MatchupUpdate::from_version)
+        .unwrap_or_default();
+    update.notes = notes;
+    update.source_url = Some(url.to_string());
+    update.patch = data.metadata.current_patch.clone();
+    matchup.edit_signed(author.as_deref(), |m| m.add_version(update));
 
     let updated = matchup.clone();
-    storage.save(&data).map_err(|e| e.to_string())?;
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
 
     Ok(updated)
 }
 
 /// Delete a matchup
 #[tauri::command]
-fn delete_matchup(id: String, state: State<AppState>) -> Result<(), String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let mut data = storage.load().map_err(|e| e.to_string())?;
+fn delete_matchup(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
 
-    data.matchups
+    let removed = data
+        .matchups
         .remove(&id)
-        .ok_or_else(|| "Matchup not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    data.unlink_matchup(&id);
 
-    storage.save(&data).map_err(|e| e.to_string())?;
+    storage.save(&mut data)?;
+    remove_attachment_files(&storage, [&removed]);
+    events::matchups_deleted(&app, vec![id]);
 
     Ok(())
 }
 
-/// Search matchups by query string
+/// Apply one operation to several matchups, with a single save
+///
+/// Fails without changing anything when an ID is unknown. Returns the updated
+/// matchups, none after a delete.
 #[tauri::command]
-fn search_matchups(query: String, state: State<AppState>) -> Result<Vec<Matchup>, String> {
-    let filter = MatchupFilter {
-        search: Some(query),
-        ..Default::default()
-    };
+fn bulk_update_matchups(
+    mut ids: Vec<String>,
+    op: BulkOperation,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Vec<Matchup>, AppError> {
+    op.validate()?;
+    let mut seen = std::collections::HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+    if ids.is_empty() {
+        return Err(AppError::validation("No matchups selected"));
+    }
 
-    get_matchups(Some(filter), state)
-}
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
 
-// ==================== Match History Commands ====================
+    if ids.iter().any(|id| !data.matchups.contains_key(id)) {
+        return Err(AppError::not_found("Matchup"));
+    }
 
-/// Get all matches
-#[tauri::command]
-fn get_matches(state: State<AppState>) -> Result<Vec<Match>, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let data = storage.load().map_err(|e| e.to_string())?;
+    if op == BulkOperation::Delete {
+        let removed: Vec<Matchup> = ids
+            .iter()
+            .filter_map(|id| data.matchups.remove(id))
+            .collect();
+        for id in &ids {
+            data.unlink_matchup(id);
+        }
+        storage.save(&mut data)?;
+        remove_attachment_files(&storage, &removed);
+        events::matchups_deleted(&app, ids);
+        return Ok(Vec::new());
+    }
 
-    let mut matches: Vec<Match> = data.matches.values().cloned().collect();
-    matches.sort_by(|a, b| b.date.cmp(&a.date));
+    let author = data.metadata.local_author();
+    let mut updated = Vec::new();
+    for id in &ids {
+        if let Some(matchup) = data.matchups.get_mut(id) {
+            matchup.edit_signed(author.as_deref(), |m| m.apply_bulk(&op));
+            updated.push(matchup.clone());
+        }
+    }
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, ids);
 
-    Ok(matches)
+    Ok(updated)
 }
 
-/// Update a match
+/// Fuzzy search matchups by query string, best matches first
+///
+/// Older versions are searched too unless `all_versions` is `false`.
 #[tauri::command]
-fn update_match(id: String, update: MatchUpdate, state: State<AppState>) -> Result<Match, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let mut data = storage.load().map_err(|e| e.to_string())?;
+fn search_matchups(
+    query: String,
+    all_versions: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<SearchResult>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
 
-    let match_entry = data
-        .matches
-        .get_mut(&id)
-        .ok_or_else(|| "Match not found".to_string())?;
+    let active_player = data.metadata.active_player;
+    Ok(search::search(
+        &query,
+        data.matchups.values().filter(|m| m.player == active_player),
+        all_versions.unwrap_or(true),
+    ))
+}
 
-    if let Some(notes) = update.notes {
-        match_entry.notes = notes;
+/// Replace text across the notes and summaries of several matchups
+///
+/// Only previews the changed lines unless `apply` is set. Notes imported from
+/// packages are left alone.
+#[tauri::command]
+fn search_and_replace(
+    query: String,
+    replacement: String,
+    scope: ReplaceScope,
+    apply: Option<bool>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Vec<ReplacedLine>, AppError> {
+    if query.is_empty() {
+        return Err(AppError::validation("Search text cannot be empty"));
     }
-    if let Some(linked) = update.linked_matchup {
-        match_entry.linked_matchup = if linked.is_empty() {
-            None
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if scope
+        .matchup_ids
+        .iter()
+        .any(|id| !data.matchups.contains_key(id))
+    {
+        return Err(AppError::not_found("Matchup"));
+    }
+
+    let active_player = data.metadata.active_player.clone();
+    let author = data.metadata.local_author();
+    let mut lines = Vec::new();
+    let mut changed_ids = Vec::new();
+    for matchup in data.matchups.values_mut() {
+        let selected = if scope.matchup_ids.is_empty() {
+            matchup.player == active_player
         } else {
-            Some(linked)
+            scope.matchup_ids.contains(&matchup.id)
         };
+        if !selected || matchup.external.is_some() {
+            continue;
+        }
+        let mut edited = matchup.clone();
+        let changed = edited.edit_signed(author.as_deref(), |m| {
+            search::replace_in_matchup(m, &query, &replacement, &scope)
+        });
+        if !changed.is_empty() {
+            changed_ids.push(matchup.id.clone());
+            lines.extend(changed);
+            *matchup = edited;
+        }
     }
+    lines.sort_by(|a, b| {
+        (&a.my_champion, &a.enemy_champion, a.version).cmp(&(
+            &b.my_champion,
+            &b.enemy_champion,
+            b.version,
+        ))
+    });
 
-    let updated = match_entry.clone();
-    storage.save(&data).map_err(|e| e.to_string())?;
+    if apply.unwrap_or(false) && !changed_ids.is_empty() {
+        storage.save(&mut data)?;
+        events::matchups_changed(&app, changed_ids);
+    }
+    Ok(lines)
+}
 
-    Ok(updated)
+/// Get the loading-screen summary, runes and spells for a pairing, with the
+/// tips for `side` when given
+#[tauri::command]
+fn get_quick_reference(
+    my_champion: String,
+    enemy_champion: String,
+    role: String,
+    side: Option<Side>,
+    state: State<AppState>,
+) -> Result<Option<QuickReference>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok(data
+        .matchups
+        .values()
+        .filter(|m| m.player == data.metadata.active_player)
+        .find(|m| m.is_pairing(&my_champion, &enemy_champion, &role))
+        .map(|matchup| {
+            let mut quick = matchup.quick_reference();
+            quick.side_notes = side
+                .and_then(|side| matchup.current()?.side_notes(side))
+                .map(str::to_string);
+            quick
+        }))
 }
 
-// ==================== LCU Commands ====================
+/// Write the current runes, spells and items of a matchup to the clipboard as
+/// text for team chat or Discord, and return the text
+///
+/// Item IDs are named from Data Dragon when its item list is available.
+#[tauri::command]
+fn copy_build_text(matchup_id: String, state: State<AppState>) -> Result<String, AppError> {
+    let matchup = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+        data.matchups
+            .get(&matchup_id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found("Matchup"))?
+    };
 
-/// Connect to the League Client
+    // Without item names the IDs are still worth sharing
+    let items = state.ddragon.items().unwrap_or_default();
+    let text = matchup
+        .build_text(|item| item_name(&items, item))
+        .ok_or_else(|| AppError::validation("This matchup has no build to copy"))?;
+
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text.clone()))
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::ClipboardFailed,
+                "Could not copy to the clipboard",
+            )
+            .with_details(e)
+        })?;
+
+    Ok(text)
+}
+
+/// Name of a stored item, usually a numeric ID, falling back to what's stored
+fn item_name(items: &[ItemInfo], item: &str) -> String {
+    item.trim()
+        .parse::<i32>()
+        .ok()
+        .and_then(|id| items.iter().find(|i| i.id == id))
+        .map(|i| i.name.clone())
+        .unwrap_or_else(|| item.to_string())
+}
+
+/// ID of a stored item, given either as a numeric ID or by name
+fn item_id(items: &[ItemInfo], item: &str) -> Option<i32> {
+    let item = item.trim();
+    item.parse::<i32>().ok().or_else(|| {
+        items
+            .iter()
+            .find(|i| i.name.eq_ignore_ascii_case(item))
+            .map(|i| i.id)
+    })
+}
+
+/// Get the (date, difficulty) series recorded across a matchup's versions
 #[tauri::command]
-fn connect_lcu(state: State<AppState>) -> Result<LcuConnectionStatus, String> {
-    let mut client = state.lcu_client.lock().map_err(|e| e.to_string())?;
-    client.connect().map_err(|e| e.to_string())
+fn get_difficulty_history(
+    id: String,
+    state: State<AppState>,
+) -> Result<Vec<DifficultyPoint>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    data.matchups
+        .get(&id)
+        .map(Matchup::difficulty_history)
+        .ok_or_else(|| AppError::not_found("Matchup"))
 }
 
-/// Import recent matches from the League Client
+/// Get win/loss stats for a matchup from its linked matches
+///
+/// With community stats enabled, everyone's win rate in the pairing is included.
 #[tauri::command]
-fn import_matches(count: Option<u32>, state: State<AppState>) -> Result<Vec<Match>, String> {
-    let client = state.lcu_client.lock().map_err(|e| e.to_string())?;
+fn get_matchup_stats(
+    id: String,
+    queue: Option<QueueType>,
+    state: State<AppState>,
+) -> Result<MatchupStats, AppError> {
+    let settings = load_settings(&state)?.community_stats;
+    // The community request can take seconds, so the data isn't held for it
+    let (mut stats, matchup, cache_path) = {
+        let storage = state.storage.lock()?;
+        let mut data = storage.load()?;
 
-    if !client.is_connected() {
-        return Err("Not connected to League client".to_string());
+        let matchup = data
+            .matchups
+            .remove(&id)
+            .ok_or_else(|| AppError::not_found("Matchup"))?;
+        let matches: Vec<Match> = data
+            .matches
+            .into_values()
+            .filter(|m| queue.is_none_or(|q| m.queue_type() == Some(q)))
+            .collect();
+        (
+            matchup.stats(&matches),
+            matchup,
+            storage.community_cache_path(),
+        )
+    };
+
+    if let Some(community) = CommunityStats::new(cache_path, settings) {
+        // My own numbers are still worth showing when the source is down
+        stats.community = community
+            .win_rate(&matchup.my_champion, &matchup.enemy_champion, &matchup.role)
+            .inspect_err(|e| tracing::warn!("Community stats unavailable: {}", e))
+            .ok()
+            .flatten();
     }
 
-    let lcu_matches = client
-        .get_match_history(count.unwrap_or(20))
-        .map_err(|e| e.to_string())?;
+    Ok(stats)
+}
 
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let mut data = storage.load().map_err(|e| e.to_string())?;
+/// Stage the most popular runes, spells and items for a matchup from the builds source
+///
+/// Nothing is saved: the returned update holds the current notes with the
+/// build swapped in, to review and pass to `update_matchup`. `None` when the
+/// source has no build for the pairing.
+#[tauri::command]
+fn fetch_community_build(
+    matchup_id: String,
+    state: State<AppState>,
+) -> Result<Option<StagedBuild>, AppError> {
+    let settings = load_settings(&state)?.community_builds;
+    if !settings.enabled || settings.url_template.is_none() {
+        return Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            "Community builds are turned off",
+        ));
+    }
 
-    let mut imported = Vec::new();
+    let matchup = {
+        let storage = state.storage.lock()?;
+        let mut data = storage.load()?;
+        data.matchups
+            .remove(&matchup_id)
+            .ok_or_else(|| AppError::not_found("Matchup"))?
+    };
 
-    for lcu_match in lcu_matches {
-        let game_id = lcu_match.game_id.to_string();
+    // The storage lock is released so a slow source doesn't hold up other commands
+    let Some((build, source)) = builds::fetch(
+        &settings,
+        &matchup.my_champion,
+        &matchup.enemy_champion,
+        &matchup.role,
+    )?
+    else {
+        return Ok(None);
+    };
 
-        // Skip if already imported
-        if data
-            .matches
-            .values()
-            .any(|m| m.game_id.as_ref() == Some(&game_id))
-        {
-            continue;
-        }
+    Ok(Some(StagedBuild {
+        update: builds::stage(matchup.current(), build),
+        source,
+    }))
+}
 
-        let result = if lcu_match.win {
-            MatchResult::Win
-        } else {
-            MatchResult::Loss
-        };
+/// Get my results and laning numbers over time for a matchup's linked games
+#[tauri::command]
+fn get_matchup_progress(
+    id: String,
+    state: State<AppState>,
+) -> Result<Vec<ProgressPoint>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
 
-        let new_match = Match::new(
-            lcu_match.my_champion_name,
-            lcu_match.enemy_champion_name.unwrap_or_else(|| "Unknown".to_string()),
-            lcu_match.role,
-            result,
-            Some(game_id),
-        );
+    let matchup = data
+        .matchups
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    let matches: Vec<Match> = data.matches.into_values().collect();
 
-        data.matches.insert(new_match.id.clone(), new_match.clone());
-        imported.push(new_match);
+    Ok(matchup.progress(&matches))
+}
+
+/// Get how a matchup's linked games went before and after each notes rewrite
+#[tauri::command]
+fn get_note_impact(id: String, state: State<AppState>) -> Result<Vec<NoteImpact>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    let matches: Vec<Match> = data.matches.into_values().collect();
+
+    Ok(matchup.note_impact(&matches))
+}
+
+/// Get a matchup's linked games compared against my usual numbers on the champion
+#[tauri::command]
+fn get_matchup_benchmark(id: String, state: State<AppState>) -> Result<MatchupBenchmark, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    let matches: Vec<Match> = data.matches.values().cloned().collect();
+
+    Ok(matchup.benchmark(&matches))
+}
+
+/// Get how early leads and deficits relate to results, per matchup
+///
+/// Only linked games imported with timeline numbers count. With `id`, only
+/// that matchup is returned.
+#[tauri::command]
+fn get_early_game_impact(
+    id: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<EarlyGameImpact>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    // One matchup's games are its player's, whoever is active
+    let player = match id {
+        Some(ref id) => data
+            .matchups
+            .get(id)
+            .ok_or_else(|| AppError::not_found("Matchup"))?
+            .player
+            .clone(),
+        None => data.metadata.active_player.clone(),
+    };
+    let matchups = data
+        .matchups
+        .values()
+        .filter(|m| m.player == player)
+        .filter(|m| id.as_ref().is_none_or(|id| &m.id == id));
+    let matches: Vec<Match> = data
+        .matches
+        .values()
+        .filter(|m| m.player == player)
+        .cloned()
+        .collect();
+
+    Ok(matchup::early_game_impact(matchups, &matches))
+}
+
+/// Get win rates per patch, for one champion or one matchup's linked games
+#[tauri::command]
+fn get_stats_by_patch(
+    my_champion: Option<String>,
+    matchup_id: Option<String>,
+    queue: Option<QueueType>,
+    game_mode: Option<GameMode>,
+    state: State<AppState>,
+) -> Result<Vec<PatchStats>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    let matches = data.matches.values().filter(|m| {
+        m.matches_filter(&filter)
+            && my_champion
+                .as_ref()
+                .is_none_or(|c| m.my_champion.eq_ignore_ascii_case(c))
+            && matchup_id
+                .as_ref()
+                .is_none_or(|id| m.linked_matchup.as_ref() == Some(id))
+            && queue.is_none_or(|q| m.queue_type() == Some(q))
+            && game_mode.is_none_or(|mode| m.mode() == mode)
+    });
+
+    Ok(matchup::stats_by_patch(matches))
+}
+
+/// Get my results by game length (under 20, 20 to 30 and 30+ minutes)
+///
+/// The filter narrows the games counted, e.g. to a champion or matchup; its
+/// paging options are ignored.
+#[tauri::command]
+fn get_stats_by_length(
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<Vec<LengthStats>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    let matches = data.matches.values().filter(|m| m.matches_filter(&filter));
+    Ok(matchup::stats_by_length(matches))
+}
+
+/// Get the game length breakdown of each matchup with timed games, most played
+/// first, to tell lanes I must close early from ones I can scale through
+#[tauri::command]
+fn get_length_stats_by_matchup(
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<Vec<MatchupLengthStats>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    let matches = data.matches.values().filter(|m| m.matches_filter(&filter));
+    Ok(matchup::length_stats_by_matchup(&data.matchups, matches))
+}
+
+/// Get my win rate per week or month, for trend charts
+///
+/// The filter narrows the games counted, e.g. to a champion, role or matchup;
+/// its paging options are ignored.
+#[tauri::command]
+fn get_winrate_timeseries(
+    granularity: Granularity,
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<Vec<WinRatePoint>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    let matches = data.matches.values().filter(|m| m.matches_filter(&filter));
+    Ok(matchup::winrate_timeseries(matches, granularity))
+}
+
+/// Get how my games spread over roles and champions, on and off role and pool
+///
+/// The filter narrows the games counted, e.g. to a date range; its paging
+/// options are ignored.
+#[tauri::command]
+fn get_pool_distribution(
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<PoolDistribution, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    let matches = data.matches.values().filter(|m| m.matches_filter(&filter));
+    Ok(matchup::pool_distribution(
+        matches,
+        &data.metadata.champion_pool,
+    ))
+}
+
+/// Get my win rates by local hour of day, weekday and game of the play session
+///
+/// The filter narrows the games counted; its paging options are ignored.
+#[tauri::command]
+fn get_session_stats(
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<SessionStats, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    let matches = data.matches.values().filter(|m| m.matches_filter(&filter));
+    Ok(matchup::session_stats(matches, &chrono::Local))
+}
+
+/// Write a report of my results in `range` to `path`, as Markdown unless asked for HTML
+///
+/// Covers the overall record, tables per role and per champion, the worst
+/// matchups and streaks, over the games of the active account and player.
+#[tauri::command]
+fn generate_stats_report(
+    range: Option<ReportRange>,
+    format: Option<ReportFormat>,
+    path: String,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    let report = StatsReport::new(
+        data.matches.values().filter(|m| m.matches_filter(&filter)),
+        range.unwrap_or_default(),
+        chrono::Utc::now(),
+    );
+    std::fs::write(&path, report.render(format.unwrap_or_default())).map_err(AppError::from)
+}
+
+/// Sum up the week up to `end` (now by default) for a review session: games per
+/// matchup, win rate movers, matchups played without notes and goals completed.
+/// Also written as Markdown to `path` when given.
+#[tauri::command]
+fn generate_weekly_digest(
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    path: Option<String>,
+    state: State<AppState>,
+) -> Result<WeeklyDigest, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    let matches: Vec<&Match> = data
+        .matches
+        .values()
+        .filter(|m| m.matches_filter(&filter))
+        .collect();
+    let matchups: Vec<&Matchup> = data
+        .matchups
+        .values()
+        .filter(|m| m.player == data.metadata.active_player && m.external.is_none())
+        .collect();
+
+    let digest = WeeklyDigest::new(&matchups, &matches, end.unwrap_or_else(chrono::Utc::now));
+    if let Some(path) = path {
+        std::fs::write(&path, digest.to_markdown())?;
     }
+    Ok(digest)
+}
 
-    storage.save(&data).map_err(|e| e.to_string())?;
+/// Get my record, best and worst matchups and average stats on a champion
+///
+/// Only games of `account` count, the active account when unset.
+#[tauri::command]
+fn get_champion_stats(
+    my_champion: String,
+    account: Option<String>,
+    state: State<AppState>,
+) -> Result<ChampionStats, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let player = &data.metadata.active_player;
+    let account = account.or_else(|| data.metadata.active_account.clone());
 
-    Ok(imported)
+    Ok(matchup::champion_stats(
+        data.matches.values().filter(|m| {
+            &m.player == player
+                && account
+                    .as_deref()
+                    .is_none_or(|puuid| m.is_from_account(puuid))
+        }),
+        &my_champion,
+    ))
 }
 
-/// Debug LCU endpoint (for troubleshooting)
+/// Get matchups whose current notes are empty or shorter than `min_length`
 #[tauri::command]
-fn debug_lcu(endpoint: String, state: State<AppState>) -> Result<String, String> {
-    let client = state.lcu_client.lock().map_err(|e| e.to_string())?;
+fn get_matchups_needing_notes(
+    min_length: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<MatchupListItem>, AppError> {
+    let filter = MatchupFilter {
+        needs_notes: Some(true),
+        min_notes_length: min_length,
+        ..Default::default()
+    };
 
-    if !client.is_connected() {
-        return Err("Not connected to League client".to_string());
+    get_matchups(Some(filter), state)
+}
+
+/// Get every matchup against one enemy champion across my pool
+#[tauri::command]
+fn get_notes_vs_enemy(
+    enemy: String,
+    role: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<MatchupListItem>, AppError> {
+    let filter = MatchupFilter {
+        enemy_champion: Some(enemy),
+        role,
+        ..Default::default()
+    };
+
+    let mut matchups = get_matchups(Some(filter), state)?;
+    matchups.sort_by_key(|m| m.matchup.my_champion.to_lowercase());
+
+    Ok(matchups)
+}
+
+/// Confirm a matchup's notes still hold on `patch` without creating a version
+#[tauri::command]
+fn mark_still_valid(
+    id: String,
+    patch: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    if matchup::parse_patch(&patch).is_none() {
+        return Err(AppError::validation(format!("Invalid patch: {}", patch)));
     }
 
-    client.debug_endpoint(&endpoint).map_err(|e| e.to_string())
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+
+    matchup.mark_still_valid(patch);
+
+    let updated = matchup.clone();
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Jot a timestamped line at the end of a matchup's current notes
+///
+/// Meant for a quick thought between games: no version is created, and the
+/// line is stamped with local time. Returns the line added.
+#[tauri::command]
+fn append_note(
+    matchup_id: String,
+    text: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<String, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+
+    let line = matchup
+        .append_note(&text, chrono::Local::now())
+        .ok_or_else(|| AppError::validation("Note cannot be empty"))?;
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(line)
+}
+
+/// Suggest existing tags starting with `prefix`, with usage counts
+#[tauri::command]
+fn suggest_tags(prefix: String, state: State<AppState>) -> Result<Vec<TagSuggestion>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let scope = data.metadata.player_scope();
+    Ok(matchup::suggest_tags(
+        data.matchups
+            .values()
+            .filter(|m| scope.includes(m.player.as_deref())),
+        &prefix,
+    ))
+}
+
+/// Pairings I've faced in the last `days` (30 by default) without notes, most played first
+#[tauri::command]
+fn get_matchup_suggestions(
+    days: Option<i64>,
+    limit: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<MatchupSuggestion>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let since = chrono::Utc::now()
+        - chrono::Duration::days(days.unwrap_or(matchup::DEFAULT_SUGGESTION_DAYS).max(1));
+    let player = &data.metadata.active_player;
+    let recent = data
+        .matches
+        .values()
+        .filter(|m| m.date >= since && &m.player == player);
+
+    let mut suggestions = matchup::matchup_suggestions(data.matchups.values(), recent);
+    // Off-role games, e.g. autofilled ones, aren't worth notes
+    suggestions
+        .retain(|s| matchup::plays_role(&data.metadata.champion_roles, &s.my_champion, &s.role));
+    if let Some(limit) = limit {
+        suggestions.truncate(limit);
+    }
+    Ok(suggestions)
+}
+
+/// Create the matchup for a suggested pairing and link its unlinked games to it
+#[tauri::command]
+fn create_from_suggestion(
+    suggestion: NewMatchup,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let my_champion = champions::canonicalize(&suggestion.my_champion)?;
+    let enemy_champion = champions::canonicalize(&suggestion.enemy_champion)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let role = matchup_role(&data, &my_champion, suggestion.role)?;
+    let mut new_matchup = Matchup::new(my_champion, enemy_champion, role);
+    new_matchup.versions[0].patch = data.metadata.current_patch.clone();
+    new_matchup.versions[0].author = data.metadata.local_author();
+    new_matchup.player = data.metadata.active_player.clone();
+    if data.matchups.values().any(|m| {
+        m.player == new_matchup.player
+            && m.external.is_none()
+            && m.is_pairing(
+                &new_matchup.my_champion,
+                &new_matchup.enemy_champion,
+                &new_matchup.role,
+            )
+    }) {
+        return Err(AppError::validation("This pairing already has a matchup"));
+    }
+
+    let mut linked = Vec::new();
+    for m in data.matches.values_mut() {
+        if m.linked_matchup.is_none()
+            && m.player == new_matchup.player
+            && new_matchup.is_related_match(m)
+        {
+            m.linked_matchup = Some(new_matchup.id.clone());
+            linked.push(m.id.clone());
+        }
+    }
+
+    data.matchups.insert(new_matchup.id.clone(), new_matchup.clone());
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![new_matchup.id.clone()]);
+    if !linked.is_empty() {
+        events::matches_changed(&app, linked);
+    }
+
+    Ok(new_matchup)
+}
+
+/// Find the same lane seen from the other side (enemy as me, me as enemy)
+#[tauri::command]
+fn get_reverse_matchup(id: String, state: State<AppState>) -> Result<Option<Matchup>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    let scope = data.metadata.player_scope();
+    Ok(data
+        .matchups
+        .values()
+        .filter(|m| scope.includes(m.player.as_deref()))
+        .find(|m| m.is_reverse_of(matchup))
+        .cloned())
+}
+
+/// Copy the runes and summoner spells from my latest game in a matchup into a new version
+#[tauri::command]
+fn copy_last_loadout(
+    matchup_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let author = data.metadata.local_author();
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+
+    let loadout = data
+        .matches
+        .values()
+        .filter(|m| m.matches_filter(&filter) && matchup.is_related_match(m))
+        .filter_map(|m| m.loadout.as_ref().map(|loadout| (m.date, loadout)))
+        .max_by_key(|(date, _)| *date)
+        .map(|(_, loadout)| loadout)
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::NotFound,
+                "No imported game with runes for this matchup",
+            )
+        })?;
+
+    matchup.edit_signed(author.as_deref(), |m| m.copy_loadout(loadout));
+
+    let updated = matchup.clone();
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(updated)
+}
+
+// ==================== Goal Commands ====================
+
+/// Add a practice goal to a matchup
+#[tauri::command]
+fn add_goal(
+    matchup_id: String,
+    text: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Goal, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::validation("Goal text cannot be empty"));
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    let goal = matchup.add_goal(text);
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(goal)
+}
+
+/// Mark a goal as completed, or reopen it with `done: false`
+#[tauri::command]
+fn complete_goal(
+    matchup_id: String,
+    goal_id: String,
+    done: Option<bool>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Goal, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    let goal = matchup
+        .set_goal_done(&goal_id, done.unwrap_or(true))
+        .cloned()
+        .ok_or_else(|| AppError::not_found("Goal"))?;
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(goal)
+}
+
+/// Remove a goal from a matchup
+#[tauri::command]
+fn delete_goal(
+    matchup_id: String,
+    goal_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    if !matchup.remove_goal(&goal_id) {
+        return Err(AppError::not_found("Goal"));
+    }
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(())
+}
+
+// ==================== Checklist Commands ====================
+
+/// Get a matchup's pre-game checklist
+#[tauri::command]
+fn get_checklist(
+    matchup_id: String,
+    state: State<AppState>,
+) -> Result<Vec<ChecklistItem>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    data.matchups
+        .get(&matchup_id)
+        .map(|m| m.checklist.clone())
+        .ok_or_else(|| AppError::not_found("Matchup"))
+}
+
+/// Add an item to a matchup's pre-game checklist
+#[tauri::command]
+fn add_checklist_item(
+    matchup_id: String,
+    text: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ChecklistItem, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::validation("Checklist item cannot be empty"));
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    let item = matchup.add_checklist_item(text);
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(item)
+}
+
+/// Check or uncheck a checklist item
+#[tauri::command]
+fn toggle_checklist_item(
+    matchup_id: String,
+    item_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ChecklistItem, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    let item = matchup
+        .toggle_checklist_item(&item_id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found("Checklist item"))?;
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(item)
+}
+
+/// Remove an item from a matchup's checklist
+#[tauri::command]
+fn remove_checklist_item(
+    matchup_id: String,
+    item_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    if !matchup.remove_checklist_item(&item_id) {
+        return Err(AppError::not_found("Checklist item"));
+    }
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(())
+}
+
+/// Uncheck every checklist item before a new game
+#[tauri::command]
+fn reset_checklist(
+    matchup_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Vec<ChecklistItem>, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    matchup.reset_checklist();
+
+    let checklist = matchup.checklist.clone();
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(checklist)
+}
+
+// ==================== Comment Commands ====================
+
+/// Leave a comment on a version without touching its notes
+///
+/// Allowed on imported notes too, so a reviewer can answer the author.
+#[tauri::command]
+fn add_comment(
+    matchup_id: String,
+    version: u32,
+    author: String,
+    text: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Comment, AppError> {
+    let author = author.trim().to_string();
+    let text = text.trim().to_string();
+    if author.is_empty() {
+        return Err(AppError::validation("Comment author cannot be empty"));
+    }
+    if text.is_empty() {
+        return Err(AppError::validation("Comment text cannot be empty"));
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    let comment = matchup
+        .add_comment(version, author, text)
+        .ok_or_else(|| AppError::not_found("Version"))?;
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(comment)
+}
+
+/// Comments on one version of a matchup, oldest first
+#[tauri::command]
+fn list_comments(
+    matchup_id: String,
+    version: u32,
+    state: State<AppState>,
+) -> Result<Vec<Comment>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup
+        .versions
+        .iter()
+        .find(|v| v.version == version)
+        .map(|v| v.comments.clone())
+        .ok_or_else(|| AppError::not_found("Version"))
+}
+
+/// Remove a comment
+#[tauri::command]
+fn delete_comment(
+    matchup_id: String,
+    comment_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    if !matchup.remove_comment(&comment_id) {
+        return Err(AppError::not_found("Comment"));
+    }
+
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(())
+}
+
+// ==================== Attachment Commands ====================
+
+/// Attach a voice memo to a version, from base64 encoded audio
+#[tauri::command]
+fn add_audio_attachment(
+    matchup_id: String,
+    version: u32,
+    mime_type: String,
+    contents: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Attachment, AppError> {
+    attach_to_version(&app, &state, matchup_id, version, |attachments| {
+        attachments.add(AttachmentKind::Audio, &mime_type, &contents)
+    })
+}
+
+/// Attach a drawing to a version, e.g. ward spots sketched on the minimap
+///
+/// The drawing is stored as given and must be a JSON object.
+#[tauri::command]
+fn add_drawing_attachment(
+    matchup_id: String,
+    version: u32,
+    drawing: serde_json::Value,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Attachment, AppError> {
+    attach_to_version(&app, &state, matchup_id, version, |attachments| {
+        attachments.add_drawing(&drawing)
+    })
+}
+
+/// Save an attachment with `create` and add it to a version
+fn attach_to_version(
+    app: &AppHandle,
+    state: &AppState,
+    matchup_id: String,
+    version: u32,
+    create: impl FnOnce(&AttachmentStore) -> Result<Attachment, AttachmentError>,
+) -> Result<Attachment, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+    let version = matchup
+        .versions
+        .iter_mut()
+        .find(|v| v.version == version)
+        .ok_or_else(|| AppError::not_found("Version"))?;
+
+    let attachments = AttachmentStore::new(storage.attachments_dir());
+    let attachment = create(&attachments)?;
+    version.attachments.push(attachment.clone());
+
+    if let Err(e) = storage.save(&mut data) {
+        // Don't leave a file nothing refers to
+        let _ = attachments.remove(&attachment);
+        return Err(e.into());
+    }
+    events::matchups_changed(app, vec![matchup_id]);
+
+    Ok(attachment)
+}
+
+/// An attachment with its base64 encoded contents
+#[tauri::command]
+fn get_attachment(
+    matchup_id: String,
+    attachment_id: String,
+    state: State<AppState>,
+) -> Result<AttachmentData, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let attachment = data
+        .matchups
+        .get(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?
+        .attachments()
+        .find(|a| a.id == attachment_id)
+        .ok_or_else(|| AppError::not_found("Attachment"))?;
+
+    Ok(AttachmentStore::new(storage.attachments_dir()).read(attachment)?)
+}
+
+/// Remove an attachment and its file
+#[tauri::command]
+fn delete_attachment(
+    matchup_id: String,
+    attachment_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+    let attachment = matchup
+        .remove_attachment(&attachment_id)
+        .ok_or_else(|| AppError::not_found("Attachment"))?;
+
+    storage.save(&mut data)?;
+    AttachmentStore::new(storage.attachments_dir()).remove(&attachment)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(())
+}
+
+/// Delete the attachment files of removed matchups
+///
+/// The matchups are already gone, so failures are only logged.
+fn remove_attachment_files<'a>(storage: &Storage, matchups: impl IntoIterator<Item = &'a Matchup>) {
+    let attachments = AttachmentStore::new(storage.attachments_dir());
+    for attachment in matchups.into_iter().flat_map(Matchup::attachments) {
+        if let Err(e) = attachments.remove(attachment) {
+            tracing::warn!("Could not delete attachment {}: {}", attachment.id, e);
+        }
+    }
+}
+
+// ==================== Review Commands ====================
+
+/// Get matchups due for re-reading, most urgent first
+#[tauri::command]
+fn get_review_queue(
+    limit: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<ReviewItem>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let mut queue = review::build_queue(&data, chrono::Utc::now());
+    if let Some(limit) = limit {
+        queue.truncate(limit);
+    }
+
+    Ok(queue)
+}
+
+/// Record that a matchup's notes were reviewed
+#[tauri::command]
+fn mark_reviewed(id: String, app: AppHandle, state: State<AppState>) -> Result<Matchup, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.mark_reviewed();
+
+    let updated = matchup.clone();
+    let targets = log_plan_review(&mut data, &updated);
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![id]);
+    if !targets.is_empty() {
+        events::plan_changed(&app, targets);
+    }
+
+    Ok(updated)
+}
+
+/// Log a review of `matchup` on the practice targets it counts for
+///
+/// Returns the IDs of the targets that changed.
+fn log_plan_review(data: &mut AppData, matchup: &Matchup) -> Vec<String> {
+    let stale = data
+        .metadata
+        .current_patch
+        .as_deref()
+        .is_some_and(|patch| matchup.is_stale(patch, matchup::DEFAULT_STALE_PATCHES));
+    let now = chrono::Utc::now();
+    plans::log_review(
+        &mut data.plan_targets,
+        matchup,
+        stale,
+        now,
+        plans::week_start(now, &chrono::Local),
+    )
+}
+
+/// Self-test questions from the notes of the matchups passing `filter`
+///
+/// Answers are left out; ask for one with `reveal_quiz_answer`.
+#[tauri::command]
+fn generate_quiz(
+    filter: Option<MatchupFilter>,
+    count: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<QuizQuestion>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+    Ok(quiz::generate(
+        data.matchups.values().filter(|m| m.matches_filter(&filter)),
+        count.unwrap_or(quiz::DEFAULT_QUIZ_LENGTH),
+    ))
+}
+
+/// The stored answer to a quiz question
+///
+/// Item IDs are named from Data Dragon when its item list is available.
+#[tauri::command]
+fn reveal_quiz_answer(
+    matchup_id: String,
+    topic: QuizTopic,
+    state: State<AppState>,
+) -> Result<Vec<String>, AppError> {
+    let matchup = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+        data.matchups
+            .get(&matchup_id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found("Matchup"))?
+    };
+
+    let items = state.ddragon.items().unwrap_or_default();
+    quiz::answer(&matchup, topic, |item| item_name(&items, item))
+        .ok_or_else(|| AppError::not_found("Answer"))
+}
+
+/// Record how well I answered a quiz question, rescheduling the matchup's review
+#[tauri::command]
+fn grade_quiz_question(
+    matchup_id: String,
+    topic: QuizTopic,
+    grade: QuizGrade,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    let reviewed = quiz::record_grade(matchup, topic, grade);
+
+    let updated = matchup.clone();
+    let targets = if reviewed {
+        log_plan_review(&mut data, &updated)
+    } else {
+        Vec::new()
+    };
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+    if !targets.is_empty() {
+        events::plan_changed(&app, targets);
+    }
+
+    Ok(updated)
+}
+
+// ==================== Match History Commands ====================
+
+/// Get matches, newest first, optionally filtered and paged
+#[tauri::command]
+fn get_matches(
+    filter: Option<MatchFilter>,
+    state: State<AppState>,
+) -> Result<Vec<Match>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = filter.unwrap_or_default().with_defaults(&data.metadata);
+
+    Ok(matchup::filter_matches(data.matches.values(), &filter))
+}
+
+/// Get a single match by ID
+#[tauri::command]
+fn get_match(id: String, state: State<AppState>) -> Result<Match, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    data.matches
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found("Match"))
+}
+
+/// Get the games linked to a matchup, newest first
+#[tauri::command]
+fn get_matches_for_matchup(id: String, state: State<AppState>) -> Result<Vec<Match>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matchup = data
+        .matchups
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+
+    Ok(matchup.linked_matches(data.matches.values()))
+}
+
+/// Log a match by hand (games not available through the client import)
+#[tauri::command]
+fn create_match(
+    new_match: NewMatch,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Match, AppError> {
+    let my_champion = champions::canonicalize(&new_match.my_champion)?;
+    let enemy_champion = champions::canonicalize(&new_match.enemy_champion)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if let Some(ref linked) = new_match.linked_matchup {
+        if !data.matchups.contains_key(linked) {
+            return Err(AppError::not_found("Matchup"));
+        }
+    }
+
+    let mut match_entry = Match::new(
+        my_champion,
+        enemy_champion,
+        new_match.role,
+        new_match.result,
+        None,
+    );
+    match new_match.date {
+        Some(date) => match_entry.date = date,
+        // Only a game logged right away was played on the current patch
+        None => match_entry.patch = data.metadata.current_patch.clone(),
+    }
+    match_entry.notes = new_match.notes;
+    match_entry.linked_matchup = new_match.linked_matchup;
+    match_entry.player = data.metadata.active_player.clone();
+
+    data.matches
+        .insert(match_entry.id.clone(), match_entry.clone());
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_entry.id.clone()]);
+
+    Ok(match_entry)
+}
+
+/// Update a match
+#[tauri::command]
+fn update_match(
+    id: String,
+    update: MatchUpdate,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Match, AppError> {
+    let enemy_champion = update
+        .enemy_champion
+        .as_deref()
+        .map(champions::canonicalize)
+        .transpose()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+
+    if let Some(notes) = update.notes {
+        match_entry.notes = notes;
+    }
+    if let Some(enemy) = enemy_champion {
+        // The detected player was not my laner after all
+        if match_entry.enemy_champion != enemy {
+            match_entry.enemy_riot_id = None;
+        }
+        match_entry.enemy_champion = enemy;
+        match_entry.opponent_uncertain = false;
+    }
+    if let Some(linked) = update.linked_matchup {
+        match_entry.linked_matchup = if linked.is_empty() {
+            None
+        } else {
+            Some(linked)
+        };
+    }
+
+    let updated = match_entry.clone();
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Save the post-game review of a match, replacing any earlier one
+#[tauri::command]
+fn save_match_review(
+    match_id: String,
+    review: NewPostGameReview,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<PostGameReview, AppError> {
+    review.validate()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+
+    let review = PostGameReview {
+        went_well: review.went_well,
+        mistake: review.mistake,
+        try_next: review.try_next,
+        reviewed_at: chrono::Utc::now(),
+    };
+    match_entry.review = Some(review.clone());
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_id]);
+
+    Ok(review)
+}
+
+/// Get the post-game review of a match, if it has one
+#[tauri::command]
+fn get_match_review(
+    match_id: String,
+    state: State<AppState>,
+) -> Result<Option<PostGameReview>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+
+    Ok(match_entry.review.clone())
+}
+
+/// Delete a match from the history
+///
+/// Matchup statistics are computed from the stored matches, so removing the
+/// match also removes it from every per-matchup record.
+#[tauri::command]
+fn delete_match(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.matches
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    data.unlink_match(&id);
+
+    storage.save(&mut data)?;
+    events::matches_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+/// Attach a recording of the game
+#[tauri::command]
+fn add_vod_ref(
+    match_id: String,
+    url: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<VodRef, AppError> {
+    let vod = VodRef::new(&url)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    match_entry.vod_refs.push(vod.clone());
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_id]);
+
+    Ok(vod)
+}
+
+/// Remove a recording and its bookmarks
+#[tauri::command]
+fn remove_vod_ref(
+    match_id: String,
+    vod_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    let before = match_entry.vod_refs.len();
+    match_entry.vod_refs.retain(|v| v.id != vod_id);
+    if match_entry.vod_refs.len() == before {
+        return Err(AppError::not_found("VOD"));
+    }
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_id]);
+
+    Ok(())
+}
+
+/// Bookmark a moment of a recording, `seconds` from its start
+#[tauri::command]
+fn add_vod_bookmark(
+    match_id: String,
+    vod_id: String,
+    seconds: u32,
+    label: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<VodBookmark, AppError> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err(AppError::validation("Bookmark label cannot be empty"));
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    let bookmark = match_entry
+        .add_vod_bookmark(&vod_id, seconds, label)
+        .ok_or_else(|| AppError::not_found("VOD"))?;
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_id]);
+
+    Ok(bookmark)
+}
+
+/// Remove a bookmark
+#[tauri::command]
+fn remove_vod_bookmark(
+    match_id: String,
+    bookmark_id: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    if !match_entry.remove_vod_bookmark(&bookmark_id) {
+        return Err(AppError::not_found("Bookmark"));
+    }
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, vec![match_id]);
+
+    Ok(())
+}
+
+/// Link to a recording that starts at a bookmark, or at the start without one
+#[tauri::command]
+fn get_vod_link(
+    match_id: String,
+    vod_id: String,
+    bookmark_id: Option<String>,
+    state: State<AppState>,
+) -> Result<String, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let vod = data
+        .matches
+        .get(&match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?
+        .vod_refs
+        .iter()
+        .find(|v| v.id == vod_id)
+        .ok_or_else(|| AppError::not_found("VOD"))?;
+    match bookmark_id {
+        Some(id) => vod
+            .bookmarks
+            .iter()
+            .find(|b| b.id == id)
+            .map(|b| vod.url_at(b.seconds))
+            .ok_or_else(|| AppError::not_found("Bookmark")),
+        None => Ok(vod.url.clone()),
+    }
+}
+
+/// Every game against a player, by Riot ID ("Name#TAG")
+#[tauri::command]
+fn get_opponent_history(
+    riot_id: String,
+    state: State<AppState>,
+) -> Result<OpponentHistory, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    Ok(matchup::opponent_history(
+        data.matches.values().filter(|m| m.matches_filter(&filter)),
+        riot_id.trim(),
+    ))
+}
+
+/// Remove matches played before `older_than`, returning how many were removed
+///
+/// Matches with notes, a review or VODs are always kept, linked ones unless `keep_linked` is `false`.
+#[tauri::command]
+fn prune_matches(
+    older_than: chrono::DateTime<chrono::Utc>,
+    keep_linked: Option<bool>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<usize, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let removed = data.prune_matches(older_than, keep_linked.unwrap_or(true));
+    if !removed.is_empty() {
+        storage.save(&mut data)?;
+        storage.remove_raw_games(&removed);
+        events::matches_deleted(&app, Vec::new());
+    }
+
+    Ok(removed.len())
+}
+
+/// Set or clear the automatic retention policy applied after imports
+#[tauri::command]
+fn set_match_retention(
+    policy: Option<RetentionPolicy>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<usize, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.metadata.match_retention = policy;
+    let removed = data.apply_retention(chrono::Utc::now());
+    storage.save(&mut data)?;
+    storage.remove_raw_games(&removed);
+    events::matches_deleted(&app, Vec::new());
+
+    Ok(removed.len())
+}
+
+/// Write the games of the active account and player, without any matchup
+/// notes, to `path`
+///
+/// Returns how many games were written.
+#[tauri::command]
+fn export_match_history(path: String, state: State<AppState>) -> Result<usize, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    let matches = data
+        .matches
+        .values()
+        .filter(|m| m.matches_filter(&filter))
+        .cloned()
+        .collect();
+    Ok(match_history::export(
+        &PathBuf::from(path),
+        matches,
+        &data.matchups,
+        &data.metadata.players,
+        chrono::Utc::now(),
+    )?)
+}
+
+/// Add the games of a file from `export_match_history` that aren't here yet
+///
+/// Games are linked to my matchups for the same pairing; matchups themselves
+/// are never created or changed.
+#[tauri::command]
+fn import_match_history(
+    path: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<HistoryImportReport, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let report = match_history::import(&mut data, &PathBuf::from(path))?;
+    if report.imported > 0 {
+        storage.save(&mut data)?;
+        events::matches_changed(&app, Vec::new());
+    }
+
+    Ok(report)
+}
+
+// ==================== LCU Commands ====================
+
+/// Connect to the League Client
+#[tauri::command]
+fn connect_lcu(state: State<AppState>) -> Result<LcuConnectionStatus, AppError> {
+    let status = connect_client(&state)?;
+
+    remember_account(&state)?;
+    let _ = refresh_current_patch(&state);
+
+    Ok(status)
+}
+
+/// Connect a copy of the client and keep its connection, so the requests
+/// connecting makes don't hold the client lock
+///
+/// A disconnect or TLS change made while connecting wins over the connection.
+fn connect_client(state: &AppState) -> Result<LcuConnectionStatus, AppError> {
+    let mut connecting = state.lcu_client.lock()?.clone();
+    let status = connecting.connect();
+    let mut client = state.lcu_client.lock()?;
+    if !client.adopt_connection(connecting) {
+        return Ok(client.status());
+    }
+    Ok(status?)
+}
+
+/// Disconnect from the League Client
+///
+/// The background monitor won't reconnect until `connect_lcu` is called.
+#[tauri::command]
+fn disconnect_lcu(state: State<AppState>) -> Result<LcuConnectionStatus, AppError> {
+    let mut client = state.lcu_client.lock()?;
+    client.disconnect_by_user();
+    Ok(client.status())
+}
+
+/// Get the connection state without touching the client
+#[tauri::command]
+fn get_lcu_status(state: State<AppState>) -> Result<LcuConnectionStatus, AppError> {
+    let client = state.lcu_client.lock()?;
+    Ok(client.status())
+}
+
+/// Get the live patch, e.g. "14.3"
+///
+/// Asks the client when connected, else Data Dragon, else returns the last detected patch.
+#[tauri::command]
+fn get_current_patch(state: State<AppState>) -> Result<Option<String>, AppError> {
+    refresh_current_patch(&state)
+}
+
+/// Detect the live patch and cache it for stamping new versions and matches
+fn refresh_current_patch(state: &AppState) -> Result<Option<String>, AppError> {
+    let detected = {
+        let client = state.lcu_client.lock()?.clone();
+        if client.is_connected() {
+            client.get_game_version().ok()
+        } else {
+            None
+        }
+    }
+    .or_else(|| state.ddragon.version())
+    .and_then(|version| lcu::patch_from_game_version(&version));
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if let Some(patch) = detected {
+        if data.metadata.current_patch.as_ref() != Some(&patch) {
+            data.metadata.current_patch = Some(patch);
+            storage.save(&mut data)?;
+        }
+    }
+
+    Ok(data.metadata.current_patch)
+}
+
+/// Record the connected account so its matches can be told apart
+fn remember_account(state: &AppState) -> Result<(), AppError> {
+    let (puuid, summoner_name) = {
+        let client = state.lcu_client.lock()?.clone();
+        let Some(puuid) = client.summoner_puuid() else {
+            return Ok(());
+        };
+        (puuid.to_string(), client.status().summoner_name)
+    };
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    data.metadata
+        .remember_account(&puuid, summoner_name, chrono::Utc::now());
+    storage.save(&mut data)?;
+
+    Ok(())
+}
+
+/// Get every account seen in the client and the active one's PUUID
+#[tauri::command]
+fn get_accounts(state: State<AppState>) -> Result<(Vec<Account>, Option<String>), AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok((data.metadata.accounts, data.metadata.active_account))
+}
+
+/// Choose whose matches are shown by default, or `None` for all accounts
+#[tauri::command]
+fn set_active_account(puuid: Option<String>, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if let Some(ref puuid) = puuid {
+        if !data.metadata.accounts.iter().any(|a| &a.puuid == puuid) {
+            return Err(AppError::not_found("Account"));
+        }
+    }
+    data.metadata.active_account = puuid;
+
+    storage.save(&mut data)?;
+
+    Ok(())
+}
+
+/// Link a newly imported match to its matchup, creating one if asked to
+///
+/// Returns whether a matchup was created.
+fn link_imported_match(data: &mut AppData, new_match: &mut Match, create_missing: bool) -> bool {
+    if !new_match.has_lane_opponent() {
+        return false;
+    }
+    new_match.linked_matchup =
+        matchup::find_matchup_for(data.matchups.values(), new_match).map(|m| m.id.clone());
+
+    // An empty matchup shows up under "needs notes" until it gets written up
+    if new_match.linked_matchup.is_none() && create_missing && new_match.enemy_champion != "Unknown"
+    {
+        // Made like `create_matchup` does, but for whoever played the game
+        let mut matchup = Matchup::new(
+            new_match.my_champion.clone(),
+            new_match.enemy_champion.clone(),
+            new_match.role.clone(),
+        );
+        matchup.versions[0].patch = new_match.patch.clone();
+        matchup.versions[0].author = data.metadata.local_author();
+        matchup.player = new_match.player.clone();
+        new_match.linked_matchup = Some(matchup.id.clone());
+        data.matchups.insert(matchup.id.clone(), matchup);
+        return true;
+    }
+    false
+}
+
+/// Fetch the timelines of the games an import will store, by game ID
+///
+/// Done before the data is locked, as each timeline is a request of its own.
+/// Laning numbers are a bonus, so a missing timeline is left out rather than
+/// blocking the import.
+fn fetch_early_stats(
+    client: &LcuClient,
+    state: &AppState,
+    lcu_matches: &[LcuMatchData],
+    options: &ImportOptions,
+) -> Result<HashMap<i64, EarlyGameStats>, AppError> {
+    let known_games = state.storage.lock()?.load()?.imported_game_ids();
+
+    Ok(lcu_matches
+        .iter()
+        .filter(|m| options.allows(m.queue_id, m.game_mode, m.game_creation))
+        .filter(|m| options.merge_existing || !known_games.contains(&m.game_id.to_string()))
+        .filter_map(|m| {
+            client
+                .get_early_game_stats(m.game_id, m.my_participant_id, m.enemy_participant_id)
+                .ok()
+                .map(|early| (m.game_id, early))
+        })
+        .collect())
+}
+
+/// Store freshly fetched games, skipping ones already imported unless the
+/// options ask to merge them
+///
+/// Links each new match to its matchup (creating one when `create_missing` is set),
+/// advances the import marker and applies the retention policy. `early` holds the
+/// timelines from `fetch_early_stats`. Saving is left to the caller.
+fn store_imported_matches(
+    client: &LcuClient,
+    storage: &Storage,
+    data: &mut AppData,
+    lcu_matches: Vec<LcuMatchData>,
+    mut early: HashMap<i64, EarlyGameStats>,
+    create_missing: bool,
+    options: &ImportOptions,
+) -> Result<ImportSummary, AppError> {
+    let mut imported = Vec::new();
+    let mut linked = 0;
+    let mut skipped = 0;
+    let mut filtered = 0;
+    let mut created = 0;
+    let mut merged = Vec::new();
+    let mut known_games = data.imported_match_ids();
+
+    for lcu_match in lcu_matches {
+        if !options.allows(
+            lcu_match.queue_id,
+            lcu_match.game_mode,
+            lcu_match.game_creation,
+        ) {
+            filtered += 1;
+            continue;
+        }
+        // Already imported games are skipped, or refreshed when merging
+        let game_id = lcu_match.game_id.to_string();
+        let existing = match known_games.get(&game_id) {
+            None => None,
+            Some(id) if options.merge_existing => Some(id.clone()),
+            Some(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let mut new_match = lcu_match.to_match();
+        // The current rank, so only accurate for games imported soon after playing
+        new_match.rank = new_match
+            .queue_type()
+            .filter(|q| matches!(q, QueueType::RankedSolo | QueueType::RankedFlex))
+            .and_then(|q| client.rank_for_queue(q));
+        new_match.early = early.remove(&lcu_match.game_id);
+        // Captured when the game ended, usually before it reached match history
+        new_match.end_of_game = storage
+            .load_eog_block(lcu_match.game_id)
+            .ok()
+            .flatten()
+            .and_then(|block| lcu::parse_eog_stats(&block))
+            .map(|(_, stats)| stats);
+        storage.save_raw_game(lcu_match.game_id, &lcu_match.raw)?;
+        if let Some(mut stored) = existing.and_then(|id| data.matches.get(&id)).cloned() {
+            if stored.merge_imported(new_match, data.matchups.values())
+                && link_imported_match(data, &mut stored, create_missing)
+            {
+                created += 1;
+            }
+            data.matches.insert(stored.id.clone(), stored.clone());
+            merged.push(stored);
+            continue;
+        }
+        if link_imported_match(data, &mut new_match, create_missing) {
+            created += 1;
+        }
+        if new_match.linked_matchup.is_some() {
+            linked += 1;
+        }
+
+        data.metadata.record_imported_game(
+            new_match.account_puuid.as_deref(),
+            lcu_match.game_id,
+            lcu_match.game_creation,
+        );
+        known_games.insert(game_id, new_match.id.clone());
+        data.matches.insert(new_match.id.clone(), new_match.clone());
+        imported.push(new_match);
+    }
+
+    storage.remove_raw_games(&data.apply_retention(chrono::Utc::now()));
+
+    Ok(ImportSummary {
+        repeat_opponents: matchup::repeat_opponents(data.matches.values(), &imported),
+        imported,
+        linked,
+        skipped,
+        filtered,
+        failed: 0,
+        created,
+        merged,
+    })
+}
+
+/// Import recent matches from the League Client
+#[tauri::command]
+fn import_matches(
+    count: Option<u32>,
+    create_missing: Option<bool>,
+    options: Option<ImportOptions>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ImportSummary, AppError> {
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    let count = match count {
+        Some(count) => count,
+        None => load_settings(&state)?.default_import_count,
+    };
+    let options = import_options(&state, options)?;
+    let lcu_matches = client.get_match_history(count)?;
+    let early = fetch_early_stats(&client, &state, &lcu_matches, &options)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let summary = store_imported_matches(
+        &client,
+        &storage,
+        &mut data,
+        lcu_matches,
+        early,
+        create_missing.unwrap_or(false),
+        &options,
+    )?;
+    storage.save(&mut data)?;
+    events::matches_imported(&app, &summary);
+
+    Ok(summary)
+}
+
+/// Import only games played after the newest previously imported one
+#[tauri::command]
+fn import_new_matches(
+    count: Option<u32>,
+    create_missing: Option<bool>,
+    options: Option<ImportOptions>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ImportSummary, AppError> {
+    let count = match count {
+        Some(count) => count,
+        None => load_settings(&state)?.default_import_count,
+    };
+    let summary = import_new(
+        &state,
+        count,
+        create_missing.unwrap_or(false),
+        &import_options(&state, options)?,
+    )?;
+    events::matches_imported(&app, &summary);
+
+    Ok(summary)
+}
+
+/// Incremental import shared by `import_new_matches` and the post-game auto-import
+///
+/// Callers emit the change events.
+fn import_new(
+    state: &AppState,
+    count: u32,
+    create_missing: bool,
+    options: &ImportOptions,
+) -> Result<ImportSummary, AppError> {
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    let marker = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+        data.metadata
+            .import_marker(client.summoner_puuid())
+            .map(|m| m.game_creation)
+    };
+
+    // Fetched without the data locked; games another import stores meanwhile
+    // are skipped as already imported
+    let mut lcu_matches = client.get_match_history(count)?;
+    if let Some(game_creation) = marker {
+        lcu_matches.retain(|m| m.game_creation > game_creation);
+    }
+    let early = fetch_early_stats(&client, state, &lcu_matches, options)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let summary = store_imported_matches(
+        &client,
+        &storage,
+        &mut data,
+        lcu_matches,
+        early,
+        create_missing,
+        options,
+    )?;
+    storage.save(&mut data)?;
+
+    Ok(summary)
+}
+
+/// The given import options, or the ones saved in the settings
+fn import_options(
+    state: &AppState,
+    options: Option<ImportOptions>,
+) -> Result<ImportOptions, AppError> {
+    match options {
+        Some(options) => Ok(options),
+        None => Ok(load_settings(state)?.import_options),
+    }
+}
+
+/// Import up to `max` games of history, fetching page by page
+///
+/// Emits `import-progress` after every page so the UI can show a progress bar.
+#[tauri::command]
+fn import_all_matches(
+    max: Option<u32>,
+    create_missing: Option<bool>,
+    options: Option<ImportOptions>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ImportSummary, AppError> {
+    let max = max.unwrap_or(200);
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    let options = import_options(&state, options)?;
+    let lcu_matches = client.get_match_history_paged(max, |fetched| {
+        let _ = app.emit("import-progress", ImportProgress { fetched, max });
+    })?;
+    let early = fetch_early_stats(&client, &state, &lcu_matches, &options)?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let summary = store_imported_matches(
+        &client,
+        &storage,
+        &mut data,
+        lcu_matches,
+        early,
+        create_missing.unwrap_or(false),
+        &options,
+    )?;
+    storage.save(&mut data)?;
+    events::matches_imported(&app, &summary);
+
+    Ok(summary)
+}
+
+/// Set the Riot API key and region, or `None` to turn the integration off
+#[tauri::command]
+fn set_riot_api_settings(
+    settings: Option<RiotApiSettings>,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    modify_settings(&state, |s| s.riot_api = settings)?;
+    Ok(())
+}
+
+/// Import recent matches through the Riot API, for when the client isn't running
+///
+/// Imports for `puuid`, else the active account, else the most recently seen one.
+/// Games are fetched before the data is locked, and one that fails to come
+/// through is counted as failed rather than stopping the rest.
+#[tauri::command]
+fn import_riot_matches(
+    count: Option<u32>,
+    puuid: Option<String>,
+    create_missing: Option<bool>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ImportSummary, AppError> {
+    let settings = load_settings(&state)?;
+    let count = count.unwrap_or(settings.default_import_count);
+    let api_settings = settings
+        .riot_api
+        .ok_or_else(|| AppError::new(ErrorCode::NotConfigured, "Riot API is not configured"))?;
+    let (puuid, known_games) = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+        let puuid = puuid
+            .or_else(|| data.metadata.active_account.clone())
+            .or_else(|| {
+                data.metadata
+                    .accounts
+                    .iter()
+                    .max_by_key(|a| a.last_seen)
+                    .map(|a| a.puuid.clone())
+            })
+            .ok_or_else(|| AppError::new(ErrorCode::NotConfigured, "No account to import for"))?;
+        (puuid, data.imported_game_ids())
+    };
+
+    let client = RiotApiClient::new(api_settings);
+    let match_ids = client.get_match_ids(&puuid, count)?;
+
+    let mut summary = ImportSummary::default();
+    let mut fetched = Vec::new();
+    for match_id in match_ids {
+        // Match IDs are the platform plus the game ID, e.g. "EUW1_6812345678"
+        let game_id = match_id.rsplit('_').next().unwrap_or(&match_id);
+        if known_games.contains(game_id) {
+            summary.skipped += 1;
+            continue;
+        }
+        match client.get_match(&match_id, &puuid) {
+            Ok(new_match) => fetched.push(new_match),
+            Err(e) => {
+                tracing::warn!("Could not fetch {} from the Riot API: {}", match_id, e);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    // Another import may have stored some of these while fetching
+    let mut known_games = data.imported_game_ids();
+    for mut new_match in fetched {
+        if let Some(ref game_id) = new_match.game_id {
+            if !known_games.insert(game_id.clone()) {
+                summary.skipped += 1;
+                continue;
+            }
+        }
+        if link_imported_match(&mut data, &mut new_match, create_missing.unwrap_or(false)) {
+            summary.created += 1;
+        }
+        if new_match.linked_matchup.is_some() {
+            summary.linked += 1;
+        }
+
+        data.matches.insert(new_match.id.clone(), new_match.clone());
+        summary.imported.push(new_match);
+    }
+
+    let pruned = data.apply_retention(chrono::Utc::now());
+    summary.repeat_opponents = matchup::repeat_opponents(data.matches.values(), &summary.imported);
+    storage.save(&mut data)?;
+    storage.remove_raw_games(&pruned);
+    events::matches_imported(&app, &summary);
+
+    Ok(summary)
+}
+
+/// Re-run the current parser over cached raw games and update matches in place
+///
+/// Kept as when merging a fresh import: notes, timeline numbers and links made
+/// by hand stay, and games whose pairing changed are linked again. Returns how
+/// many matches were updated.
+#[tauri::command]
+fn reprocess_matches(app: AppHandle, state: State<AppState>) -> Result<usize, AppError> {
+    let client = state.lcu_client.lock()?.clone();
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let mut updated = 0;
+    let ids: Vec<String> = data.matches.keys().cloned().collect();
+    for id in ids {
+        let Some(mut m) = data.matches.get(&id).cloned() else {
+            continue;
+        };
+        let (Some(game_id), Some(puuid)) = (m.game_id.as_ref(), m.account_puuid.as_ref()) else {
+            continue;
+        };
+        let Ok(game_id) = game_id.parse::<i64>() else {
+            continue;
+        };
+        let Some(raw) = storage.load_raw_game(game_id)? else {
+            continue;
+        };
+        let Some(lcu_match) = client.parse_game(&raw, puuid) else {
+            continue;
+        };
+
+        if m.merge_imported(lcu_match.to_match(), data.matchups.values()) {
+            link_imported_match(&mut data, &mut m, false);
+        }
+        data.matches.insert(id, m);
+        updated += 1;
+    }
+
+    storage.save(&mut data)?;
+    events::matches_changed(&app, Vec::new());
+
+    Ok(updated)
+}
+
+/// Get the full scoreboard of a game, from the local cache or the client
+#[tauri::command]
+fn get_match_details(game_id: i64, state: State<AppState>) -> Result<GameDetails, AppError> {
+    let cached = {
+        let storage = state.storage.lock()?;
+        storage.load_raw_game(game_id)?
+    };
+
+    let game = match cached {
+        Some(game) => game,
+        None => {
+            let client = state.lcu_client.lock()?.clone();
+            if !client.is_connected() {
+                return Err(AppError::lcu_not_connected());
+            }
+            let game = client.get_game(game_id)?;
+
+            let storage = state.storage.lock()?;
+            storage.save_raw_game(game_id, &game)?;
+            game
+        }
+    };
+
+    lcu::parse_game_details(&game)
+        .ok_or_else(|| AppError::new(ErrorCode::LcuRequestFailed, "Failed to parse game details"))
+}
+
+/// Push the rune page of a matchup's current version to the client
+#[tauri::command]
+fn apply_runes(matchup_id: String, state: State<AppState>) -> Result<(), AppError> {
+    let (name, page) = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+
+        let matchup = data
+            .matchups
+            .get(&matchup_id)
+            .ok_or_else(|| AppError::not_found("Matchup"))?;
+        let page = matchup
+            .current()
+            .and_then(|v| v.rune_page.clone())
+            .ok_or_else(|| {
+                AppError::new(ErrorCode::NotFound, "This matchup has no rune page saved")
+            })?;
+
+        (
+            format!("{} vs {}", matchup.my_champion, matchup.enemy_champion),
+            page,
+        )
+    };
+
+    let client = state.lcu_client.lock()?.clone();
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    client.apply_rune_page(&name, &page).map_err(AppError::from)
+}
+
+/// Save the rune page selected in the client into a matchup
+///
+/// Creates a new version unless `amend` is set, which updates the current one.
+#[tauri::command]
+fn import_current_runes(
+    matchup_id: String,
+    amend: Option<bool>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Matchup, AppError> {
+    let page = {
+        let client = state.lcu_client.lock()?.clone();
+        if !client.is_connected() {
+            return Err(AppError::lcu_not_connected());
+        }
+        client.get_current_rune_page()?
+    };
+    page.validate()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let author = data.metadata.local_author();
+
+    let matchup = data
+        .matchups
+        .get_mut(&matchup_id)
+        .ok_or_else(|| AppError::not_found("Matchup"))?;
+    matchup.ensure_editable()?;
+
+    matchup.edit_signed(author.as_deref(), |m| {
+        m.set_rune_page(page, amend.unwrap_or(false))
+    });
+
+    let updated = matchup.clone();
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, vec![matchup_id]);
+
+    Ok(updated)
+}
+
+/// Write a matchup's item build to the client as an item set for my champion
+///
+/// Items are stored by name or numeric ID; names are looked up in Data Dragon.
+/// Items that can't be resolved are returned so the UI can point them out.
+#[tauri::command]
+fn export_item_set(matchup_id: String, state: State<AppState>) -> Result<Vec<String>, AppError> {
+    let (title, champion_id, items) = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+
+        let matchup = data
+            .matchups
+            .get(&matchup_id)
+            .ok_or_else(|| AppError::not_found("Matchup"))?;
+        let champion_id = champions::id_for_name(&matchup.my_champion).ok_or_else(|| {
+            AppError::validation(format!("Unknown champion: {}", matchup.my_champion))
+        })?;
+        let items = matchup
+            .current()
+            .map(|v| v.items.clone())
+            .unwrap_or_default();
+
+        (
+            format!("{} vs {}", matchup.my_champion, matchup.enemy_champion),
+            champion_id,
+            items,
+        )
+    };
+
+    // Without Data Dragon, items stored as IDs can still be exported
+    let known_items = state.ddragon.items().unwrap_or_default();
+    let mut item_ids = Vec::new();
+    let mut skipped = Vec::new();
+    for item in items {
+        match item_id(&known_items, &item) {
+            Some(id) => item_ids.push(id),
+            None => skipped.push(item),
+        }
+    }
+    if item_ids.is_empty() {
+        return Err(AppError::validation(
+            "None of the items could be found to export",
+        ));
+    }
+
+    let client = state.lcu_client.lock()?.clone();
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    client.save_item_set(lcu::build_item_set(&title, champion_id, &item_ids))?;
+
+    Ok(skipped)
+}
+
+/// Get the client's gameflow phase
+///
+/// Changes are pushed as `gameflow-phase-changed` events while the app runs.
+#[tauri::command]
+fn get_gameflow_phase(state: State<AppState>) -> Result<GameflowPhase, AppError> {
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    client.get_gameflow_phase().map_err(AppError::from)
+}
+
+/// Get the current champ select session and my notes for it, if in champ select
+///
+/// The same data is pushed as `champ-select-update` events while the app runs.
+#[tauri::command]
+fn get_champ_select(state: State<AppState>) -> Result<Option<ChampSelectUpdate>, AppError> {
+    monitor::champ_select_update(&state)
+}
+
+/// Cache the end of game stats block and attach it to the match if already imported
+///
+/// Returns `None` when the client has no block for a finished game.
+fn capture_end_of_game(state: &AppState) -> Result<Option<EndOfGameStats>, AppError> {
+    let block = {
+        let client = state.lcu_client.lock()?.clone();
+
+        if !client.is_connected() {
+            return Err(AppError::lcu_not_connected());
+        }
+
+        client.get_eog_stats_block()?
+    };
+    let Some((block, (game_id, stats))) =
+        block.and_then(|block| lcu::parse_eog_stats(&block).map(|parsed| (block, parsed)))
+    else {
+        return Ok(None);
+    };
+
+    let storage = state.storage.lock()?;
+    storage.save_eog_block(game_id, &block)?;
+
+    let mut data = storage.load()?;
+    let game_id = game_id.to_string();
+    if let Some(m) = data
+        .matches
+        .values_mut()
+        .find(|m| m.game_id.as_deref() == Some(game_id.as_str()))
+    {
+        m.end_of_game = Some(stats.clone());
+        storage.save(&mut data)?;
+    }
+
+    Ok(Some(stats))
+}
+
+/// Store the champ select pick intent on an imported match
+fn record_pick_intent(
+    state: &AppState,
+    match_id: &str,
+    pick: PickIntent,
+) -> Result<Match, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let match_entry = data
+        .matches
+        .get_mut(match_id)
+        .ok_or_else(|| AppError::not_found("Match"))?;
+    match_entry.pick = Some(pick);
+
+    let updated = match_entry.clone();
+    storage.save(&mut data)?;
+
+    Ok(updated)
+}
+
+/// Get my matchups for the champion and role in champ select, likeliest opponents first
+///
+/// Empty outside champ select or before my champion and position are known.
+#[tauri::command]
+fn get_relevant_matchups(state: State<AppState>) -> Result<Vec<Matchup>, AppError> {
+    let session = {
+        let client = state.lcu_client.lock()?.clone();
+
+        if !client.is_connected() {
+            return Err(AppError::lcu_not_connected());
+        }
+
+        client.get_champ_select_session()?
+    };
+    let Some(session) = session else {
+        return Ok(Vec::new());
+    };
+    let (Some(my_champion), Some(role)) = (&session.my_champion, &session.my_position) else {
+        return Ok(Vec::new());
+    };
+
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let filter = MatchFilter::default().with_defaults(&data.metadata);
+    Ok(matchup::relevant_matchups(
+        data.matchups
+            .values()
+            .filter(|m| filter.player.includes(m.player.as_deref())),
+        data.matches.values().filter(|m| m.matches_filter(&filter)),
+        my_champion,
+        role,
+        session.enemy_champion.as_deref(),
+        &session.enemy_picks,
+        &session.bans,
+    ))
+}
+
+/// Replace my ban list
+#[tauri::command]
+fn set_ban_list(champions: Vec<String>, state: State<AppState>) -> Result<Vec<String>, AppError> {
+    let ban_list = champions
+        .iter()
+        .map(|c| champions::canonicalize(c))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.metadata.ban_list = ban_list.clone();
+    storage.save(&mut data)?;
+
+    Ok(ban_list)
+}
+
+/// Replace my champion pool
+#[tauri::command]
+fn set_champion_pool(
+    champions: Vec<String>,
+    state: State<AppState>,
+) -> Result<Vec<String>, AppError> {
+    let pool = champions
+        .iter()
+        .map(|c| champions::canonicalize(c))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.metadata.champion_pool = pool.clone();
+    storage.save(&mut data)?;
+
+    Ok(pool)
+}
+
+/// Get the roles I play each of my champions in
+#[tauri::command]
+fn get_champion_roles(state: State<AppState>) -> Result<HashMap<String, ChampionRoles>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok(data.metadata.champion_roles)
+}
+
+/// Set the roles I play a champion in, or clear them with `None`
+///
+/// Returns the roles of every champion.
+#[tauri::command]
+fn set_champion_roles(
+    champion: String,
+    roles: Option<ChampionRoles>,
+    state: State<AppState>,
+) -> Result<HashMap<String, ChampionRoles>, AppError> {
+    let champion = champions::canonicalize(&champion)?;
+    let roles = roles.map(ChampionRoles::normalize).transpose()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    match roles {
+        Some(roles) => data.metadata.champion_roles.insert(champion, roles),
+        None => data.metadata.champion_roles.remove(&champion),
+    };
+    storage.save(&mut data)?;
+
+    Ok(data.metadata.champion_roles)
+}
+
+/// Rank my champion pool against the enemy laner, best pick first
+///
+/// The enemy and role default to what champ select shows, where banned and
+/// enemy-picked champions are also left out. Empty until the enemy laner is known.
+#[tauri::command]
+fn get_pick_suggestions(
+    enemy_champion: Option<String>,
+    role: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<PickSuggestion>, AppError> {
+    let session = {
+        let client = state.lcu_client.lock()?.clone();
+        if client.is_connected() {
+            client.get_champ_select_session()?
+        } else {
+            None
+        }
+    };
+
+    let enemy = match enemy_champion {
+        Some(enemy) => Some(champions::canonicalize(&enemy)?),
+        None => session.as_ref().and_then(|s| s.enemy_champion.clone()),
+    };
+    let role = role.or_else(|| session.as_ref().and_then(|s| s.my_position.clone()));
+    let (Some(enemy), Some(role)) = (enemy, role) else {
+        return Ok(Vec::new());
+    };
+    let unavailable: Vec<String> = session
+        .map(|s| s.bans.into_iter().chain(s.enemy_picks).collect())
+        .unwrap_or_default();
+
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let player = &data.metadata.active_player;
+
+    Ok(matchup::pick_suggestions(
+        data.matchups.values().filter(|m| &m.player == player),
+        data.matches.values().filter(|m| &m.player == player),
+        &data.metadata.champion_pool,
+        &data.metadata.champion_roles,
+        &enemy,
+        &role,
+        &unavailable,
+    ))
+}
+
+/// Get my ban list and worst matchups for the champion I'm playing
+///
+/// Without `my_champion`, the champion and role are taken from champ select.
+/// The same data is pushed as a `ban-phase` event when my ban turn starts.
+#[tauri::command]
+fn get_ban_suggestions(
+    my_champion: Option<String>,
+    role: Option<String>,
+    state: State<AppState>,
+) -> Result<BanSuggestions, AppError> {
+    let (my_champion, role) = match my_champion {
+        Some(champion) => (Some(champions::canonicalize(&champion)?), role),
+        None => {
+            let client = state.lcu_client.lock()?.clone();
+            let session = if client.is_connected() {
+                client.get_champ_select_session()?
+            } else {
+                None
+            };
+            session
+                .map(|s| (s.my_champion, s.my_position))
+                .unwrap_or_default()
+        }
+    };
+
+    ban_suggestions(&state, my_champion, role)
+}
+
+/// Ban list plus worst matchups, shared with the ban phase monitor
+fn ban_suggestions(
+    state: &AppState,
+    my_champion: Option<String>,
+    role: Option<String>,
+) -> Result<BanSuggestions, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let player = &data.metadata.active_player;
+    let worst_matchups = my_champion
+        .as_deref()
+        .map(|champion| {
+            matchup::worst_matchups(
+                data.matches.values().filter(|m| &m.player == player),
+                champion,
+                role.as_deref(),
+            )
+        })
+        .unwrap_or_default();
+
+    Ok(BanSuggestions {
+        ban_list: data.metadata.ban_list,
+        my_champion,
+        worst_matchups,
+    })
+}
+
+/// Debug LCU endpoint (for troubleshooting)
+#[tauri::command]
+fn debug_lcu(endpoint: String, state: State<AppState>) -> Result<String, AppError> {
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    client.debug_endpoint(&endpoint).map_err(AppError::from)
+}
+
+/// Allow or forbid `lcu_request` on this PC
+///
+/// Kept in the settings, so it never travels with exported or synced data.
+#[tauri::command]
+fn set_lcu_requests_allowed(allowed: bool, state: State<AppState>) -> Result<(), AppError> {
+    modify_settings(&state, |s| s.allow_lcu_requests = allowed)?;
+    Ok(())
+}
+
+/// Replace the built-in Riot root CA (PEM) used to verify the client connection
+#[tauri::command]
+fn set_riot_root_ca(pem: String, state: State<AppState>) -> Result<(), AppError> {
+    let allow_insecure = load_settings(&state)?.allow_insecure_lcu;
+    let mut client = state.lcu_client.lock()?;
+    let storage = state.storage.lock()?;
+
+    client.configure_tls(Some(pem.as_bytes()), allow_insecure)?;
+    std::fs::write(storage.riot_root_ca_path(), pem)?;
+
+    Ok(())
+}
+
+/// Allow unverified client connections when Riot's root CA is not installed
+///
+/// Kept in the settings, so it never travels with exported or synced data. Only
+/// saved once the client took it, so a bad root CA file leaves it unchanged.
+#[tauri::command]
+fn set_insecure_lcu_allowed(allowed: bool, state: State<AppState>) -> Result<(), AppError> {
+    let root_ca = {
+        let storage = state.storage.lock()?;
+        std::fs::read(storage.riot_root_ca_path()).ok()
+    };
+    state
+        .lcu_client
+        .lock()?
+        .configure_tls(root_ca.as_deref(), allowed)?;
+    modify_settings(&state, |s| s.allow_insecure_lcu = allowed)?;
+
+    Ok(())
+}
+
+/// Send any request to the League Client, for exploring endpoints from the dev tools
+///
+/// Only works after opting in with `set_lcu_requests_allowed`, since writes can
+/// change client state.
+#[tauri::command]
+fn lcu_request(
+    method: String,
+    endpoint: String,
+    body: Option<serde_json::Value>,
+    state: State<AppState>,
+) -> Result<serde_json::Value, AppError> {
+    if !load_settings(&state)?.allow_lcu_requests {
+        return Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            "Raw LCU requests are disabled",
+        ));
+    }
+
+    let client = state.lcu_client.lock()?.clone();
+
+    if !client.is_connected() {
+        return Err(AppError::lcu_not_connected());
+    }
+
+    client
+        .raw_request(&method, &endpoint, body.as_ref())
+        .map_err(AppError::from)
+}
+
+// ==================== Team Note Commands ====================
+
+/// Team notes matching the filter, most recent game first
+#[tauri::command]
+fn get_team_notes(
+    filter: Option<TeamNoteFilter>,
+    state: State<AppState>,
+) -> Result<Vec<TeamNote>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    Ok(team_notes::filter_notes(
+        data.team_notes.values(),
+        &filter.unwrap_or_default(),
+    ))
+}
+
+/// Get a single team note by ID
+#[tauri::command]
+fn get_team_note(id: String, state: State<AppState>) -> Result<TeamNote, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    data.team_notes
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found("Team note"))
+}
+
+/// Create a team note
+#[tauri::command]
+fn create_team_note(
+    note: TeamNoteInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<TeamNote, AppError> {
+    let note = TeamNote::new(note.normalize()?);
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    data.team_notes.insert(note.id.clone(), note.clone());
+    storage.save(&mut data)?;
+    events::team_notes_changed(&app, vec![note.id.clone()]);
+
+    Ok(note)
+}
+
+/// Replace the contents of a team note
+#[tauri::command]
+fn update_team_note(
+    id: String,
+    note: TeamNoteInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<TeamNote, AppError> {
+    let input = note.normalize()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let note = data
+        .team_notes
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Team note"))?;
+    note.update(input);
+
+    let updated = note.clone();
+    storage.save(&mut data)?;
+    events::team_notes_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Delete a team note
+#[tauri::command]
+fn delete_team_note(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.team_notes
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Team note"))?;
+
+    storage.save(&mut data)?;
+    events::team_notes_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+// ==================== Draft Commands ====================
+
+/// Drafts, most recent first, optionally only those with a champion picked or banned
+#[tauri::command]
+fn get_drafts(champion: Option<String>, state: State<AppState>) -> Result<Vec<Draft>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    Ok(draft::filter_drafts(
+        data.drafts.values(),
+        champion.as_deref(),
+    ))
+}
+
+/// Get a single draft by ID
+#[tauri::command]
+fn get_draft(id: String, state: State<AppState>) -> Result<Draft, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    data.drafts
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found("Draft"))
+}
+
+/// Create a draft entered by hand
+#[tauri::command]
+fn create_draft(
+    draft: DraftInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Draft, AppError> {
+    let input = draft.normalize()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if let Some(ref id) = input.linked_match {
+        if !data.matches.contains_key(id) {
+            return Err(AppError::not_found("Match"));
+        }
+    }
+    let draft = Draft::new(input);
+    data.drafts.insert(draft.id.clone(), draft.clone());
+    storage.save(&mut data)?;
+    events::drafts_changed(&app, vec![draft.id.clone()]);
+
+    Ok(draft)
+}
+
+/// Save the bans and picks of the current champ select as a new draft
+///
+/// Best called once picks are locked, before the session closes.
+#[tauri::command]
+fn import_champ_select_draft(app: AppHandle, state: State<AppState>) -> Result<Draft, AppError> {
+    let (my_side, actions) = {
+        let client = state.lcu_client.lock()?.clone();
+        if !client.is_connected() {
+            return Err(AppError::lcu_not_connected());
+        }
+        client
+            .get_champ_select_draft()?
+            .ok_or_else(|| AppError::validation("Not in champ select"))?
+    };
+    if actions.is_empty() {
+        return Err(AppError::validation("No bans or picks made yet"));
+    }
+
+    let draft = Draft::new(DraftInput {
+        date: None,
+        my_side,
+        actions,
+        result: None,
+        notes: String::new(),
+        linked_match: None,
+    });
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    data.drafts.insert(draft.id.clone(), draft.clone());
+    storage.save(&mut data)?;
+    events::drafts_changed(&app, vec![draft.id.clone()]);
+
+    Ok(draft)
+}
+
+/// Replace the contents of a draft, e.g. to add notes per pick or the result
+#[tauri::command]
+fn update_draft(
+    id: String,
+    draft: DraftInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Draft, AppError> {
+    let input = draft.normalize()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if let Some(ref linked) = input.linked_match {
+        if !data.matches.contains_key(linked) {
+            return Err(AppError::not_found("Match"));
+        }
+    }
+    let draft = data
+        .drafts
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Draft"))?;
+    draft.update(input);
+
+    let updated = draft.clone();
+    storage.save(&mut data)?;
+    events::drafts_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Delete a draft
+#[tauri::command]
+fn delete_draft(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.drafts
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Draft"))?;
+
+    storage.save(&mut data)?;
+    events::drafts_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+// ==================== Reminder Commands ====================
+
+/// Every reminder, open ones first, optionally only those for a matchup
+#[tauri::command]
+fn get_reminders(
+    matchup_id: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<Reminder>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let mut reminders: Vec<Reminder> = data
+        .reminders
+        .into_values()
+        .filter(|r| matchup_id.is_none() || r.matchup_id == matchup_id)
+        .collect();
+    reminders.sort_by(|a, b| {
+        a.done
+            .cmp(&b.done)
+            .then(a.due.is_none().cmp(&b.due.is_none()))
+            .then(a.due.cmp(&b.due))
+            .then(a.created.cmp(&b.created))
+    });
+    Ok(reminders)
+}
+
+/// Reminders past their date or patch, for the frontend to show on startup
+#[tauri::command]
+fn get_due_reminders(state: State<AppState>) -> Result<Vec<Reminder>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok(reminders::due_reminders(
+        data.reminders.values(),
+        chrono::Utc::now(),
+        data.metadata.current_patch.as_deref(),
+    ))
+}
+
+/// Create a reminder, optionally linked to a matchup
+#[tauri::command]
+fn create_reminder(
+    reminder: NewReminder,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Reminder, AppError> {
+    reminder.validate()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if let Some(ref id) = reminder.matchup_id {
+        if !data.matchups.contains_key(id) {
+            return Err(AppError::not_found("Matchup"));
+        }
+    }
+
+    let reminder = Reminder::new(reminder);
+    data.reminders.insert(reminder.id.clone(), reminder.clone());
+    storage.save(&mut data)?;
+    events::reminders_changed(&app, vec![reminder.id.clone()]);
+
+    Ok(reminder)
+}
+
+/// Mark a reminder as done, or open it again with `done: false`
+#[tauri::command]
+fn complete_reminder(
+    id: String,
+    done: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Reminder, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let reminder = data
+        .reminders
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Reminder"))?;
+    reminder.set_done(done);
+
+    let updated = reminder.clone();
+    storage.save(&mut data)?;
+    events::reminders_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Delete a reminder
+#[tauri::command]
+fn delete_reminder(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.reminders
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Reminder"))?;
+
+    storage.save(&mut data)?;
+    events::reminders_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+// ==================== Practice Plan Commands ====================
+
+/// This week's progress on every practice target of the active player, for
+/// the dashboard; games are counted from the active account
+#[tauri::command]
+fn get_plan_progress(state: State<AppState>) -> Result<PlanProgress, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    let matches: Vec<&Match> = data.matches.values().collect();
+    Ok(plans::progress(
+        data.plan_targets.values(),
+        &matches,
+        &data.metadata.active_player,
+        data.metadata.active_account.as_deref(),
+        plans::week_start(chrono::Utc::now(), &chrono::Local),
+    ))
+}
+
+/// Add a weekly target for the active player, such as 5 games of a matchup
+/// or 3 reviews of stale notes
+#[tauri::command]
+fn create_plan_target(
+    target: NewPlanTarget,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<PlanTarget, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    let player = data.metadata.active_player.clone();
+    let existing = data
+        .plan_targets
+        .values()
+        .filter(|t| t.player == player)
+        .count();
+    let target = PlanTarget::new(target, player, existing)?;
+    data.plan_targets.insert(target.id.clone(), target.clone());
+    storage.save(&mut data)?;
+    events::plan_changed(&app, vec![target.id.clone()]);
+
+    Ok(target)
+}
+
+/// Delete a practice target
+#[tauri::command]
+fn delete_plan_target(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.plan_targets
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Practice target"))?;
+
+    storage.save(&mut data)?;
+    events::plan_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+// ==================== Collection Commands ====================
+
+/// Collections in my order
+#[tauri::command]
+fn get_collections(state: State<AppState>) -> Result<Vec<Collection>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    Ok(collections::sorted(data.collections.values()))
+}
+
+/// A collection's matchups, in the collection's order
+#[tauri::command]
+fn get_collection_matchups(id: String, state: State<AppState>) -> Result<Vec<Matchup>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let collection = data
+        .collections
+        .get(&id)
+        .ok_or_else(|| AppError::not_found("Collection"))?;
+    Ok(collection.matchups(&data.matchups))
+}
+
+/// Create a collection, placed after the existing ones
+#[tauri::command]
+fn create_collection(
+    collection: CollectionInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Collection, AppError> {
+    let input = collection.normalize()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if input
+        .matchup_ids
+        .iter()
+        .any(|id| !data.matchups.contains_key(id))
+    {
+        return Err(AppError::not_found("Matchup"));
+    }
+    let position = data
+        .collections
+        .values()
+        .map(|c| c.position + 1)
+        .max()
+        .unwrap_or(0);
+    let collection = Collection::new(input, position);
+    data.collections
+        .insert(collection.id.clone(), collection.clone());
+    storage.save(&mut data)?;
+    events::collections_changed(&app, vec![collection.id.clone()]);
+
+    Ok(collection)
+}
+
+/// Replace the name, description and matchups of a collection
+#[tauri::command]
+fn update_collection(
+    id: String,
+    collection: CollectionInput,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Collection, AppError> {
+    let input = collection.normalize()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if input
+        .matchup_ids
+        .iter()
+        .any(|id| !data.matchups.contains_key(id))
+    {
+        return Err(AppError::not_found("Matchup"));
+    }
+    let collection = data
+        .collections
+        .get_mut(&id)
+        .ok_or_else(|| AppError::not_found("Collection"))?;
+    collection.update(input);
+
+    let updated = collection.clone();
+    storage.save(&mut data)?;
+    events::collections_changed(&app, vec![id]);
+
+    Ok(updated)
+}
+
+/// Put the collections in the order of `ids`, returning them in that order
+#[tauri::command]
+fn reorder_collections(
+    ids: Vec<String>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Vec<Collection>, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    if ids.iter().any(|id| !data.collections.contains_key(id)) {
+        return Err(AppError::not_found("Collection"));
+    }
+    collections::reorder(&mut data.collections, &ids);
+    storage.save(&mut data)?;
+    events::collections_changed(&app, Vec::new());
+
+    Ok(collections::sorted(data.collections.values()))
+}
+
+/// Delete a collection; its matchups stay
+#[tauri::command]
+fn delete_collection(id: String, app: AppHandle, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    data.collections
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found("Collection"))?;
+
+    storage.save(&mut data)?;
+    events::collections_deleted(&app, vec![id]);
+
+    Ok(())
+}
+
+// ==================== Scratchpad Commands ====================
+
+/// The scratchpad notes, in my order
+#[tauri::command]
+fn get_scratchpad(state: State<AppState>) -> Result<Vec<ScratchNote>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    Ok(data.scratchpad)
+}
+
+/// Save the whole scratchpad, returning the notes as stored
+///
+/// New notes get an ID and blank ones are dropped.
+#[tauri::command]
+fn save_scratchpad(
+    notes: Vec<ScratchNote>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Vec<ScratchNote>, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    data.scratchpad = scratchpad::save(&data.scratchpad, notes, chrono::Utc::now())?;
+    storage.save(&mut data)?;
+    events::scratchpad_changed(&app);
+
+    Ok(data.scratchpad)
+}
+
+// ==================== Player Commands ====================
+
+/// Most characters in a player name
+const MAX_PLAYER_NAME_LENGTH: usize = 50;
+
+/// Add a player to keep notes for, e.g. a student
+#[tauri::command]
+fn create_player(name: String, state: State<AppState>) -> Result<Player, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_PLAYER_NAME_LENGTH {
+        return Err(AppError::validation(format!(
+            "Player name must be 1 to {} characters",
+            MAX_PLAYER_NAME_LENGTH
+        )));
+    }
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if data
+        .metadata
+        .players
+        .iter()
+        .any(|p| p.name.eq_ignore_ascii_case(&name))
+    {
+        return Err(AppError::validation(format!("{} already exists", name)));
+    }
+    let player = Player::new(name);
+    data.metadata.players.push(player.clone());
+    storage.save(&mut data)?;
+
+    Ok(player)
+}
+
+/// Get every player and the active one's ID, `None` when my own notes are shown
+#[tauri::command]
+fn get_players(state: State<AppState>) -> Result<(Vec<Player>, Option<String>), AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok((data.metadata.players, data.metadata.active_player))
+}
+
+/// Switch to a player's notes, or `None` for my own
+#[tauri::command]
+fn set_active_player(
+    id: Option<String>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if let Some(ref id) = id {
+        if !data.metadata.players.iter().any(|p| &p.id == id) {
+            return Err(AppError::not_found("Player"));
+        }
+    }
+    data.metadata.active_player = id;
+    storage.save(&mut data)?;
+    // Every list shows another player's data now
+    events::matchups_changed(&app, Vec::new());
+    events::matches_changed(&app, Vec::new());
+
+    Ok(())
+}
+
+/// Remove a player, which must have no matchups or matches left
+#[tauri::command]
+fn delete_player(id: String, state: State<AppState>) -> Result<(), AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if !data.metadata.players.iter().any(|p| p.id == id) {
+        return Err(AppError::not_found("Player"));
+    }
+    let owned = |player: &Option<String>| player.as_ref() == Some(&id);
+    if data.matchups.values().any(|m| owned(&m.player))
+        || data.matches.values().any(|m| owned(&m.player))
+    {
+        return Err(AppError::validation(
+            "Delete or archive this player's matchups and matches first",
+        ));
+    }
+
+    data.metadata.players.retain(|p| p.id != id);
+    if data.metadata.active_player.as_ref() == Some(&id) {
+        data.metadata.active_player = None;
+    }
+    storage.save(&mut data)?;
+
+    Ok(())
+}
+
+// ==================== Settings Commands ====================
+
+/// Get the current settings
+#[tauri::command]
+fn get_settings(state: State<AppState>) -> Result<Settings, AppError> {
+    load_settings(&state)
+}
+
+/// Replace the settings and emit `settings-changed` with the new values
+///
+/// A changed overlay shortcut is registered first, so one that can't be used
+/// leaves the settings untouched, as does an OBS page port that is taken. Saved
+/// window places are kept as they are. A blank OBS page token keeps the
+/// current one, or gets a new one when there is none.
+#[tauri::command]
+fn update_settings(
+    mut settings: Settings,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Settings, AppError> {
+    settings.obs.token = settings.obs.token.filter(|t| !t.trim().is_empty());
+    settings.validate()?;
+
+    let store = state.settings.lock()?;
+    let old = store.load()?;
+    #[cfg(desktop)]
+    shortcuts::replace(
+        &app,
+        old.overlay.toggle_shortcut.as_deref(),
+        settings.overlay.toggle_shortcut.as_deref(),
+    )?;
+    // Window places change by moving the windows, and the Riot API key and
+    // client connection opt-ins through their own commands, not through here
+    settings.windows = old.windows;
+    settings.riot_api = old.riot_api;
+    settings.allow_lcu_requests = old.allow_lcu_requests;
+    settings.allow_insecure_lcu = old.allow_insecure_lcu;
+    if settings.obs.token.is_none() {
+        settings.obs.token = old.obs.token;
+    }
+    if settings.obs.enabled && settings.obs.token.is_none() {
+        settings.obs.token = Some(obs::new_token());
+    }
+    obs::apply(&app, &settings.obs).map_err(|e| obs_port_error(settings.obs.port, e))?;
+    store.save(&settings)?;
+    overlay::apply_settings(&app, &settings.overlay)?;
+    state.presence.configure(&settings.rich_presence);
+    logging::set_level(settings.log_level);
+    champions::set_custom_aliases(&settings.champion_aliases);
+
+    let _ = app.emit("settings-changed", settings.clone());
+    Ok(settings)
+}
+
+fn load_settings(state: &AppState) -> Result<Settings, AppError> {
+    let store = state.settings.lock()?;
+    store.load().map_err(AppError::from)
+}
+
+/// Load, change and save the settings, returning the saved values
+fn modify_settings(
+    state: &AppState,
+    change: impl FnOnce(&mut Settings),
+) -> Result<Settings, AppError> {
+    let store = state.settings.lock()?;
+    let mut settings = store.load()?;
+    change(&mut settings);
+    store.save(&settings)?;
+    Ok(settings)
+}
+
+// ==================== OBS Page Commands ====================
+
+/// Get the URL of the OBS page, `None` while it is disabled
+#[tauri::command]
+fn get_obs_url(state: State<AppState>) -> Result<Option<String>, AppError> {
+    Ok(obs::url(&load_settings(&state)?.obs))
+}
+
+/// Replace the OBS page token, so old URLs stop working, and return the new URL
+#[tauri::command]
+fn regenerate_obs_token(
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Option<String>, AppError> {
+    let settings = modify_settings(&state, |settings| {
+        settings.obs.token = Some(obs::new_token());
+    })?;
+    obs::apply(&app, &settings.obs).map_err(|e| obs_port_error(settings.obs.port, e))?;
+
+    let _ = app.emit("settings-changed", settings.clone());
+    Ok(obs::url(&settings.obs))
+}
+
+fn obs_port_error(port: u16, e: std::io::Error) -> AppError {
+    AppError::validation(format!("Port {} is not available for the OBS page", port)).with_details(e)
+}
+
+// ==================== Webhook Commands ====================
+
+/// Post a sample message to `url`, to check it before saving it in the settings
+#[tauri::command]
+fn test_webhook(url: String) -> Result<(), AppError> {
+    webhook::validate_url(&url)?;
+
+    let sample = Match::new(
+        "Darius".to_string(),
+        "Garen".to_string(),
+        "top".to_string(),
+        MatchResult::Win,
+        None,
+    );
+    webhook::send(&url, &webhook::game_message(&sample, None)).map_err(AppError::from)
+}
+
+/// Post an imported game to the webhook, once the player agreed to a `share-game-prompt`
+#[tauri::command]
+fn share_game(match_id: String, state: State<AppState>) -> Result<(), AppError> {
+    if load_settings(&state)?.webhook_url.is_none() {
+        return Err(AppError::new(
+            ErrorCode::NotConfigured,
+            "No webhook is set up",
+        ));
+    }
+    let game = {
+        let storage = state.storage.lock()?;
+        storage
+            .load()?
+            .matches
+            .remove(&match_id)
+            .ok_or_else(|| AppError::not_found("Match"))?
+    };
+    webhook::post_game(&state, &game);
+
+    Ok(())
+}
+
+/// Take the matchup a `matchuphelper://matchup/<id>` link asked to open, if any
+#[tauri::command]
+fn take_linked_matchup(state: State<AppState>) -> Result<Option<String>, AppError> {
+    Ok(state.linked_matchup.lock()?.take())
+}
+
+// ==================== Rich Presence Commands ====================
+
+/// Show the matchup open in the app as the Discord presence, `None` when closed
+#[tauri::command]
+fn set_presence_matchup(
+    matchup_id: Option<String>,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let pairing = match matchup_id {
+        Some(id) => {
+            let storage = state.storage.lock()?;
+            let data = storage.load()?;
+            let matchup = data
+                .matchups
+                .get(&id)
+                .ok_or_else(|| AppError::not_found("Matchup"))?;
+            Some(presence::pairing_label(
+                &matchup.my_champion,
+                &matchup.enemy_champion,
+                None,
+            ))
+        }
+        None => None,
+    };
+
+    state.presence.set_reviewing(pairing);
+    Ok(())
+}
+
+// ==================== Overlay Commands ====================
+
+/// Show the overlay on a matchup's quick reference, or as it was left without one
+///
+/// Async so the window isn't built on the main thread, where it deadlocks on Windows.
+#[tauri::command]
+async fn open_overlay(
+    matchup_id: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let settings = load_settings(&state)?;
+
+    match matchup_id {
+        Some(id) => {
+            {
+                let storage = state.storage.lock()?;
+                if !storage.load()?.matchups.contains_key(&id) {
+                    return Err(AppError::not_found("Matchup"));
+                }
+            }
+            overlay::show_matchup(&app, &id, &settings.overlay)?;
+        }
+        None => overlay::show(&app, &settings.overlay)?,
+    }
+
+    Ok(())
+}
+
+/// Hide the overlay, remembering where it was
+#[tauri::command]
+fn hide_overlay(app: AppHandle) -> Result<(), AppError> {
+    overlay::hide(&app).map_err(AppError::from)
+}
+
+/// Move and resize the overlay, and open it there from now on
+#[tauri::command]
+fn set_overlay_geometry(
+    geometry: WindowGeometry,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    geometry.validate()?;
+
+    overlay::set_geometry(&app, geometry)?;
+    let settings = modify_settings(&state, |settings| {
+        settings.windows.insert(
+            overlay::OVERLAY_WINDOW.to_string(),
+            WindowState {
+                geometry,
+                maximized: false,
+            },
+        );
+    })?;
+
+    let _ = app.emit("settings-changed", settings);
+    Ok(())
+}
+
+/// Let clicks pass through the overlay to the game, or catch them again
+///
+/// With click-through on, the overlay can still be hidden with the shortcut.
+#[tauri::command]
+fn set_overlay_click_through(
+    enabled: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    let settings = modify_settings(&state, |settings| {
+        settings.overlay.click_through = enabled;
+    })?;
+    overlay::apply_settings(&app, &settings.overlay)?;
+
+    let _ = app.emit("settings-changed", settings);
+    Ok(())
+}
+
+// ==================== Data Dragon Commands ====================
+
+/// Get every champion with its title and icon
+///
+/// Uses the embedded roster, without titles or icons, until Data Dragon has loaded.
+#[tauri::command]
+fn get_champions(state: State<AppState>) -> Result<Vec<ChampionInfo>, AppError> {
+    Ok(state.ddragon.champions())
+}
+
+/// Show champion names in another language, e.g. "es_ES"
+///
+/// Localized names are accepted wherever a champion name is typed. Returns the
+/// champions with their new display names.
+#[tauri::command]
+fn set_locale(locale: String, state: State<AppState>) -> Result<Vec<ChampionInfo>, AppError> {
+    state.ddragon.set_locale(&locale)?;
+    state.ddragon.refresh()?;
+
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    data.metadata.locale = Some(locale);
+    storage.save(&mut data)?;
+
+    Ok(state.ddragon.champions())
+}
+
+/// Get purchasable Summoner's Rift items for the current patch
+#[tauri::command]
+fn get_items_reference(state: State<AppState>) -> Result<Vec<ItemInfo>, AppError> {
+    state.ddragon.items().map_err(AppError::from)
+}
+
+/// Get rune trees and their runes for the current patch
+#[tauri::command]
+fn get_runes_reference(state: State<AppState>) -> Result<Vec<RuneTreeInfo>, AppError> {
+    state.ddragon.runes().map_err(AppError::from)
+}
+
+/// Get summoner spells for the current patch
+#[tauri::command]
+fn get_spells_reference(state: State<AppState>) -> Result<Vec<SummonerSpellInfo>, AppError> {
+    state.ddragon.spells().map_err(AppError::from)
+}
+
+/// Get the enemy champion's ability cooldowns and ranges for a matchup
+#[tauri::command]
+fn get_matchup_abilities(
+    matchup_id: String,
+    state: State<AppState>,
+) -> Result<ChampionAbilities, AppError> {
+    let enemy_champion = {
+        let storage = state.storage.lock()?;
+        let data = storage.load()?;
+        data.matchups
+            .get(&matchup_id)
+            .map(|m| m.enemy_champion.clone())
+            .ok_or_else(|| AppError::not_found("Matchup"))?
+    };
+
+    state
+        .ddragon
+        .abilities(&enemy_champion)
+        .map_err(AppError::from)
+}
+
+/// Find where to load an icon from, preferring the offline cache
+///
+/// `key` is the champion name, item ID or rune ID.
+#[tauri::command]
+fn resolve_asset(
+    kind: AssetKind,
+    key: String,
+    state: State<AppState>,
+) -> Result<AssetLocation, AppError> {
+    state
+        .ddragon
+        .resolve_asset(kind, &key)
+        .map_err(AppError::from)
+}
+
+/// Load Data Dragon data in the background so startup isn't held up by the download,
+/// then fill the icon cache
+fn refresh_data_dragon(app: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        // Offline with nothing cached: the embedded roster stays in use
+        if state.ddragon.refresh().is_err() {
+            return;
+        }
+        let missing = state.ddragon.missing_assets().unwrap_or_default();
+        let _ = refresh_current_patch(&state);
+
+        // A failed icon is retried next launch
+        let http_client = reqwest::blocking::Client::new();
+        for (url, path) in missing {
+            let _ = ddragon::download_asset(&http_client, &url, &path);
+        }
+    });
+}
+
+// ==================== Diagnostics Commands ====================
+
+/// Write a zip for bug reports to `path`: recent logs, system and connection
+/// details, and counts of the saved data without any note text
+#[tauri::command]
+fn create_diagnostics_bundle(path: String, state: State<AppState>) -> Result<(), AppError> {
+    let settings = load_settings(&state)?;
+    let (diagnostics, logs_dir) = {
+        let client = state.lcu_client.lock()?.clone();
+        let storage = state.storage.lock()?;
+        (
+            Diagnostics::collect(&storage, &client, &settings)?,
+            storage.logs_dir(),
+        )
+    };
+
+    diagnostics::write_bundle(Path::new(&path), &diagnostics, &logs_dir).map_err(|e| {
+        AppError::new(
+            ErrorCode::StorageFailed,
+            "Could not write the diagnostics bundle",
+        )
+        .with_details(e)
+    })
+}
+
+/// Lines logged when no count is given
+const DEFAULT_LOG_LINES: usize = 200;
+
+/// Most lines a single call returns
+const MAX_LOG_LINES: usize = 5000;
+
+/// Get the last `lines` lines of the log, oldest first
+#[tauri::command]
+fn get_recent_logs(lines: Option<usize>, state: State<AppState>) -> Result<Vec<String>, AppError> {
+    let count = lines.unwrap_or(DEFAULT_LOG_LINES).clamp(1, MAX_LOG_LINES);
+    let logs_dir = state.storage.lock()?.logs_dir();
+    logging::recent_lines(&logs_dir, count).map_err(AppError::from)
+}
+
+// ==================== Migration Commands ====================
+
+/// Find files with notes that can be imported: data left by older versions,
+/// backups and exports
+#[tauri::command]
+fn detect_importable_sources(state: State<AppState>) -> Result<Vec<ImportSource>, AppError> {
+    let storage = state.storage.lock()?;
+    Ok(migration::detect(&storage))
+}
+
+/// Import the matchups and games in a file found by `detect_importable_sources`
+///
+/// Matchups already present are kept as they are and listed as conflicts.
+#[tauri::command]
+fn import_from_source(
+    path: String,
+    kind: SourceKind,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ImportReport, AppError> {
+    let storage = state.storage.lock()?;
+    let path = PathBuf::from(path);
+    if path == *storage.data_path() {
+        return Err(AppError::validation("This is the current data file"));
+    }
+
+    let (imported, conflicts) = migration::load(&path, kind)?;
+    let mut report = ImportReport {
+        conflicts,
+        ..Default::default()
+    };
+    let mut data = storage.load()?;
+    migration::import(&mut data, imported, &mut report);
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, Vec::new());
+    events::matches_changed(&app, Vec::new());
+
+    Ok(report)
+}
+
+// ==================== Archive Commands ====================
+
+/// Write everything needed to move the app to another PC into one zip at
+/// `path`: the data, attachments, settings and the newest backups
+#[tauri::command]
+fn export_archive(path: String, state: State<AppState>) -> Result<ArchiveSummary, AppError> {
+    let settings = load_settings(&state)?;
+    let storage = state.storage.lock()?;
+    Ok(archive::export(
+        Path::new(&path),
+        &storage,
+        &settings,
+        chrono::Utc::now(),
+    )?)
+}
+
+/// Replace the data, attachments and settings with an archive from
+/// `export_archive`, backing up the current data first
+///
+/// This PC's data directory and window places are kept. Settings that can't
+/// be applied, such as a taken OBS port, are left as they were and
+/// `settings` is false in the summary.
+#[tauri::command]
+fn restore_archive(
+    path: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ArchiveSummary, AppError> {
+    let restored = {
+        let storage = state.storage.lock()?;
+        archive::restore(Path::new(&path), &storage)?
+    };
+    events::data_replaced(&app);
+
+    let mut summary = restored.summary;
+    if let Some(mut settings) = restored.settings {
+        settings.data_directory = load_settings(&state)?.data_directory;
+        if let Err(e) = update_settings(settings, app, state) {
+            tracing::warn!("Could not apply the archived settings: {}", e);
+            summary.settings = false;
+        }
+    }
+    Ok(summary)
+}
+
+// ==================== Package Commands ====================
+
+/// Write the given matchups to `path` as a read-only package for someone else
+#[tauri::command]
+fn export_package(
+    ids: Vec<String>,
+    options: PackageOptions,
+    path: String,
+    state: State<AppState>,
+) -> Result<(), AppError> {
+    if options.author.trim().is_empty() {
+        return Err(AppError::validation("Author is required"));
+    }
+
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let matchups = ids
+        .iter()
+        .map(|id| {
+            data.matchups
+                .get(id)
+                .cloned()
+                .ok_or_else(|| AppError::not_found("Matchup"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if matchups.iter().any(|m| m.external.is_some()) {
+        return Err(AppError::validation(
+            "Imported notes can't be exported again",
+        ));
+    }
+
+    package::export(&PathBuf::from(path), matchups, &options, chrono::Utc::now())?;
+    Ok(())
+}
+
+/// Add the matchups of a package as read-only notes for the active player
+///
+/// Existing notes are never replaced, except earlier imports of the same
+/// package author.
+#[tauri::command]
+fn import_package(
+    path: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<PackageImportReport, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+    let player = data.metadata.active_player.clone();
+    let report = package::import(&mut data, &PathBuf::from(path), player, chrono::Utc::now())?;
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, Vec::new());
+
+    Ok(report)
+}
+
+// ==================== Sample Data Commands ====================
+
+/// Fill empty storage with made-up matchups and games, for demos and development
+///
+/// Refuses to run once there are any matchups or matches, so real data is never
+/// mixed with or replaced by samples.
+#[tauri::command]
+fn seed_sample_data(app: AppHandle, state: State<AppState>) -> Result<MergeSummary, AppError> {
+    let storage = state.storage.lock()?;
+    let mut data = storage.load()?;
+
+    if !data.matchups.is_empty() || !data.matches.is_empty() {
+        return Err(AppError::validation(
+            "Sample data can only be added when there are no matchups or matches",
+        ));
+    }
+
+    let summary = data.merge(sample::sample_data(chrono::Utc::now()));
+    storage.save(&mut data)?;
+    events::matchups_changed(&app, Vec::new());
+    events::matches_changed(&app, Vec::new());
+
+    Ok(summary)
 }
 
 // ==================== Application Entry Point ====================
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    #[cfg(desktop)]
+    let builder = builder.plugin(deep_link::single_instance());
+    builder
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState::default())
         .invoke_handler(tauri::generate_handler![
             get_matchups,
             get_matchup,
+            get_backlinks,
             create_matchup,
             update_matchup,
+            import_notes_from_url,
             delete_matchup,
+            bulk_update_matchups,
             search_matchups,
+            search_and_replace,
+            get_quick_reference,
+            copy_build_text,
+            get_difficulty_history,
+            get_matchup_stats,
+            fetch_community_build,
+            get_stats_by_patch,
+            get_champion_stats,
+            get_winrate_timeseries,
+            get_stats_by_length,
+            get_length_stats_by_matchup,
+            get_session_stats,
+            generate_stats_report,
+            generate_weekly_digest,
+            get_pool_distribution,
+            get_matchup_progress,
+            get_note_impact,
+            get_early_game_impact,
+            get_matchup_benchmark,
+            get_matchups_needing_notes,
+            get_notes_vs_enemy,
+            mark_still_valid,
+            append_note,
+            suggest_tags,
+            get_matchup_suggestions,
+            create_from_suggestion,
+            get_reverse_matchup,
+            copy_last_loadout,
+            add_goal,
+            complete_goal,
+            delete_goal,
+            get_checklist,
+            add_checklist_item,
+            toggle_checklist_item,
+            remove_checklist_item,
+            reset_checklist,
+            add_comment,
+            list_comments,
+            delete_comment,
+            add_audio_attachment,
+            add_drawing_attachment,
+            get_attachment,
+            delete_attachment,
+            get_review_queue,
+            mark_reviewed,
+            generate_quiz,
+            reveal_quiz_answer,
+            grade_quiz_question,
             get_matches,
+            get_match,
+            get_matches_for_matchup,
+            create_match,
             update_match,
+            delete_match,
+            add_vod_ref,
+            remove_vod_ref,
+            add_vod_bookmark,
+            remove_vod_bookmark,
+            get_vod_link,
+            get_opponent_history,
+            save_match_review,
+            get_match_review,
+            prune_matches,
+            set_match_retention,
+            export_match_history,
+            import_match_history,
             connect_lcu,
+            disconnect_lcu,
+            get_lcu_status,
+            get_accounts,
+            set_active_account,
+            get_team_notes,
+            get_team_note,
+            create_team_note,
+            update_team_note,
+            delete_team_note,
+            get_drafts,
+            get_draft,
+            create_draft,
+            import_champ_select_draft,
+            update_draft,
+            delete_draft,
+            get_reminders,
+            get_due_reminders,
+            create_reminder,
+            complete_reminder,
+            delete_reminder,
+            get_plan_progress,
+            create_plan_target,
+            delete_plan_target,
+            get_collections,
+            get_collection_matchups,
+            create_collection,
+            update_collection,
+            reorder_collections,
+            delete_collection,
+            get_scratchpad,
+            save_scratchpad,
+            create_player,
+            get_players,
+            set_active_player,
+            delete_player,
+            get_current_patch,
             import_matches,
+            import_new_matches,
+            import_all_matches,
+            get_match_details,
+            set_riot_api_settings,
+            import_riot_matches,
+            reprocess_matches,
+            apply_runes,
+            import_current_runes,
+            export_item_set,
+            get_gameflow_phase,
+            get_champ_select,
+            get_relevant_matchups,
+            set_ban_list,
+            get_ban_suggestions,
+            set_champion_pool,
+            get_champion_roles,
+            set_champion_roles,
+            get_pick_suggestions,
+            get_settings,
+            update_settings,
+            open_overlay,
+            hide_overlay,
+            set_overlay_geometry,
+            set_overlay_click_through,
+            get_obs_url,
+            regenerate_obs_token,
+            test_webhook,
+            share_game,
+            take_linked_matchup,
+            set_presence_matchup,
+            get_champions,
+            set_locale,
+            get_items_reference,
+            get_runes_reference,
+            get_spells_reference,
+            get_matchup_abilities,
+            resolve_asset,
             debug_lcu,
+            set_lcu_requests_allowed,
+            lcu_request,
+            set_riot_root_ca,
+            set_insecure_lcu_allowed,
+            create_diagnostics_bundle,
+            get_recent_logs,
+            seed_sample_data,
+            detect_importable_sources,
+            import_from_source,
+            export_archive,
+            restore_archive,
+            export_package,
+            import_package,
         ])
+        .setup(|app| {
+            let settings = load_settings(&app.state::<AppState>()).unwrap_or_default();
+            champions::set_custom_aliases(&settings.champion_aliases);
+            if let Ok(storage) = app.state::<AppState>().storage.lock() {
+                // The app works the same without a log file
+                if logging::init(&storage.logs_dir(), settings.log_level).is_ok() {
+                    tracing::info!("MatchupHelper {} started", env!("CARGO_PKG_VERSION"));
+                }
+                if let Some(ref dir) = settings.data_directory {
+                    if storage.data_path().parent() != Some(dir.as_path()) {
+                        tracing::warn!(
+                            "Data directory {} is unavailable, using {} instead",
+                            dir.display(),
+                            storage.data_path().display()
+                        );
+                    }
+                }
+            }
+            // The configured asset scope only covers the default data directory
+            let icons = app.state::<AppState>().ddragon.assets_dir();
+            if let Err(e) = app.asset_protocol_scope().allow_directory(&icons, true) {
+                tracing::warn!("Cached icons in {} can't be shown: {}", icons.display(), e);
+            }
+            #[cfg(desktop)]
+            {
+                deep_link::setup(app)?;
+                app.handle().plugin(shortcuts::plugin())?;
+                // A shortcut taken by another app shouldn't keep the app from starting
+                let _ = shortcuts::replace(
+                    app.handle(),
+                    None,
+                    settings.overlay.toggle_shortcut.as_deref(),
+                );
+            }
+            for window in app.webview_windows().values() {
+                if let Err(e) = window_state::manage(window) {
+                    tracing::warn!("Could not restore window {}: {}", window.label(), e);
+                    let _ = window.show();
+                }
+            }
+            app.state::<AppState>()
+                .presence
+                .configure(&settings.rich_presence);
+            // A taken port is reported when the OBS page is next enabled
+            let _ = obs::apply(app.handle(), &settings.obs);
+            monitor::start(app.handle().clone());
+            refresh_data_dragon(app.handle().clone());
+            Ok(())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
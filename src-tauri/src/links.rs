@@ -0,0 +1,186 @@
+//! Wiki-style links between matchup notes
+//!
+//! `[[Darius vs Garen (top)]]` links to a pairing, with the role optional, and
+//! `[[matchup:<id>]]` to a matchup by ID. Links are resolved against the
+//! notes of the same player, so a friend's notes link among themselves.
+
+use crate::champions;
+use crate::matchup::Matchup;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A link found in notes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NoteLink {
+    /// Text between the brackets
+    pub target: String,
+    /// Matchup it points to, `None` when nothing matches
+    pub matchup_id: Option<String>,
+}
+
+/// Link targets in `notes`, in order
+pub fn targets(notes: &str) -> Vec<&str> {
+    written(notes).into_iter().map(str::trim).collect()
+}
+
+/// Link targets in `notes` as written between the brackets, spaces included
+fn written(notes: &str) -> Vec<&str> {
+    let mut targets = Vec::new();
+    let mut rest = notes;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let target = &rest[..end];
+        if !target.trim().is_empty() && !target.contains(['[', ']', '\n']) {
+            targets.push(target);
+            rest = &rest[end + 2..];
+        }
+    }
+    targets
+}
+
+/// The matchup a link in `from`'s notes points to
+///
+/// Without a role, a matchup in `from`'s role wins over the others.
+pub fn resolve<'a>(
+    target: &str,
+    from: &Matchup,
+    matchups: &'a HashMap<String, Matchup>,
+) -> Option<&'a Matchup> {
+    if let Some(id) = target.strip_prefix("matchup:") {
+        return matchups.get(id.trim()).filter(|m| m.player == from.player);
+    }
+
+    let (champions, role) = match target.strip_suffix(')').and_then(|t| t.rsplit_once('(')) {
+        Some((champions, role)) => (champions, Some(role.trim())),
+        None => (target, None),
+    };
+    // ASCII only, so byte offsets in `lower` hold in `champions`
+    let lower = champions.to_ascii_lowercase();
+    let split = lower.find(" vs ")?;
+    let name = |n: &str| champions::canonicalize(n.trim()).unwrap_or_else(|_| n.trim().to_string());
+    let my_champion = name(&champions[..split]);
+    let enemy_champion = name(&champions[split + 4..]);
+
+    matchups
+        .values()
+        .filter(|m| m.player == from.player)
+        .filter(|m| {
+            m.my_champion.eq_ignore_ascii_case(&my_champion)
+                && m.enemy_champion.eq_ignore_ascii_case(&enemy_champion)
+                && role.is_none_or(|role| m.role.eq_ignore_ascii_case(role))
+        })
+        .min_by_key(|m| (!m.role.eq_ignore_ascii_case(&from.role), &m.id))
+}
+
+/// The links in `notes`, resolved from `from`
+pub fn links(notes: &str, from: &Matchup, matchups: &HashMap<String, Matchup>) -> Vec<NoteLink> {
+    targets(notes)
+        .into_iter()
+        .map(|target| NoteLink {
+            target: target.to_string(),
+            matchup_id: resolve(target, from, matchups).map(|m| m.id.clone()),
+        })
+        .collect()
+}
+
+/// `notes` with resolved links turned into Markdown links to `#matchup/<id>`
+///
+/// Links to nothing stay as they were written.
+pub fn to_markdown(notes: &str, from: &Matchup, matchups: &HashMap<String, Matchup>) -> String {
+    let mut out = notes.to_string();
+    for written in written(notes) {
+        let target = written.trim();
+        if let Some(matchup) = resolve(target, from, matchups) {
+            let label = match target.strip_prefix("matchup:") {
+                Some(_) => format!(
+                    "{} vs {} ({})",
+                    matchup.my_champion, matchup.enemy_champion, matchup.role
+                ),
+                None => target.to_string(),
+            };
+            out = out.replacen(
+                &format!("[[{}]]", written),
+                &format!("[{}](#matchup/{})", label, matchup.id),
+                1,
+            );
+        }
+    }
+    out
+}
+
+/// Matchups whose current notes link to `id`, by champion
+pub fn backlinks(id: &str, matchups: &HashMap<String, Matchup>) -> Vec<Matchup> {
+    let mut linking: Vec<Matchup> = matchups
+        .values()
+        .filter(|m| m.id != id)
+        .filter(|m| {
+            m.current().is_some_and(|v| {
+                targets(&v.notes)
+                    .into_iter()
+                    .any(|target| resolve(target, m, matchups).is_some_and(|t| t.id == id))
+            })
+        })
+        .cloned()
+        .collect();
+    linking.sort_by(|a, b| {
+        (&a.my_champion, &a.enemy_champion, &a.role).cmp(&(
+            &b.my_champion,
+            &b.enemy_champion,
+            &b.role,
+        ))
+    });
+    linking
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::MatchupUpdate;
+
+    #[test]
+    fn test_links() {
+        let garen = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut sett = Matchup::new("Darius".to_string(), "Sett".to_string(), "top".to_string());
+        sett.add_version(MatchupUpdate {
+            notes: format!(
+                "Like [[darius vs garen]], see [[matchup:{}]]. Not [[Darius vs Teemo (top)]] [[ ]] \
+                 [[ Darius vs Garen ]] [[İ vs Garen]]",
+                garen.id
+            ),
+            ..Default::default()
+        });
+        let matchups = HashMap::from([
+            (garen.id.clone(), garen.clone()),
+            (sett.id.clone(), sett.clone()),
+        ]);
+
+        let notes = &sett.current().unwrap().notes;
+        assert_eq!(targets(notes).len(), 5);
+        let found = links(notes, &sett, &matchups);
+        assert_eq!(found[0].matchup_id.as_ref(), Some(&garen.id));
+        assert_eq!(found[1].matchup_id.as_ref(), Some(&garen.id));
+        assert_eq!(found[2].matchup_id, None);
+        assert!(resolve("Darius vs Garen (mid)", &sett, &matchups).is_none());
+
+        let markdown = to_markdown(notes, &sett, &matchups);
+        assert!(markdown.starts_with(&format!("Like [darius vs garen](#matchup/{})", garen.id)));
+        assert!(markdown.contains(&format!("[Darius vs Garen (top)](#matchup/{})", garen.id)));
+        assert!(markdown.contains("[[Darius vs Teemo (top)]]"));
+        assert!(markdown.contains(&format!("[Darius vs Garen](#matchup/{})", garen.id)));
+
+        // Another player's notes are out of reach, even by ID
+        let mut friend = garen.clone();
+        friend.id = "friend".to_string();
+        friend.player = Some("Friend".to_string());
+        let matchups_with_friend = HashMap::from([(friend.id.clone(), friend.clone())]);
+        assert!(resolve("matchup:friend", &sett, &matchups_with_friend).is_none());
+
+        let linking = backlinks(&garen.id, &matchups);
+        assert_eq!(linking.len(), 1);
+        assert_eq!(linking[0].id, sett.id);
+        assert!(backlinks(&sett.id, &matchups).is_empty());
+    }
+}
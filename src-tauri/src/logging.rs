@@ -0,0 +1,110 @@
+//! Log file in the data directory's `logs` folder, started a new one each day
+//!
+//! The level comes from the settings and can be changed while the app runs.
+
+use crate::settings::LogLevel;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+const LOG_FILE_PREFIX: &str = "matchuphelper";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Days of logs kept
+const MAX_LOG_FILES: usize = 7;
+
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Start writing logs into `dir`
+pub fn init(dir: &Path, level: LogLevel) -> io::Result<()> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(io::Error::other)?;
+
+    let (filter, handle) = reload::Layer::new(level_filter(level));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(appender).with_ansi(false))
+        .try_init()
+        .map_err(io::Error::other)?;
+
+    let _ = LEVEL.set(handle);
+    Ok(())
+}
+
+/// Change the level of a running logger
+pub fn set_level(level: LogLevel) {
+    if let Some(handle) = LEVEL.get() {
+        let _ = handle.reload(level_filter(level));
+    }
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::ERROR,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Info => LevelFilter::INFO,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Trace => LevelFilter::TRACE,
+    }
+}
+
+/// Log files in `dir`, newest first; none if it doesn't exist yet
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((metadata.modified().ok()?, entry.path()))
+        })
+        .collect();
+    logs.sort_by(|a, b| b.0.cmp(&a.0));
+    logs.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The last `count` lines logged into `dir`, oldest first
+pub fn recent_lines(dir: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for file in log_files(dir) {
+        let contents = String::from_utf8_lossy(&fs::read(file)?).into_owned();
+        lines.extend(
+            contents
+                .lines()
+                .rev()
+                .take(count - lines.len())
+                .map(String::from),
+        );
+        if lines.len() >= count {
+            break;
+        }
+    }
+    lines.reverse();
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(recent_lines(dir.path(), 10).unwrap().is_empty());
+
+        fs::write(dir.path().join("matchuphelper.2024-01-01.log"), "a\nb\nc\n").unwrap();
+        assert_eq!(recent_lines(dir.path(), 2).unwrap(), vec!["b", "c"]);
+        assert_eq!(recent_lines(dir.path(), 10).unwrap(), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,148 @@
+//! Markdown rendering for notes
+//!
+//! Notes can come from imports and shared files, so raw HTML is escaped and
+//! only safe link targets survive before anything reaches the webview.
+
+use crate::links::{self, NoteLink};
+use crate::matchup::Matchup;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Rendered HTML for one version's notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedNotes {
+    pub version: u32,
+    pub html: String,
+    pub blue_side_html: Option<String>,
+    pub red_side_html: Option<String>,
+}
+
+/// A matchup together with the sanitized HTML of its notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupDetail {
+    #[serde(flatten)]
+    pub matchup: Matchup,
+    pub rendered_notes: Vec<RenderedNotes>,
+    /// `[[...]]` links in the current notes
+    pub links: Vec<NoteLink>,
+}
+
+impl MatchupDetail {
+    /// Render the notes, with links to other matchups in `matchups` resolved
+    pub fn new(matchup: Matchup, matchups: &HashMap<String, Matchup>) -> Self {
+        let html = |notes: &str| render(&links::to_markdown(notes, &matchup, matchups));
+        let rendered_notes = matchup
+            .versions
+            .iter()
+            .map(|v| RenderedNotes {
+                version: v.version,
+                html: html(&v.notes),
+                blue_side_html: v.blue_side_notes.as_deref().map(html),
+                red_side_html: v.red_side_notes.as_deref().map(html),
+            })
+            .collect();
+        let links = matchup
+            .current()
+            .map(|v| links::links(&v.notes, &matchup, matchups))
+            .unwrap_or_default();
+
+        Self {
+            matchup,
+            rendered_notes,
+            links,
+        }
+    }
+}
+
+/// Whether a link or image target is safe to hand to the webview
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    let scheme_end = url.find(':');
+    let path_start = url.find(['/', '?', '#']);
+
+    match (scheme_end, path_start) {
+        // Relative URLs and anchors have no scheme
+        (None, _) => true,
+        (Some(colon), Some(slash)) if slash < colon => true,
+        _ => ["http:", "https:", "mailto:"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme)),
+    }
+}
+
+/// Render Markdown notes into sanitized HTML
+pub fn render(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        // Never pass raw HTML through, show it as text instead
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) if !is_safe_url(&dest_url) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: CowStr::Borrowed("#"),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) if !is_safe_url(&dest_url) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: CowStr::Borrowed(""),
+            title,
+            id,
+        }),
+        other => other,
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_basic_markdown() {
+        let html = render("**Level 2** all-in\n\n- respect E\n- buy refillable");
+        assert!(html.contains("<strong>Level 2</strong>"));
+        assert!(html.contains("<li>respect E</li>"));
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped() {
+        let html = render("<script>alert(1)</script>\n\nHi <img src=x onerror=alert(1)>");
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img src=x"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_unsafe_links_are_neutralized() {
+        let html = render("[click](javascript:alert(1)) [guide](https://example.com/guide)");
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("href=\"#\""));
+        assert!(html.contains("href=\"https://example.com/guide\""));
+    }
+
+    #[test]
+    fn test_safe_url() {
+        assert!(is_safe_url("https://u.gg"));
+        assert!(is_safe_url("#section"));
+        assert!(is_safe_url("notes/garen.md"));
+        assert!(is_safe_url("/a:b"));
+        assert!(!is_safe_url("JavaScript:alert(1)"));
+        assert!(!is_safe_url("data:text/html;base64,xx"));
+    }
+}
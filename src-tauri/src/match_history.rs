@@ -0,0 +1,246 @@
+//! Match history files, for moving the game log between installs
+//!
+//! Only matches are written, never matchup notes. Matchup and player IDs differ
+//! between installs, so a link to a matchup is saved as its champion pairing
+//! and a player as their name, both matched again on import.
+
+use crate::matchup::{Match, Matchup};
+use crate::storage::{AppData, Player};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// File layout version, bumped on incompatible changes
+const HISTORY_FORMAT: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a match history file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Match history format {0} is not supported by this version")]
+    UnsupportedFormat(u32),
+}
+
+/// Champions and role of the matchup a game was linked to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinkedPairing {
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportedMatch {
+    #[serde(flatten)]
+    game: Match,
+    #[serde(default)]
+    linked_pairing: Option<LinkedPairing>,
+    /// Name of the player the game was logged for, `None` for my own
+    #[serde(default)]
+    player_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryFile {
+    format: u32,
+    exported: DateTime<Utc>,
+    matches: Vec<ExportedMatch>,
+}
+
+/// What `import` added
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct HistoryImportReport {
+    pub imported: usize,
+    /// Games already here, by ID or game ID
+    pub skipped: usize,
+    /// Imported games linked to one of my matchups again
+    pub linked: usize,
+    /// Players added because no player here had their name
+    pub players_added: usize,
+}
+
+/// Write `matches` to `path`, oldest first
+pub fn export(
+    path: &Path,
+    matches: Vec<Match>,
+    matchups: &HashMap<String, Matchup>,
+    players: &[Player],
+    now: DateTime<Utc>,
+) -> Result<usize, HistoryError> {
+    let mut matches: Vec<ExportedMatch> = matches
+        .into_iter()
+        .map(|mut game| {
+            let linked_pairing = game
+                .linked_matchup
+                .take()
+                .and_then(|id| matchups.get(&id))
+                .map(|matchup| LinkedPairing {
+                    my_champion: matchup.my_champion.clone(),
+                    enemy_champion: matchup.enemy_champion.clone(),
+                    role: matchup.role.clone(),
+                });
+            let player_name = game
+                .player
+                .take()
+                .and_then(|id| players.iter().find(|p| p.id == id))
+                .map(|player| player.name.clone());
+            ExportedMatch {
+                game,
+                linked_pairing,
+                player_name,
+            }
+        })
+        .collect();
+    matches.sort_by(|a, b| a.game.date.cmp(&b.game.date));
+
+    let count = matches.len();
+    let file = HistoryFile {
+        format: HISTORY_FORMAT,
+        exported: now,
+        matches,
+    };
+    fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    Ok(count)
+}
+
+/// Add the games in the file at `path` that aren't in `data` yet
+///
+/// Games are given to the player here with the same name, who is added when
+/// missing. Links are restored to the matchup for the same pairing and player
+/// when there is one; no matchups are created.
+pub fn import(data: &mut AppData, path: &Path) -> Result<HistoryImportReport, HistoryError> {
+    let file: HistoryFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if file.format > HISTORY_FORMAT {
+        return Err(HistoryError::UnsupportedFormat(file.format));
+    }
+
+    let mut report = HistoryImportReport::default();
+    let mut known_games = data.imported_game_ids();
+    for ExportedMatch {
+        mut game,
+        linked_pairing,
+        player_name,
+    } in file.matches
+    {
+        let known_game = game
+            .game_id
+            .as_ref()
+            .is_some_and(|game_id| !known_games.insert(game_id.clone()));
+        if known_game || data.matches.contains_key(&game.id) {
+            report.skipped += 1;
+            continue;
+        }
+
+        // Files from before player names were written keep no player
+        game.player = player_name.map(|name| {
+            let players = &mut data.metadata.players;
+            match players.iter().find(|p| p.name.eq_ignore_ascii_case(&name)) {
+                Some(player) => player.id.clone(),
+                None => {
+                    let player = Player::new(name);
+                    let id = player.id.clone();
+                    players.push(player);
+                    report.players_added += 1;
+                    id
+                }
+            }
+        });
+        game.linked_matchup = linked_pairing.and_then(|pairing| {
+            data.matchups
+                .values()
+                .filter(|m| m.player == game.player && m.external.is_none())
+                .find(|m| {
+                    m.is_pairing(&pairing.my_champion, &pairing.enemy_champion, &pairing.role)
+                })
+                .map(|m| m.id.clone())
+        });
+        if game.linked_matchup.is_some() {
+            report.linked += 1;
+        }
+        report.imported += 1;
+        data.matches.insert(game.id.clone(), game);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::MatchResult;
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let old = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut game = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            Some("EUW1_1".to_string()),
+        );
+        game.notes = "Won lane".to_string();
+        game.linked_matchup = Some(old.id.clone());
+        let matchups = HashMap::from([(old.id.clone(), old.clone())]);
+        let student = Player::new("Student".to_string());
+        let mut student_game = Match::new(
+            "Riven".to_string(),
+            "Fiora".to_string(),
+            "top".to_string(),
+            MatchResult::Loss,
+            Some("EUW1_2".to_string()),
+        );
+        student_game.player = Some(student.id.clone());
+        assert_eq!(
+            export(
+                &path,
+                vec![game.clone(), student_game.clone()],
+                &matchups,
+                &[student],
+                Utc::now()
+            )
+            .unwrap(),
+            2
+        );
+
+        // A fresh install with its own copy of the notes
+        let mut data = AppData::default();
+        let new = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        data.matchups.insert(new.id.clone(), new.clone());
+
+        let report = import(&mut data, &path).unwrap();
+        assert_eq!(
+            report,
+            HistoryImportReport {
+                imported: 2,
+                skipped: 0,
+                linked: 1,
+                players_added: 1,
+            }
+        );
+        assert_eq!(data.matches[&game.id].linked_matchup, Some(new.id.clone()));
+        assert_eq!(data.matches[&game.id].notes, "Won lane");
+        assert_eq!(data.matches[&game.id].player, None);
+        assert_eq!(data.matchups.len(), 1);
+
+        // The student is added under a new ID and given their game
+        assert_eq!(data.metadata.players.len(), 1);
+        let added = &data.metadata.players[0];
+        assert_eq!(added.name, "Student");
+        assert_eq!(
+            data.matches[&student_game.id].player,
+            Some(added.id.clone())
+        );
+
+        let report = import(&mut data, &path).unwrap();
+        assert_eq!((report.imported, report.skipped), (0, 2));
+        assert_eq!(data.metadata.players.len(), 1);
+    }
+}
@@ -1,7 +1,16 @@
 //! Matchup data structures and logic
 
-use chrono::{DateTime, Utc};
+use crate::attachments::Attachment;
+use crate::community::CommunityWinRate;
+use crate::draft::Side;
+use crate::package::ExternalSource;
+use crate::quiz::QuizResult;
+use crate::runes;
+use crate::storage::Metadata;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Timelike, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// A single version of matchup notes
@@ -18,8 +27,104 @@ pub struct MatchupVersion {
     pub summoner_spells: Vec<String>,
     #[serde(default)]
     pub items: Vec<String>,
+    /// Game patch the notes were written (or last confirmed) for, e.g. "14.12"
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// Perceived difficulty from 1 (free lane) to 10 (unplayable)
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    /// A few short bullets to read during the loading screen
+    #[serde(default)]
+    pub summary: Vec<String>,
+    /// Exact rune page that can be pushed to the client
+    #[serde(default)]
+    pub rune_page: Option<RunePage>,
+    /// Feedback left on this version, oldest first
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    /// Voice memos and other files recorded for this version
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Page the notes were imported from, e.g. a friend's guide
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Whose advice this version is, e.g. my summoner name or a coach's
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Trading and warding differences when playing from blue side
+    #[serde(default)]
+    pub blue_side_notes: Option<String>,
+    #[serde(default)]
+    pub red_side_notes: Option<String>,
+}
+
+impl MatchupVersion {
+    /// The tips for playing from `side`, if written
+    pub fn side_notes(&self, side: Side) -> Option<&str> {
+        match side {
+            Side::Blue => self.blue_side_notes.as_deref(),
+            Side::Red => self.red_side_notes.as_deref(),
+        }
+    }
+
+    /// The notes, then the side notes that are written
+    pub fn note_texts(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.notes.as_str())
+            .chain(self.blue_side_notes.as_deref())
+            .chain(self.red_side_notes.as_deref())
+    }
+}
+
+/// Feedback from a reviewer or duo partner, kept apart from the notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+    pub text: String,
+}
+
+/// A complete rune page as the client expects it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunePage {
+    pub primary_style_id: i32,
+    pub sub_style_id: i32,
+    /// Keystone, three primary runes, two secondary runes and three stat shards
+    pub selected_perk_ids: Vec<i32>,
+}
+
+/// Runes in a complete page, stat shards included
+pub const RUNE_PAGE_SIZE: usize = 9;
+
+impl RunePage {
+    /// Keystone followed by the primary and secondary tree, by name
+    pub fn rune_names(&self) -> Vec<String> {
+        self.selected_perk_ids
+            .first()
+            .into_iter()
+            .chain([&self.primary_style_id, &self.sub_style_id])
+            .map(|id| runes::rune_name(*id))
+            .collect()
+    }
+
+    /// Reject pages the client would refuse
+    pub fn validate(&self) -> Result<(), String> {
+        if self.selected_perk_ids.len() != RUNE_PAGE_SIZE {
+            return Err(format!(
+                "A rune page needs exactly {} runes",
+                RUNE_PAGE_SIZE
+            ));
+        }
+        if self.primary_style_id == self.sub_style_id {
+            return Err("Primary and secondary trees must differ".to_string());
+        }
+        Ok(())
+    }
 }
 
+/// Maximum number of bullets in a version summary
+pub const MAX_SUMMARY_BULLETS: usize = 3;
+
 /// A matchup between two champions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Matchup {
@@ -29,6 +134,46 @@ pub struct Matchup {
     pub role: String,
     pub versions: Vec<MatchupVersion>,
     pub current_version: u32,
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub last_reviewed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub review_count: u32,
+    /// Hidden from the matchup list unless asked for
+    #[serde(default)]
+    pub archived: bool,
+    /// Player the notes are for, `None` for my own
+    #[serde(default)]
+    pub player: Option<String>,
+    /// Set on read-only notes imported from someone else's package
+    #[serde(default)]
+    pub external: Option<ExternalSource>,
+    /// Self-grades from quizzes, oldest first
+    #[serde(default)]
+    pub quiz_results: Vec<QuizResult>,
+}
+
+/// A pre-game reminder, unchecked again before every game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub checked: bool,
+}
+
+/// A concrete thing to execute next time the matchup is played
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Goal {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+    pub created: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
 }
 
 impl Matchup {
@@ -43,6 +188,16 @@ impl Matchup {
             runes: Vec::new(),
             summoner_spells: Vec::new(),
             items: Vec::new(),
+            patch: None,
+            difficulty: None,
+            summary: Vec::new(),
+            rune_page: None,
+            comments: Vec::new(),
+            attachments: Vec::new(),
+            source_url: None,
+            author: None,
+            blue_side_notes: None,
+            red_side_notes: None,
         };
 
         Self {
@@ -52,7 +207,43 @@ impl Matchup {
             role,
             versions: vec![initial_version],
             current_version: 1,
+            goals: Vec::new(),
+            checklist: Vec::new(),
+            last_reviewed: None,
+            review_count: 0,
+            archived: false,
+            player: None,
+            external: None,
+            quiz_results: Vec::new(),
+        }
+    }
+
+    /// Fail for notes imported from a package, which only their author edits
+    ///
+    /// Goals, the checklist and comments stay mine to change.
+    pub fn ensure_editable(&self) -> Result<(), String> {
+        match &self.external {
+            Some(source) => Err(format!(
+                "Notes imported from {} can't be edited",
+                source.author
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Make a change, signing the versions it adds that have no author with `author`
+    ///
+    /// Commands make their edits through this, so every new version carries
+    /// my name however it was created.
+    pub fn edit_signed<T>(&mut self, author: Option<&str>, edit: impl FnOnce(&mut Self) -> T) -> T {
+        let existing = self.versions.len();
+        let result = edit(self);
+        for version in self.versions.iter_mut().skip(existing) {
+            if version.author.is_none() {
+                version.author = author.map(str::to_string);
+            }
         }
+        result
     }
 
     /// Add a new version with updated data
@@ -66,6 +257,24 @@ impl Matchup {
             runes: update.runes,
             summoner_spells: update.summoner_spells,
             items: update.items,
+            patch: update.patch,
+            difficulty: update.difficulty.map(|d| d.clamp(1, 10)),
+            summary: update
+                .summary
+                .into_iter()
+                .map(|b| b.trim().to_string())
+                .filter(|b| !b.is_empty())
+                .collect(),
+            rune_page: update.rune_page,
+            comments: Vec::new(),
+            attachments: Vec::new(),
+            source_url: update.source_url,
+            author: update
+                .author
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty()),
+            blue_side_notes: update.blue_side_notes.filter(|n| !n.trim().is_empty()),
+            red_side_notes: update.red_side_notes.filter(|n| !n.trim().is_empty()),
         };
 
         self.versions.push(new_version);
@@ -76,206 +285,4807 @@ impl Matchup {
     pub fn current(&self) -> Option<&MatchupVersion> {
         self.versions.get(self.current_version as usize - 1)
     }
-}
 
-/// Data for creating a new matchup
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NewMatchup {
-    pub my_champion: String,
-    pub enemy_champion: String,
-    pub role: String,
-}
+    /// Whether the current notes are more than `max_patches` behind `current_patch`
+    ///
+    /// Versions without a recorded patch are never considered stale.
+    pub fn is_stale(&self, current_patch: &str, max_patches: u32) -> bool {
+        self.current()
+            .and_then(|v| v.patch.as_deref())
+            .and_then(|patch| patches_between(patch, current_patch))
+            .map(|distance| distance > max_patches)
+            .unwrap_or(false)
+    }
 
-/// Data for updating a matchup (creates new version)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MatchupUpdate {
-    pub notes: String,
-    #[serde(default)]
-    pub tags: Vec<String>,
-    #[serde(default)]
-    pub runes: Vec<String>,
-    #[serde(default)]
-    pub summoner_spells: Vec<String>,
-    #[serde(default)]
-    pub items: Vec<String>,
-}
+    /// Whether the current notes are empty or shorter than `min_chars`
+    pub fn needs_notes(&self, min_chars: usize) -> bool {
+        self.current()
+            .map(|v| v.notes.trim().chars().count() < min_chars)
+            .unwrap_or(true)
+    }
 
-/// Filter options for querying matchups
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct MatchupFilter {
-    pub my_champion: Option<String>,
-    pub enemy_champion: Option<String>,
-    pub role: Option<String>,
-    pub tags: Option<Vec<String>>,
-    pub search: Option<String>,
-}
+    /// Confirm the current notes still hold on `patch` without creating a version
+    pub fn mark_still_valid(&mut self, patch: String) {
+        let index = self.current_version as usize - 1;
+        if let Some(version) = self.versions.get_mut(index) {
+            version.patch = Some(patch);
+        }
+    }
 
-impl Matchup {
-    /// Check if matchup matches the filter
-    pub fn matches_filter(&self, filter: &MatchupFilter) -> bool {
-        // Filter by my champion
-        if let Some(ref champ) = filter.my_champion {
-            if !self.my_champion.eq_ignore_ascii_case(champ) {
-                return false;
-            }
+    /// Append a line stamped with `at` to the current notes without creating a version
+    ///
+    /// Returns the line added, `None` when `text` is blank.
+    pub fn append_note<Tz: TimeZone>(&mut self, text: &str, at: DateTime<Tz>) -> Option<String>
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return None;
         }
+        let index = self.current_version as usize - 1;
+        let version = self.versions.get_mut(index)?;
 
-        // Filter by enemy champion
-        if let Some(ref champ) = filter.enemy_champion {
-            if !self.enemy_champion.eq_ignore_ascii_case(champ) {
-                return false;
-            }
+        let line = format!("- [{}] {}", at.format("%Y-%m-%d %H:%M"), text);
+        if !version.notes.is_empty() && !version.notes.ends_with('\n') {
+            version.notes.push('\n');
         }
+        version.notes.push_str(&line);
+        Some(line)
+    }
 
-        // Filter by role
-        if let Some(ref role) = filter.role {
-            if !self.role.eq_ignore_ascii_case(role) {
-                return false;
+    /// Add a version that keeps the current notes but takes runes and spells from a game
+    pub fn copy_loadout(&mut self, loadout: &GameLoadout) {
+        let mut update = self
+            .current()
+            .map(MatchupUpdate::from_version)
+            .unwrap_or_default();
+        update.runes = loadout.rune_names();
+        update.summoner_spells = loadout.summoner_spell_names();
+        update.rune_page = loadout.rune_page();
+        self.add_version(update);
+    }
+
+    /// Save a rune page, as a new version or by amending the current one
+    pub fn set_rune_page(&mut self, page: RunePage, amend: bool) {
+        let runes = page.rune_names();
+        if amend {
+            let index = self.current_version as usize - 1;
+            if let Some(version) = self.versions.get_mut(index) {
+                version.runes = runes;
+                version.rune_page = Some(page);
             }
+            return;
         }
 
-        // Filter by tags (must have all specified tags)
-        if let Some(ref filter_tags) = filter.tags {
-            if let Some(current) = self.current() {
-                for tag in filter_tags {
-                    if !current.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
-                        return false;
-                    }
-                }
-            } else {
-                return false;
-            }
+        let mut update = self
+            .current()
+            .map(MatchupUpdate::from_version)
+            .unwrap_or_default();
+        update.runes = runes;
+        update.rune_page = Some(page);
+        self.add_version(update);
+    }
+
+    /// Add an unchecked item to the pre-game checklist
+    pub fn add_checklist_item(&mut self, text: String) -> ChecklistItem {
+        let item = ChecklistItem {
+            id: Uuid::new_v4().to_string(),
+            text,
+            checked: false,
+        };
+
+        self.checklist.push(item.clone());
+        item
+    }
+
+    /// Flip an item's checked state
+    pub fn toggle_checklist_item(&mut self, item_id: &str) -> Option<&ChecklistItem> {
+        let item = self.checklist.iter_mut().find(|i| i.id == item_id)?;
+        item.checked = !item.checked;
+        Some(item)
+    }
+
+    /// Remove a checklist item, returning whether it existed
+    pub fn remove_checklist_item(&mut self, item_id: &str) -> bool {
+        let before = self.checklist.len();
+        self.checklist.retain(|i| i.id != item_id);
+        self.checklist.len() != before
+    }
+
+    /// Uncheck every item for a new game
+    pub fn reset_checklist(&mut self) {
+        for item in &mut self.checklist {
+            item.checked = false;
         }
+    }
 
-        // Search in notes and champion names
-        if let Some(ref search) = filter.search {
-            let search_lower = search.to_lowercase();
-            let my_champ_match = self.my_champion.to_lowercase().contains(&search_lower);
-            let enemy_champ_match = self.enemy_champion.to_lowercase().contains(&search_lower);
-            let notes_match = self
-                .current()
-                .map(|v| v.notes.to_lowercase().contains(&search_lower))
-                .unwrap_or(false);
+    /// Record that the notes were re-read
+    pub fn mark_reviewed(&mut self) {
+        self.last_reviewed = Some(Utc::now());
+        self.review_count += 1;
+    }
 
-            if !my_champ_match && !enemy_champ_match && !notes_match {
-                return false;
-            }
+    /// Last time the notes were either written or re-read
+    pub fn last_touched(&self) -> DateTime<Utc> {
+        let last_edit = self
+            .versions
+            .iter()
+            .map(|v| v.date)
+            .max()
+            .unwrap_or_else(Utc::now);
+
+        match self.last_reviewed {
+            Some(reviewed) if reviewed > last_edit => reviewed,
+            _ => last_edit,
         }
+    }
 
-        true
+    /// Whether `other` is the same lane seen from the enemy's side
+    ///
+    /// A matchup is never its own reverse, even in a mirror pairing.
+    pub fn is_reverse_of(&self, other: &Matchup) -> bool {
+        self.id != other.id
+            && self.my_champion.eq_ignore_ascii_case(&other.enemy_champion)
+            && self.enemy_champion.eq_ignore_ascii_case(&other.my_champion)
+            && self.role.eq_ignore_ascii_case(&other.role)
     }
-}
 
-/// A single match from game history
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Match {
-    pub id: String,
-    pub game_id: Option<String>,
-    pub date: DateTime<Utc>,
-    pub my_champion: String,
-    pub enemy_champion: String,
-    pub role: String,
-    pub result: MatchResult,
-    pub notes: String,
-    pub linked_matchup: Option<String>,
-}
+    /// Date of the most recent version
+    pub fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.versions.iter().map(|v| v.date).max()
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum MatchResult {
-    Win,
-    Loss,
-}
+    /// The games among `matches` explicitly linked to this matchup, newest first
+    pub fn linked_matches<'a>(&self, matches: impl IntoIterator<Item = &'a Match>) -> Vec<Match> {
+        let mut linked: Vec<Match> = matches
+            .into_iter()
+            .filter(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()))
+            .cloned()
+            .collect();
+        linked.sort_by(|a, b| b.date.cmp(&a.date));
+        linked
+    }
 
-impl Match {
-    pub fn new(
-        my_champion: String,
-        enemy_champion: String,
-        role: String,
-        result: MatchResult,
-        game_id: Option<String>,
-    ) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            game_id,
-            date: Utc::now(),
-            my_champion,
-            enemy_champion,
-            role,
-            result,
-            notes: String::new(),
-            linked_matchup: None,
+    /// Win/loss record from the related matches in `matches`
+    pub fn record<'a>(&self, matches: impl IntoIterator<Item = &'a Match>) -> MatchRecord {
+        let mut record = MatchRecord::default();
+        for m in matches.into_iter().filter(|m| self.is_related_match(m)) {
+            record.games += 1;
+            if m.result == MatchResult::Win {
+                record.wins += 1;
+            }
         }
+        record
     }
-}
 
-/// Update data for a match
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MatchUpdate {
-    pub notes: Option<String>,
-    pub linked_matchup: Option<String>,
-}
+    /// Stats over the matches explicitly linked to this matchup
+    pub fn stats(&self, matches: &[Match]) -> MatchupStats {
+        let mut linked: Vec<&Match> = matches
+            .iter()
+            .filter(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()))
+            .collect();
+        linked.sort_by(|a, b| b.date.cmp(&a.date));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let wins = linked
+            .iter()
+            .filter(|m| m.result == MatchResult::Win)
+            .count() as u32;
+        let record = MatchRecord {
+            games: linked.len() as u32,
+            wins,
+        };
+        let record_where = |include: &dyn Fn(&Match) -> bool| MatchRecord {
+            games: linked.iter().filter(|m| include(m)).count() as u32,
+            wins: linked
+                .iter()
+                .filter(|m| include(m) && m.result == MatchResult::Win)
+                .count() as u32,
+        };
+        let pick_order = |m: &Match| m.pick.as_ref().and_then(PickIntent::pick_order);
 
-    #[test]
-    fn test_create_matchup() {
-        let matchup = Matchup::new(
-            "Darius".to_string(),
-            "Garen".to_string(),
-            "top".to_string(),
-        );
+        MatchupStats {
+            games: record.games,
+            wins: record.wins,
+            losses: record.games - record.wins,
+            win_rate: record.win_rate(),
+            recent_form: linked
+                .iter()
+                .take(RECENT_FORM_GAMES)
+                .map(|m| m.result.clone())
+                .collect(),
+            community: None,
+            blue_side: record_where(&|m| m.side == Some(Side::Blue)),
+            red_side: record_where(&|m| m.side == Some(Side::Red)),
+            blind_pick: record_where(&|m| pick_order(m) == Some(PickOrder::Blind)),
+            counter_pick: record_where(&|m| pick_order(m) == Some(PickOrder::Counter)),
+        }
+    }
 
-        assert_eq!(matchup.my_champion, "Darius");
-        assert_eq!(matchup.enemy_champion, "Garen");
-        assert_eq!(matchup.role, "top");
-        assert_eq!(matchup.versions.len(), 1);
-        assert_eq!(matchup.current_version, 1);
+    /// Linked games oldest first, with the notes version I had when playing each
+    pub fn progress(&self, matches: &[Match]) -> Vec<ProgressPoint> {
+        let mut linked: Vec<&Match> = matches
+            .iter()
+            .filter(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()))
+            .collect();
+        linked.sort_by(|a, b| a.date.cmp(&b.date));
+
+        linked
+            .into_iter()
+            .map(|m| ProgressPoint {
+                match_id: m.id.clone(),
+                date: m.date,
+                result: m.result.clone(),
+                notes_version: self
+                    .versions
+                    .iter()
+                    .rev()
+                    .find(|v| v.date <= m.date)
+                    .map(|v| v.version),
+                cs_at_10: m.early.as_ref().map(|e| e.cs_at_10),
+                cs_diff_at_10: m.early.as_ref().and_then(|e| e.cs_diff_at_10),
+                deaths_before_10: m.early.as_ref().and_then(|e| e.deaths_before_10),
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_add_version() {
-        let mut matchup = Matchup::new(
-            "Darius".to_string(),
-            "Garen".to_string(),
-            "top".to_string(),
-        );
+    /// How linked games went on each notes version compared with the one before
+    ///
+    /// One entry per rewrite of the notes, oldest first; versions that only
+    /// changed tags, runes or items count towards the notes they kept. Games
+    /// played before the matchup was created count towards the first version.
+    pub fn note_impact(&self, matches: &[Match]) -> Vec<NoteImpact> {
+        let mut rewrites: Vec<&MatchupVersion> = Vec::new();
+        // Index into `rewrites` of each version's notes
+        let mut rewrite_of = Vec::with_capacity(self.versions.len());
+        for version in &self.versions {
+            if rewrites
+                .last()
+                .is_none_or(|last| last.notes != version.notes)
+            {
+                rewrites.push(version);
+            }
+            rewrite_of.push(rewrites.len() - 1);
+        }
 
-        matchup.add_version(MatchupUpdate {
-            notes: "Test notes".to_string(),
-            tags: vec!["easy".to_string()],
-            runes: vec![],
-            summoner_spells: vec![],
-            items: vec![],
-        });
+        // Games played on each rewrite
+        let mut records = vec![MatchRecord::default(); rewrites.len()];
+        for m in matches
+            .iter()
+            .filter(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()))
+        {
+            let version = self
+                .versions
+                .iter()
+                .rposition(|v| v.date <= m.date)
+                .unwrap_or(0);
+            let index = rewrite_of.get(version).copied().unwrap_or(0);
+            if let Some(record) = records.get_mut(index) {
+                record.games += 1;
+                if m.result == MatchResult::Win {
+                    record.wins += 1;
+                }
+            }
+        }
 
-        assert_eq!(matchup.versions.len(), 2);
-        assert_eq!(matchup.current_version, 2);
-        assert_eq!(matchup.current().unwrap().notes, "Test notes");
+        records
+            .windows(2)
+            .zip(rewrites.iter().skip(1))
+            .map(|(pair, version)| {
+                let (before, after) = (pair[0], pair[1]);
+                NoteImpact {
+                    version: version.version,
+                    date: version.date,
+                    before,
+                    after,
+                    win_rate_delta: after
+                        .win_rate()
+                        .zip(before.win_rate())
+                        .map(|(after, before)| after - before),
+                }
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_filter() {
-        let matchup = Matchup::new(
-            "Darius".to_string(),
-            "Garen".to_string(),
-            "top".to_string(),
-        );
+    /// Linked games with stats compared against my usual numbers on the champion
+    ///
+    /// The baseline is every other game on my champion, so the matchup's own
+    /// games don't pull it towards themselves.
+    pub fn benchmark(&self, matches: &[Match]) -> MatchupBenchmark {
+        let (linked, others): (Vec<&Match>, Vec<&Match>) = matches
+            .iter()
+            .filter(|m| m.my_champion.eq_ignore_ascii_case(&self.my_champion))
+            .partition(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()));
+        let baseline = PerformanceAverages::new(others.iter().filter_map(|m| m.stats.as_ref()));
 
-        let filter = MatchupFilter {
-            my_champion: Some("Darius".to_string()),
-            ..Default::default()
-        };
+        let mut games: Vec<GameBenchmark> = linked
+            .iter()
+            .filter_map(|m| {
+                let stats = m.stats.as_ref().filter(|s| s.game_duration > 0)?;
+                Some(GameBenchmark::new(m, stats, baseline.as_ref()))
+            })
+            .collect();
+        games.sort_by(|a, b| b.date.cmp(&a.date));
 
-        assert!(matchup.matches_filter(&filter));
+        MatchupBenchmark {
+            matchup: PerformanceAverages::new(linked.iter().filter_map(|m| m.stats.as_ref())),
+            baseline,
+            games,
+        }
+    }
 
-        let filter2 = MatchupFilter {
-            my_champion: Some("Garen".to_string()),
-            ..Default::default()
-        };
+    /// Whether a match from history was played in this matchup
+    pub fn is_related_match(&self, m: &Match) -> bool {
+        match m.linked_matchup {
+            Some(ref linked) => linked == &self.id,
+            None => {
+                m.my_champion.eq_ignore_ascii_case(&self.my_champion)
+                    && m.enemy_champion.eq_ignore_ascii_case(&self.enemy_champion)
+                    && m.role.eq_ignore_ascii_case(&self.role)
+            }
+        }
+    }
+
+    /// Add a new open goal
+    pub fn add_goal(&mut self, text: String) -> Goal {
+        let goal = Goal {
+            id: Uuid::new_v4().to_string(),
+            text,
+            done: false,
+            created: Utc::now(),
+            completed_at: None,
+        };
+
+        self.goals.push(goal.clone());
+        goal
+    }
+
+    /// Mark a goal as done (or reopen it)
+    pub fn set_goal_done(&mut self, goal_id: &str, done: bool) -> Option<&Goal> {
+        let goal = self.goals.iter_mut().find(|g| g.id == goal_id)?;
+
+        goal.done = done;
+        // Marking a done goal again keeps when it was first completed
+        if !done {
+            goal.completed_at = None;
+        } else if goal.completed_at.is_none() {
+            goal.completed_at = Some(Utc::now());
+        }
+
+        Some(goal)
+    }
+
+    /// Remove a goal, returning whether it existed
+    pub fn remove_goal(&mut self, goal_id: &str) -> bool {
+        let before = self.goals.len();
+        self.goals.retain(|g| g.id != goal_id);
+        self.goals.len() != before
+    }
+
+    /// Comment on a version, `None` if there's no such version
+    pub fn add_comment(&mut self, version: u32, author: String, text: String) -> Option<Comment> {
+        let version = self.versions.iter_mut().find(|v| v.version == version)?;
+        let comment = Comment {
+            id: Uuid::new_v4().to_string(),
+            author,
+            date: Utc::now(),
+            text,
+        };
+
+        version.comments.push(comment.clone());
+        Some(comment)
+    }
+
+    /// Remove a comment from whichever version has it, returning whether it existed
+    pub fn remove_comment(&mut self, comment_id: &str) -> bool {
+        self.versions.iter_mut().any(|v| {
+            let before = v.comments.len();
+            v.comments.retain(|c| c.id != comment_id);
+            v.comments.len() != before
+        })
+    }
+
+    /// Attachments of every version
+    pub fn attachments(&self) -> impl Iterator<Item = &Attachment> {
+        self.versions.iter().flat_map(|v| &v.attachments)
+    }
+
+    /// Take an attachment off whichever version has it
+    pub fn remove_attachment(&mut self, attachment_id: &str) -> Option<Attachment> {
+        self.versions.iter_mut().find_map(|v| {
+            let index = v.attachments.iter().position(|a| a.id == attachment_id)?;
+            Some(v.attachments.remove(index))
+        })
+    }
+
+    /// Apply a bulk operation, returning whether anything changed
+    ///
+    /// Tag changes add a version like any other edit, and leave imported notes
+    /// alone along with role changes. `Delete` is up to the caller.
+    pub fn apply_bulk(&mut self, operation: &BulkOperation) -> bool {
+        match operation {
+            BulkOperation::AddTag { .. }
+            | BulkOperation::RemoveTag { .. }
+            | BulkOperation::SetRole { .. }
+                if self.external.is_some() =>
+            {
+                false
+            }
+            BulkOperation::AddTag { tag } | BulkOperation::RemoveTag { tag } => {
+                let adding = matches!(operation, BulkOperation::AddTag { .. });
+                let mut update = self
+                    .current()
+                    .map(MatchupUpdate::from_version)
+                    .unwrap_or_default();
+                let has_tag = update.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+                if has_tag == adding {
+                    return false;
+                }
+                if adding {
+                    update.tags.push(tag.trim().to_string());
+                } else {
+                    update.tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+                }
+                self.add_version(update);
+                true
+            }
+            BulkOperation::SetRole { role } => {
+                let role = role.trim();
+                if self.role == role {
+                    return false;
+                }
+                self.role = role.to_string();
+                true
+            }
+            BulkOperation::Archive | BulkOperation::Unarchive => {
+                let archive = matches!(operation, BulkOperation::Archive);
+                let changed = self.archived != archive;
+                self.archived = archive;
+                changed
+            }
+            BulkOperation::Delete => false,
+        }
+    }
+}
+
+/// A change applied to many matchups at once
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    AddTag { tag: String },
+    RemoveTag { tag: String },
+    SetRole { role: String },
+    Archive,
+    Unarchive,
+    Delete,
+}
+
+impl BulkOperation {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            BulkOperation::AddTag { tag } | BulkOperation::RemoveTag { tag }
+                if tag.trim().is_empty() =>
+            {
+                Err("Tag cannot be empty".to_string())
+            }
+            BulkOperation::SetRole { role } if role.trim().is_empty() => {
+                Err("Role cannot be empty".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Patches per season, used to compare patches across season boundaries
+const PATCHES_PER_SEASON: u32 = 24;
+
+/// Parse a game version like "14.12" or "14.12.601.1234" into (season, patch)
+pub fn parse_patch(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let season = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    Some((season, patch))
+}
+
+/// Number of patches from `old` to `new` (0 if `new` is not newer)
+pub fn patches_between(old: &str, new: &str) -> Option<u32> {
+    let (old_season, old_patch) = parse_patch(old)?;
+    let (new_season, new_patch) = parse_patch(new)?;
+
+    let old_index = old_season * PATCHES_PER_SEASON + old_patch;
+    let new_index = new_season * PATCHES_PER_SEASON + new_patch;
+    Some(new_index.saturating_sub(old_index))
+}
+
+/// Data for creating a new matchup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMatchup {
+    pub my_champion: String,
+    pub enemy_champion: String,
+    /// Defaults to the main role set for my champion
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// Data for updating a matchup (creates new version)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MatchupUpdate {
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub runes: Vec<String>,
+    #[serde(default)]
+    pub summoner_spells: Vec<String>,
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub patch: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    #[serde(default)]
+    pub summary: Vec<String>,
+    #[serde(default)]
+    pub rune_page: Option<RunePage>,
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Defaults to my summoner name, also while coaching another player
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub blue_side_notes: Option<String>,
+    #[serde(default)]
+    pub red_side_notes: Option<String>,
+}
+
+impl MatchupUpdate {
+    /// An update that recreates an existing version's content for a new local version
+    ///
+    /// Where the content came from (guide URL and author) isn't carried over,
+    /// as the new version is my own edit.
+    pub fn from_version(version: &MatchupVersion) -> Self {
+        Self {
+            notes: version.notes.clone(),
+            tags: version.tags.clone(),
+            runes: version.runes.clone(),
+            summoner_spells: version.summoner_spells.clone(),
+            items: version.items.clone(),
+            patch: version.patch.clone(),
+            difficulty: version.difficulty,
+            summary: version.summary.clone(),
+            rune_page: version.rune_page.clone(),
+            source_url: None,
+            author: None,
+            blue_side_notes: version.blue_side_notes.clone(),
+            red_side_notes: version.red_side_notes.clone(),
+        }
+    }
+
+    /// Check user-provided fields before creating a version
+    pub fn validate(&self) -> Result<(), String> {
+        let bullets = self.summary.iter().filter(|b| !b.trim().is_empty()).count();
+        if bullets > MAX_SUMMARY_BULLETS {
+            return Err(format!(
+                "Summary is limited to {} bullets",
+                MAX_SUMMARY_BULLETS
+            ));
+        }
+        if let Some(ref page) = self.rune_page {
+            page.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Difficulty recorded on one version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DifficultyPoint {
+    pub version: u32,
+    pub date: DateTime<Utc>,
+    pub difficulty: u8,
+}
+
+/// Compact view of a matchup for the loading screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickReference {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub summary: Vec<String>,
+    pub runes: Vec<String>,
+    pub summoner_spells: Vec<String>,
+    /// Tips for the side I'm on, when it's known
+    #[serde(default)]
+    pub side_notes: Option<String>,
+}
+
+impl Matchup {
+    /// Build the quick reference from the current version
+    pub fn quick_reference(&self) -> QuickReference {
+        let current = self.current();
+        QuickReference {
+            matchup_id: self.id.clone(),
+            my_champion: self.my_champion.clone(),
+            enemy_champion: self.enemy_champion.clone(),
+            role: self.role.clone(),
+            summary: current.map(|v| v.summary.clone()).unwrap_or_default(),
+            runes: current.map(|v| v.runes.clone()).unwrap_or_default(),
+            summoner_spells: current
+                .map(|v| v.summoner_spells.clone())
+                .unwrap_or_default(),
+            side_notes: None,
+        }
+    }
+
+    /// The current runes, spells and items as a few lines of text for chat
+    ///
+    /// `item_name` turns stored items, usually numeric IDs, into names. `None` when
+    /// the current version has no build.
+    pub fn build_text(&self, item_name: impl Fn(&str) -> String) -> Option<String> {
+        let current = self.current()?;
+        let mut lines = Vec::new();
+        if !current.runes.is_empty() {
+            lines.push(format!("Runes: {}", current.runes.join(", ")));
+        }
+        if !current.summoner_spells.is_empty() {
+            lines.push(format!("Spells: {}", current.summoner_spells.join(" / ")));
+        }
+        if !current.items.is_empty() {
+            let items: Vec<String> = current.items.iter().map(|i| item_name(i)).collect();
+            lines.push(format!("Build: {}", items.join(" > ")));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+
+        let mut title = format!(
+            "{} vs {} ({})",
+            self.my_champion, self.enemy_champion, self.role
+        );
+        if let Some(ref patch) = current.patch {
+            title.push_str(&format!(" · {}", patch));
+        }
+        lines.insert(0, title);
+        Some(lines.join("\n"))
+    }
+
+    /// Difficulty over time, skipping versions that did not record one
+    pub fn difficulty_history(&self) -> Vec<DifficultyPoint> {
+        let mut points: Vec<DifficultyPoint> = self
+            .versions
+            .iter()
+            .filter_map(|v| {
+                v.difficulty.map(|difficulty| DifficultyPoint {
+                    version: v.version,
+                    date: v.date,
+                    difficulty,
+                })
+            })
+            .collect();
+
+        points.sort_by_key(|p| p.date);
+        points
+    }
+
+    /// Whether this matchup is for the given pairing and role
+    pub fn is_pairing(&self, my_champion: &str, enemy_champion: &str, role: &str) -> bool {
+        self.my_champion.eq_ignore_ascii_case(my_champion)
+            && self.enemy_champion.eq_ignore_ascii_case(enemy_champion)
+            && self.role.eq_ignore_ascii_case(role)
+    }
+}
+
+/// An existing tag offered for autocomplete
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagSuggestion {
+    pub tag: String,
+    pub count: u32,
+}
+
+/// Existing tags starting with `prefix`, most used first
+///
+/// Tags are grouped case-insensitively and counted across every version.
+pub fn suggest_tags<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    prefix: &str,
+) -> Vec<TagSuggestion> {
+    let prefix = prefix.trim().to_lowercase();
+    let mut counts: HashMap<String, TagSuggestion> = HashMap::new();
+
+    let tags = matchups
+        .into_iter()
+        .flat_map(|m| m.versions.iter())
+        .flat_map(|v| v.tags.iter());
+
+    for tag in tags {
+        let key = tag.trim().to_lowercase();
+        if key.is_empty() || !key.starts_with(&prefix) {
+            continue;
+        }
+        counts
+            .entry(key)
+            .or_insert_with(|| TagSuggestion {
+                tag: tag.trim().to_string(),
+                count: 0,
+            })
+            .count += 1;
+    }
+
+    let mut suggestions: Vec<TagSuggestion> = counts.into_values().collect();
+    suggestions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    suggestions
+}
+
+/// Games needed for the games component of the confidence score to max out
+const CONFIDENCE_GAMES: u32 = 10;
+/// Days after which a review no longer adds confidence
+const CONFIDENCE_REVIEW_DAYS: f64 = 60.0;
+
+impl Matchup {
+    /// How well I actually know this lane, from 0 to 100
+    ///
+    /// Combines games played (40), recent win rate (30) and how recently the
+    /// notes were written or reviewed (30).
+    pub fn confidence(&self, matches: &[Match], now: DateTime<Utc>) -> f64 {
+        let mut related: Vec<&Match> = matches
+            .iter()
+            .filter(|m| self.is_related_match(m))
+            .collect();
+        related.sort_by(|a, b| b.date.cmp(&a.date));
+        related.truncate(CONFIDENCE_GAMES as usize);
+
+        let record = self.record(related.iter().copied());
+        let games_score = 40.0 * record.games as f64 / CONFIDENCE_GAMES as f64;
+        let win_rate_score = 30.0 * record.win_rate().unwrap_or(0.0);
+
+        let days = (now - self.last_touched()).num_days().max(0) as f64;
+        let recency_score = 30.0 * (1.0 - days / CONFIDENCE_REVIEW_DAYS).max(0.0);
+
+        ((games_score + win_rate_score + recency_score) * 10.0).round() / 10.0
+    }
+
+    /// Linked games played since the notes were last written or reviewed
+    pub fn games_since_touched(&self, matches: &[Match]) -> u32 {
+        let touched = self.last_touched();
+        matches
+            .iter()
+            .filter(|m| m.linked_matchup.as_deref() == Some(self.id.as_str()))
+            .filter(|m| m.date > touched)
+            .count() as u32
+    }
+}
+
+/// A matchup in list results, with computed metrics for badges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupListItem {
+    #[serde(flatten)]
+    pub matchup: Matchup,
+    pub confidence: f64,
+    /// Linked games and wins, for list badges
+    pub record: MatchRecord,
+    /// Linked games since the notes were last written or reviewed
+    pub games_since_review: u32,
+}
+
+/// Results before and after a rewrite of a matchup's notes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NoteImpact {
+    /// The version written
+    pub version: u32,
+    pub date: DateTime<Utc>,
+    /// Linked games played on the previous version
+    pub before: MatchRecord,
+    /// Linked games played on this version, until the next rewrite
+    pub after: MatchRecord,
+    /// Change in win rate, `None` unless both sides have games
+    pub win_rate_delta: Option<f64>,
+}
+
+/// One linked game in a matchup's improvement timeline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgressPoint {
+    pub match_id: String,
+    pub date: DateTime<Utc>,
+    pub result: MatchResult,
+    /// Notes version that was current when the game was played
+    pub notes_version: Option<u32>,
+    pub cs_at_10: Option<u32>,
+    pub cs_diff_at_10: Option<i32>,
+    pub deaths_before_10: Option<u32>,
+}
+
+/// How many of the latest games make up a matchup's recent form
+pub const RECENT_FORM_GAMES: usize = 5;
+
+/// Results of the matches linked to a matchup
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchupStats {
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_rate: Option<f64>,
+    /// Latest results first
+    pub recent_form: Vec<MatchResult>,
+    /// Everyone's win rate in the pairing, when community stats are enabled
+    #[serde(default)]
+    pub community: Option<CommunityWinRate>,
+    /// Games with a known side only
+    #[serde(default)]
+    pub blue_side: MatchRecord,
+    #[serde(default)]
+    pub red_side: MatchRecord,
+    /// Games with a known pick order only
+    #[serde(default)]
+    pub blind_pick: MatchRecord,
+    #[serde(default)]
+    pub counter_pick: MatchRecord,
+}
+
+impl MatchupStats {
+    /// The compact games/wins pair shown in matchup lists
+    pub fn record(&self) -> MatchRecord {
+        MatchRecord {
+            games: self.games,
+            wins: self.wins,
+        }
+    }
+}
+
+/// Games and wins played in a matchup
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct MatchRecord {
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl MatchRecord {
+    /// Win rate in the 0.0..=1.0 range, `None` without games
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.games == 0 {
+            None
+        } else {
+            Some(self.wins as f64 / self.games as f64)
+        }
+    }
+}
+
+/// Sort orders for matchup lists
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchupSort {
+    #[default]
+    Alphabetical,
+    LastUpdated,
+    GamesPlayed,
+    WinRate,
+    Difficulty,
+}
+
+/// Default number of patches before notes are flagged as possibly stale
+pub const DEFAULT_STALE_PATCHES: u32 = 3;
+
+/// Default minimum note length before a matchup counts as written
+pub const DEFAULT_MIN_NOTES_LENGTH: usize = 20;
+
+/// Options for filtering possibly stale matchups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleFilter {
+    pub current_patch: String,
+    pub max_patches: Option<u32>,
+}
+
+/// Whose matchups or matches a filter covers
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerScope {
+    /// The active player's, or mine when I'm not coaching anyone
+    ///
+    /// Commands resolve it with `with_defaults`; left unresolved it covers mine.
+    #[default]
+    Active,
+    /// Mine, also while another player is active
+    Me,
+    /// The player with this ID
+    Player(String),
+}
+
+impl PlayerScope {
+    /// Whether something kept for `player` (`None` for me) is in scope
+    pub fn includes(&self, player: Option<&str>) -> bool {
+        match self {
+            Self::Active | Self::Me => player.is_none(),
+            Self::Player(id) => player == Some(id.as_str()),
+        }
+    }
+
+    /// `Active` replaced by whoever is active in `metadata`
+    pub fn resolve(self, metadata: &Metadata) -> Self {
+        match self {
+            Self::Active => match metadata.active_player {
+                Some(ref id) => Self::Player(id.clone()),
+                None => Self::Me,
+            },
+            scope => scope,
+        }
+    }
+}
+
+/// Filter options for querying matchups
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MatchupFilter {
+    pub my_champion: Option<String>,
+    pub enemy_champion: Option<String>,
+    pub role: Option<String>,
+    /// Any of these champions (OR), combined with `my_champion`
+    pub my_champions: Option<Vec<String>>,
+    /// Any of these enemies (OR), combined with `enemy_champion`
+    pub enemy_champions: Option<Vec<String>>,
+    /// Any of these roles (OR), combined with `role`
+    pub roles: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+    pub search: Option<String>,
+    /// Also search the notes of older versions
+    pub search_all_versions: Option<bool>,
+    pub stale: Option<StaleFilter>,
+    pub needs_notes: Option<bool>,
+    pub min_notes_length: Option<usize>,
+    pub sort_by: Option<MatchupSort>,
+    pub descending: Option<bool>,
+    pub min_confidence: Option<f64>,
+    pub max_confidence: Option<f64>,
+    /// Only archived (`true`) or only active (`false`) matchups, active ones when unset
+    pub archived: Option<bool>,
+    #[serde(default)]
+    pub player: PlayerScope,
+}
+
+impl MatchupFilter {
+    /// The filter with the active player filled in
+    pub fn with_defaults(mut self, metadata: &Metadata) -> Self {
+        self.player = self.player.resolve(metadata);
+        self
+    }
+
+    /// Check the computed confidence against the filter bounds
+    pub fn matches_confidence(&self, confidence: f64) -> bool {
+        self.min_confidence.is_none_or(|min| confidence >= min)
+            && self.max_confidence.is_none_or(|max| confidence <= max)
+    }
+}
+
+/// Check a field against a single value and/or a list of accepted values
+///
+/// Values within the field are OR-ed; no values means no restriction.
+fn field_matches(value: &str, single: &Option<String>, many: &Option<Vec<String>>) -> bool {
+    let mut accepted = single.iter().chain(many.iter().flatten()).peekable();
+    if accepted.peek().is_none() {
+        return true;
+    }
+    accepted.any(|v| value.eq_ignore_ascii_case(v))
+}
+
+/// Compare optional keys so that missing values always sort last
+fn cmp_missing_last<T: PartialOrd>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ord = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if descending {
+                ord.reverse()
+            } else {
+                ord
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Sort matchups in place, using `matches` for game-based orders
+pub fn sort_matchups(
+    matchups: &mut [Matchup],
+    sort: MatchupSort,
+    descending: bool,
+    matches: &[Match],
+) {
+    let records: HashMap<String, MatchRecord> = match sort {
+        // The linked games, as the records shown next to each matchup
+        MatchupSort::GamesPlayed | MatchupSort::WinRate => matchups
+            .iter()
+            .map(|m| (m.id.clone(), m.stats(matches).record()))
+            .collect(),
+        _ => HashMap::new(),
+    };
+
+    let alphabetical = |a: &Matchup, b: &Matchup| {
+        a.my_champion
+            .to_lowercase()
+            .cmp(&b.my_champion.to_lowercase())
+            .then_with(|| {
+                a.enemy_champion
+                    .to_lowercase()
+                    .cmp(&b.enemy_champion.to_lowercase())
+            })
+            .then_with(|| a.role.cmp(&b.role))
+    };
+
+    matchups.sort_by(|a, b| {
+        let primary = match sort {
+            MatchupSort::Alphabetical => {
+                let ord = alphabetical(a, b);
+                return if descending { ord.reverse() } else { ord };
+            }
+            MatchupSort::LastUpdated => {
+                cmp_missing_last(a.last_updated(), b.last_updated(), descending)
+            }
+            MatchupSort::GamesPlayed => {
+                let ord = records[&a.id].games.cmp(&records[&b.id].games);
+                if descending {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            }
+            MatchupSort::WinRate => cmp_missing_last(
+                records[&a.id].win_rate(),
+                records[&b.id].win_rate(),
+                descending,
+            ),
+            MatchupSort::Difficulty => cmp_missing_last(
+                a.current().and_then(|v| v.difficulty),
+                b.current().and_then(|v| v.difficulty),
+                descending,
+            ),
+        };
+
+        primary.then_with(|| alphabetical(a, b))
+    });
+}
+
+impl Matchup {
+    /// Check if matchup matches the filter
+    pub fn matches_filter(&self, filter: &MatchupFilter) -> bool {
+        if self.archived != filter.archived.unwrap_or(false)
+            || !filter.player.includes(self.player.as_deref())
+        {
+            return false;
+        }
+
+        // Filter by my champion(s)
+        if !field_matches(&self.my_champion, &filter.my_champion, &filter.my_champions) {
+            return false;
+        }
+
+        // Filter by enemy champion(s)
+        if !field_matches(
+            &self.enemy_champion,
+            &filter.enemy_champion,
+            &filter.enemy_champions,
+        ) {
+            return false;
+        }
+
+        // Filter by role(s)
+        if !field_matches(&self.role, &filter.role, &filter.roles) {
+            return false;
+        }
+
+        // Filter by tags (must have all specified tags)
+        if let Some(ref filter_tags) = filter.tags {
+            if let Some(current) = self.current() {
+                for tag in filter_tags {
+                    if !current.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                        return false;
+                    }
+                }
+            } else {
+                return false;
+            }
+        }
+
+        // Only possibly stale notes
+        if let Some(ref stale) = filter.stale {
+            let max_patches = stale.max_patches.unwrap_or(DEFAULT_STALE_PATCHES);
+            if !self.is_stale(&stale.current_patch, max_patches) {
+                return false;
+            }
+        }
+
+        // Only matchups with empty or near-empty notes (or only written ones)
+        if let Some(needs_notes) = filter.needs_notes {
+            let min_chars = filter.min_notes_length.unwrap_or(DEFAULT_MIN_NOTES_LENGTH);
+            if self.needs_notes(min_chars) != needs_notes {
+                return false;
+            }
+        }
+
+        // Search in notes and champion names
+        if let Some(ref search) = filter.search {
+            let search_lower = search.to_lowercase();
+            let my_champ_match = self.my_champion.to_lowercase().contains(&search_lower);
+            let enemy_champ_match = self.enemy_champion.to_lowercase().contains(&search_lower);
+            let notes_match = if filter.search_all_versions.unwrap_or(false) {
+                self.versions
+                    .iter()
+                    .any(|v| v.notes.to_lowercase().contains(&search_lower))
+            } else {
+                self.current()
+                    .map(|v| v.notes.to_lowercase().contains(&search_lower))
+                    .unwrap_or(false)
+            };
+
+            if !my_champ_match && !enemy_champ_match && !notes_match {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// A single match from game history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub id: String,
+    pub game_id: Option<String>,
+    pub date: DateTime<Utc>,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub result: MatchResult,
+    pub notes: String,
+    pub linked_matchup: Option<String>,
+    /// LCU queue ID (420 = ranked solo, 440 = ranked flex, ...)
+    #[serde(default)]
+    pub queue_id: Option<i32>,
+    #[serde(default)]
+    pub queue: Option<QueueType>,
+    /// Patch the game was played on, e.g. "14.3"
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// Performance numbers, only available for imported games
+    #[serde(default)]
+    pub stats: Option<MatchStats>,
+    /// Laning numbers from the game timeline
+    #[serde(default)]
+    pub early: Option<EarlyGameStats>,
+    /// Account the game was imported from
+    #[serde(default)]
+    pub account_puuid: Option<String>,
+    #[serde(default)]
+    pub review: Option<PostGameReview>,
+    /// The enemy laner was guessed and may need a manual fix
+    #[serde(default)]
+    pub opponent_uncertain: bool,
+    /// Riot ID of the enemy laner, for recognising repeat opponents
+    #[serde(default)]
+    pub enemy_riot_id: Option<String>,
+    /// `None` for hand-logged and older imports, treated as Summoner's Rift
+    #[serde(default)]
+    pub game_mode: Option<GameMode>,
+    /// Duo details for Arena games
+    #[serde(default)]
+    pub arena: Option<ArenaResult>,
+    /// Runes and summoner spells I took
+    #[serde(default)]
+    pub loadout: Option<GameLoadout>,
+    /// My rank in the game's queue when it was imported
+    #[serde(default)]
+    pub rank: Option<RankInfo>,
+    /// Multikills and team totals from the end of game screen
+    #[serde(default)]
+    pub end_of_game: Option<EndOfGameStats>,
+    /// What I hovered versus what I locked in champ select
+    #[serde(default)]
+    pub pick: Option<PickIntent>,
+    /// Player the game was logged for, `None` for my own
+    #[serde(default)]
+    pub player: Option<String>,
+    /// Recordings of the game
+    #[serde(default)]
+    pub vod_refs: Vec<VodRef>,
+    /// Map side I played on; `None` for hand-logged games and modes without sides
+    #[serde(default)]
+    pub side: Option<Side>,
+}
+
+/// A recording of a game, with the moments worth rewatching
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VodRef {
+    pub id: String,
+    pub url: String,
+    /// Sorted by time
+    #[serde(default)]
+    pub bookmarks: Vec<VodBookmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VodBookmark {
+    pub id: String,
+    /// Offset into the recording
+    pub seconds: u32,
+    pub label: String,
+}
+
+impl VodRef {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let url = url.trim();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err("VOD link must start with http:// or https://".to_string());
+        }
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            bookmarks: Vec::new(),
+        })
+    }
+
+    /// Link that starts playback `seconds` in
+    ///
+    /// YouTube and Twitch get their `t` parameter; anything else a media
+    /// fragment, which browsers honour for plain video files.
+    pub fn url_at(&self, seconds: u32) -> String {
+        let url = self.url.split('#').next().unwrap_or_default();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        if url.contains("youtube.com/") || url.contains("youtu.be/") {
+            format!("{}{}t={}", url, separator, seconds)
+        } else if url.contains("twitch.tv/") {
+            format!(
+                "{}{}t={}h{}m{}s",
+                url,
+                separator,
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60
+            )
+        } else {
+            format!("{}#t={}", url, seconds)
+        }
+    }
+}
+
+/// My pick in champ select, recorded by the client monitor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PickIntent {
+    /// First champion I hovered, `None` if I never hovered before locking
+    pub intended: Option<String>,
+    pub locked: String,
+    /// The enemy laner was picked after I locked in
+    pub counter_picked: bool,
+    /// Whether I locked before or after the enemy laner, when known
+    #[serde(default)]
+    pub order: Option<PickOrder>,
+}
+
+/// When I locked in relative to the enemy laner
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PickOrder {
+    /// Before the enemy laner, not knowing the matchup
+    Blind,
+    /// After the enemy laner, into a known matchup
+    Counter,
+}
+
+impl PickIntent {
+    /// My pick order, also for picks recorded before the order was
+    pub fn pick_order(&self) -> Option<PickOrder> {
+        self.order
+            .or(self.counter_picked.then_some(PickOrder::Blind))
+    }
+
+    /// I locked something other than what I first hovered
+    pub fn changed(&self) -> bool {
+        self.intended
+            .as_deref()
+            .is_some_and(|intended| !intended.eq_ignore_ascii_case(&self.locked))
+    }
+}
+
+/// Runes and summoner spells taken in a game, as client IDs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct GameLoadout {
+    pub primary_tree: Option<i32>,
+    pub secondary_tree: Option<i32>,
+    /// Keystone first, then the other runes in order
+    pub runes: Vec<i32>,
+    pub summoner_spells: Vec<i32>,
+}
+
+impl GameLoadout {
+    /// Keystone followed by the primary and secondary tree, by name
+    pub fn rune_names(&self) -> Vec<String> {
+        self.runes
+            .first()
+            .into_iter()
+            .chain(self.primary_tree.as_ref())
+            .chain(self.secondary_tree.as_ref())
+            .map(|id| runes::rune_name(*id))
+            .collect()
+    }
+
+    /// The full page, if the game reported every rune
+    pub fn rune_page(&self) -> Option<RunePage> {
+        let page = RunePage {
+            primary_style_id: self.primary_tree?,
+            sub_style_id: self.secondary_tree?,
+            selected_perk_ids: self.runes.clone(),
+        };
+        page.validate().ok().map(|_| page)
+    }
+
+    /// Summoner spells by name
+    pub fn summoner_spell_names(&self) -> Vec<String> {
+        self.summoner_spells
+            .iter()
+            .map(|id| runes::summoner_spell_name(*id))
+            .collect()
+    }
+}
+
+/// Structured post-game journal entry for a match
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PostGameReview {
+    pub went_well: String,
+    pub mistake: String,
+    pub try_next: String,
+    pub reviewed_at: DateTime<Utc>,
+}
+
+/// Answers to the post-game prompts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPostGameReview {
+    #[serde(default)]
+    pub went_well: String,
+    #[serde(default)]
+    pub mistake: String,
+    #[serde(default)]
+    pub try_next: String,
+}
+
+impl NewPostGameReview {
+    /// Reject a review with every prompt left blank
+    pub fn validate(&self) -> Result<(), String> {
+        if [&self.went_well, &self.mistake, &self.try_next]
+            .iter()
+            .all(|answer| answer.trim().is_empty())
+        {
+            return Err("Review needs at least one answer".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Laning snapshot at 10 minutes, diffs are mine minus my lane opponent's
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct EarlyGameStats {
+    pub cs_at_10: u32,
+    pub gold_at_10: u32,
+    pub cs_diff_at_10: Option<i32>,
+    pub gold_diff_at_10: Option<i32>,
+    #[serde(default)]
+    pub deaths_before_10: Option<u32>,
+    /// Game time of my first death; `None` if I never died or for older imports
+    #[serde(default)]
+    pub first_death_seconds: Option<u32>,
+}
+
+/// Gold lead at 10 minutes that counts as winning or losing lane
+pub const LANE_LEAD_GOLD: i32 = 500;
+
+/// CS lead used instead when the gold diff is missing
+const LANE_LEAD_CS: i32 = 10;
+
+/// How lane went by 10 minutes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LaneState {
+    Ahead,
+    Even,
+    Behind,
+}
+
+impl EarlyGameStats {
+    /// From the gold diff, else the CS diff; `None` without my lane opponent's numbers
+    pub fn lane_state(&self) -> Option<LaneState> {
+        let (diff, lead) = match (self.gold_diff_at_10, self.cs_diff_at_10) {
+            (Some(gold), _) => (gold, LANE_LEAD_GOLD),
+            (None, Some(cs)) => (cs, LANE_LEAD_CS),
+            (None, None) => return None,
+        };
+        Some(if diff >= lead {
+            LaneState::Ahead
+        } else if diff <= -lead {
+            LaneState::Behind
+        } else {
+            LaneState::Even
+        })
+    }
+}
+
+/// How a matchup's early game relates to its results
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EarlyGameImpact {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    /// Linked games with timeline numbers
+    pub games: u32,
+    pub ahead: MatchRecord,
+    pub even: MatchRecord,
+    pub behind: MatchRecord,
+    /// Games where I died before 10 minutes
+    pub died_early: MatchRecord,
+    pub no_early_death: MatchRecord,
+    pub average_cs_diff_at_10: Option<f64>,
+    /// Over the games where I died
+    pub average_first_death_seconds: Option<f64>,
+    /// Losses after falling behind in lane
+    pub lost_in_lane: u32,
+    /// Losses despite being even or ahead at 10 minutes
+    pub lost_later: u32,
+}
+
+/// Early game breakdown of every matchup with timeline numbers, most lane losses first
+pub fn early_game_impact<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    matches: &[Match],
+) -> Vec<EarlyGameImpact> {
+    let mut impacts: Vec<EarlyGameImpact> = matchups
+        .into_iter()
+        .filter_map(|matchup| {
+            let mut impact = EarlyGameImpact {
+                matchup_id: matchup.id.clone(),
+                my_champion: matchup.my_champion.clone(),
+                enemy_champion: matchup.enemy_champion.clone(),
+                role: matchup.role.clone(),
+                games: 0,
+                ahead: MatchRecord::default(),
+                even: MatchRecord::default(),
+                behind: MatchRecord::default(),
+                died_early: MatchRecord::default(),
+                no_early_death: MatchRecord::default(),
+                average_cs_diff_at_10: None,
+                average_first_death_seconds: None,
+                lost_in_lane: 0,
+                lost_later: 0,
+            };
+            let mut cs_diffs = Vec::new();
+            let mut first_deaths = Vec::new();
+
+            for m in matches
+                .iter()
+                .filter(|m| m.linked_matchup.as_deref() == Some(matchup.id.as_str()))
+            {
+                let Some(early) = &m.early else {
+                    continue;
+                };
+                let won = m.result == MatchResult::Win;
+                impact.games += 1;
+                cs_diffs.extend(early.cs_diff_at_10.map(f64::from));
+                first_deaths.extend(early.first_death_seconds.map(f64::from));
+
+                let mut records = Vec::new();
+                match early.lane_state() {
+                    Some(LaneState::Ahead) => records.push(&mut impact.ahead),
+                    Some(LaneState::Even) => records.push(&mut impact.even),
+                    Some(LaneState::Behind) => records.push(&mut impact.behind),
+                    None => {}
+                }
+                match early.deaths_before_10 {
+                    Some(0) => records.push(&mut impact.no_early_death),
+                    Some(_) => records.push(&mut impact.died_early),
+                    None => {}
+                }
+                for record in records {
+                    record.games += 1;
+                    record.wins += won as u32;
+                }
+
+                if !won {
+                    match early.lane_state() {
+                        Some(LaneState::Behind) => impact.lost_in_lane += 1,
+                        Some(_) => impact.lost_later += 1,
+                        None => {}
+                    }
+                }
+            }
+
+            impact.average_cs_diff_at_10 = average(&cs_diffs);
+            impact.average_first_death_seconds = average(&first_deaths);
+            (impact.games > 0).then_some(impact)
+        })
+        .collect();
+
+    impacts.sort_by(|a, b| {
+        b.lost_in_lane
+            .cmp(&a.lost_in_lane)
+            .then(b.games.cmp(&a.games))
+            .then_with(|| a.my_champion.cmp(&b.my_champion))
+    });
+    impacts
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Extra numbers from the end of game screen
+///
+/// The full payload, badges included, is kept with the cached raw game.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct EndOfGameStats {
+    pub largest_multi_kill: u32,
+    pub largest_killing_spree: u32,
+    pub double_kills: u32,
+    pub triple_kills: u32,
+    pub quadra_kills: u32,
+    pub penta_kills: u32,
+    pub my_team: TeamTotals,
+    pub enemy_team: TeamTotals,
+}
+
+/// Summed stats of one team's players
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct TeamTotals {
+    pub kills: u32,
+    pub gold_earned: u32,
+    pub turrets_killed: u32,
+}
+
+/// My end-of-game numbers for a match
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MatchStats {
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    /// Lane minions plus neutral monsters
+    pub cs: u32,
+    pub gold_earned: u32,
+    pub damage_to_champions: u32,
+    pub vision_score: u32,
+    /// Game length in seconds
+    pub game_duration: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchResult {
+    Win,
+    Loss,
+}
+
+/// Game mode, which decides whether a game has a lane opponent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    /// Summoner's Rift
+    Classic,
+    Aram,
+    Arena,
+    Other,
+}
+
+impl GameMode {
+    /// Map the client's `gameMode` string
+    pub fn from_lcu(mode: &str) -> Self {
+        match mode {
+            "CLASSIC" => GameMode::Classic,
+            "ARAM" => GameMode::Aram,
+            "CHERRY" => GameMode::Arena,
+            _ => GameMode::Other,
+        }
+    }
+}
+
+/// Arena duos from my point of view
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ArenaResult {
+    pub partner: Option<String>,
+    /// 1 for first place
+    pub placement: Option<u32>,
+    /// Champion pairs of the other duos
+    pub opponents: Vec<Vec<String>>,
+}
+
+/// Ranked tier, ordered from lowest to highest
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Tier {
+    Iron,
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+    Emerald,
+    Diamond,
+    Master,
+    Grandmaster,
+    Challenger,
+}
+
+/// My rank in one ranked queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RankInfo {
+    pub tier: Tier,
+    /// "I" to "IV", empty for apex tiers
+    pub division: String,
+    pub league_points: i32,
+}
+
+/// Kind of queue a game was played in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueType {
+    RankedSolo,
+    RankedFlex,
+    Normal,
+    Aram,
+    Clash,
+    Other,
+}
+
+impl QueueType {
+    /// Classify an LCU queue ID
+    pub fn from_queue_id(queue_id: i32) -> Self {
+        match queue_id {
+            420 => QueueType::RankedSolo,
+            440 => QueueType::RankedFlex,
+            400 | 430 | 490 => QueueType::Normal,
+            450 => QueueType::Aram,
+            700 | 720 => QueueType::Clash,
+            _ => QueueType::Other,
+        }
+    }
+}
+
+impl Match {
+    pub fn new(
+        my_champion: String,
+        enemy_champion: String,
+        role: String,
+        result: MatchResult,
+        game_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            game_id,
+            date: Utc::now(),
+            my_champion,
+            enemy_champion,
+            role,
+            result,
+            notes: String::new(),
+            linked_matchup: None,
+            queue_id: None,
+            queue: None,
+            patch: None,
+            stats: None,
+            early: None,
+            account_puuid: None,
+            review: None,
+            opponent_uncertain: false,
+            enemy_riot_id: None,
+            game_mode: None,
+            arena: None,
+            loadout: None,
+            rank: None,
+            end_of_game: None,
+            pick: None,
+            player: None,
+            vod_refs: Vec::new(),
+            side: None,
+        }
+    }
+
+    /// Take the game data of a fresh import of the same game
+    ///
+    /// My notes, review, recordings and champ select pick are kept, and so are
+    /// timeline, end of game and rank numbers the new import couldn't get. A
+    /// matchup I linked by hand stays linked; one the import picked out of
+    /// `matchups` is unlinked when the pairing changed, and `true` returned so
+    /// the caller links the game again.
+    pub fn merge_imported<'a>(
+        &mut self,
+        fresh: Match,
+        matchups: impl IntoIterator<Item = &'a Matchup>,
+    ) -> bool {
+        let linked_by_import = self.linked_matchup.as_deref()
+            == find_matchup_for(matchups, self).map(|m| m.id.as_str());
+        let pairing_changed = !self.my_champion.eq_ignore_ascii_case(&fresh.my_champion)
+            || !self
+                .enemy_champion
+                .eq_ignore_ascii_case(&fresh.enemy_champion)
+            || !self.role.eq_ignore_ascii_case(&fresh.role);
+
+        self.date = fresh.date;
+        self.my_champion = fresh.my_champion;
+        self.enemy_champion = fresh.enemy_champion;
+        self.role = fresh.role;
+        self.result = fresh.result;
+        self.queue_id = fresh.queue_id;
+        self.queue = fresh.queue;
+        self.patch = fresh.patch;
+        self.stats = fresh.stats;
+        self.account_puuid = fresh.account_puuid.or(self.account_puuid.take());
+        self.opponent_uncertain = fresh.opponent_uncertain;
+        self.enemy_riot_id = fresh.enemy_riot_id;
+        self.game_mode = fresh.game_mode;
+        self.arena = fresh.arena;
+        self.loadout = fresh.loadout;
+        self.side = fresh.side.or(self.side);
+        self.early = fresh.early.or(self.early.take());
+        self.end_of_game = fresh.end_of_game.or(self.end_of_game.take());
+        self.rank = self.rank.take().or(fresh.rank);
+
+        let relink = pairing_changed && linked_by_import;
+        if relink {
+            self.linked_matchup = None;
+        }
+        relink
+    }
+
+    /// Bookmark a moment of one of the recordings, `None` if there's no such recording
+    pub fn add_vod_bookmark(
+        &mut self,
+        vod_id: &str,
+        seconds: u32,
+        label: String,
+    ) -> Option<VodBookmark> {
+        let vod = self.vod_refs.iter_mut().find(|v| v.id == vod_id)?;
+        let bookmark = VodBookmark {
+            id: Uuid::new_v4().to_string(),
+            seconds,
+            label,
+        };
+
+        let index = vod.bookmarks.partition_point(|b| b.seconds <= seconds);
+        vod.bookmarks.insert(index, bookmark.clone());
+        Some(bookmark)
+    }
+
+    /// Remove a bookmark from whichever recording has it, returning whether it existed
+    pub fn remove_vod_bookmark(&mut self, bookmark_id: &str) -> bool {
+        self.vod_refs.iter_mut().any(|v| {
+            let before = v.bookmarks.len();
+            v.bookmarks.retain(|b| b.id != bookmark_id);
+            v.bookmarks.len() != before
+        })
+    }
+
+    /// Game mode, assuming Summoner's Rift when unknown
+    pub fn mode(&self) -> GameMode {
+        self.game_mode.unwrap_or(GameMode::Classic)
+    }
+
+    /// Whether the game was played on the account with `puuid`
+    ///
+    /// Games logged by hand or imported before accounts were tracked belong
+    /// to any account.
+    pub fn is_from_account(&self, puuid: &str) -> bool {
+        self.account_puuid.as_ref().is_none_or(|p| p == puuid)
+    }
+
+    /// Whether the game had a lane opponent and can belong to a matchup
+    pub fn has_lane_opponent(&self) -> bool {
+        self.mode() == GameMode::Classic
+    }
+
+    /// Queue type, falling back to the raw queue ID for older imports
+    pub fn queue_type(&self) -> Option<QueueType> {
+        self.queue
+            .or_else(|| self.queue_id.map(QueueType::from_queue_id))
+    }
+
+    /// Check if the match matches the filter (ignores limit/offset)
+    pub fn matches_filter(&self, filter: &MatchFilter) -> bool {
+        if let Some(ref champ) = filter.my_champion {
+            if !self.my_champion.eq_ignore_ascii_case(champ) {
+                return false;
+            }
+        }
+
+        if let Some(ref champ) = filter.enemy_champion {
+            if !self.enemy_champion.eq_ignore_ascii_case(champ) {
+                return false;
+            }
+        }
+
+        if let Some(ref role) = filter.role {
+            if !self.role.eq_ignore_ascii_case(role) {
+                return false;
+            }
+        }
+
+        if let Some(ref result) = filter.result {
+            if &self.result != result {
+                return false;
+            }
+        }
+
+        if let Some(queue_id) = filter.queue_id {
+            if self.queue_id != Some(queue_id) {
+                return false;
+            }
+        }
+
+        if let Some(queue) = filter.queue {
+            if self.queue_type() != Some(queue) {
+                return false;
+            }
+        }
+
+        if let Some(mode) = filter.game_mode {
+            if self.mode() != mode {
+                return false;
+            }
+        }
+
+        if let Some(side) = filter.side {
+            if self.side != Some(side) {
+                return false;
+            }
+        }
+
+        if let Some(uncertain) = filter.opponent_uncertain {
+            if self.opponent_uncertain != uncertain {
+                return false;
+            }
+        }
+
+        if let Some(min_tier) = filter.min_tier {
+            if self.rank.as_ref().is_none_or(|r| r.tier < min_tier) {
+                return false;
+            }
+        }
+
+        if let Some(order) = filter.pick_order {
+            if self.pick.as_ref().and_then(PickIntent::pick_order) != Some(order) {
+                return false;
+            }
+        }
+
+        if let Some(counter_picked) = filter.counter_picked {
+            if self.pick.as_ref().map(|p| p.counter_picked) != Some(counter_picked) {
+                return false;
+            }
+        }
+
+        if let Some(off_intent) = filter.off_intent {
+            if self.pick.as_ref().map(PickIntent::changed) != Some(off_intent) {
+                return false;
+            }
+        }
+
+        if let Some(ref account) = filter.account {
+            if !self.is_from_account(account) {
+                return false;
+            }
+        }
+
+        if !filter.player.includes(self.player.as_deref()) {
+            return false;
+        }
+
+        if let Some(reviewed) = filter.reviewed {
+            if self.review.is_some() != reviewed {
+                return false;
+            }
+        }
+
+        if let Some(ref linked) = filter.linked_matchup {
+            if self.linked_matchup.as_ref() != Some(linked) {
+                return false;
+            }
+        }
+
+        if let Some(from) = filter.from {
+            if self.date < from {
+                return false;
+            }
+        }
+
+        if let Some(to) = filter.to {
+            if self.date > to {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Filter and paging options for querying match history
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MatchFilter {
+    pub my_champion: Option<String>,
+    pub enemy_champion: Option<String>,
+    pub role: Option<String>,
+    pub result: Option<MatchResult>,
+    pub queue_id: Option<i32>,
+    pub queue: Option<QueueType>,
+    pub game_mode: Option<GameMode>,
+    pub side: Option<Side>,
+    pub linked_matchup: Option<String>,
+    /// Only matches with (or without) a post-game review
+    pub reviewed: Option<bool>,
+    /// Only matches whose lane opponent was (or wasn't) a guess
+    pub opponent_uncertain: Option<bool>,
+    /// Only matches played at this tier or above
+    pub min_tier: Option<Tier>,
+    /// Only matches where I was (or wasn't) counter-picked; needs recorded pick intent
+    pub counter_picked: Option<bool>,
+    /// Only matches where I locked something other than my first hover; needs recorded pick intent
+    pub off_intent: Option<bool>,
+    /// Only blind picks or counter picks; needs recorded pick intent
+    pub pick_order: Option<PickOrder>,
+    /// Only matches imported from this account (PUUID) or with no account;
+    /// defaults to the active account
+    pub account: Option<String>,
+    #[serde(default)]
+    pub player: PlayerScope,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl MatchFilter {
+    /// The filter with the active account and player filled in where it
+    /// leaves them open
+    pub fn with_defaults(mut self, metadata: &Metadata) -> Self {
+        if self.account.is_none() {
+            self.account = metadata.active_account.clone();
+        }
+        self.player = self.player.resolve(metadata);
+        self
+    }
+}
+
+/// Filter matches, newest first, applying the filter's offset and limit
+pub fn filter_matches<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    filter: &MatchFilter,
+) -> Vec<Match> {
+    let mut filtered: Vec<&Match> = matches
+        .into_iter()
+        .filter(|m| m.matches_filter(filter))
+        .collect();
+    filtered.sort_by(|a, b| b.date.cmp(&a.date));
+
+    filtered
+        .into_iter()
+        .skip(filter.offset.unwrap_or(0))
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect()
+}
+
+/// Data for logging a match by hand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMatch {
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub result: MatchResult,
+    pub date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notes: String,
+    pub linked_matchup: Option<String>,
+}
+
+/// Games and wins on a single patch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PatchStats {
+    pub patch: String,
+    pub games: u32,
+    pub wins: u32,
+    pub win_rate: Option<f64>,
+}
+
+/// Group matches by patch, oldest patch first; matches without a patch are skipped
+pub fn stats_by_patch<'a>(matches: impl IntoIterator<Item = &'a Match>) -> Vec<PatchStats> {
+    let mut records: HashMap<String, MatchRecord> = HashMap::new();
+    for m in matches {
+        if let Some(ref patch) = m.patch {
+            let record = records.entry(patch.clone()).or_default();
+            record.games += 1;
+            if m.result == MatchResult::Win {
+                record.wins += 1;
+            }
+        }
+    }
+
+    let mut stats: Vec<PatchStats> = records
+        .into_iter()
+        .map(|(patch, record)| PatchStats {
+            patch,
+            games: record.games,
+            wins: record.wins,
+            win_rate: record.win_rate(),
+        })
+        .collect();
+    stats.sort_by_key(|s| parse_patch(&s.patch));
+    stats
+}
+
+/// Games ending before this many seconds are taken for remakes
+const REMAKE_SECONDS: u32 = 5 * 60;
+
+/// Game length ranges for closing-out versus scaling analysis
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GameLength {
+    /// Under 20 minutes
+    Short,
+    /// 20 to 30 minutes
+    Medium,
+    /// 30 minutes or more
+    Long,
+}
+
+impl GameLength {
+    pub const ALL: [GameLength; 3] = [GameLength::Short, GameLength::Medium, GameLength::Long];
+
+    /// The range a game of `seconds` falls in
+    pub fn of(seconds: u32) -> Self {
+        match seconds / 60 {
+            0..20 => GameLength::Short,
+            20..30 => GameLength::Medium,
+            _ => GameLength::Long,
+        }
+    }
+}
+
+/// Games and wins in one game length range
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LengthStats {
+    pub length: GameLength,
+    pub games: u32,
+    pub wins: u32,
+    pub win_rate: Option<f64>,
+}
+
+/// Game length breakdown of one matchup's linked games
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchupLengthStats {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub lengths: Vec<LengthStats>,
+}
+
+/// Results per game length, shortest first; remakes and games without a
+/// duration are skipped
+pub fn stats_by_length<'a>(matches: impl IntoIterator<Item = &'a Match>) -> Vec<LengthStats> {
+    let mut records: HashMap<GameLength, MatchRecord> = HashMap::new();
+    for m in matches {
+        let Some(duration) = m
+            .stats
+            .as_ref()
+            .map(|s| s.game_duration)
+            .filter(|d| *d >= REMAKE_SECONDS)
+        else {
+            continue;
+        };
+        let record = records.entry(GameLength::of(duration)).or_default();
+        record.games += 1;
+        if m.result == MatchResult::Win {
+            record.wins += 1;
+        }
+    }
+
+    GameLength::ALL
+        .into_iter()
+        .map(|length| {
+            let record = records.get(&length).copied().unwrap_or_default();
+            LengthStats {
+                length,
+                games: record.games,
+                wins: record.wins,
+                win_rate: record.win_rate(),
+            }
+        })
+        .collect()
+}
+
+/// Game length breakdown per matchup, of the matches linked to one of `matchups`
+///
+/// Matchups without a timed game are left out; the most played come first.
+pub fn length_stats_by_matchup<'a>(
+    matchups: &HashMap<String, Matchup>,
+    matches: impl IntoIterator<Item = &'a Match>,
+) -> Vec<MatchupLengthStats> {
+    let mut linked: HashMap<&str, Vec<&Match>> = HashMap::new();
+    for m in matches {
+        if let Some(id) = m.linked_matchup.as_deref() {
+            linked.entry(id).or_default().push(m);
+        }
+    }
+
+    let mut stats: Vec<MatchupLengthStats> = linked
+        .into_iter()
+        .filter_map(|(id, games)| {
+            let matchup = matchups.get(id)?;
+            let lengths = stats_by_length(games);
+            lengths
+                .iter()
+                .any(|l| l.games > 0)
+                .then(|| MatchupLengthStats {
+                    matchup_id: matchup.id.clone(),
+                    my_champion: matchup.my_champion.clone(),
+                    enemy_champion: matchup.enemy_champion.clone(),
+                    role: matchup.role.clone(),
+                    lengths,
+                })
+        })
+        .collect();
+    let games = |s: &MatchupLengthStats| s.lengths.iter().map(|l| l.games).sum::<u32>();
+    stats.sort_by(|a, b| {
+        games(b)
+            .cmp(&games(a))
+            .then_with(|| a.enemy_champion.cmp(&b.enemy_champion))
+    });
+    stats
+}
+
+/// Length of the periods a win rate time series is split into
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    /// Weeks starting on Monday
+    #[default]
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// Start of the period containing `date`, at midnight UTC
+    fn period_start(self, date: DateTime<Utc>) -> DateTime<Utc> {
+        let day = date.date_naive();
+        let start = match self {
+            Granularity::Week => {
+                day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64)
+            }
+            Granularity::Month => day.with_day(1).unwrap_or(day),
+        };
+        start.and_time(NaiveTime::MIN).and_utc()
+    }
+}
+
+/// Games and wins in one period of a time series
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WinRatePoint {
+    /// Start of the period, at midnight UTC
+    pub start: DateTime<Utc>,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub win_rate: Option<f64>,
+}
+
+/// Win rate per week or month, oldest first; periods without games are skipped
+pub fn winrate_timeseries<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    granularity: Granularity,
+) -> Vec<WinRatePoint> {
+    let mut records: HashMap<DateTime<Utc>, MatchRecord> = HashMap::new();
+    for m in matches {
+        let record = records.entry(granularity.period_start(m.date)).or_default();
+        record.games += 1;
+        if m.result == MatchResult::Win {
+            record.wins += 1;
+        }
+    }
+
+    let mut points: Vec<WinRatePoint> = records
+        .into_iter()
+        .map(|(start, record)| WinRatePoint {
+            start,
+            record,
+            win_rate: record.win_rate(),
+        })
+        .collect();
+    points.sort_by_key(|p| p.start);
+    points
+}
+
+/// Games and wins under one role or champion, with its share of the games
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShareStats {
+    pub name: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub win_rate: Option<f64>,
+    /// 0.0..=1.0 of the games counted
+    pub share: f64,
+}
+
+/// Role names used by matchups and imported games
+pub const ROLES: [&str; 5] = ["top", "jungle", "mid", "adc", "support"];
+
+/// The roles I play a champion in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChampionRoles {
+    pub primary: String,
+    #[serde(default)]
+    pub secondary: Vec<String>,
+}
+
+impl ChampionRoles {
+    /// Check the roles are known, lowercasing them and dropping repeats
+    pub fn normalize(mut self) -> Result<Self, String> {
+        let known = |role: &str| {
+            let role = role.trim().to_lowercase();
+            if ROLES.contains(&role.as_str()) {
+                Ok(role)
+            } else {
+                Err(format!("Unknown role '{}'", role))
+            }
+        };
+        self.primary = known(&self.primary)?;
+        let mut secondary = Vec::new();
+        for role in &self.secondary {
+            let role = known(role)?;
+            if role != self.primary && !secondary.contains(&role) {
+                secondary.push(role);
+            }
+        }
+        self.secondary = secondary;
+        Ok(self)
+    }
+
+    pub fn plays(&self, role: &str) -> bool {
+        self.primary.eq_ignore_ascii_case(role)
+            || self.secondary.iter().any(|r| r.eq_ignore_ascii_case(role))
+    }
+}
+
+/// Whether I play `champion` in `role`; champions without roles set go anywhere
+pub fn plays_role(roles: &HashMap<String, ChampionRoles>, champion: &str, role: &str) -> bool {
+    roles
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(champion))
+        .is_none_or(|(_, roles)| roles.plays(role))
+}
+
+/// How my games spread over roles and champions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PoolDistribution {
+    pub games: u32,
+    /// Most played first; only games with a lane opponent have a role
+    pub by_role: Vec<ShareStats>,
+    /// Most played first
+    pub by_champion: Vec<ShareStats>,
+    pub main_role: Option<String>,
+    /// Games in my most played role, and in the others
+    pub on_role: MatchRecord,
+    pub off_role: MatchRecord,
+    /// Games on and off my champion pool, `None` without a pool
+    pub in_pool: Option<MatchRecord>,
+    pub off_pool: Option<MatchRecord>,
+    /// From near 0 (many champions played evenly) to 1 (a single champion)
+    ///
+    /// The sum of squared champion shares.
+    pub one_trick_index: f64,
+}
+
+/// Role and champion spread of `matches`, compared against my main role and `pool`
+pub fn pool_distribution<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    pool: &[String],
+) -> PoolDistribution {
+    let matches: Vec<&Match> = matches.into_iter().collect();
+    let mut by_role: HashMap<String, MatchRecord> = HashMap::new();
+    let mut by_champion: HashMap<String, MatchRecord> = HashMap::new();
+    let mut in_pool = MatchRecord::default();
+    let mut off_pool = MatchRecord::default();
+
+    for m in &matches {
+        let won = m.result == MatchResult::Win;
+        let mut records = vec![by_champion.entry(m.my_champion.clone()).or_default()];
+        if m.has_lane_opponent() && !m.role.is_empty() {
+            records.push(by_role.entry(m.role.clone()).or_default());
+        }
+        if pool.iter().any(|c| c.eq_ignore_ascii_case(&m.my_champion)) {
+            records.push(&mut in_pool);
+        } else {
+            records.push(&mut off_pool);
+        }
+        for record in records {
+            record.games += 1;
+            record.wins += won as u32;
+        }
+    }
+
+    let by_role = share_stats(by_role);
+    let by_champion = share_stats(by_champion);
+    let main_role = by_role.first().map(|r| r.name.clone());
+    let mut off_role = MatchRecord::default();
+    for role in by_role.iter().skip(1) {
+        off_role.games += role.record.games;
+        off_role.wins += role.record.wins;
+    }
+
+    PoolDistribution {
+        games: matches.len() as u32,
+        on_role: by_role.first().map(|r| r.record).unwrap_or_default(),
+        off_role,
+        main_role,
+        in_pool: (!pool.is_empty()).then_some(in_pool),
+        off_pool: (!pool.is_empty()).then_some(off_pool),
+        one_trick_index: by_champion.iter().map(|c| c.share * c.share).sum(),
+        by_role,
+        by_champion,
+    }
+}
+
+/// Most played first, shares taken over the games in `records`
+fn share_stats(records: HashMap<String, MatchRecord>) -> Vec<ShareStats> {
+    let total: u32 = records.values().map(|r| r.games).sum();
+    let mut stats: Vec<ShareStats> = records
+        .into_iter()
+        .map(|(name, record)| ShareStats {
+            share: record.games as f64 / total as f64,
+            win_rate: record.win_rate(),
+            name,
+            record,
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.record
+            .games
+            .cmp(&a.record.games)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    stats
+}
+
+/// Longest break between the start of two games of the same play session
+pub const SESSION_GAP_MINUTES: i64 = 90;
+
+/// Games and wins in one bucket of session stats
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BucketStats {
+    /// Hour (0-23), weekday (0 = Monday) or 1-based game of the session
+    pub bucket: u32,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub win_rate: Option<f64>,
+}
+
+/// Win rates by when I play, with empty buckets left out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct SessionStats {
+    pub by_hour: Vec<BucketStats>,
+    pub by_weekday: Vec<BucketStats>,
+    /// By how many games into a play session the game was
+    pub by_session_game: Vec<BucketStats>,
+    pub sessions: u32,
+}
+
+/// Bucket games by local hour, weekday and position in their play session
+///
+/// A session ends when the next game starts more than `SESSION_GAP_MINUTES`
+/// after the previous one.
+pub fn session_stats<'a, Tz: TimeZone>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    tz: &Tz,
+) -> SessionStats {
+    let mut played: Vec<&Match> = matches.into_iter().collect();
+    played.sort_by_key(|m| m.date);
+
+    let mut by_hour: HashMap<u32, MatchRecord> = HashMap::new();
+    let mut by_weekday: HashMap<u32, MatchRecord> = HashMap::new();
+    let mut by_session_game: HashMap<u32, MatchRecord> = HashMap::new();
+    let mut sessions = 0;
+    let mut session_game = 0;
+    let mut previous: Option<DateTime<Utc>> = None;
+
+    for m in played {
+        let new_session = previous.is_none_or(|previous| {
+            m.date - previous > chrono::Duration::minutes(SESSION_GAP_MINUTES)
+        });
+        if new_session {
+            sessions += 1;
+            session_game = 0;
+        }
+        session_game += 1;
+        previous = Some(m.date);
+
+        let local = m.date.with_timezone(tz);
+        let won = m.result == MatchResult::Win;
+        for (buckets, bucket) in [
+            (&mut by_hour, local.hour()),
+            (&mut by_weekday, local.weekday().num_days_from_monday()),
+            (&mut by_session_game, session_game),
+        ] {
+            let record = buckets.entry(bucket).or_default();
+            record.games += 1;
+            record.wins += won as u32;
+        }
+    }
+
+    SessionStats {
+        by_hour: sorted_buckets(by_hour),
+        by_weekday: sorted_buckets(by_weekday),
+        by_session_game: sorted_buckets(by_session_game),
+        sessions,
+    }
+}
+
+fn sorted_buckets(records: HashMap<u32, MatchRecord>) -> Vec<BucketStats> {
+    let mut buckets: Vec<BucketStats> = records
+        .into_iter()
+        .map(|(bucket, record)| BucketStats {
+            bucket,
+            record,
+            win_rate: record.win_rate(),
+        })
+        .collect();
+    buckets.sort_by_key(|b| b.bucket);
+    buckets
+}
+
+/// Find the matchup covering a match's champion pairing and role, among the
+/// notes of the player the match belongs to; imported packages are left out
+pub fn find_matchup_for<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    m: &Match,
+) -> Option<&'a Matchup> {
+    matchups
+        .into_iter()
+        .filter(|matchup| matchup.player == m.player && matchup.external.is_none())
+        .find(|matchup| matchup.is_pairing(&m.my_champion, &m.enemy_champion, &m.role))
+}
+
+/// Days of matches looked at for matchup suggestions
+pub const DEFAULT_SUGGESTION_DAYS: i64 = 30;
+
+/// A pairing I've played recently without having notes for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupSuggestion {
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub last_played: DateTime<Utc>,
+}
+
+/// Pairings from `matches` with no matchup, most played first
+///
+/// Games without a lane opponent or with an unknown enemy are left out.
+pub fn matchup_suggestions<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    matches: impl IntoIterator<Item = &'a Match>,
+) -> Vec<MatchupSuggestion> {
+    let matchups: Vec<&Matchup> = matchups.into_iter().collect();
+    let mut suggestions: HashMap<(String, String, String), MatchupSuggestion> = HashMap::new();
+
+    for m in matches {
+        if !m.has_lane_opponent()
+            || m.enemy_champion == "Unknown"
+            || find_matchup_for(matchups.iter().copied(), m).is_some()
+        {
+            continue;
+        }
+        let key = (
+            m.my_champion.to_lowercase(),
+            m.enemy_champion.to_lowercase(),
+            m.role.to_lowercase(),
+        );
+        let suggestion = suggestions.entry(key).or_insert_with(|| MatchupSuggestion {
+            my_champion: m.my_champion.clone(),
+            enemy_champion: m.enemy_champion.clone(),
+            role: m.role.clone(),
+            record: MatchRecord::default(),
+            last_played: m.date,
+        });
+        suggestion.record.games += 1;
+        if m.result == MatchResult::Win {
+            suggestion.record.wins += 1;
+        }
+        suggestion.last_played = suggestion.last_played.max(m.date);
+    }
+
+    let mut suggestions: Vec<MatchupSuggestion> = suggestions.into_values().collect();
+    suggestions.sort_by(|a, b| {
+        b.record
+            .games
+            .cmp(&a.record.games)
+            .then(b.last_played.cmp(&a.last_played))
+    });
+    suggestions
+}
+
+/// My matchups for a champion and role, most likely opponents first
+///
+/// The enemy laner comes first, then other enemy picks, then the rest by how
+/// often I've played the pairing. Banned champions are left out.
+pub fn relevant_matchups<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    matches: impl IntoIterator<Item = &'a Match>,
+    my_champion: &str,
+    role: &str,
+    enemy_laner: Option<&str>,
+    enemy_picks: &[String],
+    bans: &[String],
+) -> Vec<Matchup> {
+    let mut games: HashMap<String, usize> = HashMap::new();
+    for m in matches {
+        if m.my_champion.eq_ignore_ascii_case(my_champion) && m.role.eq_ignore_ascii_case(role) {
+            *games.entry(m.enemy_champion.to_lowercase()).or_default() += 1;
+        }
+    }
+
+    let mut relevant: Vec<&Matchup> = matchups
+        .into_iter()
+        .filter(|m| m.my_champion.eq_ignore_ascii_case(my_champion))
+        .filter(|m| m.role.eq_ignore_ascii_case(role))
+        .filter(|m| {
+            !bans
+                .iter()
+                .any(|b| b.eq_ignore_ascii_case(&m.enemy_champion))
+        })
+        .collect();
+
+    let priority = |m: &Matchup| {
+        if enemy_laner.is_some_and(|e| e.eq_ignore_ascii_case(&m.enemy_champion)) {
+            0
+        } else if enemy_picks
+            .iter()
+            .any(|p| p.eq_ignore_ascii_case(&m.enemy_champion))
+        {
+            1
+        } else {
+            2
+        }
+    };
+    relevant.sort_by_key(|m| {
+        (
+            priority(m),
+            std::cmp::Reverse(
+                games
+                    .get(&m.enemy_champion.to_lowercase())
+                    .copied()
+                    .unwrap_or(0),
+            ),
+            m.enemy_champion.clone(),
+        )
+    });
+
+    relevant.into_iter().cloned().collect()
+}
+
+/// My matchups against an enemy, narrowed to my champion and role when known
+pub fn matchups_against<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    enemy_champion: &str,
+    my_champion: Option<&str>,
+    role: Option<&str>,
+) -> Vec<Matchup> {
+    let mut found: Vec<Matchup> = matchups
+        .into_iter()
+        .filter(|m| m.enemy_champion.eq_ignore_ascii_case(enemy_champion))
+        .filter(|m| my_champion.is_none_or(|c| m.my_champion.eq_ignore_ascii_case(c)))
+        .filter(|m| role.is_none_or(|r| m.role.eq_ignore_ascii_case(r)))
+        .cloned()
+        .collect();
+    found.sort_by(|a, b| a.my_champion.cmp(&b.my_champion));
+    found
+}
+
+/// Fewest games against a champion before it counts as a bad matchup
+pub const MIN_BAN_SUGGESTION_GAMES: u32 = 2;
+
+/// Bad matchups offered during the ban phase
+const BAN_SUGGESTIONS: usize = 5;
+
+/// My record against one enemy champion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnemyRecord {
+    pub enemy_champion: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+}
+
+/// What to consider banning, sent as `ban-phase` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BanSuggestions {
+    pub ban_list: Vec<String>,
+    pub my_champion: Option<String>,
+    /// Lowest win rate first
+    pub worst_matchups: Vec<EnemyRecord>,
+}
+
+/// My lowest win rate lane opponents on a champion, optionally in one role
+pub fn worst_matchups<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    my_champion: &str,
+    role: Option<&str>,
+) -> Vec<EnemyRecord> {
+    let mut records: HashMap<String, MatchRecord> = HashMap::new();
+    for m in matches {
+        if !m.has_lane_opponent()
+            || !m.my_champion.eq_ignore_ascii_case(my_champion)
+            || role.is_some_and(|r| !m.role.eq_ignore_ascii_case(r))
+        {
+            continue;
+        }
+        let record = records.entry(m.enemy_champion.clone()).or_default();
+        record.games += 1;
+        if m.result == MatchResult::Win {
+            record.wins += 1;
+        }
+    }
+
+    let mut worst: Vec<EnemyRecord> = records
+        .into_iter()
+        .filter(|(_, record)| record.games >= MIN_BAN_SUGGESTION_GAMES)
+        .map(|(enemy_champion, record)| EnemyRecord {
+            enemy_champion,
+            record,
+        })
+        .collect();
+    worst.sort_by(|a, b| {
+        a.record
+            .win_rate()
+            .partial_cmp(&b.record.win_rate())
+            .unwrap_or(Ordering::Equal)
+            .then(b.record.games.cmp(&a.record.games))
+    });
+    worst.truncate(BAN_SUGGESTIONS);
+
+    worst
+}
+
+/// Fewest games against an enemy before it's listed as a best or worst matchup
+pub const MIN_CHAMPION_MATCHUP_GAMES: u32 = 2;
+
+/// Best and worst matchups listed on a champion's summary
+const CHAMPION_MATCHUPS: usize = 5;
+
+/// My averages over games with end-of-game stats
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceAverages {
+    /// Games the averages are taken from
+    pub games: u32,
+    pub kills: f64,
+    pub deaths: f64,
+    pub assists: f64,
+    /// (kills + assists) / deaths, `None` without deaths
+    pub kda: Option<f64>,
+    pub cs_per_minute: f64,
+    pub gold_per_minute: f64,
+    pub damage_per_minute: f64,
+    pub vision_score: f64,
+}
+
+impl PerformanceAverages {
+    /// `None` when none of the stats has a game length to divide by
+    fn new<'a>(stats: impl IntoIterator<Item = &'a MatchStats>) -> Option<Self> {
+        let stats: Vec<&MatchStats> = stats.into_iter().filter(|s| s.game_duration > 0).collect();
+        if stats.is_empty() {
+            return None;
+        }
+
+        let games = stats.len() as f64;
+        let total =
+            |field: fn(&MatchStats) -> u32| -> f64 { stats.iter().map(|s| field(s) as f64).sum() };
+        let minutes = total(|s| s.game_duration) / 60.0;
+        let (kills, deaths, assists) = (
+            total(|s| s.kills),
+            total(|s| s.deaths),
+            total(|s| s.assists),
+        );
+
+        Some(Self {
+            games: stats.len() as u32,
+            kills: kills / games,
+            deaths: deaths / games,
+            assists: assists / games,
+            kda: (deaths > 0.0).then(|| (kills + assists) / deaths),
+            cs_per_minute: total(|s| s.cs) / minutes,
+            gold_per_minute: total(|s| s.gold_earned) / minutes,
+            damage_per_minute: total(|s| s.damage_to_champions) / minutes,
+            vision_score: total(|s| s.vision_score) / games,
+        })
+    }
+}
+
+/// How far from my average a game has to be to count as better or worse, as a fraction
+pub const PERFORMANCE_MARGIN: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Performance {
+    Above,
+    Usual,
+    Below,
+}
+
+/// One linked game against my usual numbers on the champion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GameBenchmark {
+    pub match_id: String,
+    pub date: DateTime<Utc>,
+    pub result: MatchResult,
+    /// (kills + assists) / deaths, counting a deathless game as one death
+    pub kda: f64,
+    pub cs_per_minute: f64,
+    pub damage_per_minute: f64,
+    /// Relative to my average, e.g. -0.2 for 20% below; `None` without a baseline
+    pub kda_diff: Option<f64>,
+    pub cs_diff: Option<f64>,
+    pub damage_diff: Option<f64>,
+    /// From the mean of the three diffs; `None` without a baseline
+    pub performance: Option<Performance>,
+}
+
+impl GameBenchmark {
+    fn new(m: &Match, stats: &MatchStats, baseline: Option<&PerformanceAverages>) -> Self {
+        let minutes = stats.game_duration as f64 / 60.0;
+        let kda = (stats.kills + stats.assists) as f64 / stats.deaths.max(1) as f64;
+        let cs_per_minute = stats.cs as f64 / minutes;
+        let damage_per_minute = stats.damage_to_champions as f64 / minutes;
+
+        let relative = |value: f64, usual: f64| (usual > 0.0).then(|| value / usual - 1.0);
+        let kda_diff = baseline.and_then(|b| relative(kda, b.kda.unwrap_or(b.kills + b.assists)));
+        let cs_diff = baseline.and_then(|b| relative(cs_per_minute, b.cs_per_minute));
+        let damage_diff = baseline.and_then(|b| relative(damage_per_minute, b.damage_per_minute));
+
+        let diffs: Vec<f64> = [kda_diff, cs_diff, damage_diff]
+            .into_iter()
+            .flatten()
+            .collect();
+        let performance = average(&diffs).map(|diff| {
+            if diff >= PERFORMANCE_MARGIN {
+                Performance::Above
+            } else if diff <= -PERFORMANCE_MARGIN {
+                Performance::Below
+            } else {
+                Performance::Usual
+            }
+        });
+
+        Self {
+            match_id: m.id.clone(),
+            date: m.date,
+            result: m.result.clone(),
+            kda,
+            cs_per_minute,
+            damage_per_minute,
+            kda_diff,
+            cs_diff,
+            damage_diff,
+            performance,
+        }
+    }
+}
+
+/// My numbers in a matchup next to my usual ones on the champion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchupBenchmark {
+    /// My other games on the champion, `None` without stats
+    pub baseline: Option<PerformanceAverages>,
+    pub matchup: Option<PerformanceAverages>,
+    /// Linked games with stats, newest first
+    pub games: Vec<GameBenchmark>,
+}
+
+/// Summary of every game I played on one champion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChampionStats {
+    pub my_champion: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub win_rate: Option<f64>,
+    /// Lane opponents I win against most, highest win rate first
+    pub best_matchups: Vec<EnemyRecord>,
+    /// Lane opponents I lose against most, lowest win rate first
+    pub worst_matchups: Vec<EnemyRecord>,
+    /// `None` when no game has end-of-game stats
+    pub averages: Option<PerformanceAverages>,
+}
+
+/// Record, best and worst matchups and averages for `my_champion`
+///
+/// Matchups need a few games to be listed; even ones are in neither list.
+pub fn champion_stats<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    my_champion: &str,
+) -> ChampionStats {
+    let played: Vec<&Match> = matches
+        .into_iter()
+        .filter(|m| m.my_champion.eq_ignore_ascii_case(my_champion))
+        .collect();
+
+    let mut record = MatchRecord::default();
+    let mut enemies: HashMap<String, MatchRecord> = HashMap::new();
+    for m in &played {
+        let won = m.result == MatchResult::Win;
+        record.games += 1;
+        record.wins += won as u32;
+        if m.has_lane_opponent() {
+            let enemy = enemies.entry(m.enemy_champion.clone()).or_default();
+            enemy.games += 1;
+            enemy.wins += won as u32;
+        }
+    }
+
+    let mut best = Vec::new();
+    let mut worst = Vec::new();
+    for (enemy_champion, enemy) in enemies {
+        if enemy.games < MIN_CHAMPION_MATCHUP_GAMES {
+            continue;
+        }
+        let entry = EnemyRecord {
+            enemy_champion,
+            record: enemy,
+        };
+        match (enemy.wins * 2).cmp(&enemy.games) {
+            Ordering::Greater => best.push(entry),
+            Ordering::Less => worst.push(entry),
+            Ordering::Equal => {}
+        }
+    }
+    let by_win_rate = |a: &EnemyRecord, b: &EnemyRecord| {
+        a.record
+            .win_rate()
+            .partial_cmp(&b.record.win_rate())
+            .unwrap_or(Ordering::Equal)
+    };
+    best.sort_by(|a, b| by_win_rate(b, a).then(b.record.games.cmp(&a.record.games)));
+    worst.sort_by(|a, b| by_win_rate(a, b).then(b.record.games.cmp(&a.record.games)));
+    best.truncate(CHAMPION_MATCHUPS);
+    worst.truncate(CHAMPION_MATCHUPS);
+
+    ChampionStats {
+        my_champion: played
+            .first()
+            .map_or_else(|| my_champion.to_string(), |m| m.my_champion.clone()),
+        record,
+        win_rate: record.win_rate(),
+        best_matchups: best,
+        worst_matchups: worst,
+        averages: PerformanceAverages::new(played.iter().filter_map(|m| m.stats.as_ref())),
+    }
+}
+
+/// A champion from my pool to consider against the enemy laner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickSuggestion {
+    pub champion: String,
+    /// My games with it against the enemy laner in this role
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    /// My notes for the pairing, if any
+    pub matchup_id: Option<String>,
+    pub difficulty: Option<u8>,
+}
+
+/// Rank my pool against `enemy` in `role`, best pick first
+///
+/// Without a configured pool, every champion I have notes or games with in the
+/// role is considered. Unavailable (banned or picked) champions are left out,
+/// as are champions I've set other roles for.
+/// Win rates are smoothed towards 50% so a single win doesn't top the list.
+pub fn pick_suggestions<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    matches: impl IntoIterator<Item = &'a Match>,
+    pool: &[String],
+    roles: &HashMap<String, ChampionRoles>,
+    enemy: &str,
+    role: &str,
+    unavailable: &[String],
+) -> Vec<PickSuggestion> {
+    let matchups: Vec<&Matchup> = matchups
+        .into_iter()
+        .filter(|m| m.role.eq_ignore_ascii_case(role))
+        .collect();
+    let matches: Vec<&Match> = matches
+        .into_iter()
+        .filter(|m| m.role.eq_ignore_ascii_case(role))
+        .collect();
+
+    let mut candidates: Vec<String> = if pool.is_empty() {
+        matchups
+            .iter()
+            .map(|m| m.my_champion.clone())
+            .chain(matches.iter().map(|m| m.my_champion.clone()))
+            .collect()
+    } else {
+        pool.to_vec()
+    };
+    candidates.sort_by_key(|c| c.to_lowercase());
+    candidates.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    candidates.retain(|c| {
+        !c.eq_ignore_ascii_case(enemy)
+            && !unavailable.iter().any(|u| u.eq_ignore_ascii_case(c))
+            && plays_role(roles, c, role)
+    });
+
+    let mut suggestions: Vec<PickSuggestion> = candidates
+        .into_iter()
+        .map(|champion| {
+            let mut record = MatchRecord::default();
+            for m in matches.iter().filter(|m| {
+                m.my_champion.eq_ignore_ascii_case(&champion)
+                    && m.enemy_champion.eq_ignore_ascii_case(enemy)
+            }) {
+                record.games += 1;
+                if m.result == MatchResult::Win {
+                    record.wins += 1;
+                }
+            }
+            let matchup = matchups.iter().find(|m| {
+                m.my_champion.eq_ignore_ascii_case(&champion)
+                    && m.enemy_champion.eq_ignore_ascii_case(enemy)
+            });
+
+            PickSuggestion {
+                champion,
+                record,
+                matchup_id: matchup.map(|m| m.id.clone()),
+                difficulty: matchup.and_then(|m| m.current()).and_then(|v| v.difficulty),
+            }
+        })
+        .collect();
+
+    let smoothed = |r: &MatchRecord| (r.wins as f64 + 1.0) / (r.games as f64 + 2.0);
+    suggestions.sort_by(|a, b| {
+        smoothed(&b.record)
+            .partial_cmp(&smoothed(&a.record))
+            .unwrap_or(Ordering::Equal)
+            .then(b.matchup_id.is_some().cmp(&a.matchup_id.is_some()))
+            .then(
+                a.difficulty
+                    .unwrap_or(u8::MAX)
+                    .cmp(&b.difficulty.unwrap_or(u8::MAX)),
+            )
+            .then_with(|| a.champion.cmp(&b.champion))
+    });
+
+    suggestions
+}
+
+/// My games against one player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpponentHistory {
+    pub riot_id: String,
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    /// Newest first
+    pub matches: Vec<Match>,
+}
+
+/// Every match where `riot_id` was my lane opponent
+///
+/// Riot IDs are compared ignoring case, like the client does.
+pub fn opponent_history<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    riot_id: &str,
+) -> OpponentHistory {
+    let mut faced: Vec<Match> = matches
+        .into_iter()
+        .filter(|m| {
+            m.enemy_riot_id
+                .as_deref()
+                .is_some_and(|id| id.eq_ignore_ascii_case(riot_id))
+        })
+        .cloned()
+        .collect();
+    faced.sort_by(|a, b| b.date.cmp(&a.date));
+
+    OpponentHistory {
+        riot_id: riot_id.to_string(),
+        record: MatchRecord {
+            games: faced.len() as u32,
+            wins: faced
+                .iter()
+                .filter(|m| m.result == MatchResult::Win)
+                .count() as u32,
+        },
+        matches: faced,
+    }
+}
+
+/// Record against the enemy laner of each new match, by match ID
+///
+/// Only players faced more than once are included, counting the new match.
+pub fn repeat_opponents<'a>(
+    matches: impl IntoIterator<Item = &'a Match>,
+    new_matches: &[Match],
+) -> HashMap<String, MatchRecord> {
+    let mut records: HashMap<String, MatchRecord> = HashMap::new();
+    for m in matches {
+        if let Some(riot_id) = &m.enemy_riot_id {
+            let record = records.entry(riot_id.to_lowercase()).or_default();
+            record.games += 1;
+            if m.result == MatchResult::Win {
+                record.wins += 1;
+            }
+        }
+    }
+
+    new_matches
+        .iter()
+        .filter_map(|m| {
+            let record = records.get(&m.enemy_riot_id.as_ref()?.to_lowercase())?;
+            (record.games > 1).then(|| (m.id.clone(), *record))
+        })
+        .collect()
+}
+
+/// Outcome of importing matches from the client
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImportSummary {
+    pub imported: Vec<Match>,
+    pub linked: usize,
+    pub skipped: usize,
+    /// Games left out by the import options
+    pub filtered: usize,
+    /// Games that couldn't be fetched, left for the next import
+    pub failed: usize,
+    /// Matchups created for pairings that had none yet
+    pub created: usize,
+    /// Games imported before and refreshed in place, with `merge_existing`
+    pub merged: Vec<Match>,
+    /// Record against enemy laners I had faced before, by imported match ID
+    pub repeat_opponents: HashMap<String, MatchRecord>,
+}
+
+/// Queue IDs of Teamfight Tactics modes
+const TFT_QUEUE_IDS: &[i32] = &[1090, 1100, 1110, 1111, 1130, 1150, 1160, 1210, 1220];
+
+/// Queue IDs of co-op vs AI and intro bot games
+const BOT_QUEUE_IDS: &[i32] = &[800, 810, 820, 830, 840, 850, 870, 880, 890];
+
+/// Which games an import should bring in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct ImportOptions {
+    /// Only these queues, or every queue when empty
+    pub queue_ids: Vec<i32>,
+    /// Only games started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    pub skip_aram: bool,
+    pub skip_tft: bool,
+    pub skip_bots: bool,
+    /// Refresh the game data of games imported before instead of skipping them
+    pub merge_existing: bool,
+}
+
+impl ImportOptions {
+    /// Whether a game passes these options; `game_creation` is Unix milliseconds
+    pub fn allows(&self, queue_id: i32, game_mode: GameMode, game_creation: i64) -> bool {
+        if !self.queue_ids.is_empty() && !self.queue_ids.contains(&queue_id) {
+            return false;
+        }
+        if self
+            .since
+            .is_some_and(|since| game_creation < since.timestamp_millis())
+        {
+            return false;
+        }
+        if self.skip_aram && game_mode == GameMode::Aram {
+            return false;
+        }
+        if self.skip_tft && TFT_QUEUE_IDS.contains(&queue_id) {
+            return false;
+        }
+        if self.skip_bots && BOT_QUEUE_IDS.contains(&queue_id) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Progress of a deep import, sent as `import-progress` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub fetched: usize,
+    pub max: u32,
+}
+
+/// Update data for a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchUpdate {
+    pub notes: Option<String>,
+    pub linked_matchup: Option<String>,
+    /// Corrects the detected lane opponent
+    #[serde(default)]
+    pub enemy_champion: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_matchup() {
+        let matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        assert_eq!(matchup.my_champion, "Darius");
+        assert_eq!(matchup.enemy_champion, "Garen");
+        assert_eq!(matchup.role, "top");
+        assert_eq!(matchup.versions.len(), 1);
+        assert_eq!(matchup.current_version, 1);
+    }
+
+    #[test]
+    fn test_edit_signed() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.edit_signed(Some("Me"), |m| {
+            m.add_version(MatchupUpdate::default());
+            m.add_version(MatchupUpdate {
+                author: Some("Coach".to_string()),
+                ..Default::default()
+            });
+        });
+        assert_eq!(matchup.versions[0].author, None);
+        assert_eq!(matchup.versions[1].author.as_deref(), Some("Me"));
+        assert_eq!(matchup.versions[2].author.as_deref(), Some("Coach"));
+    }
+
+    #[test]
+    fn test_add_version() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        matchup.add_version(MatchupUpdate {
+            notes: "Test notes".to_string(),
+            tags: vec!["easy".to_string()],
+            runes: vec![],
+            summoner_spells: vec![],
+            items: vec![],
+            patch: None,
+            difficulty: None,
+            summary: vec![],
+            rune_page: None,
+            source_url: None,
+            author: None,
+            blue_side_notes: None,
+            red_side_notes: None,
+        });
+
+        assert_eq!(matchup.versions.len(), 2);
+        assert_eq!(matchup.current_version, 2);
+        assert_eq!(matchup.current().unwrap().notes, "Test notes");
+    }
+
+    #[test]
+    fn test_filter() {
+        let matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let filter = MatchupFilter {
+            my_champion: Some("Darius".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matchup.matches_filter(&filter));
+
+        let filter2 = MatchupFilter {
+            my_champion: Some("Garen".to_string()),
+            ..Default::default()
+        };
 
         assert!(!matchup.matches_filter(&filter2));
     }
+
+    #[test]
+    fn test_summary_and_quick_reference() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let too_long = MatchupUpdate {
+            summary: vec!["a".into(), "b".into(), "c".into(), "d".into()],
+            ..Default::default()
+        };
+        assert!(too_long.validate().is_err());
+
+        let update = MatchupUpdate {
+            notes: "Long notes".to_string(),
+            summary: vec!["Trade level 1".into(), " ".into(), "Ignite".into()],
+            runes: vec!["Conqueror".into()],
+            summoner_spells: vec!["Flash".into(), "Ignite".into()],
+            ..Default::default()
+        };
+        assert!(update.validate().is_ok());
+        matchup.add_version(update);
+
+        let quick = matchup.quick_reference();
+        assert_eq!(quick.summary, vec!["Trade level 1", "Ignite"]);
+        assert_eq!(quick.runes, vec!["Conqueror"]);
+        assert!(matchup.is_pairing("darius", "GAREN", "Top"));
+    }
+
+    #[test]
+    fn test_difficulty_history() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.add_version(MatchupUpdate {
+            difficulty: Some(9),
+            ..Default::default()
+        });
+        matchup.add_version(MatchupUpdate::default());
+        matchup.add_version(MatchupUpdate {
+            difficulty: Some(14),
+            ..Default::default()
+        });
+
+        let history = matchup.difficulty_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, 2);
+        assert_eq!(history[0].difficulty, 9);
+        assert_eq!(history[1].difficulty, 10);
+    }
+
+    #[test]
+    fn test_suggest_tags() {
+        let mut a = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        a.add_version(MatchupUpdate {
+            tags: vec!["early-game".into(), "easy".into()],
+            ..Default::default()
+        });
+        let mut b = Matchup::new(
+            "Riven".to_string(),
+            "Renekton".to_string(),
+            "top".to_string(),
+        );
+        b.add_version(MatchupUpdate {
+            tags: vec!["Early-Game".into(), "hard".into()],
+            ..Default::default()
+        });
+        let matchups = vec![a, b];
+
+        let suggestions = suggest_tags(&matchups, "ea");
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].tag.to_lowercase(), "early-game");
+        assert_eq!(suggestions[0].count, 2);
+        assert_eq!(suggestions[1].tag, "easy");
+
+        assert_eq!(suggest_tags(&matchups, "").len(), 3);
+        assert!(suggest_tags(&matchups, "zz").is_empty());
+    }
+
+    #[test]
+    fn test_multi_value_filter() {
+        let riven = Matchup::new(
+            "Riven".to_string(),
+            "Renekton".to_string(),
+            "top".to_string(),
+        );
+        let fiora = Matchup::new(
+            "Fiora".to_string(),
+            "Yone".to_string(),
+            "mid".to_string(),
+        );
+        let darius = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let filter = MatchupFilter {
+            my_champions: Some(vec!["riven".to_string(), "Fiora".to_string()]),
+            roles: Some(vec!["top".to_string(), "mid".to_string()]),
+            ..Default::default()
+        };
+        assert!(riven.matches_filter(&filter));
+        assert!(fiora.matches_filter(&filter));
+        assert!(!darius.matches_filter(&filter));
+
+        let filter = MatchupFilter {
+            my_champions: Some(vec!["Riven".to_string(), "Fiora".to_string()]),
+            roles: Some(vec!["top".to_string()]),
+            ..Default::default()
+        };
+        assert!(riven.matches_filter(&filter));
+        assert!(!fiora.matches_filter(&filter));
+
+        let empty = MatchupFilter {
+            roles: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(darius.matches_filter(&empty));
+    }
+
+    #[test]
+    fn test_sort_matchups() {
+        let darius = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        let mut aatrox = Matchup::new(
+            "Aatrox".to_string(),
+            "Fiora".to_string(),
+            "top".to_string(),
+        );
+        aatrox.add_version(MatchupUpdate {
+            notes: "Hard".to_string(),
+            difficulty: Some(8),
+            ..Default::default()
+        });
+
+        let mut win = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        win.linked_matchup = Some(darius.id.clone());
+        let unlinked = Match::new(
+            "Aatrox".to_string(),
+            "Fiora".to_string(),
+            "top".to_string(),
+            MatchResult::Loss,
+            None,
+        );
+        let mut loss = unlinked.clone();
+        loss.linked_matchup = Some(aatrox.id.clone());
+        // Unlinked games of the pairing count for neither the records nor the order
+        let matches = vec![win.clone(), win, loss, unlinked.clone(), unlinked];
+
+        let mut list = vec![darius.clone(), aatrox.clone()];
+        sort_matchups(&mut list, MatchupSort::Alphabetical, false, &matches);
+        assert_eq!(list[0].my_champion, "Aatrox");
+
+        sort_matchups(&mut list, MatchupSort::GamesPlayed, true, &matches);
+        assert_eq!(list[0].my_champion, "Darius");
+
+        sort_matchups(&mut list, MatchupSort::WinRate, false, &matches);
+        assert_eq!(list[0].my_champion, "Aatrox");
+
+        // Matchups without a difficulty sort last in either direction
+        sort_matchups(&mut list, MatchupSort::Difficulty, false, &matches);
+        assert_eq!(list[0].my_champion, "Aatrox");
+        sort_matchups(&mut list, MatchupSort::Difficulty, true, &matches);
+        assert_eq!(list[0].my_champion, "Aatrox");
+    }
+
+    #[test]
+    fn test_patches_between() {
+        assert_eq!(patches_between("14.10", "14.12"), Some(2));
+        assert_eq!(patches_between("14.12.601.1234", "14.12"), Some(0));
+        assert_eq!(patches_between("14.23", "15.1"), Some(2));
+        assert_eq!(patches_between("14.12", "14.10"), Some(0));
+        assert_eq!(patches_between("garbage", "14.10"), None);
+    }
+
+    #[test]
+    fn test_stale_notes() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        assert!(!matchup.is_stale("14.20", 3));
+
+        matchup.add_version(MatchupUpdate {
+            notes: "Old notes".to_string(),
+            patch: Some("14.10".to_string()),
+            ..Default::default()
+        });
+        assert!(matchup.is_stale("14.20", 3));
+        assert!(!matchup.is_stale("14.12", 3));
+
+        matchup.mark_still_valid("14.20".to_string());
+        assert_eq!(matchup.versions.len(), 2);
+        assert!(!matchup.is_stale("14.20", 3));
+    }
+
+    #[test]
+    fn test_needs_notes_filter() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let filter = MatchupFilter {
+            needs_notes: Some(true),
+            ..Default::default()
+        };
+        assert!(matchup.matches_filter(&filter));
+
+        matchup.add_version(MatchupUpdate {
+            notes: "Short".to_string(),
+            ..Default::default()
+        });
+        assert!(matchup.matches_filter(&filter));
+
+        let strict = MatchupFilter {
+            needs_notes: Some(true),
+            min_notes_length: Some(3),
+            ..Default::default()
+        };
+        assert!(!matchup.matches_filter(&strict));
+
+        matchup.add_version(MatchupUpdate {
+            notes: "Respect level 2, he wins extended trades".to_string(),
+            ..Default::default()
+        });
+        assert!(!matchup.matches_filter(&filter));
+    }
+
+    #[test]
+    fn test_reverse_matchup() {
+        let matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        let reverse = Matchup::new(
+            "garen".to_string(),
+            "darius".to_string(),
+            "Top".to_string(),
+        );
+        let other_role = Matchup::new(
+            "Garen".to_string(),
+            "Darius".to_string(),
+            "mid".to_string(),
+        );
+
+        assert!(reverse.is_reverse_of(&matchup));
+        assert!(!other_role.is_reverse_of(&matchup));
+        assert!(!matchup.is_reverse_of(&matchup));
+
+        let mirror = Matchup::new(
+            "Darius".to_string(),
+            "Darius".to_string(),
+            "top".to_string(),
+        );
+        let other_mirror = Matchup::new(
+            "Darius".to_string(),
+            "Darius".to_string(),
+            "top".to_string(),
+        );
+        assert!(!mirror.is_reverse_of(&mirror));
+        assert!(other_mirror.is_reverse_of(&mirror));
+    }
+
+    #[test]
+    fn test_pick_intent_filters() {
+        let matches: Vec<Match> = [
+            Some(("Darius", "Darius", true)),
+            Some(("Darius", "Sett", false)),
+            None,
+        ]
+        .into_iter()
+        .map(|pick| {
+            let mut m = Match::new(
+                "Sett".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                MatchResult::Loss,
+                None,
+            );
+            m.pick = pick.map(|(intended, locked, counter_picked)| PickIntent {
+                intended: Some(intended.to_string()),
+                locked: locked.to_string(),
+                counter_picked,
+                order: None,
+            });
+            m
+        })
+        .collect();
+
+        let filter = MatchFilter {
+            counter_picked: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(filter_matches(&matches, &filter).len(), 1);
+        let filter = MatchFilter {
+            pick_order: Some(PickOrder::Blind),
+            ..Default::default()
+        };
+        assert_eq!(filter_matches(&matches, &filter).len(), 1);
+
+        let filter = MatchFilter {
+            off_intent: Some(true),
+            ..Default::default()
+        };
+        let off_intent = filter_matches(&matches, &filter);
+        assert_eq!(off_intent.len(), 1);
+        assert_eq!(off_intent[0].pick.as_ref().unwrap().locked, "Sett");
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let mut matches = Vec::new();
+        for i in 0..5 {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                if i % 2 == 0 { "Garen" } else { "Sett" }.to_string(),
+                "top".to_string(),
+                if i < 2 {
+                    MatchResult::Win
+                } else {
+                    MatchResult::Loss
+                },
+                None,
+            );
+            m.date = Utc::now() - chrono::Duration::days(i);
+            m.queue_id = Some(420);
+            matches.push(m);
+        }
+
+        let filter = MatchFilter {
+            enemy_champion: Some("garen".to_string()),
+            ..Default::default()
+        };
+        let garen = filter_matches(&matches, &filter);
+        assert_eq!(garen.len(), 3);
+        assert!(garen[0].date > garen[1].date);
+
+        let filter = MatchFilter {
+            result: Some(MatchResult::Loss),
+            queue_id: Some(420),
+            ..Default::default()
+        };
+        assert_eq!(filter_matches(&matches, &filter).len(), 3);
+
+        let filter = MatchFilter {
+            from: Some(Utc::now() - chrono::Duration::hours(36)),
+            ..Default::default()
+        };
+        assert_eq!(filter_matches(&matches, &filter).len(), 2);
+
+        let page = MatchFilter {
+            offset: Some(1),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let paged = filter_matches(&matches, &page);
+        assert_eq!(paged.len(), 2);
+        assert_eq!(paged[0].id, matches[1].id);
+
+        // Games without an account show up under any account
+        matches[0].account_puuid = Some("main".to_string());
+        matches[1].account_puuid = Some("smurf".to_string());
+        let filter = MatchFilter {
+            account: Some("main".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter_matches(&matches, &filter).len(), 4);
+    }
+
+    #[test]
+    fn test_confidence() {
+        let now = Utc::now();
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        // Fresh notes, no games: only the recency component
+        assert_eq!(matchup.confidence(&[], now), 30.0);
+
+        let win = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        let matches = vec![win; 10];
+        assert_eq!(matchup.confidence(&matches, now), 100.0);
+
+        matchup.versions[0].date = now - chrono::Duration::days(120);
+        assert_eq!(matchup.confidence(&matches, now), 70.0);
+
+        let filter = MatchupFilter {
+            min_confidence: Some(50.0),
+            ..Default::default()
+        };
+        assert!(filter.matches_confidence(70.0));
+        assert!(!filter.matches_confidence(30.0));
+    }
+
+    #[test]
+    fn test_checklist() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let level2 = matchup.add_checklist_item("Respect level 2".to_string());
+        let refill = matchup.add_checklist_item("Buy refillable".to_string());
+
+        assert!(matchup.toggle_checklist_item(&level2.id).unwrap().checked);
+        assert!(matchup.toggle_checklist_item(&refill.id).unwrap().checked);
+        assert!(!matchup.toggle_checklist_item(&refill.id).unwrap().checked);
+
+        matchup.reset_checklist();
+        assert!(matchup.checklist.iter().all(|i| !i.checked));
+
+        assert!(matchup.remove_checklist_item(&level2.id));
+        assert!(!matchup.remove_checklist_item(&level2.id));
+        assert_eq!(matchup.checklist.len(), 1);
+    }
+
+    #[test]
+    fn test_goals() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+
+        let goal = matchup.add_goal("Don't die pre-6".to_string());
+        assert_eq!(matchup.goals.len(), 1);
+        assert!(!matchup.goals[0].done);
+
+        let completed = matchup.set_goal_done(&goal.id, true).unwrap();
+        assert!(completed.done);
+        let completed_at = completed.completed_at;
+        assert!(completed_at.is_some());
+        let again = matchup.set_goal_done(&goal.id, true).unwrap();
+        assert_eq!(again.completed_at, completed_at);
+
+        let reopened = matchup.set_goal_done(&goal.id, false).unwrap();
+        assert!(!reopened.done);
+        assert!(reopened.completed_at.is_none());
+
+        assert!(matchup.set_goal_done("missing", true).is_none());
+        assert!(matchup.remove_goal(&goal.id));
+        assert!(matchup.goals.is_empty());
+    }
+
+    #[test]
+    fn test_vod_bookmarks() {
+        assert!(VodRef::new("twitch.tv/videos/1").is_err());
+        let vod = VodRef::new("https://www.twitch.tv/videos/1").unwrap();
+        assert_eq!(vod.url_at(3725), "https://www.twitch.tv/videos/1?t=1h2m5s");
+        let youtube = VodRef::new("https://www.youtube.com/watch?v=abc").unwrap();
+        assert_eq!(
+            youtube.url_at(90),
+            "https://www.youtube.com/watch?v=abc&t=90"
+        );
+
+        let mut m = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Loss,
+            None,
+        );
+        m.vod_refs.push(vod.clone());
+        m.add_vod_bookmark(&vod.id, 600, "Died to gank".to_string())
+            .unwrap();
+        let first = m
+            .add_vod_bookmark(&vod.id, 120, "Level 2 trade".to_string())
+            .unwrap();
+        assert!(m.add_vod_bookmark("missing", 1, "x".to_string()).is_none());
+        assert_eq!(m.vod_refs[0].bookmarks[0].id, first.id);
+
+        assert!(m.remove_vod_bookmark(&first.id));
+        assert_eq!(m.vod_refs[0].bookmarks.len(), 1);
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.add_version(MatchupUpdate::default());
+
+        let comment = matchup
+            .add_comment(
+                1,
+                "Coach".to_string(),
+                "Mention his ghost timer".to_string(),
+            )
+            .unwrap();
+        assert!(matchup
+            .add_comment(3, "Coach".to_string(), "No such version".to_string())
+            .is_none());
+        assert_eq!(matchup.versions[0].comments.len(), 1);
+        assert!(matchup.current().unwrap().comments.is_empty());
+
+        assert!(matchup.remove_comment(&comment.id));
+        assert!(!matchup.remove_comment(&comment.id));
+    }
+
+    #[test]
+    fn test_build_text() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        assert!(matchup.build_text(|i| i.to_string()).is_none());
+
+        matchup.add_version(MatchupUpdate {
+            runes: vec!["Conqueror".to_string(), "Resolve".to_string()],
+            summoner_spells: vec!["Flash".to_string(), "Ghost".to_string()],
+            items: vec!["3078".to_string(), "Sterak's Gage".to_string()],
+            patch: Some("14.12".to_string()),
+            ..Default::default()
+        });
+        let text = matchup
+            .build_text(|i| match i {
+                "3078" => "Trinity Force".to_string(),
+                other => other.to_string(),
+            })
+            .unwrap();
+        assert_eq!(
+            text,
+            "Darius vs Garen (top) · 14.12\nRunes: Conqueror, Resolve\nSpells: Flash / Ghost\nBuild: Trinity Force > Sterak's Gage"
+        );
+    }
+
+    #[test]
+    fn test_apply_bulk() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.versions[0].source_url = Some("https://example.com/guide".to_string());
+        matchup.versions[0].author = Some("Coach".to_string());
+
+        let add = BulkOperation::AddTag {
+            tag: "easy".to_string(),
+        };
+        assert!(matchup.apply_bulk(&add));
+        assert!(!matchup.apply_bulk(&add));
+        assert_eq!(matchup.versions.len(), 2);
+        assert_eq!(matchup.current().unwrap().tags, vec!["easy"]);
+        // The tagged version is my edit, not the guide's
+        assert_eq!(matchup.current().unwrap().source_url, None);
+        assert_eq!(matchup.current().unwrap().author, None);
+
+        let remove = BulkOperation::RemoveTag {
+            tag: "EASY".to_string(),
+        };
+        assert!(matchup.apply_bulk(&remove));
+        assert!(matchup.current().unwrap().tags.is_empty());
+
+        assert!(matchup.apply_bulk(&BulkOperation::SetRole {
+            role: "mid".to_string()
+        }));
+        assert_eq!(matchup.role, "mid");
+
+        assert!(matchup.apply_bulk(&BulkOperation::Archive));
+        assert!(!matchup.matches_filter(&MatchupFilter::default()));
+        let archived = MatchupFilter {
+            archived: Some(true),
+            ..Default::default()
+        };
+        assert!(matchup.matches_filter(&archived));
+
+        assert!(BulkOperation::AddTag {
+            tag: " ".to_string()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_find_matchup_for() {
+        let matchups = vec![
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string()),
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "mid".to_string()),
+        ];
+        let m = Match::new(
+            "darius".to_string(),
+            "Garen".to_string(),
+            "mid".to_string(),
+            MatchResult::Win,
+            None,
+        );
+
+        let found = find_matchup_for(&matchups, &m).unwrap();
+        assert_eq!(found.id, matchups[1].id);
+
+        let other = Match::new(
+            "Darius".to_string(),
+            "Sett".to_string(),
+            "top".to_string(),
+            MatchResult::Loss,
+            None,
+        );
+        assert!(find_matchup_for(&matchups, &other).is_none());
+    }
+
+    #[test]
+    fn test_matchup_stats() {
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let now = Utc::now();
+
+        let mut matches = Vec::new();
+        for (days_ago, result, side) in [
+            (3, MatchResult::Loss, Some(Side::Red)),
+            (1, MatchResult::Win, Some(Side::Blue)),
+            (2, MatchResult::Win, None),
+        ] {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = now - chrono::Duration::days(days_ago);
+            m.linked_matchup = Some(matchup.id.clone());
+            m.side = side;
+            matches.push(m);
+        }
+        matches[1].pick = Some(PickIntent {
+            intended: None,
+            locked: "Darius".to_string(),
+            counter_picked: false,
+            order: Some(PickOrder::Counter),
+        });
+        // Same pairing but not linked, so it doesn't count
+        matches.push(Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Loss,
+            None,
+        ));
+
+        let stats = matchup.stats(&matches);
+        assert_eq!(stats.games, 3);
+        assert_eq!(stats.wins, 2);
+        assert_eq!(stats.losses, 1);
+        assert_eq!(stats.win_rate, Some(2.0 / 3.0));
+        assert_eq!(
+            stats.recent_form,
+            vec![MatchResult::Win, MatchResult::Win, MatchResult::Loss]
+        );
+        assert_eq!(stats.blue_side, MatchRecord { games: 1, wins: 1 });
+        assert_eq!(stats.red_side, MatchRecord { games: 1, wins: 0 });
+        assert_eq!(stats.counter_pick, MatchRecord { games: 1, wins: 1 });
+        assert_eq!(stats.blind_pick, MatchRecord::default());
+
+        let linked = matchup.linked_matches(&matches);
+        assert_eq!(linked.len(), 3);
+        assert!(linked[0].date > linked[1].date && linked[1].date > linked[2].date);
+    }
+
+    #[test]
+    fn test_stats_by_patch() {
+        let mut matches = Vec::new();
+        for (patch, result) in [
+            (Some("14.10"), MatchResult::Win),
+            (Some("14.9"), MatchResult::Loss),
+            (Some("14.10"), MatchResult::Loss),
+            (None, MatchResult::Win),
+        ] {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.patch = patch.map(str::to_string);
+            matches.push(m);
+        }
+
+        let stats = stats_by_patch(&matches);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].patch, "14.9");
+        assert_eq!(stats[0].games, 1);
+        assert_eq!(stats[1].patch, "14.10");
+        assert_eq!(stats[1].games, 2);
+        assert_eq!(stats[1].win_rate, Some(0.5));
+    }
+
+    #[test]
+    fn test_stats_by_length() {
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut matches = Vec::new();
+        for (minutes, result) in [
+            (Some(18), MatchResult::Win),
+            (Some(25), MatchResult::Loss),
+            (Some(35), MatchResult::Loss),
+            (Some(30), MatchResult::Win),
+            (None, MatchResult::Win),
+            // A remake
+            (Some(3), MatchResult::Loss),
+        ] {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.stats = minutes.map(|minutes| MatchStats {
+                game_duration: minutes * 60,
+                ..Default::default()
+            });
+            m.linked_matchup = Some(matchup.id.clone());
+            matches.push(m);
+        }
+
+        let stats = stats_by_length(&matches);
+        assert_eq!(
+            stats
+                .iter()
+                .map(|s| (s.length, s.games, s.wins))
+                .collect::<Vec<_>>(),
+            vec![
+                (GameLength::Short, 1, 1),
+                (GameLength::Medium, 1, 0),
+                (GameLength::Long, 2, 1)
+            ]
+        );
+
+        let matchups = HashMap::from([(matchup.id.clone(), matchup.clone())]);
+        let by_matchup = length_stats_by_matchup(&matchups, &matches);
+        assert_eq!(by_matchup.len(), 1);
+        assert_eq!(by_matchup[0].lengths, stats);
+    }
+
+    #[test]
+    fn test_queue_filter() {
+        let mut ranked = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        ranked.queue = Some(QueueType::RankedSolo);
+
+        // Imported before the queue type was stored
+        let mut normal = ranked.clone();
+        normal.queue = None;
+        normal.queue_id = Some(430);
+
+        let filter = MatchFilter {
+            queue: Some(QueueType::Normal),
+            ..Default::default()
+        };
+        assert!(!ranked.matches_filter(&filter));
+        assert!(normal.matches_filter(&filter));
+        assert_eq!(QueueType::from_queue_id(1700), QueueType::Other);
+    }
+
+    #[test]
+    fn test_post_game_review_validation() {
+        let blank = NewPostGameReview {
+            went_well: " ".to_string(),
+            mistake: String::new(),
+            try_next: String::new(),
+        };
+        assert!(blank.validate().is_err());
+
+        let review = NewPostGameReview {
+            mistake: "Fought without lethal tempo stacks".to_string(),
+            ..blank
+        };
+        assert!(review.validate().is_ok());
+    }
+
+    #[test]
+    fn test_games_since_touched() {
+        let mut matchup =
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let created = matchup.versions[0].date;
+        let matches: Vec<Match> = [-1, 1, 2]
+            .into_iter()
+            .map(|days_after| {
+                let mut m = Match::new(
+                    "Darius".to_string(),
+                    "Garen".to_string(),
+                    "top".to_string(),
+                    MatchResult::Win,
+                    None,
+                );
+                m.date = created + chrono::Duration::days(days_after);
+                m.linked_matchup = Some(matchup.id.clone());
+                m
+            })
+            .collect();
+        assert_eq!(matchup.games_since_touched(&matches), 2);
+
+        matchup.last_reviewed =
+            Some(created + chrono::Duration::days(1) + chrono::Duration::hours(1));
+        assert_eq!(matchup.games_since_touched(&matches), 1);
+    }
+
+    #[test]
+    fn test_early_game_impact() {
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let matches: Vec<Match> = [
+            (Some(800), 0, MatchResult::Win),
+            (Some(-900), 2, MatchResult::Loss),
+            (Some(-600), 1, MatchResult::Loss),
+            (Some(100), 0, MatchResult::Loss),
+            (None, 0, MatchResult::Win),
+        ]
+        .into_iter()
+        .map(|(gold_diff, deaths, result)| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.linked_matchup = Some(matchup.id.clone());
+            m.early = gold_diff.map(|diff| EarlyGameStats {
+                gold_diff_at_10: Some(diff),
+                deaths_before_10: Some(deaths),
+                ..Default::default()
+            });
+            m
+        })
+        .collect();
+
+        let impacts = early_game_impact([&matchup], &matches);
+        assert_eq!(impacts.len(), 1);
+        let impact = &impacts[0];
+        assert_eq!(impact.games, 4);
+        assert_eq!(impact.ahead, MatchRecord { games: 1, wins: 1 });
+        assert_eq!(impact.behind, MatchRecord { games: 2, wins: 0 });
+        assert_eq!(impact.died_early, MatchRecord { games: 2, wins: 0 });
+        assert_eq!((impact.lost_in_lane, impact.lost_later), (2, 1));
+    }
+
+    #[test]
+    fn test_matchup_benchmark() {
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let game = |linked: bool, kills: u32, cs: u32| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                if linked { "Garen" } else { "Teemo" }.to_string(),
+                "top".to_string(),
+                MatchResult::Loss,
+                None,
+            );
+            m.linked_matchup = linked.then(|| matchup.id.clone());
+            m.stats = Some(MatchStats {
+                kills,
+                deaths: 2,
+                cs,
+                damage_to_champions: 30_000,
+                game_duration: 1800,
+                ..Default::default()
+            });
+            m
+        };
+        let matches = vec![
+            game(false, 8, 240),
+            game(false, 8, 240),
+            game(true, 2, 150),
+            game(true, 8, 240),
+        ];
+
+        let benchmark = matchup.benchmark(&matches);
+        let baseline = benchmark.baseline.unwrap();
+        assert_eq!(baseline.games, 2);
+        assert_eq!(baseline.cs_per_minute, 8.0);
+        assert_eq!(benchmark.games.len(), 2);
+
+        let performances: Vec<Option<Performance>> =
+            benchmark.games.iter().map(|g| g.performance).collect();
+        assert!(performances.contains(&Some(Performance::Below)));
+        assert!(performances.contains(&Some(Performance::Usual)));
+        assert_eq!(benchmark.matchup.unwrap().games, 2);
+    }
+
+    #[test]
+    fn test_note_impact() {
+        let mut matchup =
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let created = matchup.versions[0].date;
+        matchup.add_version(MatchupUpdate {
+            notes: "Respect level 2".to_string(),
+            ..Default::default()
+        });
+        matchup.versions[1].date = created + chrono::Duration::days(5);
+        // Tagging keeps the notes, so it isn't a rewrite
+        matchup.add_version(MatchupUpdate {
+            notes: "Respect level 2".to_string(),
+            tags: vec!["easy".to_string()],
+            ..Default::default()
+        });
+        matchup.versions[2].date = created + chrono::Duration::days(7);
+
+        let matches: Vec<Match> = [
+            (-1, MatchResult::Loss),
+            (1, MatchResult::Win),
+            (6, MatchResult::Win),
+            (8, MatchResult::Win),
+        ]
+        .into_iter()
+        .map(|(days_after, result)| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = created + chrono::Duration::days(days_after);
+            m.linked_matchup = Some(matchup.id.clone());
+            m
+        })
+        .collect();
+
+        let impact = matchup.note_impact(&matches);
+        assert_eq!(impact.len(), 1);
+        assert_eq!(impact[0].version, 2);
+        assert_eq!(impact[0].before, MatchRecord { games: 2, wins: 1 });
+        assert_eq!(impact[0].after, MatchRecord { games: 2, wins: 2 });
+        assert_eq!(impact[0].win_rate_delta, Some(0.5));
+    }
+
+    #[test]
+    fn test_matchup_progress() {
+        let mut matchup =
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let created = matchup.versions[0].date;
+
+        let game = |days_after: i64, result: MatchResult| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = created + chrono::Duration::days(days_after);
+            m.linked_matchup = Some(matchup.id.clone());
+            m
+        };
+        let mut late = game(10, MatchResult::Win);
+        late.early = Some(EarlyGameStats {
+            cs_at_10: 85,
+            deaths_before_10: Some(0),
+            ..Default::default()
+        });
+        let matches = vec![late, game(1, MatchResult::Loss)];
+
+        matchup.add_version(MatchupUpdate {
+            notes: "Respect level 2".to_string(),
+            ..Default::default()
+        });
+        matchup.versions[1].date = created + chrono::Duration::days(5);
+
+        let progress = matchup.progress(&matches);
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[0].result, MatchResult::Loss);
+        assert_eq!(progress[0].notes_version, Some(1));
+        assert_eq!(progress[1].notes_version, Some(2));
+        assert_eq!(progress[1].cs_at_10, Some(85));
+        assert_eq!(progress[1].deaths_before_10, Some(0));
+    }
+
+    #[test]
+    fn test_copy_loadout() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.add_version(MatchupUpdate {
+            notes: "Take short trades".to_string(),
+            ..Default::default()
+        });
+
+        matchup.copy_loadout(&GameLoadout {
+            primary_tree: Some(8000),
+            secondary_tree: Some(8400),
+            runes: vec![8010, 9111, 9104, 8299, 8444, 8242],
+            summoner_spells: vec![4, 12],
+        });
+
+        let current = matchup.current().unwrap();
+        assert_eq!(matchup.current_version, 3);
+        assert_eq!(current.notes, "Take short trades");
+        assert_eq!(current.runes, vec!["Conqueror", "Precision", "Resolve"]);
+        assert_eq!(current.summoner_spells, vec!["Flash", "Teleport"]);
+        // Six runes only, without stat shards, can't make a full page
+        assert!(current.rune_page.is_none());
+    }
+
+    #[test]
+    fn test_rune_page_validation() {
+        let page = RunePage {
+            primary_style_id: 8000,
+            sub_style_id: 8400,
+            selected_perk_ids: vec![8010, 9111, 9104, 8299, 8444, 8242, 5005, 5008, 5011],
+        };
+        assert!(page.validate().is_ok());
+
+        let same_trees = RunePage {
+            sub_style_id: 8000,
+            ..page.clone()
+        };
+        assert!(same_trees.validate().is_err());
+
+        let short = RunePage {
+            selected_perk_ids: vec![8010],
+            ..page
+        };
+        assert!(short.validate().is_err());
+    }
+
+    #[test]
+    fn test_set_rune_page() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        let page = RunePage {
+            primary_style_id: 8000,
+            sub_style_id: 8400,
+            selected_perk_ids: vec![8010, 9111, 9104, 8299, 8444, 8242, 5005, 5008, 5011],
+        };
+
+        matchup.set_rune_page(page.clone(), true);
+        assert_eq!(matchup.versions.len(), 1);
+        assert_eq!(matchup.current().unwrap().rune_page, Some(page.clone()));
+
+        matchup.set_rune_page(page, false);
+        assert_eq!(matchup.versions.len(), 2);
+        assert_eq!(
+            matchup.current().unwrap().runes,
+            vec!["Conqueror", "Precision", "Resolve"]
+        );
+    }
+
+    #[test]
+    fn test_append_note() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        let at = Utc.with_ymd_and_hms(2024, 6, 1, 18, 30, 0).unwrap();
+
+        assert_eq!(matchup.append_note("  ", at), None);
+        let line = matchup.append_note("ghost  before\nlevel 6", at).unwrap();
+        assert_eq!(line, "- [2024-06-01 18:30] ghost before level 6");
+        matchup.append_note("ward tri-brush", at);
+        assert_eq!(matchup.versions.len(), 1);
+        assert_eq!(
+            matchup.current().unwrap().notes,
+            "- [2024-06-01 18:30] ghost before level 6\n- [2024-06-01 18:30] ward tri-brush"
+        );
+    }
+
+    #[test]
+    fn test_min_tier_filter() {
+        let mut m = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        let filter = MatchFilter {
+            min_tier: Some(Tier::Platinum),
+            ..Default::default()
+        };
+        assert!(!m.matches_filter(&filter));
+
+        m.rank = Some(RankInfo {
+            tier: Tier::Emerald,
+            division: "IV".to_string(),
+            league_points: 12,
+        });
+        assert!(m.matches_filter(&filter));
+    }
+
+    #[test]
+    fn test_import_options() {
+        let options = ImportOptions {
+            skip_aram: true,
+            skip_bots: true,
+            ..Default::default()
+        };
+        assert!(options.allows(420, GameMode::Classic, 0));
+        assert!(!options.allows(450, GameMode::Aram, 0));
+        assert!(!options.allows(870, GameMode::Classic, 0));
+
+        let options = ImportOptions {
+            queue_ids: vec![420],
+            since: DateTime::from_timestamp_millis(1_000),
+            ..Default::default()
+        };
+        assert!(options.allows(420, GameMode::Classic, 1_000));
+        assert!(!options.allows(420, GameMode::Classic, 999));
+        assert!(!options.allows(440, GameMode::Classic, 2_000));
+    }
+
+    #[test]
+    fn test_merge_imported() {
+        let game = || {
+            Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                MatchResult::Win,
+                Some("123".to_string()),
+            )
+        };
+        let mut stored = game();
+        stored.enemy_champion = "Unknown".to_string();
+        stored.notes = "Won lane".to_string();
+        stored.linked_matchup = Some("matchup".to_string());
+        stored.early = Some(EarlyGameStats::default());
+
+        let mut fresh = game();
+        fresh.stats = Some(MatchStats {
+            kills: 7,
+            ..Default::default()
+        });
+        let id = stored.id.clone();
+        // Linked by hand, as no matchup is for the unknown opponent
+        assert!(!stored.merge_imported(fresh.clone(), &[]));
+
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.enemy_champion, "Garen");
+        assert_eq!(stored.stats.as_ref().map(|s| s.kills), Some(7));
+        assert_eq!(stored.notes, "Won lane");
+        assert_eq!(stored.linked_matchup.as_deref(), Some("matchup"));
+        assert!(stored.early.is_some());
+
+        // The matchup the first import picked no longer fits
+        let sett = Matchup::new("Darius".to_string(), "Sett".to_string(), "top".to_string());
+        let mut stored = game();
+        stored.enemy_champion = "Sett".to_string();
+        stored.linked_matchup = Some(sett.id.clone());
+        assert!(stored.merge_imported(fresh.clone(), [&sett]));
+        assert_eq!(stored.linked_matchup, None);
+        assert!(!stored.merge_imported(fresh, [&sett]));
+    }
+
+    #[test]
+    fn test_relevant_matchups() {
+        let matchups: Vec<Matchup> = ["Garen", "Teemo", "Sett", "Yasuo", "Aatrox"]
+            .iter()
+            .map(|enemy| Matchup::new("Darius".to_string(), enemy.to_string(), "top".to_string()))
+            .chain(std::iter::once(Matchup::new(
+                "Darius".to_string(),
+                "Ahri".to_string(),
+                "mid".to_string(),
+            )))
+            .collect();
+        let matches: Vec<Match> = ["Aatrox", "Aatrox", "Sett"]
+            .iter()
+            .map(|enemy| {
+                Match::new(
+                    "Darius".to_string(),
+                    enemy.to_string(),
+                    "top".to_string(),
+                    MatchResult::Win,
+                    None,
+                )
+            })
+            .collect();
+
+        let relevant = relevant_matchups(
+            &matchups,
+            &matches,
+            "Darius",
+            "top",
+            Some("Garen"),
+            &["Garen".to_string(), "Teemo".to_string()],
+            &["Yasuo".to_string()],
+        );
+        let enemies: Vec<&str> = relevant.iter().map(|m| m.enemy_champion.as_str()).collect();
+        assert_eq!(enemies, vec!["Garen", "Teemo", "Aatrox", "Sett"]);
+    }
+
+    #[test]
+    fn test_worst_matchups() {
+        let matches: Vec<Match> = [
+            ("Teemo", MatchResult::Loss),
+            ("Teemo", MatchResult::Loss),
+            ("Garen", MatchResult::Win),
+            ("Garen", MatchResult::Loss),
+            ("Sett", MatchResult::Loss),
+        ]
+        .into_iter()
+        .map(|(enemy, result)| {
+            Match::new(
+                "Darius".to_string(),
+                enemy.to_string(),
+                "top".to_string(),
+                result,
+                None,
+            )
+        })
+        .collect();
+
+        let worst = worst_matchups(&matches, "Darius", Some("top"));
+        let enemies: Vec<&str> = worst.iter().map(|r| r.enemy_champion.as_str()).collect();
+        // Sett has too few games to count
+        assert_eq!(enemies, vec!["Teemo", "Garen"]);
+        assert!(worst_matchups(&matches, "Darius", Some("mid")).is_empty());
+    }
+
+    #[test]
+    fn test_pick_suggestions() {
+        let game = |mine: &str, result: MatchResult| {
+            Match::new(
+                mine.to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            )
+        };
+        let matches = vec![
+            game("Darius", MatchResult::Win),
+            game("Darius", MatchResult::Win),
+            game("Sett", MatchResult::Win),
+            game("Sett", MatchResult::Loss),
+            game("Sett", MatchResult::Loss),
+        ];
+        let matchups = vec![
+            Matchup::new(
+                "Mordekaiser".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+            ),
+            Matchup::new("Teemo".to_string(), "Garen".to_string(), "mid".to_string()),
+        ];
+
+        let no_roles = HashMap::new();
+        let picks = pick_suggestions(&matchups, &matches, &[], &no_roles, "Garen", "top", &[]);
+        let names: Vec<&str> = picks.iter().map(|p| p.champion.as_str()).collect();
+        assert_eq!(names, vec!["Darius", "Mordekaiser", "Sett"]);
+        assert!(picks[1].matchup_id.is_some());
+
+        let roles = HashMap::from([(
+            "Sett".to_string(),
+            ChampionRoles {
+                primary: " Support".to_string(),
+                secondary: vec!["mid".to_string(), "support".to_string()],
+            }
+            .normalize()
+            .unwrap(),
+        )]);
+        assert_eq!(roles["Sett"].secondary, vec!["mid"]);
+        let picks = pick_suggestions(&matchups, &matches, &[], &roles, "Garen", "top", &[]);
+        assert_eq!(picks.len(), 2);
+
+        let pool = vec!["Sett".to_string(), "Malphite".to_string()];
+        let banned = vec!["Malphite".to_string()];
+        let picks = pick_suggestions(
+            &matchups, &matches, &pool, &no_roles, "Garen", "top", &banned,
+        );
+        assert_eq!(picks.len(), 1);
+        assert_eq!(picks[0].record, MatchRecord { games: 3, wins: 1 });
+    }
+
+    #[test]
+    fn test_matchup_suggestions() {
+        let game = |enemy: &str, result: MatchResult| {
+            Match::new(
+                "Darius".to_string(),
+                enemy.to_string(),
+                "top".to_string(),
+                result,
+                None,
+            )
+        };
+        let matches = vec![
+            game("Garen", MatchResult::Win),
+            game("Sett", MatchResult::Win),
+            game("Sett", MatchResult::Loss),
+            game("Teemo", MatchResult::Loss),
+            game("Unknown", MatchResult::Loss),
+        ];
+        let matchups = vec![Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        )];
+
+        let suggestions = matchup_suggestions(&matchups, &matches);
+        let enemies: Vec<&str> = suggestions
+            .iter()
+            .map(|s| s.enemy_champion.as_str())
+            .collect();
+        assert_eq!(enemies, vec!["Sett", "Teemo"]);
+        assert_eq!(suggestions[0].record, MatchRecord { games: 2, wins: 1 });
+    }
+
+    #[test]
+    fn test_opponent_history() {
+        let matches: Vec<Match> = [
+            (Some("Rival#EUW"), MatchResult::Win),
+            (Some("rival#euw"), MatchResult::Loss),
+            (Some("Rival#EUW"), MatchResult::Loss),
+            (Some("Someone#EUW"), MatchResult::Win),
+            (None, MatchResult::Win),
+        ]
+        .into_iter()
+        .map(|(riot_id, result)| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.enemy_riot_id = riot_id.map(str::to_string);
+            m
+        })
+        .collect();
+
+        let history = opponent_history(&matches, "RIVAL#EUW");
+        assert_eq!(history.record, MatchRecord { games: 3, wins: 1 });
+        assert_eq!(history.matches.len(), 3);
+
+        let repeats = repeat_opponents(&matches, &matches[2..4]);
+        assert_eq!(repeats.len(), 1);
+        assert_eq!(repeats[&matches[2].id], MatchRecord { games: 3, wins: 1 });
+    }
+
+    #[test]
+    fn test_winrate_timeseries() {
+        let game = |date: &str, result: MatchResult| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = date.parse().unwrap();
+            m
+        };
+        // Wednesday and Sunday of one week, then the next Monday
+        let matches = vec![
+            game("2024-05-01T10:00:00Z", MatchResult::Win),
+            game("2024-05-05T23:00:00Z", MatchResult::Loss),
+            game("2024-05-06T01:00:00Z", MatchResult::Win),
+        ];
+
+        let weeks = winrate_timeseries(&matches, Granularity::Week);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(
+            weeks[0].start,
+            "2024-04-29T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(weeks[0].record, MatchRecord { games: 2, wins: 1 });
+        assert_eq!(weeks[1].win_rate, Some(1.0));
+
+        let months = winrate_timeseries(&matches, Granularity::Month);
+        assert_eq!(months.len(), 1);
+        assert_eq!(
+            months[0].start,
+            "2024-05-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pool_distribution() {
+        let matches: Vec<Match> = [
+            ("Darius", "top", MatchResult::Win),
+            ("Darius", "top", MatchResult::Win),
+            ("Darius", "top", MatchResult::Loss),
+            ("Garen", "top", MatchResult::Win),
+            ("Ahri", "mid", MatchResult::Loss),
+            ("Ahri", "mid", MatchResult::Loss),
+        ]
+        .into_iter()
+        .map(|(champion, role, result)| {
+            Match::new(
+                champion.to_string(),
+                "Teemo".to_string(),
+                role.to_string(),
+                result,
+                None,
+            )
+        })
+        .collect();
+
+        let pool = vec!["darius".to_string(), "Garen".to_string()];
+        let distribution = pool_distribution(&matches, &pool);
+        assert_eq!(distribution.games, 6);
+        assert_eq!(distribution.main_role.as_deref(), Some("top"));
+        assert_eq!(distribution.on_role, MatchRecord { games: 4, wins: 3 });
+        assert_eq!(distribution.off_role, MatchRecord { games: 2, wins: 0 });
+        assert_eq!(
+            distribution.off_pool,
+            Some(MatchRecord { games: 2, wins: 0 })
+        );
+        assert_eq!(distribution.by_champion[0].name, "Darius");
+        assert_eq!(distribution.by_champion[0].share, 0.5);
+        // 0.5² + (1/3)² + (1/6)²
+        assert!((distribution.one_trick_index - 14.0 / 36.0).abs() < 1e-9);
+
+        assert_eq!(pool_distribution(&matches, &[]).in_pool, None);
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let matches: Vec<Match> = [
+            ("2024-05-06T18:00:00Z", MatchResult::Win),
+            ("2024-05-06T18:40:00Z", MatchResult::Win),
+            ("2024-05-06T19:30:00Z", MatchResult::Loss),
+            ("2024-05-07T18:10:00Z", MatchResult::Loss),
+        ]
+        .into_iter()
+        .map(|(date, result)| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.date = date.parse().unwrap();
+            m
+        })
+        .collect();
+
+        let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let stats = session_stats(&matches, &offset);
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.by_hour[0].bucket, 20);
+        assert_eq!(stats.by_hour[0].record, MatchRecord { games: 3, wins: 2 });
+        assert_eq!(stats.by_hour[1].bucket, 21);
+        assert_eq!(
+            stats.by_weekday[0].record,
+            MatchRecord { games: 3, wins: 2 }
+        );
+        assert_eq!(stats.by_weekday[1].bucket, 1);
+        assert_eq!(
+            stats.by_session_game[0].record,
+            MatchRecord { games: 2, wins: 1 }
+        );
+        assert_eq!(stats.by_session_game[2].bucket, 3);
+        assert_eq!(stats.by_session_game[2].win_rate, Some(0.0));
+    }
+
+    #[test]
+    fn test_champion_stats() {
+        let game = |enemy: &str, result: MatchResult, kills: u32| {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                enemy.to_string(),
+                "top".to_string(),
+                result,
+                None,
+            );
+            m.stats = Some(MatchStats {
+                kills,
+                deaths: 2,
+                cs: 180,
+                game_duration: 1800,
+                ..Default::default()
+            });
+            m
+        };
+        let matches = vec![
+            game("Garen", MatchResult::Win, 10),
+            game("Garen", MatchResult::Win, 6),
+            game("Teemo", MatchResult::Loss, 2),
+            game("Teemo", MatchResult::Loss, 2),
+            game("Teemo", MatchResult::Win, 4),
+            game("Sett", MatchResult::Win, 4),
+        ];
+
+        let stats = champion_stats(&matches, "darius");
+        assert_eq!(stats.my_champion, "Darius");
+        assert_eq!(stats.record, MatchRecord { games: 6, wins: 4 });
+        assert_eq!(stats.best_matchups.len(), 1);
+        assert_eq!(stats.best_matchups[0].enemy_champion, "Garen");
+        assert_eq!(stats.worst_matchups[0].enemy_champion, "Teemo");
+
+        let averages = stats.averages.unwrap();
+        assert_eq!(averages.games, 6);
+        assert_eq!(averages.kills, 28.0 / 6.0);
+        assert_eq!(averages.cs_per_minute, 6.0);
+        assert_eq!(averages.kda, Some(28.0 / 12.0));
+    }
+
+    #[test]
+    fn test_player_notes_kept_apart() {
+        let mine = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut student = mine.clone();
+        student.id = "student-matchup".to_string();
+        student.player = Some("student".to_string());
+        let matchups = vec![mine.clone(), student.clone()];
+
+        let filter = MatchupFilter::default();
+        assert!(mine.matches_filter(&filter));
+        assert!(!student.matches_filter(&filter));
+        let filter = MatchupFilter {
+            player: PlayerScope::Player("student".to_string()),
+            ..Default::default()
+        };
+        assert!(student.matches_filter(&filter));
+
+        // The coach can still ask for their own notes while the student is active
+        let metadata = Metadata {
+            active_player: Some("student".to_string()),
+            ..Default::default()
+        };
+        let filter = MatchupFilter::default().with_defaults(&metadata);
+        assert!(student.matches_filter(&filter));
+        assert!(!mine.matches_filter(&filter));
+        let filter = MatchupFilter {
+            player: PlayerScope::Me,
+            ..Default::default()
+        }
+        .with_defaults(&metadata);
+        assert!(mine.matches_filter(&filter));
+
+        let mut m = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        assert_eq!(find_matchup_for(&matchups, &m).unwrap().id, mine.id);
+        m.player = Some("student".to_string());
+        assert_eq!(find_matchup_for(&matchups, &m).unwrap().id, student.id);
+        assert!(!m.matches_filter(&MatchFilter::default()));
+    }
+
+    #[test]
+    fn test_matchups_against() {
+        let matchups = vec![
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string()),
+            Matchup::new("Sett".to_string(), "Garen".to_string(), "top".to_string()),
+            Matchup::new("Darius".to_string(), "Garen".to_string(), "mid".to_string()),
+            Matchup::new("Darius".to_string(), "Teemo".to_string(), "top".to_string()),
+        ];
+
+        assert_eq!(
+            matchups_against(&matchups, "Garen", None, Some("top")).len(),
+            2
+        );
+        assert_eq!(
+            matchups_against(&matchups, "garen", Some("Darius"), None).len(),
+            2
+        );
+        assert_eq!(
+            matchups_against(&matchups, "Garen", Some("Darius"), Some("top")).len(),
+            1
+        );
+    }
 }
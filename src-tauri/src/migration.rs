@@ -0,0 +1,348 @@
+//! Finding and importing notes kept outside the current data file
+//!
+//! Two kinds of files are recognised: the app's own data files (copies left by
+//! older versions, backups and exports) and flat JSON lists with one set of
+//! notes per matchup, as kept before notes had versions.
+
+use crate::champions;
+use crate::matchup::{Matchup, MatchupUpdate};
+use crate::storage::{AppData, Storage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Folder name of the app's data under the Tauri identifier, used by early versions
+const IDENTIFIER_DIR: &str = "com.matchuphelper.app";
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a {kind} file: {source}")]
+    Format {
+        kind: &'static str,
+        source: serde_json::Error,
+    },
+}
+
+/// Format of a file that can be imported
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// The app's data file, a backup or an export
+    AppData,
+    /// JSON list of `{ my_champion, enemy_champion, role, notes, tags }`
+    NotesList,
+}
+
+impl SourceKind {
+    fn label(self) -> &'static str {
+        match self {
+            SourceKind::AppData => "MatchupHelper data",
+            SourceKind::NotesList => "notes list",
+        }
+    }
+}
+
+/// A file found by `detect`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportSource {
+    pub path: PathBuf,
+    pub kind: SourceKind,
+    pub matchups: usize,
+    pub matches: usize,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// An entry of a notes list
+#[derive(Debug, Clone, Deserialize)]
+struct LegacyNote {
+    #[serde(alias = "champion")]
+    my_champion: String,
+    #[serde(alias = "enemy")]
+    enemy_champion: String,
+    role: String,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// A matchup left out of an import
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportConflict {
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub reason: String,
+}
+
+/// What `import` brought in and what it left out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ImportReport {
+    pub matchups: usize,
+    pub matches: usize,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+/// Importable files in the places older versions and exports are usually kept
+///
+/// The current data file is left out. Newest files come first.
+pub fn detect(storage: &Storage) -> Vec<ImportSource> {
+    let mut sources: Vec<ImportSource> = candidate_paths(storage)
+        .into_iter()
+        .filter(|path| path != storage.data_path())
+        .filter_map(|path| sniff(&path))
+        .collect();
+    sources.sort_by(|a, b| b.modified.cmp(&a.modified));
+    sources
+}
+
+fn candidate_paths(storage: &Storage) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // Data directories of older versions and the default one when it was moved
+    for base in [dirs::data_dir(), dirs::config_dir()].into_iter().flatten() {
+        for dir in ["matchuphelper", IDENTIFIER_DIR] {
+            let path = base.join(dir).join("data.json");
+            if path.exists() && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    paths.extend(json_files(&storage.backups_dir(), |_| true));
+
+    // Exports saved by hand
+    for dir in [
+        dirs::download_dir(),
+        dirs::document_dir(),
+        dirs::desktop_dir(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        paths.extend(json_files(&dir, |name| name.contains("matchup")));
+    }
+
+    paths
+}
+
+/// JSON files directly in `dir` whose lowercase name passes `accept`
+fn json_files(dir: &Path, accept: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_lowercase)
+                .is_some_and(|name| name.ends_with(".json") && accept(&name))
+        })
+        .collect()
+}
+
+/// Describe `path` if it holds something importable
+fn sniff(path: &Path) -> Option<ImportSource> {
+    let contents = fs::read_to_string(path).ok()?;
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+
+    let (kind, matchups, matches) = if let Ok(data) = serde_json::from_str::<AppData>(&contents) {
+        (SourceKind::AppData, data.matchups.len(), data.matches.len())
+    } else {
+        let notes: Vec<LegacyNote> = serde_json::from_str(&contents).ok()?;
+        (SourceKind::NotesList, notes.len(), 0)
+    };
+    if matchups == 0 && matches == 0 {
+        return None;
+    }
+
+    Some(ImportSource {
+        path: path.to_path_buf(),
+        kind,
+        matchups,
+        matches,
+        modified,
+    })
+}
+
+/// Read a source into the current schema
+///
+/// Notes list entries with unknown champions can't be mapped and are returned
+/// as conflicts.
+pub fn load(
+    path: &Path,
+    kind: SourceKind,
+) -> Result<(AppData, Vec<ImportConflict>), MigrationError> {
+    let contents = fs::read_to_string(path)?;
+    let format_error = |source| MigrationError::Format {
+        kind: kind.label(),
+        source,
+    };
+
+    match kind {
+        SourceKind::AppData => {
+            let mut data: AppData = serde_json::from_str(&contents).map_err(format_error)?;
+            data.upgrade();
+            Ok((data, Vec::new()))
+        }
+        SourceKind::NotesList => {
+            let notes: Vec<LegacyNote> = serde_json::from_str(&contents).map_err(format_error)?;
+            Ok(from_notes(notes))
+        }
+    }
+}
+
+fn from_notes(notes: Vec<LegacyNote>) -> (AppData, Vec<ImportConflict>) {
+    let mut data = AppData::default();
+    let mut conflicts = Vec::new();
+
+    for note in notes {
+        let role = note.role.trim().to_lowercase();
+        let champions = champions::canonicalize(&note.my_champion)
+            .and_then(|my| Ok((my, champions::canonicalize(&note.enemy_champion)?)));
+        let (my_champion, enemy_champion) = match champions {
+            Ok(names) => names,
+            Err(reason) => {
+                conflicts.push(ImportConflict {
+                    my_champion: note.my_champion,
+                    enemy_champion: note.enemy_champion,
+                    role,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let mut matchup = Matchup::new(my_champion, enemy_champion, role);
+        if !note.notes.trim().is_empty() || !note.tags.is_empty() {
+            matchup.add_version(MatchupUpdate {
+                notes: note.notes,
+                tags: note.tags,
+                ..Default::default()
+            });
+        }
+        data.matchups.insert(matchup.id.clone(), matchup);
+    }
+
+    (data, conflicts)
+}
+
+/// Add `imported` to `data`, leaving out matchups that already exist
+///
+/// A matchup exists when its ID or its champions and role are already taken;
+/// the existing one is kept and reported as a conflict. Games linked to a left
+/// out matchup are linked to the existing one instead. Games already present
+/// are skipped.
+pub fn import(data: &mut AppData, imported: AppData, report: &mut ImportReport) {
+    // Imported matchup ID to the ID its games should link to
+    let mut links = HashMap::new();
+
+    for (id, matchup) in imported.matchups {
+        let existing = data.matchups.values().find(|m| {
+            m.id == id || m.is_pairing(&matchup.my_champion, &matchup.enemy_champion, &matchup.role)
+        });
+        match existing {
+            Some(existing) => {
+                links.insert(id, existing.id.clone());
+                report.conflicts.push(ImportConflict {
+                    my_champion: matchup.my_champion,
+                    enemy_champion: matchup.enemy_champion,
+                    role: matchup.role,
+                    reason: "Already in your matchups, kept the current notes".to_string(),
+                });
+            }
+            None => {
+                links.insert(id.clone(), id.clone());
+                data.matchups.insert(id, matchup);
+                report.matchups += 1;
+            }
+        }
+    }
+
+    let matches = imported
+        .matches
+        .into_iter()
+        .map(|(id, mut m)| {
+            m.linked_matchup = m
+                .linked_matchup
+                .and_then(|linked| links.get(&linked).cloned());
+            (id, m)
+        })
+        .collect();
+    report.matches += data
+        .merge(AppData {
+            matches,
+            ..Default::default()
+        })
+        .matches;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::{Match, MatchResult};
+
+    #[test]
+    fn test_notes_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("matchups.json");
+        fs::write(
+            &path,
+            r#"[
+                { "champion": "darius", "enemy": "Garen", "role": "Top", "notes": "Fight at 3 stacks", "tags": ["even"] },
+                { "my_champion": "Nobody", "enemy_champion": "Garen", "role": "top" }
+            ]"#,
+        )
+        .unwrap();
+
+        let source = sniff(&path).unwrap();
+        assert_eq!(source.kind, SourceKind::NotesList);
+        assert_eq!(source.matchups, 2);
+
+        let (data, conflicts) = load(&path, SourceKind::NotesList).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        let matchup = data.matchups.values().next().unwrap();
+        assert!(matchup.is_pairing("Darius", "Garen", "top"));
+        assert_eq!(matchup.current().unwrap().notes, "Fight at 3 stacks");
+
+        assert!(load(&path, SourceKind::AppData).is_err());
+    }
+
+    #[test]
+    fn test_import_reports_conflicts() {
+        let existing = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut data = AppData::default();
+        data.matchups.insert(existing.id.clone(), existing.clone());
+
+        let mut imported = AppData::default();
+        let duplicate = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let new = Matchup::new("Riven".to_string(), "Fiora".to_string(), "top".to_string());
+        let mut game = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        game.linked_matchup = Some(duplicate.id.clone());
+        imported.matches.insert(game.id.clone(), game.clone());
+        imported.matchups.insert(duplicate.id.clone(), duplicate);
+        imported.matchups.insert(new.id.clone(), new);
+
+        let mut report = ImportReport::default();
+        import(&mut data, imported, &mut report);
+        assert_eq!((report.matchups, report.matches), (1, 1));
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(data.matchups.len(), 2);
+        assert_eq!(data.matches[&game.id].linked_matchup, Some(existing.id));
+    }
+}
@@ -0,0 +1,473 @@
+//! Background polling of the League client
+//!
+//! Runs on its own thread for the lifetime of the app and pushes what it sees
+//! to the frontend as events.
+
+use crate::error::AppError;
+use crate::events;
+use crate::lcu::{ChampSelectInfo, GameflowPhase, LcuError};
+use crate::matchup::{self, Matchup, PickIntent, PickOrder};
+use crate::notifications;
+use crate::overlay;
+use crate::presence;
+use crate::webhook;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the client is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Champ select state plus my notes for the detected pairing
+///
+/// `matchup` is `None` when there are no notes for the pairing yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChampSelectUpdate {
+    #[serde(flatten)]
+    pub session: ChampSelectInfo,
+    pub matchup: Option<Matchup>,
+}
+
+/// Notes for an enemy pick, sent in `enemy-picks-update` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemyPickNotes {
+    pub champion: String,
+    /// My matchups against it, for my champion and role when known
+    pub matchups: Vec<Matchup>,
+}
+
+/// Polls left to find the just-finished game in match history
+///
+/// Match history lags the end of game screen by a few seconds.
+const AUTO_IMPORT_ATTEMPTS: u32 = 10;
+
+/// Games checked by the post-game import
+const AUTO_IMPORT_COUNT: u32 = 5;
+
+/// Polls between reconnect attempts while the client is not found
+const RECONNECT_EVERY: u32 = 5;
+
+/// What the last poll saw, to only emit changes
+#[derive(Default)]
+struct Monitor {
+    connected: bool,
+    polls_until_reconnect: u32,
+    session: Option<ChampSelectInfo>,
+    phase: Option<GameflowPhase>,
+    import_attempts_left: u32,
+    /// Matchup picked in the last champ select, until its game loads
+    game_matchup: Option<String>,
+    /// "Riven vs Fiora (Top)" from the last champ select, for the Discord presence
+    game_pairing: Option<String>,
+    pick: PickTracker,
+    /// Picks of the last champ select, until its game is imported and the
+    /// enemy laner known
+    pending_pick: Option<PickTracker>,
+}
+
+/// What I hovered and locked during the current champ select
+#[derive(Default)]
+struct PickTracker {
+    intended: Option<String>,
+    locked: Option<String>,
+    /// Enemy picks locked in before mine
+    picked_before_me: Vec<String>,
+}
+
+impl PickTracker {
+    fn observe(&mut self, session: &ChampSelectInfo) {
+        if self.locked.is_some() {
+            return;
+        }
+        if session.locked {
+            self.locked = session.my_champion.clone();
+            self.picked_before_me = session.picked_before_me.clone();
+        } else if self.intended.is_none() {
+            self.intended = session.my_champion.clone();
+        }
+    }
+
+    /// The recorded intent, given the enemy laner from the played game
+    fn finish(self, enemy_laner: Option<&str>) -> Option<PickIntent> {
+        let order = enemy_laner.map(|enemy| {
+            if self
+                .picked_before_me
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(enemy))
+            {
+                PickOrder::Counter
+            } else {
+                PickOrder::Blind
+            }
+        });
+        Some(PickIntent {
+            intended: self.intended,
+            locked: self.locked?,
+            counter_picked: order == Some(PickOrder::Blind),
+            order,
+        })
+    }
+}
+
+/// Start the polling thread
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut monitor = Monitor::default();
+        loop {
+            poll_connection(&app, &mut monitor);
+            poll_gameflow(&app, &mut monitor);
+            poll_champ_select(&app, &mut monitor);
+            auto_import(&app, &mut monitor);
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Retry connecting while disconnected; emit `lcu-connection-changed` on any change
+///
+/// Also picks up connections made through `connect_lcu` and disconnects noticed
+/// by other polls. Stops retrying after `disconnect_lcu` until `connect_lcu` is called.
+fn poll_connection(app: &AppHandle, monitor: &mut Monitor) {
+    let state = app.state::<AppState>();
+    let Ok(reconnect) = state
+        .lcu_client
+        .lock()
+        .map(|client| !client.is_connected() && client.auto_reconnect())
+    else {
+        return;
+    };
+
+    if reconnect {
+        if monitor.polls_until_reconnect == 0 {
+            monitor.polls_until_reconnect = RECONNECT_EVERY;
+            let _ = crate::connect_client(&state);
+        } else {
+            monitor.polls_until_reconnect -= 1;
+        }
+    }
+
+    let Ok(client) = state.lcu_client.lock() else {
+        return;
+    };
+    let status = client.status();
+    if status.connected == monitor.connected {
+        return;
+    }
+    drop(client);
+    if status.connected {
+        tracing::info!("Connected to the League client");
+        let _ = crate::remember_account(&state);
+        let _ = crate::refresh_current_patch(&state);
+    }
+    monitor.connected = status.connected;
+    if !status.connected {
+        tracing::info!("Disconnected from the League client");
+        monitor.phase = None;
+    }
+
+    let _ = app.emit("lcu-connection-changed", status);
+}
+
+/// Emit `gameflow-phase-changed` with the new phase
+///
+/// When a game loads, opens the notes window on the matchup from champ select if enabled.
+/// After a game, starts the auto-import if enabled. On reaching the end of game screen,
+/// also captures its stats and emits `end-of-game-stats`. Desktop notifications for
+/// both moments are sent as the settings allow.
+fn poll_gameflow(app: &AppHandle, monitor: &mut Monitor) {
+    let state = app.state::<AppState>();
+    let phase = {
+        let Ok(client) = state.lcu_client.lock().map(|client| client.clone()) else {
+            return;
+        };
+        if !client.is_connected() {
+            return;
+        }
+        match client.get_gameflow_phase() {
+            Ok(phase) => phase,
+            // Nothing listening on the port anymore: the client was closed
+            Err(LcuError::RequestError(e)) if e.is_connect() => {
+                if let Ok(mut client) = state.lcu_client.lock() {
+                    client.disconnect();
+                }
+                return;
+            }
+            Err(_) => return,
+        }
+    };
+
+    if monitor.phase == Some(phase) {
+        return;
+    }
+    monitor.phase = Some(phase);
+    tracing::debug!("Gameflow phase changed to {:?}", phase);
+
+    // Defaults keep the monitor going if the settings file can't be read
+    let settings = crate::load_settings(&state).unwrap_or_default();
+
+    if matches!(
+        phase,
+        GameflowPhase::PreEndOfGame | GameflowPhase::EndOfGame
+    ) && monitor.import_attempts_left == 0
+        && settings.auto_import
+    {
+        monitor.import_attempts_left = AUTO_IMPORT_ATTEMPTS;
+    }
+
+    if matches!(phase, GameflowPhase::GameStart | GameflowPhase::InProgress) {
+        if let Some(matchup_id) = monitor.game_matchup.take() {
+            if settings.overlay.open_on_load {
+                let _ = overlay::show_matchup(app, &matchup_id, &settings.overlay);
+            }
+            if let Some(ref pairing) = monitor.game_pairing {
+                notifications::game_detected(app, &settings.notifications, pairing);
+            }
+        }
+    }
+
+    let in_game = matches!(
+        phase,
+        GameflowPhase::GameStart | GameflowPhase::InProgress | GameflowPhase::Reconnect
+    );
+    state
+        .presence
+        .set_in_game(monitor.game_pairing.clone().filter(|_| in_game));
+
+    // The stats block only exists while the end of game screen is up
+    if phase == GameflowPhase::EndOfGame {
+        notifications::game_ended(
+            app,
+            &settings.notifications,
+            monitor.game_pairing.as_deref(),
+        );
+        match crate::capture_end_of_game(&state) {
+            Ok(Some(stats)) => {
+                events::matches_changed(app, Vec::new());
+                let _ = app.emit("end-of-game-stats", stats);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Could not capture end of game stats: {:?}", e),
+        }
+    }
+
+    let _ = app.emit("gameflow-phase-changed", phase);
+}
+
+/// After a game, import it, post it to the webhook and emit `matches:imported` and `post-game-prompt`
+///
+/// The prompt carries the newest imported match so the UI can ask for notes right away.
+/// The pick intent from champ select is recorded on it when the champion matches.
+fn auto_import(app: &AppHandle, monitor: &mut Monitor) {
+    if monitor.import_attempts_left == 0 {
+        return;
+    }
+    monitor.import_attempts_left -= 1;
+
+    let state = app.state::<AppState>();
+    let options = crate::import_options(&state, None).unwrap_or_default();
+    let summary = match crate::import_new(&state, AUTO_IMPORT_COUNT, false, &options) {
+        Ok(summary) => summary,
+        Err(e) => {
+            tracing::warn!("Auto-import failed: {:?}", e);
+            return;
+        }
+    };
+    let Some(mut latest) = summary.imported.iter().max_by_key(|m| m.date).cloned() else {
+        return;
+    };
+    monitor.import_attempts_left = 0;
+
+    let enemy_laner = (latest.has_lane_opponent() && latest.enemy_champion != "Unknown")
+        .then_some(latest.enemy_champion.as_str());
+    if let Some(pick) = monitor
+        .pending_pick
+        .take()
+        .and_then(|pick| pick.finish(enemy_laner))
+    {
+        if pick.locked.eq_ignore_ascii_case(&latest.my_champion) {
+            if let Ok(updated) = crate::record_pick_intent(&state, &latest.id, pick) {
+                latest = updated;
+            }
+        }
+    }
+
+    webhook::game_imported(app, &latest);
+
+    events::matches_imported(app, &summary);
+    let _ = app.emit("post-game-prompt", latest);
+}
+
+/// Current champ select session with its matchup, if in champ select
+pub fn champ_select_update(state: &AppState) -> Result<Option<ChampSelectUpdate>, AppError> {
+    let session = {
+        let client = state.lcu_client.lock()?.clone();
+        if !client.is_connected() {
+            return Ok(None);
+        }
+        client.get_champ_select_session()?
+    };
+
+    match session {
+        Some(session) => Ok(Some(ChampSelectUpdate {
+            matchup: find_matchup(state, &session)?,
+            session,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// My notes for the pairing detected in champ select
+fn find_matchup(state: &AppState, session: &ChampSelectInfo) -> Result<Option<Matchup>, AppError> {
+    let (Some(mine), Some(enemy), Some(role)) = (
+        &session.my_champion,
+        &session.enemy_champion,
+        &session.my_position,
+    ) else {
+        return Ok(None);
+    };
+
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+
+    Ok(data
+        .matchups
+        .into_values()
+        .find(|m| m.player.is_none() && m.is_pairing(mine, enemy, role)))
+}
+
+/// My notes for every enemy pick seen so far
+fn enemy_pick_notes(
+    state: &AppState,
+    session: &ChampSelectInfo,
+) -> Result<Vec<EnemyPickNotes>, AppError> {
+    let storage = state.storage.lock()?;
+    let data = storage.load()?;
+    let scope = data.metadata.player_scope();
+
+    Ok(session
+        .enemy_picks
+        .iter()
+        .map(|champion| EnemyPickNotes {
+            champion: champion.clone(),
+            matchups: matchup::matchups_against(
+                data.matchups
+                    .values()
+                    .filter(|m| scope.includes(m.player.as_deref())),
+                champion,
+                session.my_champion.as_deref(),
+                session.my_position.as_deref(),
+            ),
+        })
+        .collect())
+}
+
+/// Emit `champ-select-update` when the session changes, `champ-select-ended` when it closes
+///
+/// Also emits `ban-phase` with ban suggestions when my ban turn starts, and
+/// `enemy-picks-update` with my notes whenever enemy hovers or picks change.
+fn poll_champ_select(app: &AppHandle, monitor: &mut Monitor) {
+    let state = app.state::<AppState>();
+    // A failed poll is retried on the next tick
+    let Ok(update) = champ_select_update(&state) else {
+        return;
+    };
+
+    let session = update.as_ref().map(|u| u.session.clone());
+    if session == monitor.session {
+        return;
+    }
+    if monitor.session.is_none() {
+        // A new champ select; the last one may have been dodged
+        monitor.game_pairing = None;
+        monitor.game_matchup = None;
+        state.obs.set_matchup(None);
+    }
+    if let Some(session) = update.as_ref().map(|u| &u.session) {
+        if let (Some(mine), Some(enemy)) = (&session.my_champion, &session.enemy_champion) {
+            monitor.game_pairing = Some(presence::pairing_label(
+                mine,
+                enemy,
+                session.my_position.as_deref(),
+            ));
+        }
+    }
+    if let Some(matchup) = update.as_ref().and_then(|u| u.matchup.as_ref()) {
+        monitor.game_matchup = Some(matchup.id.clone());
+        state.obs.set_matchup(Some(matchup.id.clone()));
+    }
+    match update.as_ref().map(|u| &u.session) {
+        Some(session) => monitor.pick.observe(session),
+        None => monitor.pending_pick = Some(std::mem::take(&mut monitor.pick)),
+    }
+    let was_banning = monitor.session.as_ref().is_some_and(|s| s.banning);
+    let previous_picks = monitor
+        .session
+        .as_ref()
+        .map(|s| s.enemy_picks.clone())
+        .unwrap_or_default();
+    monitor.session = session;
+
+    if let Some(session) = update.as_ref().map(|u| &u.session) {
+        if session.enemy_picks != previous_picks && !session.enemy_picks.is_empty() {
+            if let Ok(notes) = enemy_pick_notes(&state, session) {
+                let _ = app.emit("enemy-picks-update", notes);
+            }
+        }
+    }
+
+    if let Some(session) = update.as_ref().map(|u| &u.session) {
+        if session.banning && !was_banning {
+            if let Ok(suggestions) = crate::ban_suggestions(
+                &state,
+                session.my_champion.clone(),
+                session.my_position.clone(),
+            ) {
+                let _ = app.emit("ban-phase", suggestions);
+            }
+        }
+    }
+
+    let _ = match update {
+        Some(update) => app.emit("champ-select-update", update),
+        None => app.emit("champ-select-ended", ()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_tracker() {
+        let session = |champion: &str, locked: bool, picked_before_me: &[&str]| ChampSelectInfo {
+            my_position: Some("top".to_string()),
+            my_champion: Some(champion.to_string()),
+            enemy_champion: None,
+            enemy_picks: Vec::new(),
+            picked_before_me: picked_before_me.iter().map(|c| c.to_string()).collect(),
+            bans: Vec::new(),
+            banning: false,
+            locked,
+        };
+        let mut tracker = PickTracker::default();
+        tracker.observe(&session("Darius", false, &[]));
+        tracker.observe(&session("Sett", true, &["Garen"]));
+        // Changes after locking in don't count
+        tracker.observe(&session("Sett", true, &["Garen", "Ahri"]));
+
+        let pick = tracker.finish(Some("garen")).unwrap();
+        assert_eq!(pick.intended.as_deref(), Some("Darius"));
+        assert_eq!(pick.locked, "Sett");
+        assert_eq!(pick.order, Some(PickOrder::Counter));
+        assert!(!pick.counter_picked);
+
+        let mut tracker = PickTracker::default();
+        tracker.observe(&session("Sett", true, &["Ahri"]));
+        let pick = tracker.finish(Some("Garen")).unwrap();
+        assert_eq!(pick.order, Some(PickOrder::Blind));
+        assert!(pick.counter_picked);
+        assert!(PickTracker::default().finish(Some("Garen")).is_none());
+    }
+}
@@ -0,0 +1,53 @@
+//! Native notifications at points of a game, each one turned on in the settings
+
+use crate::settings::NotificationSettings;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// A game loaded for a pairing I have notes on
+pub fn game_detected(app: &AppHandle, settings: &NotificationSettings, pairing: &str) {
+    if settings.game_detected {
+        show(app, game_detected_message(pairing));
+    }
+}
+
+/// A game reached the end of game screen
+pub fn game_ended(app: &AppHandle, settings: &NotificationSettings, pairing: Option<&str>) {
+    if settings.game_ended {
+        show(app, game_ended_message(pairing));
+    }
+}
+
+fn game_detected_message(pairing: &str) -> (String, String) {
+    (
+        "Game detected".to_string(),
+        format!("Notes for {} are ready", pairing),
+    )
+}
+
+fn game_ended_message(pairing: Option<&str>) -> (String, String) {
+    let body = match pairing {
+        Some(pairing) => format!("Write your post-game review for {}", pairing),
+        None => "Write your post-game review".to_string(),
+    };
+    ("Game ended".to_string(), body)
+}
+
+fn show(app: &AppHandle, (title, body): (String, String)) {
+    // A notification is a nicety; the game goes on without it
+    let _ = app.notification().builder().title(title).body(body).show();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(
+            game_detected_message("Darius vs Garen (Top)").1,
+            "Notes for Darius vs Garen (Top) are ready"
+        );
+        assert_eq!(game_ended_message(None).1, "Write your post-game review");
+    }
+}
@@ -0,0 +1,287 @@
+//! Local web page with the current matchup, for an OBS browser source
+//!
+//! Off unless enabled in the settings. Listens on localhost only, and every
+//! request needs the token from the settings so other programs on the machine
+//! can't read the notes. The page polls `/matchup.json`, which follows the
+//! matchup detected in champ select.
+
+use crate::matchup::{Matchup, QuickReference};
+use crate::settings::ObsSettings;
+use crate::AppState;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often the accept loop checks whether it should stop
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { margin: 0; background: transparent; color: #fff; font: 18px sans-serif; text-shadow: 0 0 4px #000; }
+  h1 { font-size: 22px; margin: 0 0 6px; }
+  ul { margin: 0; padding-left: 20px; }
+  .loadout { opacity: 0.8; font-size: 15px; margin-top: 6px; }
+</style>
+</head>
+<body>
+<div id="matchup"></div>
+<script>
+  const root = document.getElementById('matchup');
+  function line(tag, text, className) {
+    const el = document.createElement(tag);
+    el.textContent = text;
+    if (className) el.className = className;
+    return el;
+  }
+  async function refresh() {
+    try {
+      const response = await fetch('matchup.json' + window.location.search, { cache: 'no-store' });
+      const matchup = response.ok ? await response.json() : null;
+      root.replaceChildren();
+      if (!matchup) return;
+      root.append(line('h1', `${matchup.my_champion} vs ${matchup.enemy_champion}`));
+      const summary = document.createElement('ul');
+      matchup.summary.forEach(point => summary.append(line('li', point)));
+      root.append(summary);
+      const loadout = [...matchup.runes, ...matchup.summoner_spells].join(' · ');
+      if (loadout) root.append(line('div', loadout, 'loadout'));
+    } catch (error) {
+      // The app is closed or restarting; try again on the next tick
+    }
+  }
+  refresh();
+  setInterval(refresh, 2000);
+</script>
+</body>
+</html>
+"#;
+
+/// The running server and what it shows
+#[derive(Default)]
+pub struct ObsServer {
+    /// Matchup detected in the last champ select
+    matchup_id: Mutex<Option<String>>,
+    token: Mutex<Option<String>>,
+    running: Mutex<Option<Running>>,
+}
+
+struct Running {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Running {
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+impl ObsServer {
+    /// Show a matchup on the page from now on
+    pub fn set_matchup(&self, matchup_id: Option<String>) {
+        if let Ok(mut current) = self.matchup_id.lock() {
+            *current = matchup_id;
+        }
+    }
+}
+
+/// A new random token for the page URL
+pub fn new_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// The URL to add as a browser source, when the server is enabled
+pub fn url(settings: &ObsSettings) -> Option<String> {
+    let token = settings.token.as_ref().filter(|_| settings.enabled)?;
+    Some(format!(
+        "http://127.0.0.1:{}/?token={}",
+        settings.port, token
+    ))
+}
+
+/// Start, stop or move the server to match the settings
+///
+/// A port change restarts the server; a token change applies to the next request.
+pub fn apply(app: &AppHandle, settings: &ObsSettings) -> io::Result<()> {
+    let state = app.state::<AppState>();
+    let server = &state.obs;
+    let mut running = server
+        .running
+        .lock()
+        .map_err(|_| io::Error::other("OBS server state is unavailable"))?;
+
+    if let Ok(mut token) = server.token.lock() {
+        *token = settings.token.clone();
+    }
+
+    let port = settings.enabled.then_some(settings.port);
+    if running.as_ref().map(|r| r.port) == port {
+        return Ok(());
+    }
+    if let Some(previous) = running.take() {
+        previous.stop();
+    }
+    let Some(port) = port else {
+        return Ok(());
+    };
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    listener.set_nonblocking(true)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let app = app.clone();
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || serve(&app, &listener, &stop))
+    };
+    *running = Some(Running { port, stop, thread });
+
+    Ok(())
+}
+
+fn serve(app: &AppHandle, listener: &TcpListener, stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let _ = handle(app, stream);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(STOP_CHECK_INTERVAL),
+            Err(_) => {}
+        }
+    }
+}
+
+fn handle(app: &AppHandle, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    // Only the request line matters; headers and bodies are ignored
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let state = app.state::<AppState>();
+    let token = state.obs.token.lock().ok().and_then(|t| t.clone());
+    let response = respond(&request_line, token.as_deref(), || {
+        current_reference(&state)
+    });
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+fn current_reference(state: &AppState) -> Option<QuickReference> {
+    let matchup_id = state.obs.matchup_id.lock().ok()?.clone()?;
+    let storage = state.storage.lock().ok()?;
+    let data = storage.load().ok()?;
+    data.matchups.get(&matchup_id).map(Matchup::quick_reference)
+}
+
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn text(status: &'static str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: status.to_string(),
+        }
+    }
+}
+
+/// Answer a request line like "GET /matchup.json?token=abc HTTP/1.1"
+fn respond(
+    request_line: &str,
+    token: Option<&str>,
+    current: impl FnOnce() -> Option<QuickReference>,
+) -> Response {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Response::text("400 Bad Request");
+    };
+    if method != "GET" {
+        return Response::text("405 Method Not Allowed");
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let given = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="));
+    if token.is_none_or(str::is_empty) || given != token {
+        return Response::text("403 Forbidden");
+    }
+
+    match path {
+        "/" => Response {
+            status: "200 OK",
+            content_type: "text/html; charset=utf-8",
+            body: PAGE.to_string(),
+        },
+        "/matchup.json" => Response {
+            status: "200 OK",
+            content_type: "application/json",
+            body: serde_json::to_string(&current()).unwrap_or_else(|_| "null".to_string()),
+        },
+        _ => Response::text("404 Not Found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respond() {
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let quick = matchup.quick_reference();
+
+        let response = respond(
+            "GET /matchup.json?token=abc HTTP/1.1\r\n",
+            Some("abc"),
+            || Some(quick),
+        );
+        assert_eq!(response.status, "200 OK");
+        assert!(response.body.contains("\"enemy_champion\":\"Garen\""));
+
+        let response = respond("GET /?token=abc HTTP/1.1\r\n", Some("abc"), || None);
+        assert_eq!(response.content_type, "text/html; charset=utf-8");
+
+        let response = respond("GET /matchup.json HTTP/1.1\r\n", Some("abc"), || None);
+        assert_eq!(response.status, "403 Forbidden");
+        let response = respond(
+            "GET /matchup.json?token=xyz HTTP/1.1\r\n",
+            Some("abc"),
+            || None,
+        );
+        assert_eq!(response.status, "403 Forbidden");
+        let response = respond("GET /?token= HTTP/1.1\r\n", None, || None);
+        assert_eq!(response.status, "403 Forbidden");
+        let response = respond("GET /?token= HTTP/1.1\r\n", Some(""), || None);
+        assert_eq!(response.status, "403 Forbidden");
+
+        let response = respond("GET /other?token=abc HTTP/1.1\r\n", Some("abc"), || None);
+        assert_eq!(response.status, "404 Not Found");
+    }
+}
@@ -0,0 +1,98 @@
+//! The compact always-on-top window with a matchup's quick reference
+//!
+//! Opened at the loading screen, from the global shortcut or by command. The
+//! frontend in it follows `show-quick-reference` events, so the backend decides
+//! which matchup it shows.
+
+use crate::settings::{OverlaySettings, WindowGeometry};
+use crate::window_state;
+use crate::AppState;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+/// Label of the overlay window
+pub const OVERLAY_WINDOW: &str = "notes";
+
+/// Logical size of a window opened without saved geometry
+const DEFAULT_SIZE: (f64, f64) = (360.0, 480.0);
+
+/// Open or focus the overlay on a matchup's quick reference
+///
+/// An already open window is sent `show-quick-reference` with the matchup ID instead.
+pub fn show_matchup(
+    app: &AppHandle,
+    matchup_id: &str,
+    overlay: &OverlaySettings,
+) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(OVERLAY_WINDOW) {
+        app.emit_to(OVERLAY_WINDOW, "show-quick-reference", matchup_id)?;
+        window.show()?;
+        window.unminimize()?;
+        return window.set_focus();
+    }
+
+    build(app, &format!("index.html#quick/{}", matchup_id), overlay)?.set_focus()
+}
+
+/// Show the overlay on whatever it showed last, the matchup list for a new window
+pub fn show(app: &AppHandle, overlay: &OverlaySettings) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(OVERLAY_WINDOW) {
+        window.show()?;
+        return window.set_focus();
+    }
+
+    build(app, "index.html#quick", overlay)?.set_focus()
+}
+
+/// Hide the overlay, keeping it and its matchup around for the next show
+pub fn hide(app: &AppHandle) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window(OVERLAY_WINDOW) else {
+        return Ok(());
+    };
+    window_state::save(&window);
+    window.hide()
+}
+
+/// Show or hide the overlay, for the global shortcut
+pub fn toggle(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(OVERLAY_WINDOW) {
+        if window.is_visible()? {
+            return hide(app);
+        }
+    }
+
+    let settings = crate::load_settings(&app.state::<AppState>()).unwrap_or_default();
+    show(app, &settings.overlay)
+}
+
+/// Apply always-on-top and click-through to an open overlay
+pub fn apply_settings(app: &AppHandle, overlay: &OverlaySettings) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window(OVERLAY_WINDOW) else {
+        return Ok(());
+    };
+    window.set_always_on_top(overlay.always_on_top)?;
+    window.set_ignore_cursor_events(overlay.click_through)
+}
+
+/// Move and resize an open overlay
+pub fn set_geometry(app: &AppHandle, geometry: WindowGeometry) -> tauri::Result<()> {
+    match app.get_webview_window(OVERLAY_WINDOW) {
+        Some(window) => window_state::place(&window, geometry),
+        None => Ok(()),
+    }
+}
+
+fn build(app: &AppHandle, url: &str, overlay: &OverlaySettings) -> tauri::Result<WebviewWindow> {
+    let window = WebviewWindowBuilder::new(app, OVERLAY_WINDOW, WebviewUrl::App(url.into()))
+        .title("Matchup notes")
+        .inner_size(DEFAULT_SIZE.0, DEFAULT_SIZE.1)
+        .decorations(false)
+        .skip_taskbar(true)
+        .always_on_top(overlay.always_on_top)
+        // Shown once in place, so it doesn't flash at the default position
+        .visible(false)
+        .build()?;
+
+    window.set_ignore_cursor_events(overlay.click_through)?;
+    window_state::manage(&window)?;
+    Ok(window)
+}
@@ -0,0 +1,342 @@
+//! Read-only matchup packages, for coaches handing curated guides to students
+//!
+//! A package holds copies of selected matchups without any personal data and
+//! a SHA-256 checksum of its contents, so a file damaged on the way is
+//! refused. The checksum is not a signature: anyone can edit a package and
+//! compute it again, so the author shown is only as trustworthy as whoever
+//! handed the file over. Imported matchups are marked as external notes with
+//! their author and can't be edited; they never replace my own matchups.
+
+use crate::matchup::{Matchup, MatchupVersion};
+use crate::storage::AppData;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Package layout version, bumped on incompatible changes
+///
+/// Version 1 checksummed the contents as this version serialized them, so
+/// any field added later broke the check; those packages must be exported again.
+const PACKAGE_FORMAT: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum PackageError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a matchup package: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Package format {0} is not supported by this version")]
+    UnsupportedFormat(u32),
+    #[error("Package was made by an older version, ask for it to be exported again")]
+    Outdated,
+    #[error("Package is damaged, its checksum doesn't match")]
+    DigestMismatch,
+}
+
+/// What goes into an exported package
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PackageOptions {
+    /// Shown as the notes' author to whoever imports them
+    pub author: String,
+    pub title: Option<String>,
+    /// Every version instead of only the current notes
+    pub include_history: bool,
+    pub include_goals: bool,
+}
+
+/// Where an imported matchup came from; such matchups are read-only
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalSource {
+    pub author: String,
+    pub package_title: Option<String>,
+    /// ID of the matchup in the author's data, to recognise updated packages
+    pub original_id: String,
+    pub imported: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackageContents {
+    title: Option<String>,
+    author: String,
+    created: DateTime<Utc>,
+    matchups: Vec<Matchup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MatchupPackage {
+    format: u32,
+    /// `PackageContents` as JSON text, so the checksum covers the exact bytes
+    /// written whatever fields later versions add
+    contents: String,
+    /// Lowercase hex SHA-256 of `contents`
+    digest: String,
+}
+
+/// What `import` added
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PackageImportReport {
+    pub author: String,
+    pub title: Option<String>,
+    pub imported: usize,
+    /// Earlier imports of the same matchups from this author, replaced
+    pub updated: usize,
+}
+
+fn digest(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+/// Write copies of `matchups` to `path` as a package
+///
+/// Goals and older versions are left out unless asked for; review history,
+/// comments, attachments, checklists and anything tying the notes to my data
+/// always are. Versions without an author are credited to the package's.
+pub fn export(
+    path: &Path,
+    matchups: Vec<Matchup>,
+    options: &PackageOptions,
+    now: DateTime<Utc>,
+) -> Result<(), PackageError> {
+    let matchups = matchups
+        .into_iter()
+        .map(|mut matchup| {
+            if !options.include_history {
+                let current = matchup.current().cloned();
+                matchup.versions = current
+                    .map(|version| MatchupVersion {
+                        version: 1,
+                        ..version
+                    })
+                    .into_iter()
+                    .collect();
+                matchup.current_version = 1;
+            }
+            if !options.include_goals {
+                matchup.goals.clear();
+            }
+            for version in &mut matchup.versions {
+                version.comments.clear();
+                version.attachments.clear();
+                // Versions without an author are the package author's advice
+                if version.author.is_none() {
+                    version.author = Some(options.author.trim().to_string());
+                }
+            }
+            matchup.checklist.clear();
+            matchup.last_reviewed = None;
+            matchup.review_count = 0;
+            matchup.quiz_results.clear();
+            matchup.archived = false;
+            matchup.player = None;
+            matchup
+        })
+        .collect();
+
+    let contents = serde_json::to_string(&PackageContents {
+        title: options.title.clone(),
+        author: options.author.trim().to_string(),
+        created: now,
+        matchups,
+    })?;
+    let package = MatchupPackage {
+        format: PACKAGE_FORMAT,
+        digest: digest(&contents),
+        contents,
+    };
+    fs::write(path, serde_json::to_string_pretty(&package)?)?;
+    Ok(())
+}
+
+/// Add the matchups of the package at `path` to `data` as external notes for `player`
+///
+/// A matchup imported earlier from the same author is replaced by its new
+/// copy, keeping my comments and review history on it. Comments and
+/// attachments in the package itself are dropped.
+pub fn import(
+    data: &mut AppData,
+    path: &Path,
+    player: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<PackageImportReport, PackageError> {
+    let text = fs::read_to_string(path)?;
+    let format: PackageFormat = serde_json::from_str(&text)?;
+    if format.format > PACKAGE_FORMAT {
+        return Err(PackageError::UnsupportedFormat(format.format));
+    }
+    if format.format < PACKAGE_FORMAT {
+        return Err(PackageError::Outdated);
+    }
+    let package: MatchupPackage = serde_json::from_str(&text)?;
+    if digest(&package.contents) != package.digest {
+        return Err(PackageError::DigestMismatch);
+    }
+    let contents: PackageContents = serde_json::from_str(&package.contents)?;
+
+    let mut report = PackageImportReport {
+        author: contents.author.clone(),
+        title: contents.title.clone(),
+        ..Default::default()
+    };
+    for mut matchup in contents.matchups {
+        let previous = data
+            .matchups
+            .values()
+            .find(|m| {
+                m.external.as_ref().is_some_and(|source| {
+                    source.author == contents.author && source.original_id == matchup.id
+                })
+            })
+            .cloned();
+
+        // Only what I add myself belongs on my copy
+        for version in &mut matchup.versions {
+            version.comments.clear();
+            version.attachments.clear();
+        }
+        matchup.checklist.clear();
+        matchup.last_reviewed = None;
+        matchup.review_count = 0;
+        matchup.quiz_results.clear();
+        matchup.archived = false;
+        if let Some(ref previous) = previous {
+            keep_local(&mut matchup, previous);
+        }
+
+        matchup.external = Some(ExternalSource {
+            author: contents.author.clone(),
+            package_title: contents.title.clone(),
+            original_id: matchup.id.clone(),
+            imported: now,
+        });
+        matchup.player = player.clone();
+        match previous {
+            Some(previous) => {
+                matchup.id = previous.id;
+                report.updated += 1;
+            }
+            None => {
+                matchup.id = Uuid::new_v4().to_string();
+                report.imported += 1;
+            }
+        }
+        data.matchups.insert(matchup.id.clone(), matchup);
+    }
+
+    Ok(report)
+}
+
+/// Just the layout version, readable from any package
+#[derive(Deserialize)]
+struct PackageFormat {
+    format: u32,
+}
+
+/// Carry what I added to an earlier import over to its new copy
+///
+/// Comments stay on the version with the same number, or go to the current
+/// notes when the new copy has no such version.
+fn keep_local(matchup: &mut Matchup, previous: &Matchup) {
+    for old in &previous.versions {
+        let index = matchup
+            .versions
+            .iter()
+            .position(|v| v.version == old.version)
+            .or_else(|| {
+                matchup
+                    .versions
+                    .iter()
+                    .position(|v| v.version == matchup.current_version)
+            });
+        if let Some(version) = index.and_then(|i| matchup.versions.get_mut(i)) {
+            version.comments.extend(old.comments.iter().cloned());
+            version.attachments.extend(old.attachments.iter().cloned());
+        }
+    }
+    matchup.checklist = previous.checklist.clone();
+    matchup.last_reviewed = previous.last_reviewed;
+    matchup.review_count = previous.review_count;
+    matchup.quiz_results = previous.quiz_results.clone();
+    matchup.archived = previous.archived;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::MatchupUpdate;
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guides.json");
+        let now = Utc::now();
+
+        let mut guide = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        guide.add_version(MatchupUpdate {
+            notes: "Fight after his E".to_string(),
+            ..Default::default()
+        });
+        guide.add_goal("Private goal".to_string());
+        let options = PackageOptions {
+            author: "Coach".to_string(),
+            ..Default::default()
+        };
+        export(&path, vec![guide.clone()], &options, now).unwrap();
+
+        let mine = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let mut data = AppData::default();
+        data.matchups.insert(mine.id.clone(), mine.clone());
+
+        let report = import(&mut data, &path, None, now).unwrap();
+        assert_eq!((report.imported, report.updated), (1, 0));
+        assert_eq!(data.matchups.len(), 2);
+        assert!(data.matchups[&mine.id].external.is_none());
+        assert!(data.matchups[&mine.id].ensure_editable().is_ok());
+
+        let imported = data
+            .matchups
+            .values()
+            .find(|m| m.external.is_some())
+            .unwrap();
+        assert_eq!(imported.versions.len(), 1);
+        assert_eq!(imported.current().unwrap().notes, "Fight after his E");
+        assert!(imported.goals.is_empty());
+        assert_eq!(imported.external.as_ref().unwrap().author, "Coach");
+        assert!(imported.ensure_editable().is_err());
+        assert_eq!(imported.current().unwrap().author.as_deref(), Some("Coach"));
+
+        // Importing again replaces the earlier copy but keeps my comments
+        let imported_id = imported.id.clone();
+        let comment = data
+            .matchups
+            .get_mut(&imported_id)
+            .unwrap()
+            .add_comment(1, "Me".to_string(), "What about ghost?".to_string())
+            .unwrap();
+        let report = import(&mut data, &path, None, now).unwrap();
+        assert_eq!((report.imported, report.updated), (0, 1));
+        assert_eq!(data.matchups.len(), 2);
+        let comments = &data.matchups[&imported_id].versions[0].comments;
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].id, comment.id);
+
+        let edited = fs::read_to_string(&path)
+            .unwrap()
+            .replace("Fight after his E", "Fight whenever");
+        fs::write(&path, edited).unwrap();
+        assert!(matches!(
+            import(&mut data, &path, None, now),
+            Err(PackageError::DigestMismatch)
+        ));
+
+        fs::write(&path, r#"{"format": 1, "digest": ""}"#).unwrap();
+        assert!(matches!(
+            import(&mut data, &path, None, now),
+            Err(PackageError::Outdated)
+        ));
+    }
+}
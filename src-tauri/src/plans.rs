@@ -0,0 +1,344 @@
+//! Practice plans: weekly targets such as "5 games of Camille vs Renekton"
+//!
+//! Targets repeat every week, starting Monday in local time. Games and wins
+//! are counted from the match history of the active account as it is
+//! imported; reviews are logged on the target when a matchup is marked
+//! reviewed or its quiz answer is known.
+
+use crate::champions;
+use crate::matchup::{Match, MatchResult, Matchup};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Most targets in a plan
+const MAX_TARGETS: usize = 20;
+
+/// Highest count a target can ask for in a week
+const MAX_COUNT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Games,
+    Wins,
+    /// Matchups marked reviewed
+    Reviews,
+}
+
+/// A matchup review counted towards a target
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoggedReview {
+    pub matchup_id: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanTarget {
+    pub id: String,
+    pub kind: TargetKind,
+    /// Games, wins or reviews wanted each week
+    pub count: u32,
+    #[serde(default)]
+    pub my_champion: Option<String>,
+    #[serde(default)]
+    pub enemy_champion: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Only count reviews of matchups whose notes were stale
+    #[serde(default)]
+    pub stale_only: bool,
+    #[serde(default)]
+    pub player: Option<String>,
+    pub created: DateTime<Utc>,
+    /// Reviews logged this week
+    #[serde(default)]
+    pub reviews: Vec<LoggedReview>,
+}
+
+/// Data for creating a target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPlanTarget {
+    pub kind: TargetKind,
+    pub count: u32,
+    #[serde(default)]
+    pub my_champion: Option<String>,
+    #[serde(default)]
+    pub enemy_champion: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub stale_only: bool,
+}
+
+/// How far a target got this week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetProgress {
+    pub target: PlanTarget,
+    pub done: u32,
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanProgress {
+    pub week_start: DateTime<Utc>,
+    pub week_end: DateTime<Utc>,
+    /// Oldest target first
+    pub targets: Vec<TargetProgress>,
+    pub completed: usize,
+}
+
+impl PlanTarget {
+    /// Validate `target` and create it for `player`
+    pub fn new(
+        target: NewPlanTarget,
+        player: Option<String>,
+        existing: usize,
+    ) -> Result<Self, String> {
+        if existing >= MAX_TARGETS {
+            return Err(format!("A plan holds at most {} targets", MAX_TARGETS));
+        }
+        if target.count == 0 || target.count > MAX_COUNT {
+            return Err(format!("Target counts go from 1 to {} a week", MAX_COUNT));
+        }
+        let champion = |name: Option<String>| -> Result<Option<String>, String> {
+            name.filter(|n| !n.trim().is_empty())
+                .map(|n| champions::canonicalize(n.trim()))
+                .transpose()
+        };
+
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            kind: target.kind,
+            count: target.count,
+            my_champion: champion(target.my_champion)?,
+            enemy_champion: champion(target.enemy_champion)?,
+            role: target
+                .role
+                .map(|r| r.trim().to_lowercase())
+                .filter(|r| !r.is_empty()),
+            stale_only: target.kind == TargetKind::Reviews && target.stale_only,
+            player,
+            created: Utc::now(),
+            reviews: Vec::new(),
+        })
+    }
+
+    /// Whether a game or matchup with these champions and role counts
+    fn covers(&self, my_champion: &str, enemy_champion: &str, role: &str) -> bool {
+        let same = |wanted: &Option<String>, value: &str| {
+            wanted
+                .as_ref()
+                .is_none_or(|w| w.eq_ignore_ascii_case(value))
+        };
+        same(&self.my_champion, my_champion)
+            && same(&self.enemy_champion, enemy_champion)
+            && same(&self.role, role)
+    }
+
+    /// Progress in the week starting at `week_start`, counting the games of `account`
+    fn done(&self, matches: &[&Match], account: Option<&str>, week_start: DateTime<Utc>) -> u32 {
+        let counted = |m: &&&Match| {
+            m.date >= week_start
+                && m.player == self.player
+                && account.is_none_or(|puuid| m.is_from_account(puuid))
+                && (self.enemy_champion.is_none() || m.has_lane_opponent())
+                && self.covers(&m.my_champion, &m.enemy_champion, &m.role)
+        };
+        match self.kind {
+            TargetKind::Games => matches.iter().filter(counted).count() as u32,
+            TargetKind::Wins => matches
+                .iter()
+                .filter(counted)
+                .filter(|m| m.result == MatchResult::Win)
+                .count() as u32,
+            TargetKind::Reviews => {
+                self.reviews.iter().filter(|r| r.at >= week_start).count() as u32
+            }
+        }
+    }
+}
+
+/// Start of the week `now` falls in: Monday at midnight in `tz`
+pub fn week_start<Tz: TimeZone>(now: DateTime<Utc>, tz: &Tz) -> DateTime<Utc> {
+    let local = now.with_timezone(tz).date_naive();
+    let monday = local - Duration::days(local.weekday().num_days_from_monday() as i64);
+    let midnight = monday.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        // Midnight skipped by a clock change; an hour off doesn't matter here
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// Log a review of `matchup` on the review targets it counts for
+///
+/// Each matchup counts once a week; reviews from earlier weeks are dropped.
+/// Returns the IDs of the targets that changed.
+pub fn log_review(
+    targets: &mut HashMap<String, PlanTarget>,
+    matchup: &Matchup,
+    stale: bool,
+    now: DateTime<Utc>,
+    week_start: DateTime<Utc>,
+) -> Vec<String> {
+    let mut changed = Vec::new();
+    for target in targets.values_mut() {
+        if target.kind != TargetKind::Reviews
+            || target.player != matchup.player
+            || (target.stale_only && !stale)
+            || !target.covers(&matchup.my_champion, &matchup.enemy_champion, &matchup.role)
+        {
+            continue;
+        }
+        target.reviews.retain(|r| r.at >= week_start);
+        if target.reviews.iter().any(|r| r.matchup_id == matchup.id) {
+            continue;
+        }
+        target.reviews.push(LoggedReview {
+            matchup_id: matchup.id.clone(),
+            at: now,
+        });
+        changed.push(target.id.clone());
+    }
+    changed
+}
+
+/// Progress of every target of `player` in the week starting at `week_start`
+///
+/// Games and wins are those of `account`, or of every account with `None`.
+pub fn progress<'a>(
+    targets: impl IntoIterator<Item = &'a PlanTarget>,
+    matches: &[&Match],
+    player: &Option<String>,
+    account: Option<&str>,
+    week_start: DateTime<Utc>,
+) -> PlanProgress {
+    let mut targets: Vec<TargetProgress> = targets
+        .into_iter()
+        .filter(|t| &t.player == player)
+        .map(|target| {
+            let done = target.done(matches, account, week_start);
+            TargetProgress {
+                complete: done >= target.count,
+                done: done.min(target.count),
+                target: target.clone(),
+            }
+        })
+        .collect();
+    targets.sort_by_key(|t| t.target.created);
+
+    PlanProgress {
+        week_start,
+        week_end: week_start + Duration::days(7),
+        completed: targets.iter().filter(|t| t.complete).count(),
+        targets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Weekday};
+
+    #[test]
+    fn test_plan_progress() {
+        let now = Utc::now();
+        let start = week_start(now, &Utc);
+        assert_eq!(start.weekday(), Weekday::Mon);
+        assert!(start <= now && now - start < Duration::days(7));
+        let thursday = NaiveDate::from_ymd_opt(2024, 6, 13)
+            .unwrap()
+            .and_hms_opt(18, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(week_start(thursday, &Utc).day(), 10);
+
+        let games = PlanTarget::new(
+            NewPlanTarget {
+                kind: TargetKind::Games,
+                count: 2,
+                my_champion: Some("camille".to_string()),
+                enemy_champion: Some("Renekton".to_string()),
+                role: None,
+                stale_only: false,
+            },
+            None,
+            0,
+        )
+        .unwrap();
+        assert_eq!(games.my_champion.as_deref(), Some("Camille"));
+        let reviews = PlanTarget::new(
+            NewPlanTarget {
+                kind: TargetKind::Reviews,
+                count: 3,
+                my_champion: None,
+                enemy_champion: None,
+                role: None,
+                stale_only: true,
+            },
+            None,
+            1,
+        )
+        .unwrap();
+        let mut targets = HashMap::from([
+            (games.id.clone(), games.clone()),
+            (reviews.id.clone(), reviews.clone()),
+        ]);
+
+        let game = |enemy: &str, date: DateTime<Utc>| {
+            let mut m = Match::new(
+                "Camille".to_string(),
+                enemy.to_string(),
+                "top".to_string(),
+                MatchResult::Win,
+                None,
+            );
+            m.date = date;
+            m
+        };
+        let mut smurf = game("Renekton", now);
+        smurf.account_puuid = Some("smurf".to_string());
+        let played = [
+            game("Renekton", now),
+            game("Renekton", start - Duration::hours(1)),
+            game("Darius", now),
+            smurf,
+        ];
+        let matches: Vec<&Match> = played.iter().collect();
+
+        let matchup = Matchup::new(
+            "Camille".to_string(),
+            "Renekton".to_string(),
+            "top".to_string(),
+        );
+        assert!(log_review(&mut targets, &matchup, false, now, start).is_empty());
+        assert_eq!(
+            log_review(&mut targets, &matchup, true, now, start),
+            vec![reviews.id.clone()]
+        );
+        assert!(log_review(&mut targets, &matchup, true, now, start).is_empty());
+
+        let plan = progress(targets.values(), &matches, &None, Some("main"), start);
+        assert_eq!(plan.targets.len(), 2);
+        assert_eq!(plan.targets[0].done, 1);
+        assert!(!plan.targets[0].complete);
+        assert_eq!(plan.targets[1].done, 1);
+        assert_eq!(plan.completed, 0);
+
+        // The smurf game counts once every account is included
+        let plan = progress(targets.values(), &matches, &None, None, start);
+        assert!(plan.targets[0].complete);
+
+        let bad = NewPlanTarget {
+            kind: TargetKind::Wins,
+            count: 0,
+            my_champion: None,
+            enemy_champion: None,
+            role: None,
+            stale_only: false,
+        };
+        assert!(PlanTarget::new(bad, None, 0).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Discord Rich Presence: "Reviewing Darius vs Garen" or "In game: Riven vs Fiora (Top)"
+//!
+//! Talks to the local Discord client over its IPC socket (a named pipe on
+//! Windows). Off unless enabled in the settings, and it needs a Discord
+//! application ID to show the presence under. A missing or restarted Discord
+//! is retried on the next update. Discord is talked to from a thread of its
+//! own, so one that stops answering never holds up the caller.
+
+use crate::settings::RichPresenceSettings;
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Discord listens on the first free of `discord-ipc-0` to `discord-ipc-9`
+const IPC_SLOTS: u32 = 10;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+/// Longest to wait for Discord to answer a frame
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What the app is doing, shown as the presence
+#[derive(Debug, Default)]
+struct Activity {
+    /// Matchup open in the app, e.g. "Darius vs Garen"
+    reviewing: Option<String>,
+    /// Pairing of the game in progress, e.g. "Riven vs Fiora (Top)"
+    in_game: Option<String>,
+    /// When the game was first seen in progress
+    game_started: Option<u64>,
+}
+
+impl Activity {
+    /// The presence to show, `None` to clear it; a game beats reviewing
+    fn to_json(&self) -> Option<Value> {
+        if let Some(ref pairing) = self.in_game {
+            let mut activity = json!({ "details": format!("In game: {}", pairing) });
+            if let Some(start) = self.game_started {
+                activity["timestamps"] = json!({ "start": start });
+            }
+            return Some(activity);
+        }
+        self.reviewing
+            .as_ref()
+            .map(|pairing| json!({ "details": format!("Reviewing {}", pairing) }))
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    settings: RichPresenceSettings,
+    activity: Activity,
+}
+
+/// The application to show a presence under and the activity, `None` for no
+/// presence at all
+type Wanted = Option<(String, Option<Value>)>;
+
+/// Shared presence state, updated from commands and the client monitor
+#[derive(Default)]
+pub struct RichPresence {
+    inner: Mutex<Inner>,
+    /// Hands what to show to the thread talking to Discord, started on first use
+    worker: OnceLock<Sender<Wanted>>,
+}
+
+impl RichPresence {
+    /// Apply new settings, clearing the presence when turned off
+    pub fn configure(&self, settings: &RichPresenceSettings) {
+        self.update(|inner| inner.settings = settings.clone());
+    }
+
+    /// Show the matchup open in the app, or nothing with `None`
+    pub fn set_reviewing(&self, pairing: Option<String>) {
+        self.update(|inner| inner.activity.reviewing = pairing);
+    }
+
+    /// Show the game in progress, or nothing with `None`
+    pub fn set_in_game(&self, pairing: Option<String>) {
+        self.update(|inner| {
+            if pairing.is_none() {
+                inner.activity.game_started = None;
+            } else if inner.activity.game_started.is_none() {
+                inner.activity.game_started = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|d| d.as_secs());
+            }
+            inner.activity.in_game = pairing;
+        });
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Inner)) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        change(&mut inner);
+
+        let wanted = match inner.settings.application_id.clone() {
+            Some(application_id) if inner.settings.enabled => {
+                Some((application_id, inner.activity.to_json()))
+            }
+            _ => None,
+        };
+        if wanted.is_none() && self.worker.get().is_none() {
+            return;
+        }
+        let worker = self.worker.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || show(receiver));
+            sender
+        });
+        let _ = worker.send(wanted);
+    }
+}
+
+/// Keep Discord showing the newest of what `receiver` hands over
+fn show(receiver: Receiver<Wanted>) {
+    // The application the connection was opened for, with the connection
+    let mut connection: Option<(String, Connection)> = None;
+    // Last activity sent, to skip updates that change nothing
+    let mut shown: Option<Option<Value>> = None;
+
+    while let Ok(mut wanted) = receiver.recv() {
+        // After a slow answer only the newest update matters
+        while let Ok(newer) = receiver.try_recv() {
+            wanted = newer;
+        }
+        let Some((application_id, activity)) = wanted else {
+            // Closing the connection clears the presence
+            connection = None;
+            shown = None;
+            continue;
+        };
+        // The handshake ties the connection to one application
+        if connection
+            .as_ref()
+            .is_some_and(|(id, _)| *id != application_id)
+        {
+            connection = None;
+            shown = None;
+        }
+        if shown.as_ref() == Some(&activity) {
+            continue;
+        }
+
+        if connection.is_none() {
+            connection = Connection::open(&application_id)
+                .ok()
+                .map(|c| (application_id, c));
+        }
+        let Some((_, ref mut open)) = connection else {
+            continue;
+        };
+        match open.set_activity(activity.as_ref()) {
+            Ok(()) => shown = Some(activity),
+            Err(_) => {
+                connection = None;
+                shown = None;
+            }
+        }
+    }
+}
+
+/// "Riven vs Fiora (Top)", or without the role when it isn't known
+pub fn pairing_label(my_champion: &str, enemy_champion: &str, role: Option<&str>) -> String {
+    match role {
+        Some(role) => format!(
+            "{} vs {} ({})",
+            my_champion,
+            enemy_champion,
+            capitalize(role)
+        ),
+        None => format!("{} vs {}", my_champion, enemy_champion),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+trait Pipe: Read + Write + Send {}
+impl<T: Read + Write + Send> Pipe for T {}
+
+struct Connection {
+    pipe: Box<dyn Pipe>,
+}
+
+impl Connection {
+    fn open(application_id: &str) -> io::Result<Self> {
+        let pipe = (0..IPC_SLOTS)
+            .find_map(|slot| open_pipe(slot).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Discord is not running"))?;
+
+        let mut connection = Self { pipe };
+        connection.send(
+            OP_HANDSHAKE,
+            &json!({ "v": 1, "client_id": application_id }),
+        )?;
+        Ok(connection)
+    }
+
+    fn set_activity(&mut self, activity: Option<&Value>) -> io::Result<()> {
+        let mut args = json!({ "pid": std::process::id() });
+        if let Some(activity) = activity {
+            args["activity"] = activity.clone();
+        }
+        self.send(
+            OP_FRAME,
+            &json!({
+                "cmd": "SET_ACTIVITY",
+                "args": args,
+                "nonce": uuid::Uuid::new_v4().to_string(),
+            }),
+        )
+    }
+
+    /// Send a frame and read Discord's answer to it
+    fn send(&mut self, op: u32, payload: &Value) -> io::Result<()> {
+        self.pipe.write_all(&encode_frame(op, payload))?;
+        self.pipe.flush()?;
+
+        let mut header = [0u8; 8];
+        self.pipe.read_exact(&mut header)?;
+        let op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let mut body = vec![0u8; length as usize];
+        self.pipe.read_exact(&mut body)?;
+
+        // Anything but a frame back (an error or close) ends the connection
+        if op != OP_FRAME {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                String::from_utf8_lossy(&body).into_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Frames are the opcode and payload length as little-endian u32s, then the JSON
+fn encode_frame(op: u32, payload: &Value) -> Vec<u8> {
+    let body = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&op.to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(body.as_bytes());
+    frame
+}
+
+#[cfg(unix)]
+fn open_pipe(slot: u32) -> io::Result<Box<dyn Pipe>> {
+    use std::os::unix::net::UnixStream;
+
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .unwrap_or_else(|| "/tmp".to_string());
+    let stream = UnixStream::connect(format!("{}/discord-ipc-{}", dir, slot))?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(windows)]
+fn open_pipe(slot: u32) -> io::Result<Box<dyn Pipe>> {
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!(r"\\.\pipe\discord-ipc-{}", slot))?;
+    Ok(Box::new(pipe))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_frame() {
+        let frame = encode_frame(OP_HANDSHAKE, &json!({ "v": 1 }));
+        assert_eq!(&frame[..4], &[0, 0, 0, 0]);
+        assert_eq!(&frame[4..8], &[7, 0, 0, 0]);
+        assert_eq!(&frame[8..], br#"{"v":1}"#);
+    }
+
+    #[test]
+    fn test_activity() {
+        let mut activity = Activity {
+            reviewing: Some(pairing_label("Darius", "Garen", None)),
+            ..Default::default()
+        };
+        assert_eq!(
+            activity.to_json().unwrap()["details"],
+            "Reviewing Darius vs Garen"
+        );
+
+        activity.in_game = Some(pairing_label("Riven", "Fiora", Some("top")));
+        assert_eq!(
+            activity.to_json().unwrap()["details"],
+            "In game: Riven vs Fiora (Top)"
+        );
+
+        assert!(Activity::default().to_json().is_none());
+    }
+}
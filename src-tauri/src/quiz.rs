@@ -0,0 +1,215 @@
+//! Self-test questions built from my matchup notes
+//!
+//! Each question asks for one part of a matchup's current notes, such as the
+//! runes or the loading screen summary. The answer is only sent when asked
+//! for, and the grade I give myself feeds the review schedule.
+
+use crate::matchup::Matchup;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Questions asked when no count is given
+pub const DEFAULT_QUIZ_LENGTH: usize = 10;
+
+/// Grades kept per matchup, oldest dropped first
+const MAX_RESULTS_KEPT: usize = 50;
+
+/// Part of the notes a question asks for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizTopic {
+    Runes,
+    SummonerSpells,
+    Items,
+    Summary,
+}
+
+impl QuizTopic {
+    const ALL: [QuizTopic; 4] = [
+        QuizTopic::Runes,
+        QuizTopic::SummonerSpells,
+        QuizTopic::Items,
+        QuizTopic::Summary,
+    ];
+
+    fn question(self) -> &'static str {
+        match self {
+            QuizTopic::Runes => "which runes do you take?",
+            QuizTopic::SummonerSpells => "which summoner spells do you take?",
+            QuizTopic::Items => "what do you build?",
+            QuizTopic::Summary => "what are the key points?",
+        }
+    }
+}
+
+/// How well I remembered, from my own judgement
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizGrade {
+    Forgot,
+    Partial,
+    Knew,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuizResult {
+    pub topic: QuizTopic,
+    pub grade: QuizGrade,
+    pub date: DateTime<Utc>,
+}
+
+/// A question, without its answer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuizQuestion {
+    pub matchup_id: String,
+    pub topic: QuizTopic,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    /// e.g. "Darius vs Pantheon (top): which runes do you take?"
+    pub prompt: String,
+}
+
+/// What the current notes of `matchup` say about `topic`, `None` when they don't cover it
+///
+/// `item_name` turns stored items, usually numeric IDs, into names.
+pub fn answer(
+    matchup: &Matchup,
+    topic: QuizTopic,
+    item_name: impl Fn(&str) -> String,
+) -> Option<Vec<String>> {
+    let current = matchup.current()?;
+    let answer = match topic {
+        QuizTopic::Runes if current.runes.is_empty() => current
+            .rune_page
+            .as_ref()
+            .map(|page| page.rune_names())
+            .unwrap_or_default(),
+        QuizTopic::Runes => current.runes.clone(),
+        QuizTopic::SummonerSpells => current.summoner_spells.clone(),
+        QuizTopic::Items => current.items.iter().map(|i| item_name(i)).collect(),
+        QuizTopic::Summary => current.summary.clone(),
+    };
+    (!answer.is_empty()).then_some(answer)
+}
+
+/// Record a self-grade and reschedule the matchup's review to match
+///
+/// Knowing the answer counts as a review, once a day however many of the
+/// matchup's questions come up; a partial answer restarts the current
+/// interval, and forgetting it starts the schedule over. Returns whether the
+/// grade counted as a review.
+pub fn record_grade(matchup: &mut Matchup, topic: QuizTopic, grade: QuizGrade) -> bool {
+    let now = Utc::now();
+    matchup.quiz_results.push(QuizResult {
+        topic,
+        grade,
+        date: now,
+    });
+    let excess = matchup.quiz_results.len().saturating_sub(MAX_RESULTS_KEPT);
+    matchup.quiz_results.drain(..excess);
+
+    let reviewed_today = matchup
+        .last_reviewed
+        .is_some_and(|at| at.date_naive() == now.date_naive());
+    match grade {
+        QuizGrade::Knew if !reviewed_today => {
+            matchup.mark_reviewed();
+            return true;
+        }
+        QuizGrade::Knew | QuizGrade::Partial => matchup.last_reviewed = Some(now),
+        QuizGrade::Forgot => {
+            matchup.review_count = 0;
+            matchup.last_reviewed = Some(now);
+        }
+    }
+    false
+}
+
+fn last_result(matchup: &Matchup, topic: QuizTopic) -> Option<&QuizResult> {
+    matchup.quiz_results.iter().rev().find(|r| r.topic == topic)
+}
+
+/// Up to `count` questions about `matchups`, those most in need of practice first
+///
+/// Questions never asked come first, then those I last forgot, then the ones
+/// asked longest ago.
+pub fn generate<'a>(
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    count: usize,
+) -> Vec<QuizQuestion> {
+    let mut candidates: Vec<(Option<&QuizResult>, QuizQuestion)> = matchups
+        .into_iter()
+        .flat_map(|matchup| {
+            QuizTopic::ALL
+                .into_iter()
+                .filter(|topic| answer(matchup, *topic, str::to_string).is_some())
+                .map(move |topic| {
+                    let question = QuizQuestion {
+                        matchup_id: matchup.id.clone(),
+                        topic,
+                        my_champion: matchup.my_champion.clone(),
+                        enemy_champion: matchup.enemy_champion.clone(),
+                        role: matchup.role.clone(),
+                        prompt: format!(
+                            "{} vs {} ({}): {}",
+                            matchup.my_champion,
+                            matchup.enemy_champion,
+                            matchup.role,
+                            topic.question()
+                        ),
+                    };
+                    (last_result(matchup, topic), question)
+                })
+        })
+        .collect();
+
+    candidates.sort_by(|(a, qa), (b, qb)| {
+        let key = |r: &Option<&QuizResult>| r.map(|r| (r.grade, r.date));
+        key(a).cmp(&key(b)).then_with(|| qa.prompt.cmp(&qb.prompt))
+    });
+    candidates
+        .into_iter()
+        .take(count)
+        .map(|(_, question)| question)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchup::MatchupUpdate;
+
+    #[test]
+    fn test_generate_and_grade() {
+        let mut garen = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        garen.add_version(MatchupUpdate {
+            runes: vec!["Conqueror".to_string()],
+            items: vec!["3078".to_string()],
+            ..Default::default()
+        });
+        let empty = Matchup::new("Darius".to_string(), "Teemo".to_string(), "top".to_string());
+
+        let questions = generate([&garen, &empty], 10);
+        assert_eq!(questions.len(), 2);
+        assert!(questions.iter().all(|q| q.matchup_id == garen.id));
+        assert_eq!(
+            answer(&garen, QuizTopic::Items, |_| "Trinity Force".to_string()),
+            Some(vec!["Trinity Force".to_string()])
+        );
+
+        // A remembered answer goes to the back and counts as a review
+        assert!(record_grade(&mut garen, QuizTopic::Items, QuizGrade::Knew));
+        assert_eq!(garen.review_count, 1);
+        let questions = generate([&garen], 1);
+        assert_eq!(questions[0].topic, QuizTopic::Runes);
+
+        record_grade(&mut garen, QuizTopic::Runes, QuizGrade::Forgot);
+        assert_eq!(garen.review_count, 0);
+        assert_eq!(generate([&garen], 1)[0].topic, QuizTopic::Runes);
+
+        // More questions the same day don't add reviews
+        assert!(!record_grade(&mut garen, QuizTopic::Items, QuizGrade::Knew));
+        assert_eq!(garen.review_count, 0);
+    }
+}
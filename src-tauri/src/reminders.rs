@@ -0,0 +1,164 @@
+//! Follow-ups to do later, such as re-testing runes after a patch
+//!
+//! A reminder can be linked to a matchup and falls due on a date, once the
+//! live patch reaches a given patch, or whichever comes first.
+
+use crate::matchup::parse_patch;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Longest reminder text
+const MAX_TEXT_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub matchup_id: Option<String>,
+    #[serde(default)]
+    pub due: Option<DateTime<Utc>>,
+    /// Patch the reminder falls due on, e.g. "14.13"
+    #[serde(default)]
+    pub due_patch: Option<String>,
+    pub created: DateTime<Utc>,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Data for creating a reminder
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct NewReminder {
+    pub text: String,
+    pub matchup_id: Option<String>,
+    pub due: Option<DateTime<Utc>>,
+    pub due_patch: Option<String>,
+}
+
+impl NewReminder {
+    pub fn validate(&self) -> Result<(), String> {
+        let length = self.text.trim().chars().count();
+        if length == 0 {
+            return Err("Reminder text cannot be empty".to_string());
+        }
+        if length > MAX_TEXT_LENGTH {
+            return Err(format!(
+                "Reminder text is limited to {} characters",
+                MAX_TEXT_LENGTH
+            ));
+        }
+        if let Some(ref patch) = self.due_patch {
+            if parse_patch(patch).is_none() {
+                return Err(format!("{} is not a patch like 14.13", patch));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Reminder {
+    pub fn new(reminder: NewReminder) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            text: reminder.text.trim().to_string(),
+            matchup_id: reminder.matchup_id,
+            due: reminder.due,
+            due_patch: reminder.due_patch.map(|p| p.trim().to_string()),
+            created: Utc::now(),
+            done: false,
+            completed_at: None,
+        }
+    }
+
+    /// Mark as done (or open again)
+    pub fn set_done(&mut self, done: bool) {
+        self.done = done;
+        // Completing it again keeps the first completion time
+        if !done {
+            self.completed_at = None;
+        } else if self.completed_at.is_none() {
+            self.completed_at = Some(Utc::now());
+        }
+    }
+
+    /// Open and past its date or patch; reminders without either are never due
+    pub fn is_due(&self, now: DateTime<Utc>, current_patch: Option<&str>) -> bool {
+        let date_reached = self.due.is_some_and(|due| due <= now);
+        let patch_reached = self
+            .due_patch
+            .as_deref()
+            .and_then(parse_patch)
+            .zip(current_patch.and_then(parse_patch))
+            .is_some_and(|(due, current)| current >= due);
+        !self.done && (date_reached || patch_reached)
+    }
+}
+
+/// Reminders due now, oldest due date first
+pub fn due_reminders<'a>(
+    reminders: impl IntoIterator<Item = &'a Reminder>,
+    now: DateTime<Utc>,
+    current_patch: Option<&str>,
+) -> Vec<Reminder> {
+    let mut due: Vec<Reminder> = reminders
+        .into_iter()
+        .filter(|r| r.is_due(now, current_patch))
+        .cloned()
+        .collect();
+    // Patch-only reminders sort first; they're due since the patch came out
+    due.sort_by(|a, b| a.due.cmp(&b.due).then_with(|| a.created.cmp(&b.created)));
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_due_reminders() {
+        let now = Utc::now();
+        let dated = Reminder::new(NewReminder {
+            text: "Review the Garen notes".to_string(),
+            due: Some(now - Duration::hours(1)),
+            ..Default::default()
+        });
+        let later = Reminder::new(NewReminder {
+            text: "Later".to_string(),
+            due: Some(now + Duration::days(1)),
+            ..Default::default()
+        });
+        let patch = NewReminder {
+            text: "Re-test Fleet vs Conqueror".to_string(),
+            due_patch: Some("14.13".to_string()),
+            ..Default::default()
+        };
+        assert!(patch.validate().is_ok());
+        let mut patch = Reminder::new(patch);
+
+        let due = due_reminders([&dated, &later, &patch], now, Some("14.12"));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, dated.id);
+        assert_eq!(
+            due_reminders([&dated, &later, &patch], now, Some("14.13")).len(),
+            2
+        );
+
+        patch.set_done(true);
+        assert!(!patch.is_due(now, Some("15.1")));
+        let completed_at = patch.completed_at;
+        patch.set_done(true);
+        assert_eq!(patch.completed_at, completed_at);
+        assert!(NewReminder {
+            text: "x".to_string(),
+            due_patch: Some("next".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Statistics report for a period, written as Markdown or a standalone HTML page
+//!
+//! Meant for a monthly self-review or to send to a coach: overall record,
+//! tables per role and per champion, the worst matchups and streaks.
+
+use crate::markdown;
+use crate::matchup::{Match, MatchRecord, MatchResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Fewest games in a pairing before it's listed as a problem matchup
+const MIN_PROBLEM_GAMES: u32 = 2;
+
+/// Problem matchups listed
+const PROBLEM_MATCHUPS: usize = 5;
+
+/// Games counted in a report; open ends are unbounded
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ReportRange {
+    fn contains(&self, date: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+
+    fn label(&self) -> String {
+        let day = |date: DateTime<Utc>| date.format("%Y-%m-%d").to_string();
+        match (self.from, self.to) {
+            (Some(from), Some(to)) => format!("{} to {}", day(from), day(to)),
+            (Some(from), None) => format!("Since {}", day(from)),
+            (None, Some(to)) => format!("Until {}", day(to)),
+            (None, None) => "All games".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// A champion pairing I keep losing
+#[derive(Debug, Clone, PartialEq)]
+struct ProblemMatchup {
+    my_champion: String,
+    enemy_champion: String,
+    role: String,
+    record: MatchRecord,
+}
+
+/// Consecutive results, in games
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Streaks {
+    longest_win: u32,
+    longest_loss: u32,
+    /// Positive for wins, negative for losses
+    current: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsReport {
+    range: ReportRange,
+    generated: DateTime<Utc>,
+    record: MatchRecord,
+    /// Most played first
+    by_role: Vec<(String, MatchRecord)>,
+    by_champion: Vec<(String, MatchRecord)>,
+    /// Lowest win rate first
+    problem_matchups: Vec<ProblemMatchup>,
+    streaks: Streaks,
+}
+
+impl StatsReport {
+    pub fn new<'a>(
+        matches: impl IntoIterator<Item = &'a Match>,
+        range: ReportRange,
+        generated: DateTime<Utc>,
+    ) -> Self {
+        let mut played: Vec<&Match> = matches
+            .into_iter()
+            .filter(|m| range.contains(m.date))
+            .collect();
+        played.sort_by_key(|m| m.date);
+
+        let mut record = MatchRecord::default();
+        let mut by_role: HashMap<String, MatchRecord> = HashMap::new();
+        let mut by_champion: HashMap<String, MatchRecord> = HashMap::new();
+        let mut pairings: HashMap<(String, String, String), MatchRecord> = HashMap::new();
+        let mut streaks = Streaks::default();
+
+        for m in &played {
+            let won = m.result == MatchResult::Win;
+            let mut records = vec![
+                &mut record,
+                by_champion.entry(m.my_champion.clone()).or_default(),
+            ];
+            if m.has_lane_opponent() {
+                records.push(by_role.entry(m.role.clone()).or_default());
+                records.push(
+                    pairings
+                        .entry((
+                            m.my_champion.clone(),
+                            m.enemy_champion.clone(),
+                            m.role.clone(),
+                        ))
+                        .or_default(),
+                );
+            }
+            for r in records {
+                r.games += 1;
+                r.wins += won as u32;
+            }
+
+            streaks.current = match (won, streaks.current) {
+                (true, current) if current > 0 => current + 1,
+                (true, _) => 1,
+                (false, current) if current < 0 => current - 1,
+                (false, _) => -1,
+            };
+            if won {
+                streaks.longest_win = streaks.longest_win.max(streaks.current as u32);
+            } else {
+                streaks.longest_loss = streaks.longest_loss.max(streaks.current.unsigned_abs());
+            }
+        }
+
+        let mut problem_matchups: Vec<ProblemMatchup> = pairings
+            .into_iter()
+            .filter(|(_, r)| r.games >= MIN_PROBLEM_GAMES && r.wins * 2 < r.games)
+            .map(
+                |((my_champion, enemy_champion, role), record)| ProblemMatchup {
+                    my_champion,
+                    enemy_champion,
+                    role,
+                    record,
+                },
+            )
+            .collect();
+        problem_matchups.sort_by(|a, b| {
+            a.record
+                .win_rate()
+                .partial_cmp(&b.record.win_rate())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.record.games.cmp(&a.record.games))
+                .then_with(|| a.my_champion.cmp(&b.my_champion))
+        });
+        problem_matchups.truncate(PROBLEM_MATCHUPS);
+
+        Self {
+            range,
+            generated,
+            record,
+            by_role: most_played(by_role),
+            by_champion: most_played(by_champion),
+            problem_matchups,
+            streaks,
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# MatchupHelper report\n");
+        let _ = writeln!(
+            out,
+            "{} · generated {}\n",
+            self.range.label(),
+            self.generated.format("%Y-%m-%d %H:%M UTC")
+        );
+
+        let _ = writeln!(out, "## Overall\n");
+        let _ = writeln!(
+            out,
+            "{} games, {} wins, {} losses ({})\n",
+            self.record.games,
+            self.record.wins,
+            self.record.games - self.record.wins,
+            percent(&self.record)
+        );
+        let streak = match self.streaks.current {
+            0 => "none".to_string(),
+            n if n > 0 => format!("{} wins", n),
+            n => format!("{} losses", n.unsigned_abs()),
+        };
+        let _ = writeln!(
+            out,
+            "Longest win streak: {} · Longest loss streak: {} · Current streak: {}\n",
+            self.streaks.longest_win, self.streaks.longest_loss, streak
+        );
+
+        table(&mut out, "By role", "Role", &self.by_role);
+        table(&mut out, "By champion", "Champion", &self.by_champion);
+
+        let _ = writeln!(out, "## Problem matchups\n");
+        if self.problem_matchups.is_empty() {
+            let _ = writeln!(out, "None with {} or more games.\n", MIN_PROBLEM_GAMES);
+        } else {
+            let _ = writeln!(out, "| Matchup | Role | Games | Win rate |");
+            let _ = writeln!(out, "| --- | --- | ---: | ---: |");
+            for p in &self.problem_matchups {
+                let _ = writeln!(
+                    out,
+                    "| {} vs {} | {} | {} | {} |",
+                    p.my_champion,
+                    p.enemy_champion,
+                    p.role,
+                    p.record.games,
+                    percent(&p.record)
+                );
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// A standalone page; names are escaped by the Markdown renderer
+    fn to_html(&self) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>MatchupHelper report</title>\n<style>{}</style>\n</head>\n\
+             <body>\n{}</body>\n</html>\n",
+            HTML_STYLE,
+            markdown::render(&self.to_markdown())
+        )
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:48em;margin:2em auto;color:#222}\
+table{border-collapse:collapse;margin-bottom:1em}th,td{border:1px solid #ccc;padding:.3em .8em}";
+
+fn most_played(records: HashMap<String, MatchRecord>) -> Vec<(String, MatchRecord)> {
+    let mut rows: Vec<(String, MatchRecord)> = records.into_iter().collect();
+    rows.sort_by(|a, b| b.1.games.cmp(&a.1.games).then_with(|| a.0.cmp(&b.0)));
+    rows
+}
+
+fn table(out: &mut String, title: &str, label: &str, rows: &[(String, MatchRecord)]) {
+    let _ = writeln!(out, "## {}\n", title);
+    if rows.is_empty() {
+        let _ = writeln!(out, "No games.\n");
+        return;
+    }
+    let _ = writeln!(out, "| {} | Games | Wins | Win rate |", label);
+    let _ = writeln!(out, "| --- | ---: | ---: | ---: |");
+    for (name, record) in rows {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            name,
+            record.games,
+            record.wins,
+            percent(record)
+        );
+    }
+    out.push('\n');
+}
+
+fn percent(record: &MatchRecord) -> String {
+    record
+        .win_rate()
+        .map_or_else(|| "-".to_string(), |rate| format!("{:.0}%", rate * 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let now = Utc::now();
+        let matches: Vec<Match> = [
+            ("Darius", "Garen", "W"),
+            ("Darius", "Teemo", "L"),
+            ("Darius", "Teemo", "L"),
+            ("Darius", "Teemo", "L"),
+            ("Ahri", "Zed", "W"),
+            ("Ahri", "Zed", "W"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (my, enemy, result))| {
+            let mut m = Match::new(
+                my.to_string(),
+                enemy.to_string(),
+                if my == "Ahri" { "mid" } else { "top" }.to_string(),
+                if result == "W" {
+                    MatchResult::Win
+                } else {
+                    MatchResult::Loss
+                },
+                None,
+            );
+            m.date = now - chrono::Duration::days(10 - i as i64);
+            m
+        })
+        .collect();
+
+        let range = ReportRange {
+            from: Some(now - chrono::Duration::days(30)),
+            to: None,
+        };
+        let report = StatsReport::new(&matches, range, now);
+        assert_eq!(report.record, MatchRecord { games: 6, wins: 3 });
+        assert_eq!(report.by_role[0].0, "top");
+        assert_eq!(report.problem_matchups.len(), 1);
+        assert_eq!(report.problem_matchups[0].enemy_champion, "Teemo");
+        assert_eq!(
+            report.streaks,
+            Streaks {
+                longest_win: 2,
+                longest_loss: 3,
+                current: 2
+            }
+        );
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<td>Darius vs Teemo</td>"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Spaced-repetition scheduling for matchup reviews
+
+use crate::matchup::{Match, MatchResult, Matchup};
+use crate::storage::AppData;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Interval before the first review, doubled after every review
+const BASE_INTERVAL_DAYS: i64 = 3;
+/// Upper bound for the review interval
+const MAX_INTERVAL_DAYS: i64 = 60;
+/// Window used to count recent games and losses
+const RECENT_WINDOW_DAYS: i64 = 14;
+
+/// A matchup scheduled for re-reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub role: String,
+    pub priority: f64,
+    pub days_since_review: i64,
+    pub interval_days: i64,
+    pub recent_games: u32,
+    pub recent_losses: u32,
+}
+
+/// Days to wait before a matchup is due again
+pub fn interval_days(review_count: u32) -> i64 {
+    let factor = 1i64 << review_count.min(10);
+    (BASE_INTERVAL_DAYS * factor).min(MAX_INTERVAL_DAYS)
+}
+
+/// Schedule a single matchup, returning `None` when it is not due yet
+pub fn schedule(matchup: &Matchup, matches: &[&Match], now: DateTime<Utc>) -> Option<ReviewItem> {
+    let last_touched = matchup.last_touched();
+    let days_since_review = (now - last_touched).num_days().max(0);
+    let mut interval = interval_days(matchup.review_count);
+
+    let recent_cutoff = now - Duration::days(RECENT_WINDOW_DAYS);
+    let recent: Vec<&&Match> = matches
+        .iter()
+        .filter(|m| m.date >= recent_cutoff && matchup.is_related_match(m))
+        .collect();
+    let recent_games = recent.len() as u32;
+    let recent_losses = recent
+        .iter()
+        .filter(|m| m.result == MatchResult::Loss)
+        .count() as u32;
+
+    // Matchups I keep playing (and losing) come back sooner
+    let games_since_review = recent.iter().filter(|m| m.date > last_touched).count() as i64;
+    interval = (interval - games_since_review).max(1);
+
+    let due = days_since_review >= interval || recent_losses >= 2;
+    if !due {
+        return None;
+    }
+
+    let priority = days_since_review as f64 / interval as f64
+        + 0.25 * recent_games as f64
+        + 0.5 * recent_losses as f64;
+
+    Some(ReviewItem {
+        matchup_id: matchup.id.clone(),
+        my_champion: matchup.my_champion.clone(),
+        enemy_champion: matchup.enemy_champion.clone(),
+        role: matchup.role.clone(),
+        priority,
+        days_since_review,
+        interval_days: interval,
+        recent_games,
+        recent_losses,
+    })
+}
+
+/// Build the review queue for the active player's matchups, most urgent first
+pub fn build_queue(data: &AppData, now: DateTime<Utc>) -> Vec<ReviewItem> {
+    let player = &data.metadata.active_player;
+    let matches: Vec<&Match> = data
+        .matches
+        .values()
+        .filter(|m| &m.player == player)
+        .collect();
+
+    let mut queue: Vec<ReviewItem> = data
+        .matchups
+        .values()
+        .filter(|m| &m.player == player)
+        .filter_map(|m| schedule(m, &matches, now))
+        .collect();
+
+    queue.sort_by(|a, b| b.priority.total_cmp(&a.priority));
+    queue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matchup_written_days_ago(days: i64) -> Matchup {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.versions[0].date = Utc::now() - Duration::days(days);
+        matchup
+    }
+
+    #[test]
+    fn test_interval_grows_and_caps() {
+        assert_eq!(interval_days(0), 3);
+        assert_eq!(interval_days(1), 6);
+        assert_eq!(interval_days(2), 12);
+        assert_eq!(interval_days(30), MAX_INTERVAL_DAYS);
+    }
+
+    #[test]
+    fn test_fresh_matchup_not_due() {
+        let matchup = matchup_written_days_ago(0);
+        assert!(schedule(&matchup, &[], Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_old_matchup_due() {
+        let matchup = matchup_written_days_ago(10);
+        let item = schedule(&matchup, &[], Utc::now()).unwrap();
+        assert_eq!(item.days_since_review, 10);
+    }
+
+    #[test]
+    fn test_recent_losses_make_due() {
+        let matchup = matchup_written_days_ago(0);
+        let loss = || {
+            Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                MatchResult::Loss,
+                None,
+            )
+        };
+        let (a, b) = (loss(), loss());
+
+        let item = schedule(&matchup, &[&a, &b], Utc::now()).unwrap();
+        assert_eq!(item.recent_losses, 2);
+    }
+
+    #[test]
+    fn test_mark_reviewed_resets() {
+        let mut matchup = matchup_written_days_ago(10);
+        matchup.mark_reviewed();
+
+        assert_eq!(matchup.review_count, 1);
+        assert!(schedule(&matchup, &[], Utc::now()).is_none());
+    }
+}
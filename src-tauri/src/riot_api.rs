@@ -0,0 +1,232 @@
+//! Riot public API: match history without the League client running
+//!
+//! Needs a user-supplied API key. Games are mapped into the same `Match`
+//! model as client imports and share their game IDs, so either import skips
+//! games already brought in by the other.
+
+use crate::champions;
+use crate::draft::Side;
+use crate::lcu::{normalize_position, patch_from_game_version};
+use crate::matchup::{GameMode, Match, MatchResult, MatchStats, QueueType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RiotApiError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Riot API returned HTTP {0}")]
+    Status(u16),
+    #[error("Unexpected Riot API response")]
+    InvalidResponse,
+}
+
+/// Routing region for match data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RiotRegion {
+    Americas,
+    Europe,
+    Asia,
+    Sea,
+}
+
+impl RiotRegion {
+    fn host(self) -> &'static str {
+        match self {
+            RiotRegion::Americas => "americas.api.riotgames.com",
+            RiotRegion::Europe => "europe.api.riotgames.com",
+            RiotRegion::Asia => "asia.api.riotgames.com",
+            RiotRegion::Sea => "sea.api.riotgames.com",
+        }
+    }
+}
+
+/// API key and region for the Riot API
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RiotApiSettings {
+    pub api_key: String,
+    pub region: RiotRegion,
+}
+
+/// Riot API client
+pub struct RiotApiClient {
+    http_client: reqwest::blocking::Client,
+    settings: RiotApiSettings,
+}
+
+impl RiotApiClient {
+    pub fn new(settings: RiotApiSettings) -> Self {
+        Self {
+            http_client: reqwest::blocking::Client::new(),
+            settings,
+        }
+    }
+
+    fn get(&self, path: &str) -> Result<Value, RiotApiError> {
+        let response = self
+            .http_client
+            .get(format!("https://{}{}", self.settings.region.host(), path))
+            .header("X-Riot-Token", &self.settings.api_key)
+            .send()?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RiotApiError::Status(status.as_u16()));
+        }
+        Ok(response.json()?)
+    }
+
+    /// IDs of the most recent matches of a player, newest first
+    pub fn get_match_ids(&self, puuid: &str, count: u32) -> Result<Vec<String>, RiotApiError> {
+        let ids = self.get(&format!(
+            "/lol/match/v5/matches/by-puuid/{}/ids?start=0&count={}",
+            puuid,
+            count.min(100)
+        ))?;
+        serde_json::from_value(ids).map_err(|_| RiotApiError::InvalidResponse)
+    }
+
+    /// Fetch a match and map it from the player's point of view
+    pub fn get_match(&self, match_id: &str, puuid: &str) -> Result<Match, RiotApiError> {
+        let game = self.get(&format!("/lol/match/v5/matches/{}", match_id))?;
+        parse_match(&game, puuid).ok_or(RiotApiError::InvalidResponse)
+    }
+}
+
+/// Map a Match-V5 game into a match for the player with `puuid`
+pub fn parse_match(game: &Value, puuid: &str) -> Option<Match> {
+    let info = game.get("info")?;
+    let participants = info.get("participants")?.as_array()?;
+    let me = participants
+        .iter()
+        .find(|p| p.get("puuid").and_then(|id| id.as_str()) == Some(puuid))?;
+
+    let num = |player: &Value, key: &str| player.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let position = |player: &Value| {
+        player
+            .get("teamPosition")
+            .and_then(|p| p.as_str())
+            .and_then(normalize_position)
+    };
+    let champion = |player: &Value| {
+        let id = player.get("championId").and_then(|c| c.as_i64())? as i32;
+        champions::name_for_id(id).or_else(|| {
+            player
+                .get("championName")
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+        })
+    };
+
+    let game_mode = GameMode::from_lcu(info.get("gameMode")?.as_str()?);
+    let role = position(me);
+    let my_team = me.get("teamId");
+    let enemy = role.as_ref().and_then(|role| {
+        participants
+            .iter()
+            .find(|p| p.get("teamId") != my_team && position(p).as_ref() == Some(role))
+    });
+
+    let mut m = Match::new(
+        champion(me)?,
+        match (enemy.and_then(champion), game_mode) {
+            (Some(name), _) => name,
+            (None, GameMode::Classic) => "Unknown".to_string(),
+            (None, _) => String::new(),
+        },
+        role.unwrap_or_default(),
+        if me.get("win")?.as_bool()? {
+            MatchResult::Win
+        } else {
+            MatchResult::Loss
+        },
+        Some(info.get("gameId")?.as_i64()?.to_string()),
+    );
+
+    let queue_id = info.get("queueId").and_then(|q| q.as_i64()).unwrap_or(0) as i32;
+    m.queue_id = Some(queue_id);
+    m.queue = Some(QueueType::from_queue_id(queue_id));
+    m.patch = info
+        .get("gameVersion")
+        .and_then(|v| v.as_str())
+        .and_then(patch_from_game_version);
+    m.game_mode = Some(game_mode);
+    m.side = my_team
+        .and_then(|t| t.as_i64())
+        .and_then(Side::from_team_id)
+        .filter(|_| game_mode != GameMode::Arena);
+    m.enemy_riot_id = enemy.and_then(|p| {
+        let name = p.get("riotIdGameName")?.as_str()?;
+        let tag = p.get("riotIdTagline")?.as_str()?;
+        (!name.is_empty()).then(|| format!("{}#{}", name, tag))
+    });
+    m.account_puuid = Some(puuid.to_string());
+    m.stats = Some(MatchStats {
+        kills: num(me, "kills") as u32,
+        deaths: num(me, "deaths") as u32,
+        assists: num(me, "assists") as u32,
+        cs: (num(me, "totalMinionsKilled") + num(me, "neutralMinionsKilled")) as u32,
+        gold_earned: num(me, "goldEarned") as u32,
+        damage_to_champions: num(me, "totalDamageDealtToChampions") as u32,
+        vision_score: num(me, "visionScore") as u32,
+        game_duration: num(info, "gameDuration") as u32,
+    });
+    if let Some(played_at) = info
+        .get("gameCreation")
+        .and_then(|c| c.as_i64())
+        .and_then(chrono::DateTime::from_timestamp_millis)
+    {
+        m.date = played_at;
+    }
+
+    Some(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_match() {
+        let game = json!({
+            "metadata": { "matchId": "EUW1_42" },
+            "info": {
+                "gameId": 42,
+                "gameCreation": 1_700_000_000_000i64,
+                "gameDuration": 1800,
+                "gameMode": "CLASSIC",
+                "gameVersion": "14.3.556.1234",
+                "queueId": 420,
+                "participants": [
+                    {
+                        "puuid": "me", "championId": 122, "teamId": 100, "teamPosition": "TOP",
+                        "win": true, "kills": 5, "deaths": 2, "assists": 3,
+                        "totalMinionsKilled": 180, "neutralMinionsKilled": 8
+                    },
+                    { "puuid": "jungler", "championId": 64, "teamId": 200, "teamPosition": "JUNGLE" },
+                    {
+                        "puuid": "laner", "championId": 86, "teamId": 200, "teamPosition": "TOP",
+                        "riotIdGameName": "Them", "riotIdTagline": "EUW"
+                    }
+                ]
+            }
+        });
+
+        let m = parse_match(&game, "me").unwrap();
+        assert_eq!(m.my_champion, "Darius");
+        assert_eq!(m.enemy_champion, "Garen");
+        assert_eq!(m.enemy_riot_id.as_deref(), Some("Them#EUW"));
+        assert_eq!(m.role, "top");
+        assert_eq!(m.result, MatchResult::Win);
+        assert_eq!(m.game_id.as_deref(), Some("42"));
+        assert_eq!(m.queue, Some(QueueType::RankedSolo));
+        assert_eq!(m.patch.as_deref(), Some("14.3"));
+        assert_eq!(m.side, Some(Side::Blue));
+        assert_eq!(m.stats.unwrap().cs, 188);
+
+        assert!(parse_match(&game, "someone else").is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Rune and summoner spell names for the IDs reported by the client
+//!
+//! Only keystones, rune trees and summoner spells are named; other runes fall
+//! back to their numeric ID.
+
+/// Rune trees as (style ID, name)
+const RUNE_TREES: &[(i32, &str)] = &[
+    (8000, "Precision"),
+    (8100, "Domination"),
+    (8200, "Sorcery"),
+    (8300, "Inspiration"),
+    (8400, "Resolve"),
+];
+
+/// Keystones as (perk ID, name)
+const KEYSTONES: &[(i32, &str)] = &[
+    (8005, "Press the Attack"),
+    (8008, "Lethal Tempo"),
+    (8010, "Conqueror"),
+    (8021, "Fleet Footwork"),
+    (8112, "Electrocute"),
+    (8128, "Dark Harvest"),
+    (9923, "Hail of Blades"),
+    (8214, "Summon Aery"),
+    (8229, "Arcane Comet"),
+    (8230, "Phase Rush"),
+    (8351, "Glacial Augment"),
+    (8360, "Unsealed Spellbook"),
+    (8369, "First Strike"),
+    (8437, "Grasp of the Undying"),
+    (8439, "Aftershock"),
+    (8465, "Guardian"),
+];
+
+/// Summoner spells as (spell ID, name)
+const SUMMONER_SPELLS: &[(i32, &str)] = &[
+    (1, "Cleanse"),
+    (3, "Exhaust"),
+    (4, "Flash"),
+    (6, "Ghost"),
+    (7, "Heal"),
+    (11, "Smite"),
+    (12, "Teleport"),
+    (13, "Clarity"),
+    (14, "Ignite"),
+    (21, "Barrier"),
+    (32, "Mark"),
+];
+
+fn lookup(table: &[(i32, &str)], id: i32) -> String {
+    table
+        .iter()
+        .find(|(entry_id, _)| *entry_id == id)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Name of a keystone, tree or other rune
+pub fn rune_name(id: i32) -> String {
+    if RUNE_TREES.iter().any(|(tree, _)| *tree == id) {
+        lookup(RUNE_TREES, id)
+    } else {
+        lookup(KEYSTONES, id)
+    }
+}
+
+/// Name of a summoner spell
+pub fn summoner_spell_name(id: i32) -> String {
+    lookup(SUMMONER_SPELLS, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(rune_name(8010), "Conqueror");
+        assert_eq!(rune_name(8400), "Resolve");
+        assert_eq!(rune_name(9111), "9111");
+        assert_eq!(summoner_spell_name(14), "Ignite");
+    }
+}
@@ -0,0 +1,218 @@
+//! Made-up matchups and games for demos, screenshots and frontend work
+
+use crate::matchup::{Match, MatchResult, MatchStats, Matchup, MatchupUpdate, QueueType};
+use crate::storage::AppData;
+use chrono::{DateTime, Duration, Utc};
+
+struct SampleVersion {
+    patch: &'static str,
+    notes: &'static str,
+    tags: &'static [&'static str],
+    difficulty: u8,
+    summary: &'static [&'static str],
+}
+
+struct SampleMatchup {
+    my_champion: &'static str,
+    enemy_champion: &'static str,
+    role: &'static str,
+    runes: &'static [&'static str],
+    summoner_spells: &'static [&'static str],
+    /// Oldest first, each one written a week after the last
+    versions: &'static [SampleVersion],
+    goals: &'static [&'static str],
+    /// Games against this matchup, oldest first: 'W' or 'L'
+    results: &'static str,
+}
+
+const SAMPLES: &[SampleMatchup] = &[
+    SampleMatchup {
+        my_champion: "Darius",
+        enemy_champion: "Garen",
+        role: "top",
+        runes: &["Conqueror", "Triumph", "Legend: Tenacity", "Last Stand"],
+        summoner_spells: &["Flash", "Ghost"],
+        versions: &[
+            SampleVersion {
+                patch: "14.10",
+                notes: "Garen wins short trades with Q silence. Stay out of his E range until I have 3 stacks.",
+                tags: &["even", "short-trades"],
+                difficulty: 6,
+                summary: &["Respect level 2 Q", "Fight once he wastes E"],
+            },
+            SampleVersion {
+                patch: "14.12",
+                notes: "Garen wins short trades with Q silence. Stay out of his E range until I have 3 stacks.\n\nGhost lets me walk him down after he spins; all in at 6 if he is below 70%.",
+                tags: &["even", "short-trades", "all-in-6"],
+                difficulty: 5,
+                summary: &["Respect level 2 Q", "Fight once he wastes E", "All in at 6 with Ghost"],
+            },
+        ],
+        goals: &["Hit 7 CS per minute by 15 minutes"],
+        results: "LWWLW",
+    },
+    SampleMatchup {
+        my_champion: "Ahri",
+        enemy_champion: "Zed",
+        role: "mid",
+        runes: &["Electrocute", "Taste of Blood", "Eyeball Collection", "Ultimate Hunter"],
+        summoner_spells: &["Flash", "Ignite"],
+        versions: &[SampleVersion {
+            patch: "14.12",
+            notes: "Hold charm for when he shadows in. Rush Seeker's Armguard and keep ult to dodge his.",
+            tags: &["hard", "assassin"],
+            difficulty: 7,
+            summary: &["Hold E for his W", "Seeker's Armguard first", "Save R to dodge Death Mark"],
+        }],
+        goals: &[],
+        results: "LLWL",
+    },
+    SampleMatchup {
+        my_champion: "Jinx",
+        enemy_champion: "Draven",
+        role: "adc",
+        runes: &["Lethal Tempo", "Presence of Mind", "Legend: Bloodline", "Cut Down"],
+        summoner_spells: &["Flash", "Heal"],
+        versions: &[SampleVersion {
+            patch: "14.11",
+            notes: "Give up early CS rather than trading into his axes. I outscale after two items.",
+            tags: &["hard", "scaling"],
+            difficulty: 8,
+            summary: &["Farm safely until two items", "Rockets to last hit under tower"],
+        }],
+        goals: &["Die fewer than 2 times before 15 minutes"],
+        results: "LWL",
+    },
+    SampleMatchup {
+        my_champion: "LeeSin",
+        enemy_champion: "Khazix",
+        role: "jungle",
+        runes: &["Conqueror", "Triumph", "Legend: Alacrity", "Last Stand"],
+        summoner_spells: &["Flash", "Smite"],
+        versions: &[SampleVersion {
+            patch: "14.12",
+            notes: "Invade early before he gets evolutions. Ward his raptors to track his start.",
+            tags: &["easy", "early-game"],
+            difficulty: 3,
+            summary: &["Level 3 invade", "Never fight him isolated after 6"],
+        }],
+        goals: &[],
+        results: "WWWLW",
+    },
+    SampleMatchup {
+        my_champion: "Thresh",
+        enemy_champion: "Blitzcrank",
+        role: "support",
+        runes: &["Aftershock", "Font of Life", "Bone Plating", "Unflinching"],
+        summoner_spells: &["Flash", "Ignite"],
+        versions: &[],
+        goals: &[],
+        results: "",
+    },
+];
+
+/// Sample matchups with a few versions each, and games linked to them
+///
+/// Everything is dated in the weeks before `now`.
+pub fn sample_data(now: DateTime<Utc>) -> AppData {
+    let mut data = AppData::default();
+
+    for (index, sample) in SAMPLES.iter().enumerate() {
+        let mut matchup = Matchup::new(
+            sample.my_champion.to_string(),
+            sample.enemy_champion.to_string(),
+            sample.role.to_string(),
+        );
+        let created = now - Duration::days(40 - index as i64);
+        matchup.versions[0].date = created;
+
+        for (week, version) in sample.versions.iter().enumerate() {
+            matchup.add_version(MatchupUpdate {
+                notes: version.notes.to_string(),
+                tags: strings(version.tags),
+                runes: strings(sample.runes),
+                summoner_spells: strings(sample.summoner_spells),
+                patch: Some(version.patch.to_string()),
+                difficulty: Some(version.difficulty),
+                summary: strings(version.summary),
+                ..Default::default()
+            });
+            if let Some(added) = matchup.versions.last_mut() {
+                added.date = created + Duration::weeks(week as i64 + 1);
+            }
+        }
+        for goal in sample.goals {
+            matchup.add_goal(goal.to_string());
+        }
+
+        let games = sample.results.len() as i64;
+        for (game, result) in sample.results.chars().enumerate() {
+            let won = result == 'W';
+            let mut m = Match::new(
+                sample.my_champion.to_string(),
+                sample.enemy_champion.to_string(),
+                sample.role.to_string(),
+                if won {
+                    MatchResult::Win
+                } else {
+                    MatchResult::Loss
+                },
+                None,
+            );
+            m.date = now - Duration::days((games - game as i64) * 3 + index as i64 % 3);
+            m.linked_matchup = Some(matchup.id.clone());
+            m.queue_id = Some(420);
+            m.queue = Some(QueueType::RankedSolo);
+            m.patch = sample.versions.last().map(|v| v.patch.to_string());
+            m.stats = Some(sample_stats(won, game as u32));
+            data.matches.insert(m.id.clone(), m);
+        }
+
+        data.matchups.insert(matchup.id.clone(), matchup);
+    }
+
+    data
+}
+
+/// Plausible numbers that vary a little from game to game
+fn sample_stats(won: bool, game: u32) -> MatchStats {
+    let game_duration = 1500 + game * 170 % 600;
+    let minutes = game_duration / 60;
+    MatchStats {
+        kills: if won { 7 + game % 4 } else { 2 + game % 3 },
+        deaths: if won { 2 + game % 2 } else { 6 + game % 3 },
+        assists: 4 + game * 3 % 7,
+        cs: minutes * (6 + game % 3),
+        gold_earned: minutes * if won { 460 } else { 390 },
+        damage_to_champions: minutes * if won { 900 } else { 650 },
+        vision_score: 12 + game * 5 % 15,
+        game_duration,
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_data() {
+        let now = Utc::now();
+        let data = sample_data(now);
+        assert_eq!(data.matchups.len(), SAMPLES.len());
+        assert_eq!(data.matches.len(), 17);
+
+        for m in data.matches.values() {
+            assert!(m.date < now);
+            let matchup = &data.matchups[m.linked_matchup.as_ref().unwrap()];
+            assert_eq!(matchup.my_champion, m.my_champion);
+        }
+        for matchup in data.matchups.values() {
+            assert!(matchup.versions.windows(2).all(|v| v[0].date < v[1].date));
+            assert!(matchup.versions.iter().all(|v| v.date < now));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Scratchpad: free notes not tied to any matchup
+//!
+//! For thoughts jotted down in the moment and filed into the right matchup
+//! later. The whole pad is saved at once, like a text editor would.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Most notes on the pad
+pub const MAX_NOTES: usize = 50;
+
+/// Longest single note
+const MAX_NOTE_LENGTH: usize = 20_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScratchNote {
+    /// Empty for a note that hasn't been saved yet
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    pub text: String,
+    /// Last change to the text or title; set when saving
+    #[serde(default = "Utc::now")]
+    pub updated: DateTime<Utc>,
+}
+
+/// The pad as saved from `notes`, in their order
+///
+/// Blank notes are dropped, new ones get an ID, and only notes whose title or
+/// text changed since `previous` get a new `updated` time.
+pub fn save(
+    previous: &[ScratchNote],
+    notes: Vec<ScratchNote>,
+    now: DateTime<Utc>,
+) -> Result<Vec<ScratchNote>, String> {
+    let mut saved: Vec<ScratchNote> = Vec::with_capacity(notes.len());
+    for mut note in notes {
+        note.title = note.title.trim().to_string();
+        if note.title.is_empty() && note.text.trim().is_empty() {
+            continue;
+        }
+        if note.text.chars().count() > MAX_NOTE_LENGTH {
+            return Err(format!(
+                "Scratchpad notes are limited to {} characters",
+                MAX_NOTE_LENGTH
+            ));
+        }
+
+        let old = previous
+            .iter()
+            .find(|p| !note.id.is_empty() && p.id == note.id);
+        match old {
+            Some(old) if old.title == note.title && old.text == note.text => {
+                note.updated = old.updated;
+            }
+            _ => note.updated = now,
+        }
+        if old.is_none() || saved.iter().any(|s| s.id == note.id) {
+            note.id = Uuid::new_v4().to_string();
+        }
+        saved.push(note);
+    }
+    if saved.len() > MAX_NOTES {
+        return Err(format!("The scratchpad holds at most {} notes", MAX_NOTES));
+    }
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_save() {
+        let then = Utc::now() - Duration::hours(1);
+        let note = |id: &str, text: &str| ScratchNote {
+            id: id.to_string(),
+            title: String::new(),
+            text: text.to_string(),
+            updated: then,
+        };
+
+        let now = Utc::now();
+        let pad = save(
+            &[],
+            vec![note("", "Garen ghosts at 6?"), note("", "  ")],
+            now,
+        )
+        .unwrap();
+        assert_eq!(pad.len(), 1);
+        assert!(!pad[0].id.is_empty());
+        assert_eq!(pad[0].updated, now);
+
+        let id = pad[0].id.clone();
+        let later = now + Duration::minutes(5);
+        let pad = save(&pad, vec![pad[0].clone(), note("", "Ward tri")], later).unwrap();
+        assert_eq!(pad[0].id, id);
+        assert_eq!(pad[0].updated, now);
+        assert_eq!(pad[1].updated, later);
+
+        let long = "x".repeat(MAX_NOTE_LENGTH + 1);
+        assert!(save(&pad, vec![note("", &long)], later).is_err());
+    }
+}
@@ -0,0 +1,486 @@
+//! Typo-tolerant fuzzy search over matchups, and exact search and replace in
+//! their notes
+
+use crate::champions;
+use crate::matchup::{Matchup, MatchupUpdate, MatchupVersion};
+use serde::{Deserialize, Serialize};
+
+/// Minimum similarity for a query token to count as found
+const MIN_TOKEN_SCORE: f64 = 0.7;
+/// Notes hits rank below champion name hits
+const NOTES_WEIGHT: f64 = 0.8;
+
+/// A matchup found by a search, with its relevance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub matchup: Matchup,
+    pub score: f64,
+    /// Version whose notes matched, `None` when the champion names matched
+    pub matched_version: Option<u32>,
+}
+
+/// Lowercase and strip everything but letters and digits ("Kai'Sa" -> "kaisa")
+pub fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Edit distance counting insertions, deletions, substitutions and adjacent swaps
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+/// Similarity of a normalized query token to a normalized word, 0.0..=1.0
+fn token_similarity(token: &str, word: &str) -> f64 {
+    if token.is_empty() || word.is_empty() {
+        return 0.0;
+    }
+    if word == token || word.starts_with(token) || (token.len() >= 3 && word.contains(token)) {
+        return 1.0;
+    }
+
+    let max_len = token.chars().count().max(word.chars().count());
+    1.0 - edit_distance(token, word) as f64 / max_len as f64
+}
+
+/// Average best similarity of every query token against the words of `text`
+///
+/// Returns 0.0 as soon as one token has no acceptable match.
+pub fn score_text(query: &str, text: &str) -> f64 {
+    let tokens: Vec<String> = query
+        .split_whitespace()
+        .map(normalize)
+        .filter(|t| !t.is_empty())
+        .collect();
+    if tokens.is_empty() {
+        return 0.0;
+    }
+
+    let mut words: Vec<String> = text
+        .split_whitespace()
+        .map(normalize)
+        .filter(|w| !w.is_empty())
+        .collect();
+    // Multi-word names ("Twisted Fate") also match as a single word
+    words.push(normalize(text));
+
+    let mut total = 0.0;
+    for token in &tokens {
+        let best = words
+            .iter()
+            .map(|w| token_similarity(token, w))
+            .fold(0.0, f64::max);
+        if best < MIN_TOKEN_SCORE {
+            return 0.0;
+        }
+        total += best;
+    }
+
+    total / tokens.len() as f64
+}
+
+/// Score a matchup's champion names and notes against a query
+///
+/// Returns the score and, when the notes scored best, the version that matched.
+/// Only the current version is searched unless `all_versions` is set.
+pub fn score_matchup(
+    query: &str,
+    matchup: &Matchup,
+    all_versions: bool,
+) -> Option<(f64, Option<u32>)> {
+    // Localized names count too, so "Kog'Maw" and a translated name both work
+    let my_display = champions::display_name(&matchup.my_champion);
+    let enemy_display = champions::display_name(&matchup.enemy_champion);
+    let names = format!("{} {}", matchup.my_champion, matchup.enemy_champion);
+    let display_names = format!("{} {}", my_display, enemy_display);
+    // Nicknames too, built-in and my own
+    let alias_score = champions::aliases(&matchup.my_champion)
+        .iter()
+        .chain(&champions::aliases(&matchup.enemy_champion))
+        .map(|alias| score_text(query, alias))
+        .fold(0.0, f64::max);
+    let name_score = score_text(query, &names)
+        .max(score_text(query, &display_names))
+        .max(score_text(query, &matchup.my_champion))
+        .max(score_text(query, &matchup.enemy_champion))
+        .max(score_text(query, &my_display))
+        .max(score_text(query, &enemy_display))
+        .max(alias_score);
+
+    let mut best: (f64, Option<u32>) = (name_score, None);
+
+    let versions: Vec<&MatchupVersion> = if all_versions {
+        // Current version first so it wins ties against older rewrites
+        let current = matchup.current();
+        current
+            .into_iter()
+            .chain(
+                matchup
+                    .versions
+                    .iter()
+                    .rev()
+                    .filter(|v| Some(v.version) != current.map(|c| c.version)),
+            )
+            .collect()
+    } else {
+        matchup.current().into_iter().collect()
+    };
+
+    for version in versions {
+        let score = version
+            .note_texts()
+            .map(|notes| score_text(query, notes))
+            .fold(0.0, f64::max)
+            * NOTES_WEIGHT;
+        if score > best.0 {
+            best = (score, Some(version.version));
+        }
+    }
+
+    if best.0 > 0.0 {
+        Some(best)
+    } else {
+        None
+    }
+}
+
+/// Rank matchups by fuzzy relevance to `query`, best first
+pub fn search<'a>(
+    query: &str,
+    matchups: impl IntoIterator<Item = &'a Matchup>,
+    all_versions: bool,
+) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = matchups
+        .into_iter()
+        .filter_map(|m| {
+            score_matchup(query, m, all_versions).map(|(score, matched_version)| SearchResult {
+                matchup: m.clone(),
+                score,
+                matched_version,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results
+}
+
+/// Which notes a search and replace goes through
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ReplaceScope {
+    /// Matchups to go through; empty means all of them
+    pub matchup_ids: Vec<String>,
+    /// Rewrite every version in place, instead of saving the current notes
+    /// with the replacement as a new version
+    pub all_versions: bool,
+    pub case_sensitive: bool,
+}
+
+/// A line of notes or summary that a replace changes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplacedLine {
+    pub matchup_id: String,
+    pub my_champion: String,
+    pub enemy_champion: String,
+    pub version: u32,
+    pub before: String,
+    pub after: String,
+}
+
+/// `text` with every occurrence of `query` replaced, `None` if there is none
+pub fn replace_text(
+    text: &str,
+    query: &str,
+    replacement: &str,
+    case_sensitive: bool,
+) -> Option<String> {
+    if query.is_empty() {
+        return None;
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut found = false;
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(length) = match_length(rest, query, case_sensitive) {
+            out.push_str(replacement);
+            rest = &rest[length..];
+            found = true;
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    found.then_some(out)
+}
+
+/// Bytes of `text` taken by `query` when `text` starts with it
+fn match_length(text: &str, query: &str, case_sensitive: bool) -> Option<usize> {
+    let mut chars = text.char_indices();
+    for q in query.chars() {
+        let (_, c) = chars.next()?;
+        let same = if case_sensitive {
+            c == q
+        } else {
+            c.to_lowercase().eq(q.to_lowercase())
+        };
+        if !same {
+            return None;
+        }
+    }
+    Some(chars.next().map(|(i, _)| i).unwrap_or(text.len()))
+}
+
+/// Replace `query` in a matchup's notes, side notes and summary, returning the
+/// changed lines
+///
+/// The current notes are saved as a new version, unless the scope rewrites
+/// every version in place.
+pub fn replace_in_matchup(
+    matchup: &mut Matchup,
+    query: &str,
+    replacement: &str,
+    scope: &ReplaceScope,
+) -> Vec<ReplacedLine> {
+    let mut changed = Vec::new();
+    let (id, my_champion, enemy_champion) = (
+        matchup.id.clone(),
+        matchup.my_champion.clone(),
+        matchup.enemy_champion.clone(),
+    );
+    let mut line = |version: u32, before: &str, after: &str| {
+        changed.push(ReplacedLine {
+            matchup_id: id.clone(),
+            my_champion: my_champion.clone(),
+            enemy_champion: enemy_champion.clone(),
+            version,
+            before: before.to_string(),
+            after: after.to_string(),
+        })
+    };
+    let mut replace_version = |version: &mut MatchupVersion| {
+        let number = version.version;
+        let mut replace_lines = |text: &mut String| {
+            let mut lines = Vec::new();
+            for before in text.split('\n') {
+                match replace_text(before, query, replacement, scope.case_sensitive) {
+                    Some(after) => {
+                        line(number, before, &after);
+                        lines.push(after);
+                    }
+                    None => lines.push(before.to_string()),
+                }
+            }
+            *text = lines.join("\n");
+        };
+        replace_lines(&mut version.notes);
+        [&mut version.blue_side_notes, &mut version.red_side_notes]
+            .into_iter()
+            .flatten()
+            .for_each(replace_lines);
+        for bullet in &mut version.summary {
+            if let Some(after) = replace_text(bullet, query, replacement, scope.case_sensitive) {
+                line(version.version, bullet, &after);
+                *bullet = after;
+            }
+        }
+    };
+
+    if scope.all_versions {
+        matchup.versions.iter_mut().for_each(&mut replace_version);
+    } else if let Some(mut current) = matchup.current().cloned() {
+        replace_version(&mut current);
+        if !changed.is_empty() {
+            matchup.add_version(MatchupUpdate::from_version(&current));
+            let version = matchup.current_version;
+            changed.iter_mut().for_each(|line| line.version = version);
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Kai'Sa"), "kaisa");
+        assert_eq!(normalize("Dr. Mundo"), "drmundo");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("darius", "darius"), 0);
+        assert_eq!(edit_distance("dairus", "darius"), 1);
+        assert_eq!(edit_distance("garen", "karen"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_champion_names() {
+        let kaisa = Matchup::new(
+            "Kai'Sa".to_string(),
+            "Ezreal".to_string(),
+            "adc".to_string(),
+        );
+        let heimer = Matchup::new(
+            "Heimerdinger".to_string(),
+            "Zed".to_string(),
+            "mid".to_string(),
+        );
+        let matchups = vec![kaisa, heimer];
+
+        let results = search("Kaisa", &matchups, false);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matchup.my_champion, "Kai'Sa");
+
+        let results = search("heimer", &matchups, false);
+        assert_eq!(results[0].matchup.my_champion, "Heimerdinger");
+
+        let results = search("ezrael", &matchups, false);
+        assert_eq!(results[0].matchup.enemy_champion, "Ezreal");
+
+        // Nicknames find the champion too
+        let results = search("ez", &matchups, false);
+        assert_eq!(results[0].matchup.enemy_champion, "Ezreal");
+
+        assert!(search("xyzzy", &matchups, false).is_empty());
+    }
+
+    #[test]
+    fn test_names_rank_above_notes() {
+        let mut in_notes = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        in_notes.add_version(MatchupUpdate {
+            notes: "Play like vs Sett, short trades".to_string(),
+            ..Default::default()
+        });
+        let in_name = Matchup::new(
+            "Sett".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        let matchups = vec![in_notes, in_name];
+
+        let results = search("sett", &matchups, false);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].matchup.my_champion, "Sett");
+        assert_eq!(results[0].matched_version, None);
+        assert_eq!(results[1].matched_version, Some(2));
+    }
+
+    #[test]
+    fn test_search_old_versions() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.add_version(MatchupUpdate {
+            notes: "Rush Stridebreaker".to_string(),
+            ..Default::default()
+        });
+        matchup.add_version(MatchupUpdate {
+            notes: "Rush Trinity".to_string(),
+            ..Default::default()
+        });
+        let matchups = vec![matchup];
+
+        assert!(search("stridebreaker", &matchups, false).is_empty());
+
+        let results = search("stridebreaker", &matchups, true);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_version, Some(2));
+
+        // Current version wins when several versions match equally
+        let results = search("rush", &matchups, true);
+        assert_eq!(results[0].matched_version, Some(3));
+    }
+
+    #[test]
+    fn test_replace_in_matchup() {
+        let mut matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        matchup.add_version(MatchupUpdate {
+            notes: "Rush Stridebreaker\nShort trades".to_string(),
+            summary: vec!["stridebreaker first".to_string()],
+            red_side_notes: Some("Ward tribush".to_string()),
+            ..Default::default()
+        });
+
+        let scope = ReplaceScope::default();
+        let mut preview = matchup.clone();
+        let lines = replace_in_matchup(&mut preview, "Stridebreaker", "Trinity", &scope);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].before, "Rush Stridebreaker");
+        assert_eq!(lines[0].after, "Rush Trinity");
+        assert_eq!(lines[1].after, "Trinity first");
+        assert_eq!(lines[0].version, 3);
+        let lines = replace_in_matchup(&mut preview, "tribush", "river", &scope);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(
+            preview.current().unwrap().red_side_notes.as_deref(),
+            Some("Ward river")
+        );
+        assert_eq!(
+            preview.current().unwrap().notes,
+            "Rush Trinity\nShort trades"
+        );
+        assert_eq!(
+            preview.versions[1].notes,
+            "Rush Stridebreaker\nShort trades"
+        );
+
+        let scope = ReplaceScope {
+            all_versions: true,
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let lines = replace_in_matchup(&mut matchup, "Stridebreaker", "Trinity", &scope);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(matchup.versions.len(), 2);
+        assert_eq!(
+            matchup.current().unwrap().summary,
+            vec!["stridebreaker first"]
+        );
+
+        assert!(replace_in_matchup(&mut matchup, "Sheen", "Trinity", &scope).is_empty());
+        assert_eq!(
+            replace_text("Kai'Sa É", "é", "e", false).as_deref(),
+            Some("Kai'Sa e")
+        );
+    }
+}
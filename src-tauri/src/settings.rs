@@ -0,0 +1,529 @@
+//! User settings, persisted in their own file apart from matchup data
+//!
+//! Settings live in the config directory so they can point the matchup data
+//! somewhere else without being moved along with it.
+
+use crate::builds;
+use crate::champions;
+use crate::community;
+use crate::matchup::ImportOptions;
+use crate::riot_api::RiotApiSettings;
+use crate::webhook;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Most games a single import may request
+pub const MAX_IMPORT_COUNT: u32 = 100;
+
+/// Smallest width or height a window can be given
+const MIN_WINDOW_SIZE: u32 = 100;
+
+const DEFAULT_TOGGLE_SHORTCUT: &str = "CommandOrControl+Shift+N";
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+}
+
+/// Everything the user can configure
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Import the game that just finished once it reaches match history
+    pub auto_import: bool,
+    /// Games fetched by an import when no count is given
+    pub default_import_count: u32,
+    /// Which games imports bring in when none are given, auto-imports included
+    pub import_options: ImportOptions,
+    /// Where matchup data is stored, the platform data directory when unset
+    ///
+    /// Applies on the next start; existing data is not moved.
+    pub data_directory: Option<PathBuf>,
+    pub overlay: OverlaySettings,
+    pub obs: ObsSettings,
+    /// Posted a summary after each auto-imported game, e.g. a Discord webhook
+    pub webhook_url: Option<String>,
+    /// Ask before each game is posted to the webhook
+    pub confirm_webhook_posts: bool,
+    pub rich_presence: RichPresenceSettings,
+    pub notifications: NotificationSettings,
+    pub community_stats: CommunityStatsSettings,
+    pub community_builds: CommunityBuildSettings,
+    pub theme: Theme,
+    /// Least severe messages written to the log file
+    pub log_level: LogLevel,
+    /// Where each window was left, by window label
+    pub windows: HashMap<String, WindowState>,
+    /// My own champion nicknames on top of the built-in ones, e.g. "cardmaster" to "TwistedFate"
+    pub champion_aliases: HashMap<String, String>,
+    /// Riot API access for importing without the client, kept here so the
+    /// key never leaves this PC with the matchup data
+    pub riot_api: Option<RiotApiSettings>,
+    /// Opt-in for the `lcu_request` passthrough, for this PC only
+    pub allow_lcu_requests: bool,
+    /// Skip certificate checks on the client connection, should Riot's root CA
+    /// fail; for this PC only
+    pub allow_insecure_lcu: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_import: true,
+            default_import_count: 20,
+            import_options: ImportOptions::default(),
+            data_directory: None,
+            overlay: OverlaySettings::default(),
+            obs: ObsSettings::default(),
+            webhook_url: None,
+            confirm_webhook_posts: true,
+            rich_presence: RichPresenceSettings::default(),
+            notifications: NotificationSettings::default(),
+            community_stats: CommunityStatsSettings::default(),
+            community_builds: CommunityBuildSettings::default(),
+            theme: Theme::default(),
+            log_level: LogLevel::default(),
+            windows: HashMap::new(),
+            champion_aliases: HashMap::new(),
+            riot_api: None,
+            allow_lcu_requests: false,
+            allow_insecure_lcu: false,
+        }
+    }
+}
+
+impl Settings {
+    /// A copy to take to another PC, without the Riot API key, the OBS page
+    /// token, the webhook URL or the client connection opt-ins
+    pub fn without_secrets(&self) -> Self {
+        let mut settings = self.clone();
+        settings.riot_api = None;
+        settings.obs.token = None;
+        // Anyone with a Discord webhook URL can post to it
+        settings.webhook_url = None;
+        settings.allow_lcu_requests = false;
+        settings.allow_insecure_lcu = false;
+        settings
+    }
+
+    /// Reject values the app can't work with
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_IMPORT_COUNT).contains(&self.default_import_count) {
+            return Err(format!(
+                "Default import count must be between 1 and {}",
+                MAX_IMPORT_COUNT
+            ));
+        }
+        if self
+            .data_directory
+            .as_ref()
+            .is_some_and(|dir| !dir.is_absolute())
+        {
+            return Err("Data directory must be an absolute path".to_string());
+        }
+        if self
+            .overlay
+            .toggle_shortcut
+            .as_ref()
+            .is_some_and(|shortcut| shortcut.trim().is_empty())
+        {
+            return Err("Shortcut cannot be empty".to_string());
+        }
+        if let Some(ref url) = self.webhook_url {
+            webhook::validate_url(url)?;
+        }
+        if self
+            .rich_presence
+            .application_id
+            .as_ref()
+            .is_some_and(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err("Discord application ID must be a number".to_string());
+        }
+        if let Some(ref template) = self.community_stats.url_template {
+            community::validate_template(template)?;
+        }
+        if let Some(ref template) = self.community_builds.url_template {
+            builds::validate_template(template)?;
+        }
+        if self.community_stats.refresh_hours == 0 {
+            return Err("Community stats must be refreshed at least every hour".to_string());
+        }
+        if self.obs.port < 1024 {
+            return Err("OBS page port must be 1024 or higher".to_string());
+        }
+        if self
+            .obs
+            .token
+            .as_ref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            return Err("OBS page token cannot be empty".to_string());
+        }
+        for window in self.windows.values() {
+            window.geometry.validate()?;
+        }
+        for (alias, champion) in &self.champion_aliases {
+            if alias.trim().is_empty() {
+                return Err("Champion nickname cannot be empty".to_string());
+            }
+            champions::canonicalize(champion)?;
+        }
+        Ok(())
+    }
+}
+
+/// The compact notes window opened while a game loads
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OverlaySettings {
+    /// Open the window on the matchup's quick reference when a game loads
+    pub open_on_load: bool,
+    pub always_on_top: bool,
+    /// Global shortcut that shows or hides the window, e.g. "CommandOrControl+Shift+N"
+    ///
+    /// Works while the game has focus. `None` registers no shortcut.
+    pub toggle_shortcut: Option<String>,
+    /// Let clicks pass through to the game underneath
+    pub click_through: bool,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            open_on_load: false,
+            always_on_top: true,
+            toggle_shortcut: Some(DEFAULT_TOGGLE_SHORTCUT.to_string()),
+            click_through: false,
+        }
+    }
+}
+
+/// Local web page with the current matchup, for an OBS browser source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ObsSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Required in the page URL, generated when the page is first enabled
+    pub token: Option<String>,
+}
+
+impl Default for ObsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 17380,
+            token: None,
+        }
+    }
+}
+
+/// Discord Rich Presence showing the matchup being reviewed or played
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct RichPresenceSettings {
+    pub enabled: bool,
+    /// ID of the Discord application the presence is shown under
+    pub application_id: Option<String>,
+}
+
+/// Which desktop notifications fire during a game
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// When a game loads for a matchup with notes
+    pub game_detected: bool,
+    /// On the end of game screen, as a reminder to review the game
+    pub game_ended: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            game_detected: true,
+            game_ended: true,
+        }
+    }
+}
+
+/// Win rates of everyone's games in a matchup, shown next to mine
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CommunityStatsSettings {
+    pub enabled: bool,
+    /// Source URL with `{champion}`, `{enemy}` and `{role}` placeholders
+    pub url_template: Option<String>,
+    /// Hours before a pairing's cached numbers are fetched again
+    pub refresh_hours: u32,
+}
+
+impl Default for CommunityStatsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url_template: None,
+            refresh_hours: 24,
+        }
+    }
+}
+
+/// Popular runes and items for a pairing, from a public builds site
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct CommunityBuildSettings {
+    pub enabled: bool,
+    /// Source URL with `{champion}`, `{enemy}` and `{role}` placeholders
+    pub url_template: Option<String>,
+}
+
+/// A window's saved place on screen
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowState {
+    #[serde(flatten)]
+    pub geometry: WindowGeometry,
+    pub maximized: bool,
+}
+
+/// A window's position and size, in physical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowGeometry {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width < MIN_WINDOW_SIZE || self.height < MIN_WINDOW_SIZE {
+            return Err(format!(
+                "Window must be at least {} pixels wide and high",
+                MIN_WINDOW_SIZE
+            ));
+        }
+        Ok(())
+    }
+
+    /// Moved and shrunk to fit on the screen it overlaps most, or the first
+    /// one when it's on none of `screens`, e.g. after a monitor was unplugged
+    pub fn fit_to(self, screens: &[WindowGeometry]) -> Self {
+        let overlap = |screen: &WindowGeometry| {
+            let span = |start: i32, len: u32, screen_start: i32, screen_len: u32| {
+                let end = (start as i64 + len as i64).min(screen_start as i64 + screen_len as i64);
+                (end - (start as i64).max(screen_start as i64)).max(0)
+            };
+            span(self.x, self.width, screen.x, screen.width)
+                * span(self.y, self.height, screen.y, screen.height)
+        };
+        let Some(screen) = screens
+            .iter()
+            .filter(|s| overlap(s) > 0)
+            .max_by_key(|s| overlap(s))
+            .or(screens.first())
+        else {
+            return self;
+        };
+
+        let width = self.width.min(screen.width);
+        let height = self.height.min(screen.height);
+        let fit = |start: i32, len: u32, screen_start: i32, screen_len: u32| {
+            let last = screen_start as i64 + (screen_len - len) as i64;
+            (start as i64).clamp(screen_start as i64, last) as i32
+        };
+        Self {
+            x: fit(self.x, width, screen.x, screen.width),
+            y: fit(self.y, height, screen.y, screen.height),
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Follow the operating system
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Reads and writes the settings file
+pub struct SettingsStore {
+    path: PathBuf,
+}
+
+impl SettingsStore {
+    pub fn new() -> Result<Self, SettingsError> {
+        let config_dir = dirs::config_dir()
+            .ok_or(SettingsError::ConfigDirNotFound)?
+            .join("matchuphelper");
+
+        if !config_dir.exists() {
+            fs::create_dir_all(&config_dir)?;
+        }
+
+        Ok(Self {
+            path: config_dir.join("settings.json"),
+        })
+    }
+
+    /// Load settings, with defaults for a missing file or missing fields
+    pub fn load(&self) -> Result<Settings, SettingsError> {
+        if !self.path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, settings: &Settings) -> Result<(), SettingsError> {
+        fs::write(&self.path, serde_json::to_string_pretty(settings)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let store = SettingsStore {
+            path: dir.path().join("settings.json"),
+        };
+        assert_eq!(store.load().unwrap(), Settings::default());
+
+        let settings = Settings {
+            auto_import: false,
+            theme: Theme::Dark,
+            ..Default::default()
+        };
+        store.save(&settings).unwrap();
+        assert_eq!(store.load().unwrap(), settings);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{ "theme": "light" }"#).unwrap();
+        assert_eq!(settings.theme, Theme::Light);
+        assert_eq!(settings.default_import_count, 20);
+        assert!(settings.overlay.always_on_top);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Settings::default().validate().is_ok());
+
+        let settings = Settings {
+            default_import_count: 0,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.overlay.toggle_shortcut = Some(" ".to_string());
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.obs.token = Some(String::new());
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.windows.insert(
+            "notes".to_string(),
+            WindowState {
+                geometry: WindowGeometry {
+                    x: -1920,
+                    y: 0,
+                    width: 360,
+                    height: 0,
+                },
+                maximized: false,
+            },
+        );
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings
+            .champion_aliases
+            .insert("cardmaster".to_string(), "Cardmastr".to_string());
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_without_secrets() {
+        let mut settings = Settings {
+            riot_api: Some(crate::riot_api::RiotApiSettings {
+                api_key: "RGAPI-key".to_string(),
+                region: crate::riot_api::RiotRegion::Europe,
+            }),
+            webhook_url: Some("https://discord.com/api/webhooks/1/token".to_string()),
+            allow_lcu_requests: true,
+            allow_insecure_lcu: true,
+            ..Default::default()
+        };
+        settings.obs.token = Some("token".to_string());
+
+        let exported = settings.without_secrets();
+        assert!(exported.riot_api.is_none());
+        assert!(exported.obs.token.is_none());
+        assert!(exported.webhook_url.is_none());
+        assert!(!exported.allow_lcu_requests);
+        assert!(!exported.allow_insecure_lcu);
+        assert_eq!(exported.theme, settings.theme);
+    }
+
+    #[test]
+    fn test_window_fit_to() {
+        let geometry = |x, y, width, height| WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+        };
+        let screens = [geometry(0, 0, 1920, 1080), geometry(1920, 0, 1280, 1024)];
+
+        let on_screen = geometry(100, 100, 800, 600);
+        assert_eq!(on_screen.fit_to(&screens), on_screen);
+        // Left on a monitor that is gone
+        assert_eq!(
+            geometry(-1920, 0, 800, 600).fit_to(&screens),
+            geometry(0, 0, 800, 600)
+        );
+        // Hanging off the right edge of the second monitor
+        assert_eq!(
+            geometry(2900, 900, 400, 300).fit_to(&screens),
+            geometry(2800, 724, 400, 300)
+        );
+        assert_eq!(
+            geometry(1800, 0, 3000, 2000).fit_to(&screens),
+            geometry(1920, 0, 1280, 1024)
+        );
+        assert_eq!(on_screen.fit_to(&[]), on_screen);
+    }
+}
@@ -0,0 +1,44 @@
+//! Global keyboard shortcuts, which work while the game has focus
+
+use crate::error::AppError;
+use crate::overlay;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// The global shortcut plugin, toggling the overlay on every press
+///
+/// The overlay toggle is the only shortcut registered, so the handler doesn't
+/// need to tell shortcuts apart. The handler runs on the event loop, which a
+/// window being built there would deadlock on Windows, so the toggle runs on
+/// its own thread.
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    let _ = overlay::toggle(&app);
+                });
+            }
+        })
+        .build()
+}
+
+/// Swap the registered overlay shortcut from `old` to `new`
+///
+/// `old` stays registered when `new` is malformed or taken by another app.
+pub fn replace(app: &AppHandle, old: Option<&str>, new: Option<&str>) -> Result<(), AppError> {
+    if old == new {
+        return Ok(());
+    }
+    if let Some(new) = new {
+        app.global_shortcut().register(new).map_err(|e| {
+            AppError::validation(format!("Shortcut {} can't be used", new)).with_details(e)
+        })?;
+    }
+    if let Some(old) = old {
+        let _ = app.global_shortcut().unregister(old);
+    }
+    Ok(())
+}
@@ -1,11 +1,33 @@
 //! Storage module for persisting matchup data to JSON
 
-use crate::matchup::{Match, Matchup};
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::champions;
+use crate::collections::Collection;
+use crate::community::COMMUNITY_CACHE_FILE;
+use crate::draft::Draft;
+use crate::lcu::RIOT_ROOT_CA_FILE;
+use crate::matchup::{ChampionRoles, Match, Matchup, PlayerScope};
+use crate::plans::PlanTarget;
+use crate::reminders::Reminder;
+use crate::scratchpad::{ScratchNote, MAX_NOTES};
+use crate::team_notes::TeamNote;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
+use uuid::Uuid;
+
+/// Backups kept when no count is given
+pub const DEFAULT_BACKUPS_KEPT: usize = 14;
+
+/// Backup file names, e.g. `data-20240101-000000.json`
+const BACKUP_NAME_FORMAT: &str = "data-%Y%m%d-%H%M%S.json";
+
+/// Layout version of data written by this build, see `AppData::upgrade`
+const DATA_VERSION: &str = "1.1";
 
 #[derive(Error, Debug)]
 pub enum StorageError {
@@ -22,6 +44,23 @@ pub enum StorageError {
 pub struct AppData {
     pub matchups: HashMap<String, Matchup>,
     pub matches: HashMap<String, Match>,
+    /// Scrim and team game notes, apart from 1v1 matchups
+    #[serde(default)]
+    pub team_notes: HashMap<String, TeamNote>,
+    /// Pick and ban sequences of reviewed games
+    #[serde(default)]
+    pub drafts: HashMap<String, Draft>,
+    #[serde(default)]
+    pub reminders: HashMap<String, Reminder>,
+    /// Named groups of matchups
+    #[serde(default)]
+    pub collections: HashMap<String, Collection>,
+    /// Free notes not tied to any matchup, in my order
+    #[serde(default)]
+    pub scratchpad: Vec<ScratchNote>,
+    /// Weekly practice targets
+    #[serde(default)]
+    pub plan_targets: HashMap<String, PlanTarget>,
     pub metadata: Metadata,
 }
 
@@ -29,29 +68,380 @@ pub struct AppData {
 pub struct Metadata {
     pub last_updated: String,
     pub version: String,
+    /// Automatic match pruning, applied after each import
+    #[serde(default)]
+    pub match_retention: Option<RetentionPolicy>,
+    /// Newest game brought in from the client when the account wasn't known,
+    /// for incremental imports
+    #[serde(default)]
+    pub last_imported: Option<ImportMarker>,
+    /// Newest game brought in from the client per account PUUID
+    #[serde(default)]
+    pub last_imported_by_account: HashMap<String, ImportMarker>,
+    /// Every account seen in the client
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    /// PUUID whose matches are shown by default, `None` for all accounts
+    #[serde(default)]
+    pub active_account: Option<String>,
+    /// Live patch as last detected, e.g. "14.3"
+    #[serde(default)]
+    pub current_patch: Option<String>,
+    /// Champions I always consider banning
+    #[serde(default)]
+    pub ban_list: Vec<String>,
+    /// Champions I play, for pick suggestions
+    #[serde(default)]
+    pub champion_pool: Vec<String>,
+    /// Roles I play each champion in, by canonical name
+    #[serde(default)]
+    pub champion_roles: HashMap<String, ChampionRoles>,
+    /// Data Dragon locale for champion names, e.g. "es_ES"; English when unset
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Players I keep notes for besides myself, e.g. students I coach
+    #[serde(default)]
+    pub players: Vec<Player>,
+    /// Player whose notes are shown and written, `None` for my own
+    #[serde(default)]
+    pub active_player: Option<String>,
+}
+
+/// A League account the app has connected to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Account {
+    pub puuid: String,
+    pub summoner_name: Option<String>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Someone else whose notes are kept in this install
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Player {
+    pub id: String,
+    pub name: String,
+    pub created: DateTime<Utc>,
+}
+
+impl Player {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            created: Utc::now(),
+        }
+    }
+}
+
+/// Identifies the newest imported game
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportMarker {
+    pub game_id: i64,
+    /// Game start as Unix milliseconds, as reported by the client
+    pub game_creation: i64,
+}
+
+impl Metadata {
+    /// Add or refresh an account after connecting to it
+    pub fn remember_account(
+        &mut self,
+        puuid: &str,
+        summoner_name: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        match self.accounts.iter_mut().find(|a| a.puuid == puuid) {
+            Some(account) => {
+                account.summoner_name = summoner_name.or(account.summoner_name.take());
+                account.last_seen = now;
+            }
+            None => self.accounts.push(Account {
+                puuid: puuid.to_string(),
+                summoner_name,
+                last_seen: now,
+            }),
+        }
+    }
+
+    /// Whose notes and games are shown: the active player's, else mine
+    pub fn player_scope(&self) -> PlayerScope {
+        PlayerScope::Active.resolve(self)
+    }
+
+    /// Name to sign new notes with: my summoner name
+    ///
+    /// Notes written for a student are still mine, so the active player
+    /// doesn't change it.
+    pub fn local_author(&self) -> Option<String> {
+        self.accounts
+            .iter()
+            .filter(|a| {
+                self.active_account
+                    .as_ref()
+                    .is_none_or(|puuid| &a.puuid == puuid)
+            })
+            .filter(|a| a.summoner_name.is_some())
+            .max_by_key(|a| a.last_seen)
+            .and_then(|a| a.summoner_name.clone())
+    }
+
+    /// Newest game imported from `puuid`'s history
+    pub fn import_marker(&self, puuid: Option<&str>) -> Option<&ImportMarker> {
+        match puuid {
+            Some(puuid) => self.last_imported_by_account.get(puuid),
+            None => self.last_imported.as_ref(),
+        }
+    }
+
+    /// Move `puuid`'s import marker forward if this game is newer
+    ///
+    /// Each account has its own marker, so switching to a smurf doesn't skip
+    /// the games it played before the main's newest one.
+    pub fn record_imported_game(&mut self, puuid: Option<&str>, game_id: i64, game_creation: i64) {
+        let marker = match puuid {
+            Some(puuid) => self
+                .last_imported_by_account
+                .entry(puuid.to_string())
+                .or_insert(ImportMarker {
+                    game_id,
+                    game_creation,
+                }),
+            None => self.last_imported.get_or_insert(ImportMarker {
+                game_id,
+                game_creation,
+            }),
+        };
+        if game_creation > marker.game_creation {
+            *marker = ImportMarker {
+                game_id,
+                game_creation,
+            };
+        }
+    }
 }
 
 impl Default for Metadata {
     fn default() -> Self {
         Self {
             last_updated: chrono::Utc::now().to_rfc3339(),
-            version: "1.0".to_string(),
+            version: DATA_VERSION.to_string(),
+            match_retention: None,
+            last_imported: None,
+            last_imported_by_account: HashMap::new(),
+            accounts: Vec::new(),
+            active_account: None,
+            current_patch: None,
+            ban_list: Vec::new(),
+            champion_pool: Vec::new(),
+            champion_roles: HashMap::new(),
+            locale: None,
+            players: Vec::new(),
+            active_player: None,
+        }
+    }
+}
+
+/// How long to keep matches in history
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RetentionPolicy {
+    pub max_age_days: u32,
+    pub keep_linked: bool,
+}
+
+impl AppData {
+    /// Bring data written by older versions up to date
+    ///
+    /// 1.1 stores champions by their Data Dragon ID, where earlier versions
+    /// kept them as typed, e.g. "Wukong" for "MonkeyKing".
+    pub fn upgrade(&mut self) {
+        if self.metadata.version == "1.0" {
+            let canonical = |name: &mut String| {
+                if let Ok(canonical) = champions::canonicalize(name) {
+                    *name = canonical;
+                }
+            };
+            for matchup in self.matchups.values_mut() {
+                canonical(&mut matchup.my_champion);
+                canonical(&mut matchup.enemy_champion);
+            }
+            for m in self.matches.values_mut() {
+                canonical(&mut m.my_champion);
+                canonical(&mut m.enemy_champion);
+            }
+        }
+        self.metadata.version = DATA_VERSION.to_string();
+    }
+
+    /// Remove matches played before `older_than`, returning the removed ones
+    ///
+    /// Matches with notes, a review or VODs are always kept; linked ones only when `keep_linked` is set.
+    pub fn prune_matches(&mut self, older_than: DateTime<Utc>, keep_linked: bool) -> Vec<Match> {
+        let (kept, removed): (HashMap<_, _>, HashMap<_, _>) = std::mem::take(&mut self.matches)
+            .into_iter()
+            .partition(|(_, m)| {
+                m.date >= older_than
+                    || !m.notes.trim().is_empty()
+                    || m.review.is_some()
+                    || !m.vod_refs.is_empty()
+                    || (keep_linked && m.linked_matchup.is_some())
+            });
+        self.matches = kept;
+        for id in removed.keys() {
+            self.unlink_match(id);
+        }
+        removed.into_values().collect()
+    }
+
+    /// Game IDs of every imported match, for duplicate checks
+    pub fn imported_game_ids(&self) -> HashSet<String> {
+        self.matches
+            .values()
+            .filter_map(|m| m.game_id.clone())
+            .collect()
+    }
+
+    /// Match ID of every imported match by its game ID, for merging re-imports
+    pub fn imported_match_ids(&self) -> HashMap<String, String> {
+        self.matches
+            .values()
+            .filter_map(|m| Some((m.game_id.clone()?, m.id.clone())))
+            .collect()
+    }
+
+    /// Add the matchups and matches from `other` that aren't here yet
+    ///
+    /// Entries are matched by ID, imported games also by game ID. Existing entries
+    /// and the metadata are left as they are.
+    pub fn merge(&mut self, other: AppData) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        for (id, matchup) in other.matchups {
+            if let Entry::Vacant(entry) = self.matchups.entry(id) {
+                entry.insert(matchup);
+                summary.matchups += 1;
+            }
+        }
+
+        for (id, note) in other.team_notes {
+            if let Entry::Vacant(entry) = self.team_notes.entry(id) {
+                entry.insert(note);
+                summary.team_notes += 1;
+            }
+        }
+
+        for (id, draft) in other.drafts {
+            if let Entry::Vacant(entry) = self.drafts.entry(id) {
+                entry.insert(draft);
+                summary.drafts += 1;
+            }
+        }
+
+        for (id, reminder) in other.reminders {
+            if let Entry::Vacant(entry) = self.reminders.entry(id) {
+                entry.insert(reminder);
+                summary.reminders += 1;
+            }
+        }
+
+        for (id, collection) in other.collections {
+            if let Entry::Vacant(entry) = self.collections.entry(id) {
+                entry.insert(collection);
+                summary.collections += 1;
+            }
+        }
+
+        // Notes past a full pad are left out, so it can still be saved
+        for note in other.scratchpad {
+            if self.scratchpad.len() >= MAX_NOTES {
+                break;
+            }
+            if !self.scratchpad.iter().any(|n| n.id == note.id) {
+                self.scratchpad.push(note);
+                summary.scratch_notes += 1;
+            }
+        }
+
+        for (id, target) in other.plan_targets {
+            if let Entry::Vacant(entry) = self.plan_targets.entry(id) {
+                entry.insert(target);
+                summary.plan_targets += 1;
+            }
+        }
+
+        let mut known_games = self.imported_game_ids();
+        for (id, m) in other.matches {
+            let known_game = m
+                .game_id
+                .as_ref()
+                .is_some_and(|game_id| !known_games.insert(game_id.clone()));
+            if !known_game && !self.matches.contains_key(&id) {
+                self.matches.insert(id, m);
+                summary.matches += 1;
+            }
+        }
+        summary
+    }
+
+    /// Clear links to a deleted matchup from reminders, which stay useful on
+    /// their own, and take it out of collections
+    pub fn unlink_matchup(&mut self, matchup_id: &str) {
+        for reminder in self.reminders.values_mut() {
+            if reminder.matchup_id.as_deref() == Some(matchup_id) {
+                reminder.matchup_id = None;
+            }
+        }
+        for collection in self.collections.values_mut() {
+            collection.matchup_ids.retain(|id| id != matchup_id);
+        }
+    }
+
+    /// Clear links to a deleted match from drafts, which stay useful on their own
+    pub fn unlink_match(&mut self, match_id: &str) {
+        for draft in self.drafts.values_mut() {
+            if draft.linked_match.as_deref() == Some(match_id) {
+                draft.linked_match = None;
+            }
+        }
+    }
+
+    /// Apply the configured retention policy, if any, returning the removed matches
+    pub fn apply_retention(&mut self, now: DateTime<Utc>) -> Vec<Match> {
+        match self.metadata.match_retention {
+            Some(policy) => self.prune_matches(
+                now - chrono::Duration::days(policy.max_age_days as i64),
+                policy.keep_linked,
+            ),
+            None => Vec::new(),
         }
     }
 }
 
+/// What `AppData::merge` added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct MergeSummary {
+    pub matchups: usize,
+    pub matches: usize,
+    pub team_notes: usize,
+    pub drafts: usize,
+    pub reminders: usize,
+    pub collections: usize,
+    pub scratch_notes: usize,
+    pub plan_targets: usize,
+}
+
 /// Storage handler for reading/writing data
 pub struct Storage {
     data_path: PathBuf,
 }
 
 impl Storage {
-    /// Create a new storage handler
-    pub fn new() -> Result<Self, StorageError> {
-        let data_dir = dirs::data_dir()
-            .or_else(|| dirs::config_dir())
-            .ok_or(StorageError::DataDirNotFound)?
-            .join("matchuphelper");
+    /// Create a new storage handler, in `data_dir` or the platform data directory
+    pub fn new(data_dir: Option<PathBuf>) -> Result<Self, StorageError> {
+        let data_dir = match data_dir {
+            Some(dir) => dir,
+            None => dirs::data_dir()
+                .or_else(dirs::config_dir)
+                .ok_or(StorageError::DataDirNotFound)?
+                .join("matchuphelper"),
+        };
 
         // Create directory if it doesn't exist
         if !data_dir.exists() {
@@ -70,17 +460,21 @@ impl Storage {
         }
 
         let contents = fs::read_to_string(&self.data_path)?;
-        let data: AppData = serde_json::from_str(&contents)?;
+        let mut data: AppData = serde_json::from_str(&contents).inspect_err(|e| {
+            tracing::error!("Could not parse {}: {}", self.data_path.display(), e);
+        })?;
+        data.upgrade();
         Ok(data)
     }
 
-    /// Save data to disk
-    pub fn save(&self, data: &AppData) -> Result<(), StorageError> {
-        let mut data = data.clone();
+    /// Save data to disk, stamping `last_updated` on `data` first
+    pub fn save(&self, data: &mut AppData) -> Result<(), StorageError> {
         data.metadata.last_updated = chrono::Utc::now().to_rfc3339();
 
-        let contents = serde_json::to_string_pretty(&data)?;
-        fs::write(&self.data_path, contents)?;
+        let contents = serde_json::to_string_pretty(data)?;
+        fs::write(&self.data_path, contents).inspect_err(|e| {
+            tracing::error!("Could not write {}: {}", self.data_path.display(), e);
+        })?;
         Ok(())
     }
 
@@ -88,18 +482,183 @@ impl Storage {
     pub fn data_path(&self) -> &PathBuf {
         &self.data_path
     }
+
+    /// Default directory for backups of the data file
+    pub fn backups_dir(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"))
+    }
+
+    /// Copy the data file into `dir` with a timestamped name, keeping the newest `keep` copies
+    ///
+    /// Returns the path of the new backup.
+    pub fn backup(&self, dir: &Path, keep: usize) -> Result<PathBuf, StorageError> {
+        fs::create_dir_all(dir)?;
+
+        let name = chrono::Utc::now().format(BACKUP_NAME_FORMAT).to_string();
+        let path = dir.join(name);
+        // Backing up empty data rather than failing keeps scheduled backups simple
+        if self.data_path.exists() {
+            fs::copy(&self.data_path, &path)?;
+        } else {
+            fs::write(&path, serde_json::to_string_pretty(&AppData::default())?)?;
+        }
+
+        let backups = Self::backups(dir)?;
+        let excess = backups.len().saturating_sub(keep.max(1));
+        for old in &backups[..excess] {
+            fs::remove_file(old)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Backups made by `backup` in `dir`, oldest first
+    ///
+    /// Only names `backup` gives are taken, so other files in a shared folder
+    /// are never pruned.
+    pub fn backups(dir: &Path) -> Result<Vec<PathBuf>, StorageError> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        // The timestamped names sort oldest first
+        let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+                    chrono::NaiveDateTime::parse_from_str(n, BACKUP_NAME_FORMAT).is_ok()
+                })
+            })
+            .collect();
+        backups.sort();
+        Ok(backups)
+    }
+
+    /// Directory holding the app's log files
+    pub fn logs_dir(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join("logs"))
+            .unwrap_or_else(|| PathBuf::from("logs"))
+    }
+
+    /// Directory holding downloaded Data Dragon files
+    pub fn ddragon_dir(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join("ddragon"))
+            .unwrap_or_else(|| PathBuf::from("ddragon"))
+    }
+
+    /// Contents of files attached to matchup versions
+    pub fn attachments_dir(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join(ATTACHMENTS_DIR))
+            .unwrap_or_else(|| PathBuf::from(ATTACHMENTS_DIR))
+    }
+
+    /// Cached community win rates
+    pub fn community_cache_path(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join(COMMUNITY_CACHE_FILE))
+            .unwrap_or_else(|| PathBuf::from(COMMUNITY_CACHE_FILE))
+    }
+
+    /// Riot's root CA, used to verify the client connection
+    pub fn riot_root_ca_path(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join(RIOT_ROOT_CA_FILE))
+            .unwrap_or_else(|| PathBuf::from(RIOT_ROOT_CA_FILE))
+    }
+
+    /// Directory holding cached raw game payloads from the client
+    fn games_dir(&self) -> PathBuf {
+        self.data_path
+            .parent()
+            .map(|p| p.join("games"))
+            .unwrap_or_else(|| PathBuf::from("games"))
+    }
+
+    /// Load a cached raw game payload, if present
+    pub fn load_raw_game(&self, game_id: i64) -> Result<Option<serde_json::Value>, StorageError> {
+        self.load_game_file(&format!("{}.json", game_id))
+    }
+
+    /// Cache a raw game payload for later drill-down and reprocessing
+    pub fn save_raw_game(
+        &self,
+        game_id: i64,
+        game: &serde_json::Value,
+    ) -> Result<(), StorageError> {
+        self.save_game_file(&format!("{}.json", game_id), game)
+    }
+
+    /// Load a cached end of game stats block, if present
+    pub fn load_eog_block(&self, game_id: i64) -> Result<Option<serde_json::Value>, StorageError> {
+        self.load_game_file(&format!("{}.eog.json", game_id))
+    }
+
+    /// Cache the end of game stats block, which can arrive before the game is imported
+    pub fn save_eog_block(
+        &self,
+        game_id: i64,
+        block: &serde_json::Value,
+    ) -> Result<(), StorageError> {
+        self.save_game_file(&format!("{}.eog.json", game_id), block)
+    }
+
+    /// Delete the cached payloads of games that were removed
+    ///
+    /// A payload that can't be deleted only takes up space, so failures are skipped.
+    pub fn remove_raw_games(&self, games: &[Match]) {
+        let dir = self.games_dir();
+        for game_id in games.iter().filter_map(|m| m.game_id.as_deref()) {
+            // Only client game IDs name files, Riot API ones aren't cached
+            let Ok(game_id) = game_id.parse::<i64>() else {
+                continue;
+            };
+            let _ = fs::remove_file(dir.join(format!("{}.json", game_id)));
+            let _ = fs::remove_file(dir.join(format!("{}.eog.json", game_id)));
+        }
+    }
+
+    fn load_game_file(&self, name: &str) -> Result<Option<serde_json::Value>, StorageError> {
+        let path = self.games_dir().join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    fn save_game_file(&self, name: &str, value: &serde_json::Value) -> Result<(), StorageError> {
+        let dir = self.games_dir();
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+
+        fs::write(dir.join(name), serde_json::to_string(value)?)?;
+        Ok(())
+    }
 }
 
 impl Default for Storage {
     fn default() -> Self {
-        Self::new().expect("Failed to initialize storage")
+        Self::new(None).expect("Failed to initialize storage")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::matchup::Matchup;
+    use crate::matchup::{MatchResult, Matchup};
     use tempfile::tempdir;
 
     #[test]
@@ -119,11 +678,221 @@ mod tests {
         );
         data.matchups.insert(matchup.id.clone(), matchup);
 
-        storage.save(&data).unwrap();
+        storage.save(&mut data).unwrap();
 
         assert!(data_path.exists());
 
         let loaded = storage.load().unwrap();
         assert_eq!(loaded.matchups.len(), 1);
     }
+
+    #[test]
+    fn test_upgrade() {
+        let mut data = AppData::default();
+        data.metadata.version = "1.0".to_string();
+        let matchup = Matchup::new("Wukong".to_string(), "Garen".to_string(), "top".to_string());
+        data.matchups.insert(matchup.id.clone(), matchup.clone());
+        data.upgrade();
+
+        assert_eq!(data.matchups[&matchup.id].my_champion, "MonkeyKing");
+        assert_eq!(data.metadata.version, DATA_VERSION);
+    }
+
+    #[test]
+    fn test_raw_game_cache() {
+        let dir = tempdir().unwrap();
+        let storage = Storage {
+            data_path: dir.path().join("data.json"),
+        };
+
+        assert!(storage.load_raw_game(42).unwrap().is_none());
+
+        let game = serde_json::json!({ "gameId": 42, "participants": [] });
+        storage.save_raw_game(42, &game).unwrap();
+
+        assert_eq!(storage.load_raw_game(42).unwrap(), Some(game.clone()));
+        assert!(storage.load_eog_block(42).unwrap().is_none());
+
+        storage.save_eog_block(42, &game).unwrap();
+        let removed = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            Some("42".to_string()),
+        );
+        storage.remove_raw_games(&[removed]);
+        assert!(storage.load_raw_game(42).unwrap().is_none());
+        assert!(storage.load_eog_block(42).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_prune_matches() {
+        let now = Utc::now();
+        let mut data = AppData::default();
+        for (days_ago, notes, linked) in [
+            (100, "", None),
+            (100, "Got ganked twice", None),
+            (100, "", Some("m1")),
+            (5, "", None),
+        ] {
+            let mut m = Match::new(
+                "Darius".to_string(),
+                "Garen".to_string(),
+                "top".to_string(),
+                MatchResult::Win,
+                None,
+            );
+            m.date = now - chrono::Duration::days(days_ago);
+            m.notes = notes.to_string();
+            m.linked_matchup = linked.map(str::to_string);
+            data.matches.insert(m.id.clone(), m);
+        }
+        for m in data.matches.values() {
+            let draft = Draft::new(crate::draft::DraftInput {
+                date: None,
+                my_side: crate::draft::Side::Blue,
+                actions: Vec::new(),
+                result: None,
+                notes: String::new(),
+                linked_match: Some(m.id.clone()),
+            });
+            data.drafts.insert(draft.id.clone(), draft);
+        }
+
+        let cutoff = now - chrono::Duration::days(30);
+        assert_eq!(data.clone().prune_matches(cutoff, false).len(), 2);
+        assert_eq!(data.prune_matches(cutoff, true).len(), 1);
+        assert_eq!(data.matches.len(), 3);
+        let linked = data.drafts.values().filter(|d| d.linked_match.is_some());
+        assert_eq!(linked.count(), 3);
+    }
+
+    #[test]
+    fn test_record_imported_game() {
+        let mut metadata = Metadata::default();
+        metadata.record_imported_game(None, 2, 2_000);
+        metadata.record_imported_game(None, 1, 1_000);
+        metadata.record_imported_game(Some("smurf"), 3, 1_500);
+
+        assert_eq!(
+            metadata.last_imported,
+            Some(ImportMarker {
+                game_id: 2,
+                game_creation: 2_000
+            })
+        );
+        assert_eq!(
+            metadata.import_marker(Some("smurf")).map(|m| m.game_id),
+            Some(3)
+        );
+        assert!(metadata.import_marker(Some("main")).is_none());
+    }
+
+    #[test]
+    fn test_remember_account() {
+        let mut metadata = Metadata::default();
+        let now = Utc::now();
+        metadata.remember_account("a", Some("Main".to_string()), now);
+        metadata.remember_account("b", None, now);
+        metadata.remember_account("a", None, now);
+
+        assert_eq!(metadata.accounts.len(), 2);
+        assert_eq!(metadata.accounts[0].summoner_name.as_deref(), Some("Main"));
+        assert_eq!(metadata.local_author().as_deref(), Some("Main"));
+
+        let student = Player::new("Student".to_string());
+        metadata.active_player = Some(student.id.clone());
+        metadata.players.push(student);
+        assert_eq!(metadata.local_author().as_deref(), Some("Main"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut data = AppData::default();
+        let matchup = Matchup::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+        );
+        data.matchups.insert(matchup.id.clone(), matchup);
+        let mut imported = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            Some("42".to_string()),
+        );
+        data.matches.insert(imported.id.clone(), imported.clone());
+
+        let mut other = data.clone();
+        // The same game imported separately on another machine
+        imported.id = "other".to_string();
+        other.matches.insert(imported.id.clone(), imported);
+        let new_matchup = Matchup::new(
+            "Riven".to_string(),
+            "Fiora".to_string(),
+            "top".to_string(),
+        );
+        other
+            .matchups
+            .insert(new_matchup.id.clone(), new_matchup);
+
+        let summary = data.merge(other);
+        assert_eq!(
+            summary,
+            MergeSummary {
+                matchups: 1,
+                matches: 0,
+                team_notes: 0,
+                drafts: 0,
+                reminders: 0,
+                collections: 0,
+                scratch_notes: 0,
+                plan_targets: 0
+            }
+        );
+        assert_eq!(data.matchups.len(), 2);
+        assert_eq!(data.matches.len(), 1);
+
+        let other = AppData {
+            scratchpad: (0..MAX_NOTES + 5)
+                .map(|i| ScratchNote {
+                    id: i.to_string(),
+                    title: String::new(),
+                    text: "Note".to_string(),
+                    updated: Utc::now(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(data.merge(other).scratch_notes, MAX_NOTES);
+        assert_eq!(data.scratchpad.len(), MAX_NOTES);
+    }
+
+    #[test]
+    fn test_backup_keeps_newest() {
+        let dir = tempdir().unwrap();
+        let storage = Storage {
+            data_path: dir.path().join("data.json"),
+        };
+        storage.save(&mut AppData::default()).unwrap();
+
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        for old in [
+            "data-20240101-000000.json",
+            "data-20240102-000000.json",
+            "data-export.json",
+        ] {
+            fs::write(backups.join(old), "{}").unwrap();
+        }
+
+        let path = storage.backup(&backups, 2).unwrap();
+        assert!(path.exists());
+        assert!(!backups.join("data-20240101-000000.json").exists());
+        assert!(backups.join("data-20240102-000000.json").exists());
+        // Not a name the app gives its backups
+        assert!(backups.join("data-export.json").exists());
+    }
 }
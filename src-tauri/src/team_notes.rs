@@ -0,0 +1,212 @@
+//! Team notes for scrims and team games, kept apart from 1v1 matchups
+//!
+//! A note is about one game or series against an opponent team: both team
+//! compositions, the date played, free notes and tags.
+
+use crate::champions;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Champions in a team composition
+const TEAM_SIZE: usize = 5;
+
+/// Longest opponent team name
+const MAX_OPPONENT_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamNote {
+    pub id: String,
+    /// Canonical champion names, in pick order or by role
+    pub our_comp: Vec<String>,
+    pub their_comp: Vec<String>,
+    pub opponent: String,
+    /// When the game was played
+    pub date: DateTime<Utc>,
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// User-provided fields of a team note, for creating and editing
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TeamNoteInput {
+    pub our_comp: Vec<String>,
+    pub their_comp: Vec<String>,
+    pub opponent: String,
+    /// Defaults to now
+    pub date: Option<DateTime<Utc>>,
+    pub notes: String,
+    pub tags: Vec<String>,
+}
+
+impl TeamNoteInput {
+    /// Check the input and resolve champion names to their canonical form
+    pub fn normalize(mut self) -> Result<Self, String> {
+        self.opponent = self.opponent.trim().to_string();
+        if self.opponent.is_empty() {
+            return Err("Opponent team name cannot be empty".to_string());
+        }
+        if self.opponent.chars().count() > MAX_OPPONENT_LENGTH {
+            return Err(format!(
+                "Opponent team name is limited to {} characters",
+                MAX_OPPONENT_LENGTH
+            ));
+        }
+        self.our_comp = normalize_comp(&self.our_comp)?;
+        self.their_comp = normalize_comp(&self.their_comp)?;
+        self.tags = self
+            .tags
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        Ok(self)
+    }
+}
+
+fn normalize_comp(comp: &[String]) -> Result<Vec<String>, String> {
+    if comp.len() > TEAM_SIZE {
+        return Err(format!(
+            "A team composition has at most {} champions",
+            TEAM_SIZE
+        ));
+    }
+    let mut champions = Vec::with_capacity(comp.len());
+    for name in comp {
+        let champion = champions::canonicalize(name)?;
+        if champions.contains(&champion) {
+            return Err(format!("{} is in the composition twice", champion));
+        }
+        champions.push(champion);
+    }
+    Ok(champions)
+}
+
+impl TeamNote {
+    /// Create a note from normalized input
+    pub fn new(input: TeamNoteInput) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            our_comp: input.our_comp,
+            their_comp: input.their_comp,
+            opponent: input.opponent,
+            date: input.date.unwrap_or(now),
+            notes: input.notes,
+            tags: input.tags,
+            created: now,
+            updated: now,
+        }
+    }
+
+    /// Replace the contents with normalized input, keeping the date if none is given
+    pub fn update(&mut self, input: TeamNoteInput) {
+        self.our_comp = input.our_comp;
+        self.their_comp = input.their_comp;
+        self.opponent = input.opponent;
+        self.date = input.date.unwrap_or(self.date);
+        self.notes = input.notes;
+        self.tags = input.tags;
+        self.updated = Utc::now();
+    }
+
+    /// Whether either composition has the champion
+    pub fn has_champion(&self, champion: &str) -> bool {
+        self.our_comp
+            .iter()
+            .chain(&self.their_comp)
+            .any(|c| c.eq_ignore_ascii_case(champion))
+    }
+}
+
+/// Criteria for listing team notes; unset fields match everything
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TeamNoteFilter {
+    /// Case-insensitive substring of the opponent team name
+    pub opponent: Option<String>,
+    /// On either side
+    pub champion: Option<String>,
+    pub tag: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl TeamNoteFilter {
+    pub fn matches(&self, note: &TeamNote) -> bool {
+        self.opponent.as_ref().is_none_or(|opponent| {
+            note.opponent
+                .to_lowercase()
+                .contains(&opponent.trim().to_lowercase())
+        }) && self
+            .champion
+            .as_ref()
+            .is_none_or(|champion| note.has_champion(champion))
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| note.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            && self.from.is_none_or(|from| note.date >= from)
+            && self.to.is_none_or(|to| note.date <= to)
+    }
+}
+
+/// Notes passing `filter`, most recent game first
+pub fn filter_notes<'a>(
+    notes: impl IntoIterator<Item = &'a TeamNote>,
+    filter: &TeamNoteFilter,
+) -> Vec<TeamNote> {
+    let mut notes: Vec<TeamNote> = notes
+        .into_iter()
+        .filter(|note| filter.matches(note))
+        .cloned()
+        .collect();
+    notes.sort_by(|a, b| b.date.cmp(&a.date));
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_team_notes() {
+        let input = TeamNoteInput {
+            our_comp: vec!["darius".to_string(), "Lee Sin".to_string()],
+            their_comp: vec!["Garen".to_string()],
+            opponent: "  Team Blue ".to_string(),
+            tags: vec![" Scrim ".to_string()],
+            ..Default::default()
+        }
+        .normalize()
+        .unwrap();
+        assert_eq!(input.opponent, "Team Blue");
+        assert_eq!(input.our_comp, vec!["Darius", "LeeSin"]);
+        assert_eq!(input.tags, vec!["scrim"]);
+
+        let note = TeamNote::new(input);
+        let filter = TeamNoteFilter {
+            opponent: Some("blue".to_string()),
+            champion: Some("Garen".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&note));
+        let other = TeamNoteFilter {
+            champion: Some("Ahri".to_string()),
+            ..Default::default()
+        };
+        assert!(!other.matches(&note));
+
+        let duplicate = TeamNoteInput {
+            our_comp: vec!["Darius".to_string(), "darius".to_string()],
+            opponent: "Team Blue".to_string(),
+            ..Default::default()
+        };
+        assert!(duplicate.normalize().is_err());
+        assert!(TeamNoteInput::default().normalize().is_err());
+    }
+}
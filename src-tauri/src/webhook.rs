@@ -0,0 +1,210 @@
+//! Webhook posted after each auto-imported game, e.g. to a Discord channel
+//!
+//! Messages use Discord's webhook format. Unless turned off in the settings,
+//! the player is asked before each game is posted.
+
+use crate::matchup::{Match, MatchRecord, MatchResult, Matchup};
+use crate::AppState;
+use serde_json::{json, Value};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Start of the links that open a matchup in the app
+pub const MATCHUP_LINK_PREFIX: &str = "matchuphelper://matchup/";
+
+/// Discord rejects embed fields longer than this
+const MAX_FIELD_LENGTH: usize = 1024;
+
+const WIN_COLOR: u32 = 0x2ecc71;
+const LOSS_COLOR: u32 = 0xe74c3c;
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Webhook returned HTTP {0}")]
+    Status(u16),
+}
+
+/// Only HTTPS URLs, so the game summary isn't sent in the clear
+pub fn validate_url(url: &str) -> Result<(), String> {
+    if !url.starts_with("https://") {
+        return Err("Webhook URL must start with https://".to_string());
+    }
+    Ok(())
+}
+
+pub fn send(url: &str, message: &Value) -> Result<(), WebhookError> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()?
+        .post(url)
+        .json(message)
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(WebhookError::Status(response.status().as_u16()));
+    }
+    Ok(())
+}
+
+/// Link that opens a matchup in the app
+pub fn matchup_link(matchup_id: &str) -> String {
+    format!("{}{}", MATCHUP_LINK_PREFIX, matchup_id)
+}
+
+/// Post a just imported game, if a webhook is set
+///
+/// With `confirm_webhook_posts` on, the main window is sent
+/// `share-game-prompt` with the game instead, and posts it through
+/// `share_game` once the player agrees.
+pub fn game_imported(app: &AppHandle, game: &Match) {
+    let state = app.state::<AppState>();
+    let Ok(settings) = crate::load_settings(&state) else {
+        return;
+    };
+    if settings.webhook_url.is_none() {
+        return;
+    }
+    if settings.confirm_webhook_posts {
+        let _ = app.emit_to("main", "share-game-prompt", game);
+        return;
+    }
+    post_game(&state, game);
+}
+
+/// Post the summary of a game, if a webhook is set
+///
+/// Sent from its own thread so a slow webhook doesn't hold up the caller.
+pub fn post_game(state: &AppState, game: &Match) {
+    let Some(url) = crate::load_settings(state)
+        .ok()
+        .and_then(|settings| settings.webhook_url)
+    else {
+        return;
+    };
+
+    let matchup = state
+        .storage
+        .lock()
+        .ok()
+        .and_then(|storage| storage.load().ok())
+        .and_then(|data| {
+            let matchup = data.matchups.get(game.linked_matchup.as_ref()?)?.clone();
+            let record = matchup.record(data.matches.values());
+            Some((matchup, record))
+        });
+    let message = game_message(game, matchup.as_ref().map(|(m, r)| (m, *r)));
+
+    thread::spawn(move || {
+        let _ = send(&url, &message);
+    });
+}
+
+/// Discord message for a finished game, with its matchup's record and notes
+pub fn game_message(game: &Match, matchup: Option<(&Matchup, MatchRecord)>) -> Value {
+    let (result, color) = match game.result {
+        MatchResult::Win => ("Win", WIN_COLOR),
+        MatchResult::Loss => ("Loss", LOSS_COLOR),
+    };
+
+    let mut fields = Vec::new();
+    if let Some(ref stats) = game.stats {
+        fields.push(json!({
+            "name": "KDA",
+            "value": format!("{}/{}/{}", stats.kills, stats.deaths, stats.assists),
+            "inline": true,
+        }));
+        fields.push(json!({ "name": "CS", "value": stats.cs.to_string(), "inline": true }));
+    }
+    if let Some((matchup, record)) = matchup {
+        let win_rate = record.win_rate().unwrap_or_default() * 100.0;
+        fields.push(json!({
+            "name": "Matchup record",
+            "value": format!(
+                "{}W {}L ({:.0}%)",
+                record.wins,
+                record.games - record.wins,
+                win_rate
+            ),
+            "inline": true,
+        }));
+
+        let summary = matchup.quick_reference().summary;
+        if !summary.is_empty() {
+            let notes = summary
+                .iter()
+                .map(|point| format!("• {}", point))
+                .collect::<Vec<_>>()
+                .join("\n");
+            fields.push(json!({ "name": "Matchup notes", "value": truncate(&notes) }));
+        }
+        // Discord only makes web links clickable, so this one is there to copy
+        fields.push(json!({
+            "name": "Open in MatchupHelper",
+            "value": format!("`{}`", matchup_link(&matchup.id)),
+        }));
+    }
+
+    let embed = json!({
+        "title": format!(
+            "{}: {} vs {} ({})",
+            result, game.my_champion, game.enemy_champion, game.role
+        ),
+        "color": color,
+        "fields": fields,
+        "timestamp": game.date.to_rfc3339(),
+    });
+
+    json!({ "username": "MatchupHelper", "embeds": [embed] })
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_FIELD_LENGTH {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_FIELD_LENGTH - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_message() {
+        let game = Match::new(
+            "Darius".to_string(),
+            "Garen".to_string(),
+            "top".to_string(),
+            MatchResult::Win,
+            None,
+        );
+        let matchup = Matchup::new("Darius".to_string(), "Garen".to_string(), "top".to_string());
+        let record = MatchRecord { games: 4, wins: 3 };
+
+        let message = game_message(&game, Some((&matchup, record)));
+        let embed = &message["embeds"][0];
+        assert_eq!(embed["title"], "Win: Darius vs Garen (top)");
+        assert_eq!(embed["fields"][0]["value"], "3W 1L (75%)");
+        assert_eq!(
+            embed["fields"][1]["value"],
+            format!("`matchuphelper://matchup/{}`", matchup.id)
+        );
+
+        let message = game_message(&game, None);
+        assert_eq!(message["embeds"][0]["fields"], json!([]));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let long = "a".repeat(2000);
+        assert_eq!(truncate(&long).chars().count(), MAX_FIELD_LENGTH);
+        assert_eq!(truncate("short"), "short");
+    }
+}
@@ -0,0 +1,88 @@
+//! Remembers where each window was left, by window label
+//!
+//! Saved in the settings when a window is closed or hidden, and applied when it
+//! is opened again.
+
+use crate::settings::{WindowGeometry, WindowState};
+use crate::AppState;
+use tauri::{Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+
+/// Put a new window back where it was left and keep track of it from now on
+///
+/// Windows start hidden so they don't flash at their default place; this shows them.
+pub fn manage(window: &WebviewWindow) -> tauri::Result<()> {
+    let state = window.app_handle().state::<AppState>();
+    let saved = crate::load_settings(&state)
+        .ok()
+        .and_then(|settings| settings.windows.get(window.label()).copied());
+    if let Some(saved) = saved {
+        place(window, saved.geometry)?;
+        if saved.maximized {
+            window.maximize()?;
+        }
+    }
+
+    // Closing the main window quits, so this is the last chance to save it
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { .. } = event {
+            save(&handle);
+        }
+    });
+
+    window.show()
+}
+
+/// Move and resize a window, keeping it on one of the connected monitors
+pub fn place(window: &WebviewWindow, geometry: WindowGeometry) -> tauri::Result<()> {
+    let screens: Vec<WindowGeometry> = window
+        .available_monitors()?
+        .iter()
+        .map(|monitor| WindowGeometry {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect();
+    let geometry = geometry.fit_to(&screens);
+    window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+    window.set_size(PhysicalSize::new(geometry.width, geometry.height))
+}
+
+/// Save where a window is now
+pub fn save(window: &WebviewWindow) {
+    let maximized = window.is_maximized().unwrap_or(false);
+    let current = match (window.outer_position(), window.inner_size()) {
+        (Ok(position), Ok(size)) => Some(WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        }),
+        _ => None,
+    }
+    // A minimized window reports a zero size on some platforms
+    .filter(|geometry| geometry.validate().is_ok());
+
+    let state = window.app_handle().state::<AppState>();
+    let label = window.label().to_string();
+    let _ = crate::modify_settings(&state, |settings| {
+        let saved = settings.windows.get(&label).map(|s| s.geometry);
+        // A maximized window keeps its earlier geometry, to unmaximize back to it
+        let geometry = if maximized {
+            saved.or(current)
+        } else {
+            current.or(saved)
+        };
+        if let Some(geometry) = geometry {
+            settings.windows.insert(
+                label,
+                WindowState {
+                    geometry,
+                    maximized,
+                },
+            );
+        }
+    });
+}
@@ -2,6 +2,7 @@
 
 const { invoke } = window.__TAURI__.core;
 const { getCurrentWindow } = window.__TAURI__.window;
+const { listen } = window.__TAURI__.event;
 
 // Data Dragon configuration - will be loaded dynamically
 let DDRAGON_VERSION = "16.2.1"; // Default fallback for Season 16
@@ -102,12 +103,78 @@ async function init() {
     setupEventListeners();
     populateChampionFilters();
     renderMatchups();
+    setupQuickReference();
+    setupDataSync();
+    await setupMainWindow();
     console.log('MatchupHelper initialized');
   } catch (error) {
     console.error('Initialization error:', error);
   }
 }
 
+// The overlay window starts on #quick, or #quick/<matchup id> at the loading screen
+function setupQuickReference() {
+  if (!window.location.hash.startsWith('#quick')) return;
+
+  // The overlay has no title bar, so it's dragged by the header
+  document.querySelector('.header').setAttribute('data-tauri-drag-region', '');
+  const match = window.location.hash.match(/^#quick\/(.+)$/);
+  if (match) {
+    openMatchupDetail(decodeURIComponent(match[1]));
+  }
+  // Sent to the overlay only; a global listener would also move the main window
+  getCurrentWindow().listen('show-quick-reference', (event) => openMatchupDetail(event.payload));
+}
+
+// Only the main window opens matchuphelper:// links and asks before posting to the webhook
+async function setupMainWindow() {
+  if (getCurrentWindow().label !== 'main') return;
+
+  const openLinkedMatchup = async () => {
+    const id = await invoke('take_linked_matchup');
+    if (id) openMatchupDetail(id);
+  };
+  listen('matchup-link-opened', openLinkedMatchup);
+  await openLinkedMatchup();
+
+  listen('share-game-prompt', async (event) => {
+    const game = event.payload;
+    if (!confirm(`Post your ${game.my_champion} vs ${game.enemy_champion} game to the webhook?`)) return;
+    try {
+      await invoke('share_game', { matchId: game.id });
+    } catch (error) {
+      console.error('Error sharing game:', error);
+    }
+  });
+
+  await showDueReminders();
+}
+
+// Reminders past their date or patch are listed once when the app starts
+async function showDueReminders() {
+  try {
+    const due = await invoke('get_due_reminders');
+    if (due.length === 0) return;
+    alert(`Due reminders:\n${due.map((reminder) => `- ${reminder.text}`).join('\n')}`);
+  } catch (error) {
+    console.error('Error loading reminders:', error);
+  }
+}
+
+// Other windows (and the background import) report their changes as events
+function setupDataSync() {
+  listen('matchups:changed', async () => {
+    await loadMatchups();
+    renderMatchups();
+  });
+  const reloadMatches = async () => {
+    await loadMatches();
+    renderHistory();
+  };
+  listen('matches:changed', reloadMatches);
+  listen('matches:imported', reloadMatches);
+}
+
 // ==================== Data Loading ====================
 
 async function loadChampions() {
@@ -381,6 +448,7 @@ async function openMatchupDetail(id) {
     elements.detailVersionDate.textContent = formatDate(currentVersion?.date);
 
     elements.modalMatchup.classList.remove('hidden');
+    invoke('set_presence_matchup', { matchupId: id }).catch(() => {});
   } catch (error) {
     console.error('Error loading matchup:', error);
   }
@@ -389,6 +457,7 @@ async function openMatchupDetail(id) {
 function closeMatchupModal() {
   elements.modalMatchup.classList.add('hidden');
   state.currentMatchup = null;
+  invoke('set_presence_matchup', { matchupId: null }).catch(() => {});
 }
 
 function renderTags(tags) {
@@ -455,12 +524,18 @@ async function saveMatchup() {
     const itemsChanged = JSON.stringify(newItems) !== JSON.stringify(currentVersion.items || []);
 
     if (notesChanged || runesChanged || summonersChanged || itemsChanged) {
+      // Carry over what this editor doesn't show, so saving doesn't drop it
       const update = {
         notes: newNotes,
         tags: newTags,
         runes: newRunes,
         summoner_spells: newSummoners,
-        items: newItems
+        items: newItems,
+        difficulty: currentVersion.difficulty || null,
+        summary: currentVersion.summary || [],
+        rune_page: currentVersion.rune_page || null,
+        blue_side_notes: currentVersion.blue_side_notes || null,
+        red_side_notes: currentVersion.red_side_notes || null
       };
 
       await invoke('update_matchup', {
@@ -597,17 +672,19 @@ async function connectToLcu() {
   } catch (error) {
     console.error('Error connecting to LCU:', error);
     updateLcuStatus(false);
-    alert('Could not connect to League client. Make sure the client is running.');
+    alert(error.code === 'LcuNotRunning'
+      ? 'Could not connect to League client. Make sure the client is running.'
+      : `Could not connect to League client: ${error.message ?? error}`);
   }
 }
 
 async function importMatches() {
   try {
-    const imported = await invoke('import_matches', { count: 20 });
-    if (imported.length > 0) {
+    const summary = await invoke('import_matches', { count: 20 });
+    if (summary.imported.length > 0) {
       await loadMatches();
       renderHistory();
-      console.log(`Imported ${imported.length} matches`);
+      console.log(`Imported ${summary.imported.length} matches (${summary.linked} linked to matchups)`);
     }
   } catch (error) {
     console.error('Error importing matches:', error);